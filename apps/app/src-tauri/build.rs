@@ -1,5 +1,7 @@
 fn main() {
-    println!("cargo:rustc-link-lib=framework=EventKit");
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("macos") {
+        println!("cargo:rustc-link-lib=framework=EventKit");
+    }
 
     tauri_build::build()
 }
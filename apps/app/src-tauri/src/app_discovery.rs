@@ -0,0 +1,164 @@
+// src-tauri/src/app_discovery.rs
+//
+// Finds .app bundles in the application folders, including the ones vendors
+// and macOS put into subfolders (`/Applications/Utilities`,
+// `/Applications/Adobe Photoshop 2024/`). Folders are searched down to a
+// limited depth and bundles are never entered, so helper apps inside
+// `Contents/` are not reported. Plain file system walking, so this runs (and
+// is tested) on any OS.
+
+use serde::Serialize;
+use std::path::Path;
+use tauri::command;
+use walkdir::{DirEntry, WalkDir};
+
+use crate::error::{Error, Result};
+
+pub const APPLICATION_DIRS: [&str; 3] = [
+    "/Applications",
+    "/System/Applications",
+    "/System/Volumes/Preboot/Cryptexes/App/System/Applications",
+];
+// Folders below an application folder to look into. Suites rarely nest
+// deeper than `/Applications/<Vendor>/<Suite>/`.
+pub const DEFAULT_MAX_DEPTH: usize = 2;
+// Deep enough for any real layout, keeps a stray symlinked home folder cheap
+const MAX_DEPTH: usize = 8;
+
+#[derive(Serialize, Clone, Debug, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppBundle {
+    // File name including the .app extension
+    pub name: String,
+    pub path: String,
+    // Folder directly inside the application folder that holds the bundle,
+    // e.g. "Utilities". None for bundles at the top level.
+    pub suite: Option<String>,
+}
+
+// Bundles in `roots` and their subfolders down to `max_depth` levels (0 only
+// reads the roots themselves), in walk order. Missing roots and unreadable
+// folders are skipped.
+pub fn find_app_bundles<P: AsRef<Path>>(roots: &[P], max_depth: usize) -> Vec<AppBundle> {
+    let max_depth = max_depth.min(MAX_DEPTH);
+    let mut bundles = Vec::new();
+
+    for root in roots {
+        let root = root.as_ref();
+        let mut walker = WalkDir::new(root)
+            .min_depth(1)
+            .max_depth(max_depth + 1)
+            .sort_by_file_name()
+            .into_iter();
+
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let is_dir = entry.file_type().is_dir();
+            if is_hidden(&entry) {
+                if is_dir {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            if is_app_bundle(&entry) {
+                bundles.push(AppBundle {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    path: entry.path().to_string_lossy().into_owned(),
+                    suite: suite_name(root, entry.path()),
+                });
+            }
+            // Bundles (and anything else with Contents/) hold helpers and
+            // resources, not installed apps
+            if is_dir && is_bundle_dir(entry.path()) {
+                walker.skip_current_dir();
+            }
+        }
+    }
+
+    bundles
+}
+
+// `.app` folders, or symlinks to one (Safari in /Applications points into the
+// Cryptexes volume)
+fn is_app_bundle(entry: &DirEntry) -> bool {
+    let is_app = entry
+        .path()
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("app"));
+    is_app && (entry.file_type().is_dir() || entry.path().is_dir())
+}
+
+fn is_bundle_dir(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("app"))
+        || path.join("Contents").is_dir()
+}
+
+fn is_hidden(entry: &DirEntry) -> bool {
+    entry
+        .file_name()
+        .to_str()
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+fn suite_name(root: &Path, bundle_path: &Path) -> Option<String> {
+    let relative = bundle_path.strip_prefix(root).ok()?;
+    let mut components = relative.components();
+    let first = components.next()?;
+    // Top level bundles have nothing after their own name
+    components.next()?;
+    Some(first.as_os_str().to_string_lossy().into_owned())
+}
+
+#[command]
+#[specta::specta]
+pub async fn find_apps(max_depth: Option<usize>) -> Result<Vec<AppBundle>> {
+    let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH);
+    tauri::async_runtime::spawn_blocking(move || find_app_bundles(&APPLICATION_DIRS, max_depth))
+        .await
+        .map_err(Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(max_depth: usize) -> Vec<(String, Option<String>)> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/applications");
+        find_app_bundles(&[root], max_depth)
+            .into_iter()
+            .map(|bundle| (bundle.name, bundle.suite))
+            .collect()
+    }
+
+    #[test]
+    fn finds_nested_bundles_with_their_suite() {
+        assert_eq!(
+            find(DEFAULT_MAX_DEPTH),
+            vec![
+                ("Calculator.app".to_string(), None),
+                ("Terminal.app".to_string(), Some("Utilities".to_string())),
+                (
+                    "Uninstaller.app".to_string(),
+                    Some("Vendor Suite".to_string())
+                ),
+                (
+                    "Photo Editor.app".to_string(),
+                    Some("Vendor Suite".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn limits_the_depth() {
+        assert_eq!(find(0), vec![("Calculator.app".to_string(), None)]);
+        assert_eq!(find(1).len(), 3);
+        // Bundles inside Contents/ stay hidden at any depth
+        assert!(find(MAX_DEPTH).iter().all(|(name, _)| name != "Helper.app"));
+        assert!(find(MAX_DEPTH).iter().any(|(name, _)| name == "Deep.app"));
+    }
+}
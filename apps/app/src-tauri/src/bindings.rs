@@ -0,0 +1,108 @@
+// src-tauri/src/bindings.rs
+//
+// The commands the webview can invoke, and the TypeScript bindings generated
+// from them: a typed wrapper per command plus every type they take or return,
+// written to src/lib/bindings.ts. Debug builds rewrite the file on startup;
+// the test below fails when the checked-in file is stale (run it with
+// UPDATE_BINDINGS=1 to update it). Commands reject with a `CommandError`.
+
+use specta_typescript::{BigIntExportBehavior, Typescript};
+use tauri_specta::{collect_commands, Builder, ErrorHandlingMode};
+
+use crate::spotlight_utils::{FileSearchBatch, FileSearchComplete};
+use crate::workspace_utils::RunningAppInfo;
+use crate::{
+    app_discovery, caldav, calendar_utils, command, content_index, favicon_utils, file_index, icns_utils,
+    icon_cache, ics, keyring_utils, open_with, spotlight_utils, theme_utils, toggle_visibility,
+    workspace_utils,
+};
+
+// Relative to src-tauri
+pub const BINDINGS_PATH: &str = "../src/lib/bindings.ts";
+
+const HEADER: &str = "// Generated from the Tauri commands in src-tauri, do not edit.\n\
+                      // Run `UPDATE_BINDINGS=1 cargo test bindings` in src-tauri to update.";
+
+pub fn builder() -> Builder<tauri::Wry> {
+    Builder::<tauri::Wry>::new()
+        .commands(collect_commands![
+            command::show,
+            command::hide,
+            theme_utils::set_vibrancy,
+            theme_utils::set_appearance,
+            icns_utils::load_app_info::<tauri::Wry>,
+            icns_utils::load_extension_icons,
+            icns_utils::list_installed_apps,
+            app_discovery::find_apps,
+            open_with::get_open_with_apps,
+            open_with::open_file_with,
+            icon_cache::clear_icon_cache,
+            icon_cache::get_icon_cache_stats,
+            spotlight_utils::search_spotlight_apps::<tauri::Wry>,
+            spotlight_utils::start_file_search::<tauri::Wry>,
+            spotlight_utils::cancel_file_search,
+            toggle_visibility::toggle_visibility,
+            workspace_utils::activate_application_by_name,
+            workspace_utils::get_frontmost_application_name,
+            favicon_utils::fetch_favicon::<tauri::Wry>,
+            keyring_utils::set_secret,
+            keyring_utils::get_secret,
+            keyring_utils::delete_secret,
+            calendar_utils::get_calendar_authorization_status,
+            calendar_utils::request_calendar_access::<tauri::Wry>,
+            calendar_utils::get_calendars,
+            calendar_utils::get_calendar_events,
+            calendar_utils::create_calendar_event,
+            calendar_utils::update_calendar_event,
+            calendar_utils::delete_calendar_event,
+            ics::get_ics_calendar,
+            ics::get_ics_events,
+            ics::export_ics_file,
+            ics::import_ics_calendar,
+            ics::remove_ics_calendar,
+            caldav::add_caldav_account,
+            caldav::list_caldav_accounts,
+            caldav::refresh_caldav_calendars,
+            caldav::remove_caldav_account,
+            file_index::rebuild_file_index::<tauri::Wry>,
+            file_index::get_file_index_stats,
+            content_index::rebuild_content_index::<tauri::Wry>,
+            content_index::clear_content_index,
+            content_index::search_file_contents::<tauri::Wry>,
+            content_index::get_content_index_stats,
+        ])
+        // Payloads of the file search events, and types the frontend uses
+        // without a command returning them
+        .typ::<FileSearchBatch>()
+        .typ::<FileSearchComplete>()
+        .typ::<RunningAppInfo>()
+        .error_handling(ErrorHandlingMode::Throw)
+}
+
+pub fn typescript() -> Typescript {
+    Typescript::default()
+        .header(HEADER)
+        // Sizes and counts stay far below 2^53
+        .bigint(BigIntExportBehavior::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn bindings_are_current() {
+        let generated = builder().export_str(typescript()).unwrap();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(BINDINGS_PATH);
+        if std::env::var_os("UPDATE_BINDINGS").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+        }
+        let checked_in = std::fs::read_to_string(&path).unwrap_or_default();
+        assert!(
+            checked_in == generated,
+            "{} is stale, run `UPDATE_BINDINGS=1 cargo test bindings` in src-tauri",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,305 @@
+// src-tauri/src/bundle_localization.rs
+//
+// Localized names of .app bundles, read from the `InfoPlist.strings` file of
+// the `.lproj` folder that best matches the user's preferred languages.
+// `.strings` files come as binary or XML plists, or as old-style text in
+// UTF-8 or UTF-16; everything is parsed with the plist crate, so this runs
+// (and is tested) on any OS.
+
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use plist::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+const STRINGS_FILE_NAME: &str = "InfoPlist.strings";
+// Keys of InfoPlist.strings that name the app, best first
+const NAME_KEYS: [&str; 2] = ["CFBundleDisplayName", "CFBundleName"];
+// Folder names of old bundles, before ISO language codes were used
+const LEGACY_LPROJ_NAMES: [(&str, &str); 8] = [
+    ("en", "English"),
+    ("de", "German"),
+    ("fr", "French"),
+    ("ja", "Japanese"),
+    ("es", "Spanish"),
+    ("it", "Italian"),
+    ("nl", "Dutch"),
+    ("pt", "Portuguese"),
+];
+
+// The user's languages in order of preference, e.g. ["de-DE", "en-US"]. Read
+// from AppleLanguages in the global preferences, falling back to $LANG.
+pub fn preferred_languages() -> Vec<String> {
+    let apple_languages = dirs::home_dir()
+        .map(|home| home.join("Library/Preferences/.GlobalPreferences.plist"))
+        .and_then(|path| Value::from_file(path).ok())
+        .and_then(|preferences| {
+            let languages = preferences.as_dictionary()?.get("AppleLanguages")?.as_array()?.clone();
+            Some(
+                languages
+                    .into_iter()
+                    .filter_map(|language| language.into_string())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .unwrap_or_default();
+    if !apple_languages.is_empty() {
+        return apple_languages;
+    }
+
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        // de_DE.UTF-8@euro -> de-DE
+        .map(|value| value.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+        .into_iter()
+        .collect()
+}
+
+// Localized names of bundles, given as bundle path -> Info.plist path and
+// returned by the same bundle path. Bundles without a localized name are left out.
+pub fn localized_app_names(info_paths: &HashMap<String, String>, languages: &[String]) -> HashMap<String, String> {
+    info_paths
+        .iter()
+        .filter_map(|(app_path, info_path)| {
+            let name = localized_bundle_name(Path::new(info_path), languages)?;
+            Some((app_path.clone(), name))
+        })
+        .collect()
+}
+
+// Localized name of the bundle with this Info.plist. Regular bundles keep
+// their .lproj folders in Contents/Resources, wrapped iOS apps next to the
+// Info.plist.
+fn localized_bundle_name(info_path: &Path, languages: &[String]) -> Option<String> {
+    let resources_dir = resources_dir(info_path)?;
+
+    let development_region = Value::from_file(info_path).ok().and_then(|info| {
+        info.as_dictionary()?
+            .get("CFBundleDevelopmentRegion")?
+            .as_string()
+            .map(str::to_string)
+    });
+
+    let localizations = localization_dirs(&resources_dir);
+    let mut wanted: Vec<&str> = languages.iter().map(String::as_str).collect();
+    wanted.extend(development_region.as_deref());
+    wanted.push("en");
+
+    wanted
+        .iter()
+        .flat_map(|language| language_candidates(language))
+        .find_map(|candidate| {
+            let (_, directory) = localizations.iter().find(|(name, _)| *name == candidate)?;
+            let strings = parse_strings(&fs::read(directory.join(STRINGS_FILE_NAME)).ok()?)?;
+            NAME_KEYS.iter().find_map(|key| strings.get(*key).cloned())
+        })
+        .filter(|name| !name.trim().is_empty())
+}
+
+// Files a localized name is read from, for callers caching it: every
+// InfoPlist.strings and the folder holding the .lproj folders, which changes
+// when one is added or removed
+pub fn localization_sources(info_path: &Path) -> Vec<PathBuf> {
+    let Some(resources_dir) = resources_dir(info_path) else {
+        return Vec::new();
+    };
+
+    let mut sources: Vec<PathBuf> = localization_dirs(&resources_dir)
+        .into_iter()
+        .map(|(_, directory)| directory.join(STRINGS_FILE_NAME))
+        .collect();
+    sources.sort();
+    sources.insert(0, resources_dir);
+    sources
+}
+
+fn resources_dir(info_path: &Path) -> Option<PathBuf> {
+    let bundle_dir = info_path.parent()?;
+    Some(if bundle_dir.ends_with("Contents") {
+        bundle_dir.join("Resources")
+    } else {
+        bundle_dir.to_path_buf()
+    })
+}
+
+// `.lproj` folders with an InfoPlist.strings, by normalized language name
+fn localization_dirs(resources_dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(resources_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = path.file_name()?.to_str()?.strip_suffix(".lproj")?.to_string();
+            path.join(STRINGS_FILE_NAME)
+                .is_file()
+                .then(|| (normalize_language(&name), path))
+        })
+        .collect()
+}
+
+// Folder names to try for a language, most specific first:
+// zh-Hans-CN -> zh-hans-cn, zh-hans, zh
+fn language_candidates(language: &str) -> Vec<String> {
+    let language = normalize_language(language);
+    let mut candidates = Vec::new();
+    let mut current = language.as_str();
+    loop {
+        candidates.push(current.to_string());
+        match current.rsplit_once('-') {
+            Some((shorter, _)) => current = shorter,
+            None => break,
+        }
+    }
+
+    if let Some((_, legacy)) = LEGACY_LPROJ_NAMES.iter().find(|(code, _)| *code == current) {
+        candidates.push(normalize_language(legacy));
+    }
+    candidates
+}
+
+fn normalize_language(language: &str) -> String {
+    language.replace('_', "-").to_lowercase()
+}
+
+// String entries of a .strings file
+fn parse_strings(data: &[u8]) -> Option<HashMap<String, String>> {
+    let value = if data.starts_with(b"bplist") || data.starts_with(b"<?xml") || data.starts_with(b"<plist") {
+        Value::from_reader(Cursor::new(data)).ok()?
+    } else {
+        // Text .strings files are a dictionary without the outer braces. The
+        // ASCII reader takes other bytes as NeXTSTEP characters, so anything
+        // beyond ASCII is passed as \U escapes.
+        let text = escape_non_ascii(&decode_text(data)?);
+        Value::from_reader_ascii(format!("{{{}}}", text).as_bytes())
+            .or_else(|_| Value::from_reader_ascii(text.as_bytes()))
+            .ok()?
+    };
+
+    Some(
+        value
+            .into_dictionary()?
+            .into_iter()
+            .filter_map(|(key, value)| Some((key, value.into_string()?)))
+            .collect(),
+    )
+}
+
+fn escape_non_ascii(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        if character.is_ascii() {
+            escaped.push(character);
+        } else {
+            for unit in character.encode_utf16(&mut [0; 2]) {
+                escaped.push_str(&format!("\\U{:04x}", unit));
+            }
+        }
+    }
+    escaped
+}
+
+// UTF-16 with or without byte order mark, or UTF-8
+fn decode_text(data: &[u8]) -> Option<String> {
+    let utf16 = |data: &[u8], little_endian: bool| {
+        let units: Vec<u16> = data
+            .chunks_exact(2)
+            .map(|pair| {
+                if little_endian {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                }
+            })
+            .collect();
+        String::from_utf16(&units).ok()
+    };
+
+    match data {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, true),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, false),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8(rest.to_vec()).ok(),
+        [0, first, ..] if *first != 0 => utf16(data, false),
+        [first, 0, ..] if *first != 0 => utf16(data, true),
+        _ => String::from_utf8(data.to_vec()).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/bundles").join(name)
+    }
+
+    fn name_for(languages: &[&str]) -> Option<String> {
+        let languages: Vec<String> = languages.iter().map(|l| l.to_string()).collect();
+        localized_bundle_name(&fixture("Localized.app/Contents/Info.plist"), &languages)
+    }
+
+    #[test]
+    fn picks_the_first_matching_language() {
+        // UTF-16 LE text
+        assert_eq!(name_for(&["de-DE", "en"]).as_deref(), Some("Lokalisiert"));
+        // Binary plist, script subtag
+        assert_eq!(name_for(&["zh-Hans-CN"]).as_deref(), Some("本地化"));
+        // XML plist with only CFBundleName, underscore folder name
+        assert_eq!(name_for(&["pt-BR"]).as_deref(), Some("Localizado"));
+        // Legacy folder name, UTF-16 BE text without byte order mark
+        assert_eq!(name_for(&["fr-CA"]).as_deref(), Some("Localisé"));
+        // Unknown language falls back to the development region
+        assert_eq!(name_for(&["sv-SE"]).as_deref(), Some("Localized (English)"));
+    }
+
+    #[test]
+    fn matches_names_to_bundle_paths() {
+        let info_paths: HashMap<String, String> = ["Unlocalized.app", "Localized.app", "Missing.app"]
+            .iter()
+            .map(|name| {
+                let info_path = fixture(name).join("Contents/Info.plist");
+                (name.to_string(), info_path.to_string_lossy().into_owned())
+            })
+            .collect();
+        let names = localized_app_names(&info_paths, &["de".to_string()]);
+
+        assert_eq!(names.len(), 1);
+        assert_eq!(names.get("Localized.app").map(String::as_str), Some("Lokalisiert"));
+    }
+
+    #[test]
+    fn lists_the_files_names_are_read_from() {
+        let resources = fixture("Localized.app/Contents/Resources");
+        let sources = localization_sources(&fixture("Localized.app/Contents/Info.plist"));
+
+        let expected: Vec<PathBuf> = std::iter::once(resources.clone())
+            .chain(
+                ["French", "de", "en", "pt_BR", "zh-Hans"]
+                    .iter()
+                    .map(|name| resources.join(format!("{}.lproj", name)).join(STRINGS_FILE_NAME)),
+            )
+            .collect();
+        assert_eq!(sources, expected);
+        assert_eq!(
+            localization_sources(&fixture("Unlocalized.app/Contents/Info.plist")),
+            [fixture("Unlocalized.app/Contents/Resources")]
+        );
+    }
+
+    #[test]
+    fn parses_text_strings_with_comments() {
+        let strings = parse_strings(
+            "/* Localized versions of Info.plist keys */\n\"CFBundleName\" = \"Größe\";\n// Line comment\nCFBundleDisplayName = \"Display \\\"Name\\\" 🚀\";\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(strings["CFBundleName"], "Größe");
+        assert_eq!(strings["CFBundleDisplayName"], "Display \"Name\" 🚀");
+    }
+}
@@ -0,0 +1,1814 @@
+// src-tauri/src/caldav.rs
+//
+// CalDAV (RFC 4791) accounts such as Nextcloud, Fastmail or Radicale. Their
+// calendars show up next to the native ones in `get_calendars` and
+// `get_calendar_events`, identified by their collection URL. Adding an
+// account finds the calendars through the current-user-principal and its
+// calendar-home-set (RFC 6764); the password goes to the keyring. The list
+// of calendars is saved with the account and refreshed in the background at
+// startup or on demand, so listing them never waits for a server. Calendars
+// that support sync-collection (RFC 6578) are mirrored into a local cache
+// that only fetches what changed since the last sync token and stays usable
+// offline; others are asked for each range with a calendar-query REPORT.
+// Events are read and expanded by ics.rs. Created events are PUT as a
+// resource of their own, edits and deletions find the event's resource by
+// UID and only go through if nobody changed it in the meantime (ETags).
+
+use chrono::{DateTime, TimeZone, Utc};
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager, Runtime, State};
+use tauri_plugin_http::reqwest::{header, redirect, Client, Method, StatusCode};
+use tokio::sync::Mutex;
+use url::Url;
+
+use crate::calendar_utils::{CalendarInfo, EventDraft, EventInfo};
+use crate::error::{Error, ErrorCode, Result};
+use crate::ics::{self, new_event_uid, normalize_color, parse_rfc3339, IcsCalendar, DEFAULT_COLOR};
+use crate::keyring_utils;
+
+const STORE_FILE_NAME: &str = "caldav.json";
+const STORE_VERSION: u32 = 1;
+// Passwords are stored under this keyring service, one entry per account id
+const KEYRING_SERVICE: &str = "grinta-caldav";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 5;
+// Calendar objects fetched per calendar-multiget REPORT
+const MULTIGET_BATCH_SIZE: usize = 100;
+const XML_CONTENT_TYPE: &str = "application/xml; charset=utf-8";
+const ICALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+
+const PRINCIPAL_PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:current-user-principal/>
+    <c:calendar-home-set/>
+  </d:prop>
+</d:propfind>"#;
+
+const CALENDARS_PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:a="http://apple.com/ns/ical/">
+  <d:prop>
+    <d:resourcetype/>
+    <d:displayname/>
+    <a:calendar-color/>
+    <c:supported-calendar-component-set/>
+    <d:sync-token/>
+  </d:prop>
+</d:propfind>"#;
+
+// An account as the settings show it
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CalDavAccount {
+    // username@host, also the keyring account of the password
+    pub id: String,
+    pub server_url: String,
+    pub username: String,
+    pub calendars: Vec<CalendarInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StoredAccount {
+    id: String,
+    server_url: String,
+    username: String,
+    home_url: String,
+    calendars: Vec<RemoteCalendar>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RemoteCalendar {
+    url: String,
+    title: String,
+    color: String,
+    supports_sync: bool,
+    // Token of the last sync, None before the first
+    sync_token: Option<String>,
+    // Calendar objects by URL, as of `sync_token`
+    items: HashMap<String, CachedItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedItem {
+    etag: Option<String>,
+    data: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredAccounts {
+    version: u32,
+    accounts: Vec<StoredAccount>,
+}
+
+// Calendars of CalDAV accounts are identified by their URL, EventKit ones by a UUID
+pub fn is_caldav_calendar(calendar_id: &str) -> bool {
+    calendar_id.starts_with("https://") || calendar_id.starts_with("http://")
+}
+
+#[derive(Default)]
+struct CalDavStore {
+    accounts: Vec<StoredAccount>,
+    // Unset until `start` ran, and in tests
+    path: Option<PathBuf>,
+}
+
+impl CalDavStore {
+    fn load(path: &Path) -> Result<Vec<StoredAccount>> {
+        let data = fs::read(path)
+            .map_err(|e| Error::io("Failed to read CalDAV accounts").with_details(e))?;
+        let stored: StoredAccounts = serde_json::from_slice(&data)
+            .map_err(|e| Error::io("Failed to parse CalDAV accounts").with_details(e))?;
+        if stored.version != STORE_VERSION {
+            return Err(Error::io(format!(
+                "Unsupported CalDAV accounts version: {}",
+                stored.version
+            )));
+        }
+        Ok(stored.accounts)
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let stored = StoredAccounts {
+            version: STORE_VERSION,
+            accounts: self.accounts.clone(),
+        };
+        let data = serde_json::to_vec(&stored)
+            .map_err(|e| Error::internal("Failed to serialize CalDAV accounts").with_details(e))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| Error::io("Failed to create CalDAV directory").with_details(e))?;
+        }
+        fs::write(path, data)
+            .map_err(|e| Error::io("Failed to save CalDAV accounts").with_details(e))
+    }
+}
+
+pub struct CalDavState {
+    // Only held briefly, never while waiting for a server
+    store: Mutex<CalDavStore>,
+    // Held across the requests of a sync or write, so two of them never
+    // interleave. They work on copies that are merged back into the store.
+    requests: Mutex<()>,
+}
+
+impl CalDavState {
+    pub fn new() -> Self {
+        Self {
+            store: Mutex::new(CalDavStore::default()),
+            requests: Mutex::new(()),
+        }
+    }
+
+    pub async fn create_event(&self, draft: &EventDraft) -> Result<EventInfo> {
+        let _requests = self.requests.lock().await;
+        let (client, mut calendar) = self.store.lock().await.calendar(&draft.calendar_id)?;
+        let event = calendar.create_event(&client, draft).await;
+        self.store.lock().await.merge(vec![calendar])?;
+        event
+    }
+
+    pub async fn update_event(
+        &self,
+        uid: &str,
+        occurrence_date: DateTime<Utc>,
+        draft: &EventDraft,
+    ) -> Result<EventInfo> {
+        let _requests = self.requests.lock().await;
+        let (client, mut calendar) = self.store.lock().await.calendar(&draft.calendar_id)?;
+        let event = calendar
+            .update_event(&client, uid, occurrence_date, draft)
+            .await;
+        self.store.lock().await.merge(vec![calendar])?;
+        event
+    }
+
+    pub async fn delete_event(&self, calendar_id: &str, uid: &str) -> Result<()> {
+        let _requests = self.requests.lock().await;
+        let (client, mut calendar) = self.store.lock().await.calendar(calendar_id)?;
+        let deleted = calendar.delete_event(&client, uid).await;
+        self.store.lock().await.merge(vec![calendar])?;
+        deleted
+    }
+
+    pub async fn has_accounts(&self) -> bool {
+        !self.store.lock().await.accounts.is_empty()
+    }
+
+    // Calendars of every account as of the last refresh
+    pub async fn calendars(&self) -> Vec<CalendarInfo> {
+        let store = self.store.lock().await;
+        store
+            .accounts
+            .iter()
+            .flat_map(StoredAccount::calendar_infos)
+            .collect()
+    }
+
+    // Takes over calendars added, renamed or removed on the servers that can
+    // be reached. The servers are asked without holding the lock, so the
+    // cached calendars and events stay available in the meantime.
+    pub async fn refresh_calendars(&self) {
+        let accounts: Vec<_> = {
+            let store = self.store.lock().await;
+            store
+                .accounts
+                .iter()
+                .map(|account| {
+                    (
+                        account.id.clone(),
+                        account.home_url.clone(),
+                        account.client(),
+                    )
+                })
+                .collect()
+        };
+        let mut listed = Vec::new();
+        for (id, home_url, client) in accounts {
+            let calendars = match (client, parse_url(&home_url)) {
+                (Ok(client), Ok(home)) => client.list_calendars(&home).await,
+                (Err(e), _) | (_, Err(e)) => Err(e),
+            };
+            match calendars {
+                Ok(calendars) => listed.push((id, calendars)),
+                Err(e) => eprintln!("Failed to refresh calendars of {}: {}", id, e),
+            }
+        }
+
+        let mut store = self.store.lock().await;
+        for (id, calendars) in listed {
+            // Unless the account was removed in the meantime
+            if let Some(account) = store.accounts.iter_mut().find(|account| account.id == id) {
+                account.replace_calendars(calendars);
+            }
+        }
+        if let Err(e) = store.save() {
+            eprintln!("{}", e);
+        }
+    }
+
+    // Events of the accounts that answered. An account that fails is left
+    // out; only when all of them do is the first error returned.
+    pub async fn events(
+        &self,
+        calendar_ids: &[String],
+        start_date_iso: &str,
+        end_date_iso: &str,
+    ) -> Result<Vec<EventInfo>> {
+        let start = parse_rfc3339(start_date_iso)?;
+        let end = parse_rfc3339(end_date_iso)?;
+
+        let _requests = self.requests.lock().await;
+        let mut accounts: Vec<StoredAccount> = {
+            let store = self.store.lock().await;
+            store
+                .accounts
+                .iter()
+                .filter(|account| {
+                    account
+                        .calendars
+                        .iter()
+                        .any(|calendar| calendar_ids.contains(&calendar.url))
+                })
+                .cloned()
+                .collect()
+        };
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+        let mut answered = false;
+        for account in &mut accounts {
+            let account_events = match account.client() {
+                Ok(client) => {
+                    account
+                        .events(&client, calendar_ids, start, end, &chrono::Local)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+            match account_events {
+                Ok(account_events) => {
+                    events.extend(account_events);
+                    answered = true;
+                }
+                Err(e) => {
+                    eprintln!("Failed to read events of {}: {}", account.id, e);
+                    errors.push(e);
+                }
+            }
+        }
+        // Keep what the syncs fetched even if an account failed
+        let synced = accounts
+            .into_iter()
+            .flat_map(|account| account.calendars)
+            .filter(|calendar| calendar_ids.contains(&calendar.url))
+            .collect();
+        if let Err(e) = self.store.lock().await.merge(synced) {
+            eprintln!("{}", e);
+        }
+        if !answered && !errors.is_empty() {
+            return Err(errors.swap_remove(0));
+        }
+        events.sort_by(|a, b| a.start_date.cmp(&b.start_date));
+        Ok(events)
+    }
+}
+
+impl CalDavStore {
+    // A copy of the calendar `calendar_id` with a client for its account
+    fn calendar(&self, calendar_id: &str) -> Result<(DavClient, RemoteCalendar)> {
+        self.accounts
+            .iter()
+            .find_map(|account| {
+                let calendar = account
+                    .calendars
+                    .iter()
+                    .find(|calendar| calendar.url == calendar_id)?;
+                Some(account.client().map(|client| (client, calendar.clone())))
+            })
+            .unwrap_or_else(|| {
+                Err(Error::not_found(format!(
+                    "No CalDAV calendar {}",
+                    calendar_id
+                )))
+            })
+    }
+
+    // Takes over the caches of copies that synced or wrote, unless their
+    // calendar was removed in the meantime
+    fn merge(&mut self, calendars: Vec<RemoteCalendar>) -> Result<()> {
+        for calendar in calendars {
+            if let Some(known) = self
+                .accounts
+                .iter_mut()
+                .flat_map(|account| account.calendars.iter_mut())
+                .find(|known| known.url == calendar.url)
+            {
+                known.sync_token = calendar.sync_token;
+                known.items = calendar.items;
+            }
+        }
+        self.save()
+    }
+}
+
+impl RemoteCalendar {
+    async fn create_event(&mut self, client: &DavClient, draft: &EventDraft) -> Result<EventInfo> {
+        let uid = new_event_uid();
+        let data = ics::add_event(None, &uid, draft, Utc::now(), &chrono::Local)?;
+        let url = parse_url(&self.url)?
+            .join(&format!("{}.ics", uid))
+            .map_err(|e| Error::internal("Invalid event URL").with_details(e))?;
+
+        let etag = client.put(&url, &data, None).await?;
+        self.cache(&url, etag, Some(data));
+        draft.event_info(uid)
+    }
+
+    async fn update_event(
+        &mut self,
+        client: &DavClient,
+        uid: &str,
+        occurrence_date: DateTime<Utc>,
+        draft: &EventDraft,
+    ) -> Result<EventInfo> {
+        let resource = client.find_event(self, uid).await?;
+        let data = ics::update_event(
+            &resource.data,
+            uid,
+            occurrence_date,
+            draft,
+            Utc::now(),
+            &chrono::Local,
+        )?;
+
+        let etag = resource.etag.as_deref().unwrap_or("*");
+        let etag = client.put(&resource.url, &data, Some(etag)).await?;
+        self.cache(&resource.url, etag, Some(data));
+        draft.event_info(uid.to_string())
+    }
+
+    // Removes the whole resource, which only holds this event and the
+    // overrides of its occurrences (RFC 4791, 4.1)
+    async fn delete_event(&mut self, client: &DavClient, uid: &str) -> Result<()> {
+        let resource = client.find_event(self, uid).await?;
+        client.delete(&resource).await?;
+        self.cache(&resource.url, None, None);
+        Ok(())
+    }
+
+    // Keeps the cache of a synced calendar in line with what was written
+    // The calendar objects to read events from: the synced cache, or what
+    // the server has in `start..end` if it can't sync
+    async fn items(
+        &mut self,
+        client: &DavClient,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        if !self.supports_sync {
+            return client.query(self, start, end).await;
+        }
+        match client.sync(self).await {
+            Ok(()) => {}
+            Err(e) if e.code == ErrorCode::Network && self.sync_token.is_some() => {
+                eprintln!("Using cached events of {}: {}", self.url, e);
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(self.items.values().map(|item| item.data.clone()).collect())
+    }
+
+    fn cache(&mut self, url: &Url, etag: Option<String>, data: Option<String>) {
+        if !self.supports_sync {
+            return;
+        }
+        match data {
+            Some(data) => {
+                self.items
+                    .insert(url.to_string(), CachedItem { etag, data });
+            }
+            None => {
+                self.items.remove(url.as_str());
+            }
+        }
+    }
+}
+
+impl StoredAccount {
+    fn info(&self) -> CalDavAccount {
+        CalDavAccount {
+            id: self.id.clone(),
+            server_url: self.server_url.clone(),
+            username: self.username.clone(),
+            calendars: self.calendar_infos().collect(),
+        }
+    }
+
+    fn calendar_infos(&self) -> impl Iterator<Item = CalendarInfo> + '_ {
+        self.calendars.iter().map(|calendar| CalendarInfo {
+            identifier: calendar.url.clone(),
+            title: calendar.title.clone(),
+            color: calendar.color.clone(),
+        })
+    }
+
+    fn client(&self) -> Result<DavClient> {
+        let password = keyring_utils::get_secret(KEYRING_SERVICE.to_string(), self.id.clone())?;
+        DavClient::new(&self.username, &password)
+    }
+
+    // Takes over the calendars listed on the server, keeping the caches of
+    // the ones that were there before
+    fn replace_calendars(&mut self, mut calendars: Vec<RemoteCalendar>) {
+        for calendar in &mut calendars {
+            if let Some(known) = self
+                .calendars
+                .iter_mut()
+                .find(|known| known.url == calendar.url)
+            {
+                calendar.sync_token = known.sync_token.take();
+                calendar.items = std::mem::take(&mut known.items);
+            }
+        }
+        self.calendars = calendars;
+    }
+
+    // Events of the account's calendars among `calendar_ids` that overlap
+    // `start..end`. Synced calendars fall back to their cache when the server
+    // can't be reached. A calendar that fails otherwise is left out; only when
+    // all of them do is the first error returned.
+    async fn events<Tz: TimeZone>(
+        &mut self,
+        client: &DavClient,
+        calendar_ids: &[String],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        local: &Tz,
+    ) -> Result<Vec<EventInfo>> {
+        let mut events = Vec::new();
+        let mut first_error = None;
+        let mut answered = false;
+        for calendar in &mut self.calendars {
+            if !calendar_ids.contains(&calendar.url) {
+                continue;
+            }
+
+            let items = match calendar.items(client, start, end).await {
+                Ok(items) => items,
+                Err(e) => {
+                    eprintln!("Failed to read events of {}: {}", calendar.url, e);
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            answered = true;
+
+            for data in &items {
+                match IcsCalendar::parse(data) {
+                    Ok(ics) => events.extend(ics.events_between(&calendar.url, start, end, local)),
+                    Err(e) => eprintln!("Skipping unreadable event in {}: {}", calendar.url, e),
+                }
+            }
+        }
+        match first_error {
+            Some(e) if !answered => Err(e),
+            _ => {
+                events.sort_by(|a, b| a.start_date.cmp(&b.start_date));
+                Ok(events)
+            }
+        }
+    }
+}
+
+// Finds the calendars of the account at `server_url`, either the server's
+// root or the user's principal or calendar home on it
+async fn discover(client: &DavClient, server_url: &str, username: &str) -> Result<StoredAccount> {
+    let server_url = server_url.trim();
+    let server_url = if server_url.contains("://") {
+        parse_url(server_url)?
+    } else {
+        parse_url(&format!("https://{}", server_url))?
+    };
+
+    let home = client.find_calendar_home(&server_url).await?;
+    let calendars = client.list_calendars(&home).await?;
+    let host = match server_url.port() {
+        Some(port) => format!("{}:{}", server_url.host_str().unwrap_or_default(), port),
+        None => server_url.host_str().unwrap_or_default().to_string(),
+    };
+
+    Ok(StoredAccount {
+        id: format!("{}@{}", username, host),
+        server_url: server_url.to_string(),
+        username: username.to_string(),
+        home_url: home.to_string(),
+        calendars,
+    })
+}
+
+struct DavClient {
+    http: Client,
+    username: String,
+    password: String,
+}
+
+struct DavReply {
+    status: StatusCode,
+    // After redirects
+    url: Url,
+    etag: Option<String>,
+    body: String,
+}
+
+// The calendar object resource that holds an event
+struct EventResource {
+    url: Url,
+    etag: Option<String>,
+    data: String,
+}
+
+impl DavClient {
+    fn new(username: &str, password: &str) -> Result<Self> {
+        // Redirects are followed by hand: reqwest turns PROPFIND into GET on a 301
+        let http = Client::builder()
+            .redirect(redirect::Policy::none())
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| Error::internal("Failed to create HTTP client").with_details(e))?;
+        Ok(Self {
+            http,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    async fn send(&self, method: &str, url: &Url, depth: &str, body: &str) -> Result<DavReply> {
+        self.request(method, url, &[("Depth", depth)], XML_CONTENT_TYPE, body)
+            .await
+    }
+
+    async fn request(
+        &self,
+        method: &str,
+        url: &Url,
+        headers: &[(&str, &str)],
+        content_type: &str,
+        body: &str,
+    ) -> Result<DavReply> {
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|e| Error::internal("Invalid WebDAV method").with_details(e))?;
+        let mut url = url.clone();
+        let origin = url.origin();
+
+        for _ in 0..=MAX_REDIRECTS {
+            let mut request = self
+                .http
+                .request(method.clone(), url.clone())
+                .header(header::CONTENT_TYPE, content_type);
+            // The password only goes to the server the request was meant for
+            if url.origin() == origin {
+                request = request.basic_auth(&self.username, Some(&self.password));
+            }
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+            let response = request.body(body.to_string()).send().await.map_err(|e| {
+                Error::network(format!(
+                    "Could not reach {}",
+                    url.host_str().unwrap_or_default()
+                ))
+                .with_details(e)
+            })?;
+
+            let status = response.status();
+            let location = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok());
+            if let (true, Some(location)) = (status.is_redirection(), location) {
+                url = redirect_target(&url, location)?;
+                continue;
+            }
+
+            let etag = response
+                .headers()
+                .get(header::ETAG)
+                .and_then(|etag| etag.to_str().ok())
+                .map(str::to_string);
+            let body = response.text().await.map_err(|e| {
+                Error::network("Failed to read the CalDAV response").with_details(e)
+            })?;
+            return Ok(DavReply {
+                status,
+                url,
+                etag,
+                body,
+            });
+        }
+        Err(Error::network(format!("Too many redirects from {}", url)))
+    }
+
+    async fn multistatus(
+        &self,
+        method: &str,
+        url: &Url,
+        depth: &str,
+        body: &str,
+    ) -> Result<Multistatus> {
+        let reply = self.send(method, url, depth, body).await?;
+        check_status(&reply)?;
+        Multistatus::parse(&reply.body, &reply.url)
+    }
+
+    // The calendar-home-set of the current user, looked up on the principal
+    // of `server_url` or its /.well-known/caldav
+    async fn find_calendar_home(&self, server_url: &Url) -> Result<Url> {
+        let well_known = server_url.join("/.well-known/caldav").ok();
+        let candidates = if server_url.path() == "/" {
+            [well_known, Some(server_url.clone())]
+        } else {
+            [Some(server_url.clone()), well_known]
+        };
+
+        let mut last_error = None;
+        for candidate in candidates.into_iter().flatten() {
+            let multistatus = match self
+                .multistatus("PROPFIND", &candidate, "0", PRINCIPAL_PROPFIND)
+                .await
+            {
+                Ok(multistatus) => multistatus,
+                Err(e) if e.code == ErrorCode::NotAuthorized => return Err(e),
+                Err(e) => {
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            let Some(response) = multistatus.responses.first() else {
+                continue;
+            };
+            if let Some(home) = response.href("calendar-home-set") {
+                return parse_url(home);
+            }
+            if let Some(principal) = response.href("current-user-principal") {
+                let principal = parse_url(principal)?;
+                let multistatus = self
+                    .multistatus("PROPFIND", &principal, "0", PRINCIPAL_PROPFIND)
+                    .await?;
+                if let Some(home) = multistatus
+                    .responses
+                    .first()
+                    .and_then(|response| response.href("calendar-home-set"))
+                {
+                    return parse_url(home);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            Error::not_found(format!("No CalDAV calendars found at {}", server_url))
+        }))
+    }
+
+    // Calendars in `home` that can hold events
+    async fn list_calendars(&self, home: &Url) -> Result<Vec<RemoteCalendar>> {
+        let multistatus = self
+            .multistatus("PROPFIND", home, "1", CALENDARS_PROPFIND)
+            .await?;
+        Ok(multistatus
+            .responses
+            .iter()
+            .filter(|response| response.is_event_calendar())
+            .map(|response| RemoteCalendar {
+                url: response.href.clone(),
+                title: response
+                    .text("displayname")
+                    .unwrap_or_else(|| last_segment(&response.href)),
+                color: response
+                    .text("calendar-color")
+                    .and_then(|color| normalize_color(&color))
+                    .unwrap_or_else(|| DEFAULT_COLOR.to_string()),
+                supports_sync: response.props.contains_key("sync-token"),
+                sync_token: None,
+                items: HashMap::new(),
+            })
+            .collect())
+    }
+
+    // Brings `calendar.items` up to date with a sync-collection REPORT, then
+    // fetches what changed. Starts over when the server no longer accepts
+    // the token.
+    async fn sync(&self, calendar: &mut RemoteCalendar) -> Result<()> {
+        let url = parse_url(&calendar.url)?;
+        let mut reply = self
+            .send(
+                "REPORT",
+                &url,
+                "1",
+                &sync_collection_body(calendar.sync_token.as_deref()),
+            )
+            .await?;
+        if calendar.sync_token.is_some()
+            && matches!(reply.status, StatusCode::FORBIDDEN | StatusCode::CONFLICT)
+        {
+            calendar.sync_token = None;
+            calendar.items.clear();
+            reply = self
+                .send("REPORT", &url, "1", &sync_collection_body(None))
+                .await?;
+        }
+        check_status(&reply)?;
+        let multistatus = Multistatus::parse(&reply.body, &reply.url)?;
+
+        let mut changed = Vec::new();
+        for response in &multistatus.responses {
+            if response.href == calendar.url || response.href.ends_with('/') {
+                continue;
+            }
+            if response.status == Some(404) {
+                calendar.items.remove(&response.href);
+                continue;
+            }
+            let etag = response.text("getetag");
+            let unchanged = calendar
+                .items
+                .get(&response.href)
+                .is_some_and(|item| etag.is_some() && item.etag == etag);
+            if !unchanged {
+                changed.push(response.href.clone());
+            }
+        }
+
+        for batch in changed.chunks(MULTIGET_BATCH_SIZE) {
+            let multistatus = self
+                .multistatus("REPORT", &url, "1", &multiget_body(batch))
+                .await?;
+            for response in multistatus.responses {
+                if let Some(data) = response.text("calendar-data") {
+                    calendar.items.insert(
+                        response.href.clone(),
+                        CachedItem {
+                            etag: response.text("getetag"),
+                            data,
+                        },
+                    );
+                }
+            }
+        }
+
+        calendar.sync_token = multistatus.sync_token;
+        Ok(())
+    }
+
+    // Calendar objects with events in `start..end`, for servers without sync
+    async fn query(
+        &self,
+        calendar: &RemoteCalendar,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<String>> {
+        let url = parse_url(&calendar.url)?;
+        let multistatus = self
+            .multistatus("REPORT", &url, "1", &calendar_query_body(start, end))
+            .await?;
+        Ok(multistatus
+            .responses
+            .iter()
+            .filter_map(|response| response.text("calendar-data"))
+            .collect())
+    }
+
+    // The resource of the event `uid` in `calendar`
+    async fn find_event(&self, calendar: &RemoteCalendar, uid: &str) -> Result<EventResource> {
+        let url = parse_url(&calendar.url)?;
+        let multistatus = self
+            .multistatus("REPORT", &url, "1", &uid_query_body(uid))
+            .await?;
+        multistatus
+            .responses
+            .into_iter()
+            .find_map(|response| {
+                Some(EventResource {
+                    url: Url::parse(&response.href).ok()?,
+                    etag: response.text("getetag"),
+                    data: response.text("calendar-data")?,
+                })
+            })
+            .ok_or_else(|| Error::not_found(format!("No event {} in {}", uid, calendar.title)))
+    }
+
+    // Stores `data` at `url`: only if nothing is there yet without an
+    // `etag`, only if nobody changed it since otherwise. Returns the new ETag
+    // if the server sent one.
+    async fn put(&self, url: &Url, data: &str, etag: Option<&str>) -> Result<Option<String>> {
+        let condition = match etag {
+            Some(etag) => ("If-Match", etag),
+            None => ("If-None-Match", "*"),
+        };
+        let reply = self
+            .request("PUT", url, &[condition], ICALENDAR_CONTENT_TYPE, data)
+            .await?;
+        check_status(&reply)?;
+        Ok(reply.etag)
+    }
+
+    async fn delete(&self, resource: &EventResource) -> Result<()> {
+        let condition: Vec<(&str, &str)> = resource
+            .etag
+            .as_deref()
+            .map(|etag| ("If-Match", etag))
+            .into_iter()
+            .collect();
+        let reply = self
+            .request("DELETE", &resource.url, &condition, XML_CONTENT_TYPE, "")
+            .await?;
+        check_status(&reply)
+    }
+}
+
+// Where a redirect from `url` to `location` leads. Redirects from https to
+// plain http are refused, they would expose everything sent afterwards.
+fn redirect_target(url: &Url, location: &str) -> Result<Url> {
+    let target = url
+        .join(location)
+        .map_err(|e| Error::network("Invalid redirect").with_details(e))?;
+    if target.scheme() != "https" && (url.scheme() == "https" || target.scheme() != "http") {
+        return Err(Error::network(format!(
+            "Refusing the redirect from {} to {}",
+            url, target
+        )));
+    }
+    Ok(target)
+}
+
+fn check_status(reply: &DavReply) -> Result<()> {
+    match reply.status {
+        status if status.is_success() => Ok(()),
+        StatusCode::UNAUTHORIZED => Err(Error::not_authorized(
+            "The CalDAV server rejected the username or password",
+        )
+        .with_details(&reply.url)),
+        // Also read-only calendars when writing
+        StatusCode::FORBIDDEN => Err(Error::not_authorized(format!(
+            "The CalDAV server denied access to {}",
+            reply.url
+        ))),
+        StatusCode::PRECONDITION_FAILED => Err(Error::conflict(
+            "The event was changed on the server in the meantime, reload it and try again",
+        )
+        .with_details(&reply.url)),
+        StatusCode::NOT_FOUND => Err(Error::not_found(format!("Nothing found at {}", reply.url))),
+        status => Err(
+            Error::network(format!("CalDAV request failed with {}", status))
+                .with_details(&reply.url),
+        ),
+    }
+}
+
+fn sync_collection_body(sync_token: Option<&str>) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<d:sync-collection xmlns:d="DAV:">
+  <d:sync-token>{}</d:sync-token>
+  <d:sync-level>1</d:sync-level>
+  <d:prop>
+    <d:getetag/>
+  </d:prop>
+</d:sync-collection>"#,
+        escape(sync_token.unwrap_or_default())
+    )
+}
+
+fn multiget_body(urls: &[String]) -> String {
+    let hrefs: String = urls
+        .iter()
+        .map(|url| {
+            // Servers expect paths
+            let path = Url::parse(url).map_or_else(|_| url.clone(), |url| url.path().to_string());
+            format!("\n  <d:href>{}</d:href>", escape(&path))
+        })
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>{}
+</c:calendar-multiget>"#,
+        hrefs
+    )
+}
+
+fn uid_query_body(uid: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:prop-filter name="UID">
+          <c:text-match collation="i;octet">{}</c:text-match>
+        </c:prop-filter>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        escape(uid)
+    )
+}
+
+fn calendar_query_body(start: DateTime<Utc>, end: DateTime<Utc>) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop>
+    <d:getetag/>
+    <c:calendar-data/>
+  </d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        start.format("%Y%m%dT%H%M%SZ"),
+        end.format("%Y%m%dT%H%M%SZ")
+    )
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url)
+        .map_err(|e| Error::invalid_argument(format!("Invalid URL: {}", url)).with_details(e))
+}
+
+fn last_segment(url: &str) -> String {
+    url.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or(url)
+        .to_string()
+}
+
+// A WebDAV multistatus (RFC 4918) reply
+#[derive(Debug, Default)]
+struct Multistatus {
+    responses: Vec<DavResponse>,
+    // From sync-collection reports
+    sync_token: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct DavResponse {
+    // Absolute URL
+    href: String,
+    // Status of the response as a whole, 404 for items deleted since a sync token
+    status: Option<u16>,
+    // Properties the server returned with a 2xx status, by element name
+    props: HashMap<String, DavProperty>,
+}
+
+#[derive(Debug, Default)]
+struct DavProperty {
+    text: String,
+    hrefs: Vec<String>,
+    // Names of the child elements, like `calendar` in resourcetype
+    children: Vec<String>,
+    // Their `name` attributes, like VEVENT in supported-calendar-component-set
+    names: Vec<String>,
+}
+
+impl DavResponse {
+    fn text(&self, name: &str) -> Option<String> {
+        self.props
+            .get(name)
+            .map(|property| property.text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    }
+
+    fn href(&self, name: &str) -> Option<&str> {
+        self.props
+            .get(name)
+            .and_then(|property| property.hrefs.first())
+            .map(String::as_str)
+    }
+
+    // Servers that don't list the supported components accept all of them
+    fn is_event_calendar(&self) -> bool {
+        let is_calendar = self
+            .props
+            .get("resourcetype")
+            .is_some_and(|property| property.children.iter().any(|child| child == "calendar"));
+        let has_events = self
+            .props
+            .get("supported-calendar-component-set")
+            .is_none_or(|property| {
+                property
+                    .names
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case("VEVENT"))
+            });
+        is_calendar && has_events
+    }
+}
+
+impl Multistatus {
+    // Elements are matched by local name, whatever prefixes the server uses.
+    // Hrefs are resolved against `base`.
+    fn parse(xml: &str, base: &Url) -> Result<Self> {
+        let mut reader = Reader::from_str(xml);
+        let mut parser = MultistatusParser::default();
+        loop {
+            let event = reader
+                .read_event()
+                .map_err(|e| Error::network("Invalid CalDAV response").with_details(e))?;
+            match event {
+                Event::Start(element) => parser.open(&element),
+                Event::Empty(element) => {
+                    parser.open(&element);
+                    parser.close(base);
+                }
+                Event::End(_) => parser.close(base),
+                Event::Text(text) => {
+                    let text = text
+                        .unescape()
+                        .map_err(|e| Error::network("Invalid CalDAV response").with_details(e))?;
+                    parser.text(&text);
+                }
+                Event::CData(data) => parser.text(&String::from_utf8_lossy(&data)),
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(parser.multistatus)
+    }
+}
+
+#[derive(Default)]
+struct MultistatusParser {
+    multistatus: Multistatus,
+    // Local names of the open elements
+    path: Vec<String>,
+    response: DavResponse,
+    propstat: Vec<(String, DavProperty)>,
+    propstat_status: Option<u16>,
+    // The property being read, with the depth of its element
+    property: Option<(usize, String, DavProperty)>,
+}
+
+impl MultistatusParser {
+    fn open(&mut self, element: &BytesStart) {
+        let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+        let depth = self.path.len();
+        match &mut self.property {
+            Some((property_depth, _, property)) if depth == *property_depth + 1 => {
+                property.children.push(name.clone());
+                property.names.extend(
+                    element
+                        .attributes()
+                        .flatten()
+                        .find(|attribute| attribute.key.local_name().as_ref() == b"name")
+                        .map(|attribute| String::from_utf8_lossy(&attribute.value).into_owned()),
+                );
+            }
+            Some(_) => {}
+            None if ancestor(&self.path, 0) == Some("prop")
+                && ancestor(&self.path, 1) == Some("propstat") =>
+            {
+                self.property = Some((depth, name.clone(), DavProperty::default()));
+            }
+            None => {}
+        }
+        self.path.push(name);
+    }
+
+    fn text(&mut self, text: &str) {
+        let (current, parent) = (ancestor(&self.path, 0), ancestor(&self.path, 1));
+        if let Some((_, _, property)) = &mut self.property {
+            match current {
+                Some("href") => property.hrefs.push(text.trim().to_string()),
+                _ => property.text.push_str(text),
+            }
+            return;
+        }
+        match (parent, current) {
+            (Some("response"), Some("href")) => self.response.href.push_str(text.trim()),
+            (Some("response"), Some("status")) => self.response.status = parse_status(text),
+            (Some("propstat"), Some("status")) => self.propstat_status = parse_status(text),
+            (Some("multistatus"), Some("sync-token")) => {
+                self.multistatus.sync_token = Some(text.trim().to_string())
+            }
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, base: &Url) {
+        let Some(name) = self.path.pop() else {
+            return;
+        };
+        if let Some(depth) = self.property.as_ref().map(|(depth, ..)| *depth) {
+            if self.path.len() == depth {
+                if let Some((_, name, property)) = self.property.take() {
+                    self.propstat.push((name, property));
+                }
+            }
+            return;
+        }
+
+        match name.as_str() {
+            "propstat" => {
+                let properties = std::mem::take(&mut self.propstat);
+                if self
+                    .propstat_status
+                    .take()
+                    .is_none_or(|status| (200..300).contains(&status))
+                {
+                    self.response.props.extend(properties);
+                }
+            }
+            "response" => {
+                let mut response = std::mem::take(&mut self.response);
+                for property in response.props.values_mut() {
+                    for href in &mut property.hrefs {
+                        *href = resolve_href(base, href);
+                    }
+                }
+                response.href = resolve_href(base, &response.href);
+                self.multistatus.responses.push(response);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Name of the open element `level` levels up, 0 being the innermost
+fn ancestor(path: &[String], level: usize) -> Option<&str> {
+    path.iter().rev().nth(level).map(String::as_str)
+}
+
+// HTTP/1.1 404 Not Found
+fn parse_status(text: &str) -> Option<u16> {
+    text.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn resolve_href(base: &Url, href: &str) -> String {
+    base.join(href)
+        .map_or_else(|_| href.to_string(), |url| url.to_string())
+}
+
+// Loads the accounts saved before. Called once from the setup hook.
+pub fn start<R: Runtime>(app_handle: &AppHandle<R>) {
+    let path = match app_handle.path().app_data_dir() {
+        Ok(dir) => dir.join(STORE_FILE_NAME),
+        Err(e) => {
+            eprintln!(
+                "Failed to resolve app data directory for CalDAV accounts: {}",
+                e
+            );
+            return;
+        }
+    };
+    let state = app_handle.state::<CalDavState>();
+    // Nothing else holds the lock this early
+    let Ok(mut store) = state.store.try_lock() else {
+        return;
+    };
+    if path.exists() {
+        match CalDavStore::load(&path) {
+            Ok(accounts) => store.accounts = accounts,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    store.path = Some(path);
+    if store.accounts.is_empty() {
+        return;
+    }
+    drop(store);
+
+    // `get_calendars` serves the saved calendars, changes on the servers
+    // show up once this is done
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        app_handle.state::<CalDavState>().refresh_calendars().await;
+    });
+}
+
+#[command]
+#[specta::specta]
+pub async fn add_caldav_account(
+    state: State<'_, CalDavState>,
+    server_url: String,
+    username: String,
+    password: String,
+) -> Result<CalDavAccount> {
+    let client = DavClient::new(&username, &password)?;
+    let account = discover(&client, &server_url, &username).await?;
+    keyring_utils::set_secret(KEYRING_SERVICE.to_string(), account.id.clone(), password)?;
+
+    let info = account.info();
+    let mut store = state.store.lock().await;
+    store.accounts.retain(|known| known.id != account.id);
+    store.accounts.push(account);
+    store.save()?;
+    Ok(info)
+}
+
+#[command]
+#[specta::specta]
+pub async fn list_caldav_accounts(state: State<'_, CalDavState>) -> Result<Vec<CalDavAccount>> {
+    let store = state.store.lock().await;
+    Ok(store.accounts.iter().map(StoredAccount::info).collect())
+}
+
+// Asks the servers for calendars added, renamed or removed since the last refresh
+#[command]
+#[specta::specta]
+pub async fn refresh_caldav_calendars(state: State<'_, CalDavState>) -> Result<Vec<CalDavAccount>> {
+    state.refresh_calendars().await;
+    list_caldav_accounts(state).await
+}
+
+#[command]
+#[specta::specta]
+pub async fn remove_caldav_account(
+    state: State<'_, CalDavState>,
+    account_id: String,
+) -> Result<()> {
+    let mut store = state.store.lock().await;
+    let count = store.accounts.len();
+    store.accounts.retain(|account| account.id != account_id);
+    if store.accounts.len() == count {
+        return Err(Error::not_found(format!(
+            "No CalDAV account {}",
+            account_id
+        )));
+    }
+    store.save()?;
+
+    match keyring_utils::delete_secret(KEYRING_SERVICE.to_string(), account_id) {
+        Err(e) if e.code != ErrorCode::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const USERNAME: &str = "ada";
+    const PASSWORD: &str = "secret";
+
+    fn event(uid: &str, title: &str, start: &str, extra: &str) -> String {
+        format!(
+            "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:{uid}\r\nDTSTART:{start}\r\nDURATION:PT1H\r\n{extra}SUMMARY:{title}\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n"
+        )
+    }
+
+    // Stand-in for a CalDAV server like Radicale: the user has a work
+    // calendar with sync support, a shared one without, and a task list.
+    // `version` switches the work calendar to its state after some edits.
+    struct FakeServer {
+        url: String,
+        version: Arc<AtomicUsize>,
+        // Where requests to /moved/ are redirected
+        redirect_to: Arc<std::sync::Mutex<String>>,
+        // Bodies of the calendar-multiget reports
+        multigets: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    // Events PUT into the work calendar, by path, with their ETag and data
+    type Written = std::sync::Mutex<HashMap<String, (String, String)>>;
+
+    impl FakeServer {
+        fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            let version = Arc::new(AtomicUsize::new(0));
+            let multigets = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let written = Arc::new(Written::default());
+            let redirect_to = Arc::new(std::sync::Mutex::new(String::new()));
+
+            let (server_version, server_multigets, server_redirect_to) =
+                (version.clone(), multigets.clone(), redirect_to.clone());
+            std::thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let (version, multigets, written, redirect_to) = (
+                        server_version.clone(),
+                        server_multigets.clone(),
+                        written.clone(),
+                        server_redirect_to.clone(),
+                    );
+                    std::thread::spawn(move || {
+                        serve(stream, &version, &multigets, &written, &redirect_to)
+                    });
+                }
+            });
+            Self {
+                url,
+                version,
+                redirect_to,
+                multigets,
+            }
+        }
+    }
+
+    fn work_items(version: usize) -> Vec<(&'static str, &'static str, String)> {
+        let planning = event("planning", "Planning", "20240701T090000Z", "");
+        let review = event("review", "Review", "20240702T140000Z", "");
+        let moved = event("review", "Review (moved)", "20240703T140000Z", "");
+        let retro = event(
+            "retro",
+            "Retro",
+            "20240705T150000Z",
+            "RRULE:FREQ=WEEKLY;COUNT=2\r\n",
+        );
+        match version {
+            0 => vec![("a.ics", "\"a1\"", planning), ("b.ics", "\"b1\"", review)],
+            _ => vec![("b.ics", "\"b2\"", moved), ("c.ics", "\"c1\"", retro)],
+        }
+    }
+
+    fn serve(
+        stream: TcpStream,
+        version: &AtomicUsize,
+        multigets: &std::sync::Mutex<Vec<String>>,
+        written: &Written,
+        redirect_to: &std::sync::Mutex<String>,
+    ) {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut stream = stream;
+        loop {
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                return;
+            }
+            let mut headers = HashMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                let (name, value) = line.split_once(':').unwrap();
+                headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+            }
+            let length = headers
+                .get("content-length")
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let body = String::from_utf8(body).unwrap();
+
+            let mut parts = request_line.split_whitespace();
+            let (method, path) = (parts.next().unwrap(), parts.next().unwrap());
+            let depth = headers.get("depth").map_or("0", String::as_str);
+            let expected_auth = format!(
+                "Basic {}",
+                STANDARD.encode(format!("{}:{}", USERNAME, PASSWORD))
+            );
+            let version = version.load(Ordering::SeqCst);
+
+            let (status, extra_header, reply) = if path == "/.well-known/caldav" {
+                (
+                    "301 Moved Permanently",
+                    "Location: /\r\n".to_string(),
+                    String::new(),
+                )
+            } else if path == "/moved/" {
+                (
+                    "307 Temporary Redirect",
+                    format!("Location: {}\r\n", redirect_to.lock().unwrap()),
+                    String::new(),
+                )
+            } else if headers.get("authorization") != Some(&expected_auth) {
+                (
+                    "401 Unauthorized",
+                    "WWW-Authenticate: Basic realm=\"Radicale\"\r\n".to_string(),
+                    String::new(),
+                )
+            } else if method == "PUT" || method == "DELETE" {
+                write(&mut written.lock().unwrap(), method, path, &headers, body)
+            } else {
+                let reply = match (method, path, depth) {
+                    ("PROPFIND", "/", _) => multistatus(&[response("/", "<D:current-user-principal><D:href>/ada/</D:href></D:current-user-principal>")]),
+                    ("PROPFIND", "/ada/", "0") => multistatus(&[response("/ada/", "<C:calendar-home-set><D:href>/ada/</D:href></C:calendar-home-set>")]),
+                    ("PROPFIND", "/ada/", _) => multistatus(&[
+                        response("/ada/", "<D:resourcetype><D:collection/></D:resourcetype>"),
+                        response("/ada/work/", "<D:resourcetype><D:collection/><C:calendar/></D:resourcetype><D:displayname>Work</D:displayname><ICAL:calendar-color>#ff2968ff</ICAL:calendar-color><C:supported-calendar-component-set><C:comp name=\"VEVENT\"/></C:supported-calendar-component-set><D:sync-token>http://radicale.org/ns/sync/0</D:sync-token>"),
+                        response("/ada/shared/", "<D:resourcetype><D:collection/><C:calendar/></D:resourcetype>"),
+                        response("/ada/tasks/", "<D:resourcetype><D:collection/><C:calendar/></D:resourcetype><D:displayname>Tasks</D:displayname><C:supported-calendar-component-set><C:comp name=\"VTODO\"/></C:supported-calendar-component-set>"),
+                    ]),
+                    ("REPORT", "/ada/work/", _) if body.contains("sync-collection") => {
+                        let responses: Vec<String> = if body.contains("<d:sync-token></d:sync-token>") || version == 0 {
+                            work_items(version).iter().map(|(name, etag, _)| response(&format!("/ada/work/{}", name), &format!("<D:getetag>{}</D:getetag>", etag))).collect()
+                        } else {
+                            // Changes since the first sync
+                            vec![
+                                "<D:response><D:href>/ada/work/a.ics</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response>".to_string(),
+                                response("/ada/work/b.ics", "<D:getetag>\"b2\"</D:getetag>"),
+                                response("/ada/work/c.ics", "<D:getetag>\"c1\"</D:getetag>"),
+                            ]
+                        };
+                        multistatus(&responses).replace("</multistatus>", &format!("<sync-token>http://radicale.org/ns/sync/{}</sync-token></multistatus>", version + 1))
+                    }
+                    ("REPORT", "/ada/work/", _) if body.contains("prop-filter") => {
+                        let uid = body.split("i;octet\">").nth(1).unwrap().split('<').next().unwrap();
+                        let responses: Vec<String> = written
+                            .lock()
+                            .unwrap()
+                            .iter()
+                            .filter(|(_, (_, data))| data.contains(&format!("UID:{}\r\n", uid)))
+                            .map(|(path, (etag, data))| response(path, &format!("<D:getetag>{}</D:getetag><C:calendar-data><![CDATA[{}]]></C:calendar-data>", etag, data)))
+                            .collect();
+                        multistatus(&responses)
+                    }
+                    ("REPORT", "/ada/work/", _) => {
+                        multigets.lock().unwrap().push(body.clone());
+                        let responses: Vec<String> = work_items(version)
+                            .iter()
+                            .filter(|(name, ..)| body.contains(&format!("/ada/work/{}", name)))
+                            .map(|(name, etag, data)| response(&format!("/ada/work/{}", name), &format!("<D:getetag>{}</D:getetag><C:calendar-data><![CDATA[{}]]></C:calendar-data>", etag, data)))
+                            .collect();
+                        multistatus(&responses)
+                    }
+                    ("REPORT", "/ada/shared/", _) => {
+                        assert!(body.contains(r#"<c:time-range start="20240701T000000Z" end="20240801T000000Z"/>"#));
+                        let lunch = event("lunch", "Lunch &amp; learn", "20240704T120000Z", "");
+                        multistatus(&[response("/ada/shared/lunch.ics", &format!("<C:calendar-data>{}</C:calendar-data>", lunch))])
+                    }
+                    _ => String::new(),
+                };
+                match reply.is_empty() {
+                    true => ("404 Not Found", String::new(), reply),
+                    false => (
+                        "207 Multi-Status",
+                        "Content-Type: application/xml; charset=utf-8\r\n".to_string(),
+                        reply,
+                    ),
+                }
+            };
+
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\n{}Content-Length: {}\r\n\r\n{}",
+                status,
+                extra_header,
+                reply.len(),
+                reply
+            )
+            .unwrap();
+        }
+    }
+
+    // PUT and DELETE with their If-Match and If-None-Match preconditions
+    fn write(
+        written: &mut HashMap<String, (String, String)>,
+        method: &str,
+        path: &str,
+        headers: &HashMap<String, String>,
+        body: String,
+    ) -> (&'static str, String, String) {
+        let current = written.get(path).map(|(etag, _)| etag.as_str());
+        let allowed = match (headers.get("if-match"), headers.get("if-none-match")) {
+            (Some(etag), _) => current == Some(etag.as_str()),
+            (None, Some(_)) => current.is_none(),
+            (None, None) => true,
+        };
+        if !allowed {
+            return ("412 Precondition Failed", String::new(), String::new());
+        }
+        match method {
+            "PUT" => {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                std::hash::Hash::hash(&body, &mut hasher);
+                let etag = format!("\"{:x}\"", std::hash::Hasher::finish(&hasher));
+                let status = match current {
+                    Some(_) => "204 No Content",
+                    None => "201 Created",
+                };
+                written.insert(path.to_string(), (etag.clone(), body));
+                (status, format!("ETag: {}\r\n", etag), String::new())
+            }
+            _ => match written.remove(path) {
+                Some(_) => ("204 No Content", String::new(), String::new()),
+                None => ("404 Not Found", String::new(), String::new()),
+            },
+        }
+    }
+
+    // Radicale's prefixes: D for DAV:, C for CalDAV, ICAL for Apple's
+    // extensions, and DAV: as the default namespace for the sync token
+    fn multistatus(responses: &[String]) -> String {
+        format!(
+            r#"<?xml version='1.0' encoding='utf-8'?><multistatus xmlns="DAV:" xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav" xmlns:ICAL="http://apple.com/ns/ical/">{}</multistatus>"#,
+            responses.concat()
+        )
+    }
+
+    fn response(href: &str, props: &str) -> String {
+        format!("<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop><D:status>HTTP/1.1 200 OK</D:status></D:propstat><D:propstat><D:prop><D:getcontentlength/></D:prop><D:status>HTTP/1.1 404 Not Found</D:status></D:propstat></D:response>", href, props)
+    }
+
+    fn titles(events: &[EventInfo]) -> Vec<&str> {
+        events.iter().map(|event| event.title.as_str()).collect()
+    }
+
+    #[test]
+    fn discovers_and_syncs_calendars() {
+        let server = FakeServer::start();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(USERNAME, PASSWORD).unwrap();
+            let mut account = discover(&client, &server.url, USERNAME).await.unwrap();
+
+            let work = format!("{}ada/work/", server.url);
+            let shared = format!("{}ada/shared/", server.url);
+            assert_eq!(account.home_url, format!("{}ada/", server.url));
+            let calendars: Vec<_> = account.calendar_infos().collect();
+            assert_eq!(calendars.len(), 2);
+            assert_eq!(
+                (
+                    calendars[0].identifier.as_str(),
+                    calendars[0].title.as_str(),
+                    calendars[0].color.as_str()
+                ),
+                (work.as_str(), "Work", "#FF2968")
+            );
+            assert_eq!(
+                (calendars[1].title.as_str(), calendars[1].color.as_str()),
+                ("shared", DEFAULT_COLOR)
+            );
+            assert!(account.calendars[0].supports_sync && !account.calendars[1].supports_sync);
+
+            let ids = [work.clone(), shared];
+            let start = parse_rfc3339("2024-07-01T00:00:00Z").unwrap();
+            let end = parse_rfc3339("2024-08-01T00:00:00Z").unwrap();
+            let events = account
+                .events(&client, &ids, start, end, &Utc)
+                .await
+                .unwrap();
+            assert_eq!(titles(&events), ["Planning", "Review", "Lunch & learn"]);
+            assert_eq!(events[0].calendar_id, work);
+            assert_eq!(
+                account.calendars[0].sync_token.as_deref(),
+                Some("http://radicale.org/ns/sync/1")
+            );
+
+            // Only what changed since the token is fetched again
+            server.version.store(1, Ordering::SeqCst);
+            let events = account
+                .events(&client, &ids, start, end, &Utc)
+                .await
+                .unwrap();
+            assert_eq!(
+                titles(&events),
+                ["Review (moved)", "Lunch & learn", "Retro", "Retro"]
+            );
+
+            // Refreshing the list keeps what was synced
+            let home = parse_url(&account.home_url).unwrap();
+            account.replace_calendars(client.list_calendars(&home).await.unwrap());
+            assert_eq!(account.calendars.len(), 2);
+            assert_eq!(account.calendars[0].items.len(), 2);
+            assert!(account.calendars[0].sync_token.is_some());
+
+            let multigets = server.multigets.lock().unwrap();
+            assert_eq!(multigets.len(), 2);
+            assert!(
+                multigets[1].contains("/ada/work/b.ics")
+                    && multigets[1].contains("/ada/work/c.ics")
+            );
+            assert!(!multigets[1].contains("/ada/work/a.ics"));
+        });
+    }
+
+    #[test]
+    fn reports_rejected_credentials() {
+        let server = FakeServer::start();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(USERNAME, "wrong").unwrap();
+            let error = discover(&client, &server.url, USERNAME)
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(error.code, ErrorCode::NotAuthorized);
+        });
+    }
+
+    #[test]
+    fn skips_calendars_that_fail() {
+        let server = FakeServer::start();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(USERNAME, PASSWORD).unwrap();
+            let mut account = discover(&client, &server.url, USERNAME).await.unwrap();
+            let work = account.calendars[0].url.clone();
+            let mut broken = account.calendars[0].clone();
+            broken.url = format!("{}ada/broken/", server.url);
+            account.calendars.push(broken.clone());
+
+            let start = parse_rfc3339("2024-07-01T00:00:00Z").unwrap();
+            let end = parse_rfc3339("2024-08-01T00:00:00Z").unwrap();
+            let ids = [work, broken.url.clone()];
+            let events = account
+                .events(&client, &ids, start, end, &Utc)
+                .await
+                .unwrap();
+            assert_eq!(titles(&events), ["Planning", "Review"]);
+
+            let error = account
+                .events(&client, &[broken.url], start, end, &Utc)
+                .await
+                .unwrap_err();
+            assert_eq!(error.code, ErrorCode::NotFound);
+        });
+    }
+
+    #[test]
+    fn keeps_the_password_from_other_servers() {
+        let server = FakeServer::start();
+        let other = FakeServer::start();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(USERNAME, PASSWORD).unwrap();
+            let moved = parse_url(&format!("{}moved/", server.url)).unwrap();
+
+            // Within the server the credentials go along
+            *server.redirect_to.lock().unwrap() = "/ada/".to_string();
+            let reply = client.send("PROPFIND", &moved, "0", "").await.unwrap();
+            assert_eq!(reply.status, StatusCode::MULTI_STATUS);
+
+            // The other server asks for them, as it never got them
+            *server.redirect_to.lock().unwrap() = format!("{}ada/", other.url);
+            let reply = client.send("PROPFIND", &moved, "0", "").await.unwrap();
+            assert_eq!(reply.url.as_str(), format!("{}ada/", other.url));
+            assert_eq!(reply.status, StatusCode::UNAUTHORIZED);
+        });
+
+        let https = parse_url("https://dav.example.com/ada/").unwrap();
+        assert_eq!(
+            redirect_target(&https, "/ada/work/").unwrap().as_str(),
+            "https://dav.example.com/ada/work/"
+        );
+        assert!(redirect_target(&https, "http://dav.example.com/ada/").is_err());
+        assert!(redirect_target(&https, "file:///etc/passwd").is_err());
+        let http = parse_url("http://localhost:5232/").unwrap();
+        assert!(redirect_target(&http, "https://dav.example.com/").is_ok());
+    }
+
+    #[test]
+    fn creates_updates_and_deletes_events() {
+        let server = FakeServer::start();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(USERNAME, PASSWORD).unwrap();
+            let mut account = discover(&client, &server.url, USERNAME).await.unwrap();
+            let work = &mut account.calendars[0];
+            let mut draft = EventDraft {
+                calendar_id: work.url.clone(),
+                title: "Standup".to_string(),
+                start_date: "2024-07-08T09:00:00Z".to_string(),
+                end_date: "2024-07-08T09:15:00Z".to_string(),
+                is_all_day: false,
+                location: None,
+                url: None,
+                notes: None,
+            };
+
+            let created = work.create_event(&client, &draft).await.unwrap();
+            assert_eq!(created.calendar_id, work.url);
+            let url = format!("{}{}.ics", work.url, created.identifier);
+            let first = work.items[&url].clone();
+            assert!(first.data.contains("SUMMARY:Standup\r\n"));
+
+            draft.title = "Daily standup".to_string();
+            let updated = work
+                .update_event(
+                    &client,
+                    &created.identifier,
+                    "2024-07-08T09:00:00Z".parse().unwrap(),
+                    &draft,
+                )
+                .await
+                .unwrap();
+            assert_eq!(updated.identifier, created.identifier);
+            let second = work.items[&url].clone();
+            assert!(second.data.contains("SUMMARY:Daily standup\r\n"));
+            assert!(second.data.contains("SEQUENCE:1\r\n"));
+            assert_ne!(first.etag, second.etag);
+
+            // Writes based on an outdated copy are refused
+            let stale = EventResource {
+                url: Url::parse(&url).unwrap(),
+                etag: first.etag,
+                data: first.data,
+            };
+            let error = client.delete(&stale).await.err().unwrap();
+            assert_eq!(error.code, ErrorCode::Conflict);
+
+            work.delete_event(&client, &created.identifier)
+                .await
+                .unwrap();
+            assert!(!work.items.contains_key(&url));
+            let error = work
+                .delete_event(&client, &created.identifier)
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(error.code, ErrorCode::NotFound);
+        });
+    }
+
+    // Against a real server, e.g. `radicale --storage-filesystem-folder /tmp/radicale`
+    // with `CALDAV_TEST_URL=http://localhost:5232/ CALDAV_TEST_USER=ada
+    // CALDAV_TEST_PASSWORD=x cargo test caldav -- --ignored`. Needs at least
+    // one calendar with events.
+    #[test]
+    #[ignore]
+    fn syncs_with_a_caldav_server() {
+        let url = std::env::var("CALDAV_TEST_URL").expect("CALDAV_TEST_URL");
+        let username = std::env::var("CALDAV_TEST_USER").unwrap_or_else(|_| USERNAME.to_string());
+        let password = std::env::var("CALDAV_TEST_PASSWORD").unwrap_or_default();
+        tauri::async_runtime::block_on(async {
+            let client = DavClient::new(&username, &password).unwrap();
+            let mut account = discover(&client, &url, &username).await.unwrap();
+            assert!(!account.calendars.is_empty());
+
+            let ids: Vec<String> = account
+                .calendars
+                .iter()
+                .map(|calendar| calendar.url.clone())
+                .collect();
+            let (start, end) = (
+                Utc::now() - chrono::Duration::days(365),
+                Utc::now() + chrono::Duration::days(365),
+            );
+            let first = account
+                .events(&client, &ids, start, end, &Utc)
+                .await
+                .unwrap();
+            let second = account
+                .events(&client, &ids, start, end, &Utc)
+                .await
+                .unwrap();
+            assert_eq!(titles(&first), titles(&second));
+        });
+    }
+}
@@ -1,22 +1,19 @@
 // apps/app/src-tauri/src/calendar_utils.rs
 
-use block::{ConcreteBlock};
-use chrono::{DateTime, NaiveDateTime, Utc};
-use cocoa::base::{id, nil, YES};
-use cocoa::foundation::{NSArray, NSAutoreleasePool, NSUInteger};
-use cocoa::appkit::{CGFloat};
-use objc::runtime::{Class, Object, BOOL as ObjcBOOL};
-use objc::{class, msg_send, sel, sel_impl};
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
-use std::ffi::CStr;
-use std::sync::mpsc;
+use tauri::{command, AppHandle, Manager, Runtime, State};
 use tokio::task; // For spawn_blocking
-use tauri::command;
-use tauri::State; 
+use url::Url;
+use crate::caldav::{is_caldav_calendar, CalDavState};
+use crate::error::{Error, Result};
+use crate::ics::{is_ics_calendar, parse_rfc3339, IcsState};
+use crate::platform::{CalendarPlatform, NativeCalendar};
+use crate::recurrence::resolve_local;
 use crate::state::CalendarState;
 
 // Define the authorization status enum matching EKAuthorizationStatus
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, specta::Type)]
 #[serde(rename_all = "camelCase")]
 pub enum CalendarAuthorizationStatus {
     NotDetermined = 0,
@@ -27,384 +24,294 @@ pub enum CalendarAuthorizationStatus {
 }
 
 // Struct to hold calendar information
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct CalendarInfo {
-    identifier: String,
-    title: String,
-    color: String, //"#RRGGBB"
+    pub identifier: String,
+    pub title: String,
+    pub color: String, //"#RRGGBB"
 }
 
 // Struct to hold participant information
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct ParticipantInfo {
-    name: Option<String>, // EKParticipant.name
+    pub name: Option<String>, // EKParticipant.name
 }
 
 // Struct to hold event information
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
 pub struct EventInfo {
-    identifier: String,         // EKEvent.eventIdentifier
-    title: String,              // EKEvent.title
-    notes: Option<String>,      // EKEvent.notes
-    url: Option<String>,        // EKEvent.URL
-    participants: Vec<ParticipantInfo>, // EKEvent.participants
-    start_date: String,         // EKEvent.startDate (ISO 8601 string)
-    end_date: String,           // EKEvent.endDate (ISO 8601 string)
-    calendar_id: String,        // EKEvent.calendar.calendarIdentifier
-    location: Option<String>,   // EKEvent.location
-    is_all_day: bool,           // EKEvent.isAllDay
+    pub identifier: String,         // EKEvent.eventIdentifier
+    pub title: String,              // EKEvent.title
+    pub notes: Option<String>,      // EKEvent.notes
+    pub url: Option<String>,        // EKEvent.URL
+    pub participants: Vec<ParticipantInfo>, // EKEvent.participants
+    pub start_date: String,         // EKEvent.startDate (ISO 8601 string)
+    pub end_date: String,           // EKEvent.endDate (ISO 8601 string)
+    pub calendar_id: String,        // EKEvent.calendar.calendarIdentifier
+    pub location: Option<String>,   // EKEvent.location
+    pub is_all_day: bool,           // EKEvent.isAllDay
 }
 
-fn event_store_class() -> &'static Class {
-    Class::get("EKEventStore").expect("EKEventStore class not found")
-}
-
-fn get_store_ptr(state: &State<CalendarState>) -> Result<id, String> {
-    let guard = state.event_store.lock().map_err(|e| format!("Failed to lock event store mutex: {}", e))?;
-    let obj_ref: &Object = &*guard;
-    let ptr = obj_ref as *const Object as id;
-    if ptr.is_null() { // Use is_null() for raw pointers
-        Err("Event store instance in state is nil".to_string())
-    } else {
-        Ok(ptr)
-    }
-}
-
-// Helper to convert NSString to Rust String
-unsafe fn nsstring_to_string(ns_string: id) -> String {
-    if ns_string == nil {
-        return String::new();
-    }
-    let c_str_ptr: *const std::os::raw::c_char = msg_send![ns_string, UTF8String];
-    if c_str_ptr.is_null() {
-        return String::new();
-    }
-    CStr::from_ptr(c_str_ptr).to_string_lossy().into_owned()
+// An event as the user creates or edits it. All-day events run from midnight
+// to midnight local time, the end being exclusive.
+#[derive(Serialize, Deserialize, Debug, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDraft {
+    pub calendar_id: String,
+    pub title: String,
+    pub start_date: String, // ISO 8601
+    pub end_date: String,   // ISO 8601
+    pub is_all_day: bool,
+    pub location: Option<String>,
+    pub url: Option<String>,
+    pub notes: Option<String>,
 }
 
-// Helper to convert NSColor directly to Hex String using components
-// NSColor -> NSColor (RGB space) -> RGBA components -> Hex
-unsafe fn nscolor_to_hex(ns_color: id) -> String {
-    if ns_color == nil {
-        return "#000000".to_string(); // Default color: Black
+impl EventDraft {
+    // Start and end, after checking what every calendar provider relies on
+    pub fn validate(&self) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        if self.title.trim().is_empty() {
+            return Err(Error::invalid_argument("An event needs a title"));
+        }
+        let start = parse_rfc3339(&self.start_date)?;
+        let end = parse_rfc3339(&self.end_date)?;
+        if end < start {
+            return Err(Error::invalid_argument("The event ends before it starts"));
+        }
+        if let Some(url) = &self.url {
+            Url::parse(url).map_err(|e| {
+                Error::invalid_argument(format!("Invalid URL: {}", url)).with_details(e)
+            })?;
+        }
+        Ok((start, end))
     }
 
-    // Get the device RGB color space
-    // Use class method deviceRGBColorSpace which is simpler
-    let device_rgb_space: id = msg_send![class!(NSColorSpace), deviceRGBColorSpace];
-
-    if device_rgb_space == nil {
-        return "#808080".to_string(); // Fallback: Gray
+    // This draft of the occurrence at `occurrence_date`, made into one for the
+    // whole series starting at `series_start`: the series moves by as much as
+    // the occurrence did in local time and takes the draft's length
+    pub fn for_series<Tz: TimeZone>(
+        &self,
+        occurrence_date: DateTime<Utc>,
+        series_start: DateTime<Utc>,
+        local: &Tz,
+    ) -> Result<EventDraft> {
+        let (start, end) = self.validate()?;
+        if occurrence_date == series_start {
+            return Ok(self.clone());
+        }
+        let wall_clock = |date: DateTime<Utc>| date.with_timezone(local).naive_local();
+        let out_of_range = || Error::invalid_argument("The event moves out of the supported date range");
+        let series_start = wall_clock(series_start)
+            .checked_add_signed(wall_clock(start) - wall_clock(occurrence_date))
+            .ok_or_else(out_of_range)?;
+        let series_end =
+            series_start.checked_add_signed(wall_clock(end) - wall_clock(start)).ok_or_else(out_of_range)?;
+        Ok(EventDraft {
+            start_date: resolve_local(local, series_start).with_timezone(&Utc).to_rfc3339(),
+            end_date: resolve_local(local, series_end).with_timezone(&Utc).to_rfc3339(),
+            ..self.clone()
+        })
     }
 
-    // Convert the original color to the device RGB color space
-    let color_rgb: id = msg_send![ns_color, colorUsingColorSpace: device_rgb_space];
-
-    if color_rgb == nil {
-        // This can happen with pattern colors, etc.
-        return "#808080".to_string(); // Fallback: Gray
+    // The saved event, for providers that don't read it back
+    pub fn event_info(&self, identifier: String) -> Result<EventInfo> {
+        let (start, end) = self.validate()?;
+        Ok(EventInfo {
+            identifier,
+            title: self.title.clone(),
+            notes: self.notes.clone(),
+            url: self.url.clone(),
+            participants: Vec::new(),
+            start_date: start.to_rfc3339(),
+            end_date: end.to_rfc3339(),
+            calendar_id: self.calendar_id.clone(),
+            location: self.location.clone(),
+            is_all_day: self.is_all_day,
+        })
     }
-
-    // Get the RGBA components directly from the NSColor object
-    let red: CGFloat = msg_send![color_rgb, redComponent];
-    let green: CGFloat = msg_send![color_rgb, greenComponent];
-    let blue: CGFloat = msg_send![color_rgb, blueComponent];
-    // let alpha: CGFloat = msg_send![color_rgb, alphaComponent]; // We don't need alpha for #RRGGBB
-
-    // Convert CGFloat (0.0-1.0) to u8 (0-255), clamping values
-    let r_u8 = (red.max(0.0).min(1.0) * 255.0).round() as u8;
-    let g_u8 = (green.max(0.0).min(1.0) * 255.0).round() as u8;
-    let b_u8 = (blue.max(0.0).min(1.0) * 255.0).round() as u8;
-
-    // Format as hex string
-    format!("#{:02X}{:02X}{:02X}", r_u8, g_u8, b_u8)
 }
 
-unsafe fn datetime_utc_to_nsdate(dt: DateTime<Utc>) -> id {
-    let timestamp = dt.timestamp() as f64;
-    let nsdate: id = msg_send![class!(NSDate), dateWithTimeIntervalSince1970: timestamp];
-    nsdate
+// Where the events of a calendar are written to
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum EventStore {
+    // EventKit on macOS
+    Native,
+    CalDav,
+    // An .ics file, identified by its path
+    IcsFile,
 }
 
-unsafe fn nsdate_to_datetime_utc(nsdate: id) -> Option<DateTime<Utc>> {
-    if nsdate == nil {
-        return None;
+impl EventStore {
+    fn of(calendar_id: &str) -> Self {
+        if is_caldav_calendar(calendar_id) {
+            Self::CalDav
+        } else if is_ics_calendar(calendar_id) {
+            Self::IcsFile
+        } else {
+            Self::Native
+        }
     }
-    let timestamp: f64 = msg_send![nsdate, timeIntervalSince1970];
-    NaiveDateTime::from_timestamp_opt(timestamp as i64, (timestamp.fract() * 1_000_000_000.0) as u32)
-        .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
-}
-
-// Helper to convert NSDate to ISO 8601 string (UTC)
-unsafe fn nsdate_to_iso_string(nsdate: id) -> String {
-    nsdate_to_datetime_utc(nsdate)
-        .map(|dt| dt.to_rfc3339())
-        .unwrap_or_else(|| "".to_string())
-}
-
-// Helper to parse ISO 8601 string to NSDate
-// Assumes input string is UTC or has offset
-unsafe fn iso_string_to_nsdate(iso_string: &str) -> Option<id> {
-    DateTime::parse_from_rfc3339(iso_string)
-        .ok()
-        .map(|dt| datetime_utc_to_nsdate(dt.with_timezone(&Utc)))
 }
 
-unsafe fn get_calendars_by_ids(store: id, calendar_ids: &[String]) -> Vec<id> {
-    const EK_ENTITY_TYPE_EVENT: i64 = 0;
-    let all_calendars_nsarray: id = msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT];
-    if all_calendars_nsarray == nil {
-        return Vec::new();
-    }
-
-    let mut matching_calendars = Vec::new();
-    let count: NSUInteger = NSArray::count(all_calendars_nsarray);
-
-    for i in 0..count {
-        let calendar: id = NSArray::objectAtIndex(all_calendars_nsarray, i);
-        if calendar == nil {
-            continue;
-        }
-
-        let identifier_nsstring: id = msg_send![calendar, calendarIdentifier];
-        let identifier_str = nsstring_to_string(identifier_nsstring);
-        if calendar_ids.contains(&identifier_str) {
-            matching_calendars.push(calendar);
+// CalDAV accounts and imported .ics files work without the system's
+// permission, so having one counts as access for the frontend
+async fn with_calendar_access(
+    status: CalendarAuthorizationStatus,
+    caldav: &CalDavState,
+    ics: &IcsState,
+) -> CalendarAuthorizationStatus {
+    match status {
+        CalendarAuthorizationStatus::Restricted | CalendarAuthorizationStatus::Denied
+            if ics.has_calendars() || caldav.has_accounts().await =>
+        {
+            CalendarAuthorizationStatus::Authorized
         }
+        status => status,
     }
-    matching_calendars
 }
 
 #[command]
-pub fn get_calendar_authorization_status() -> Result<CalendarAuthorizationStatus, String> {
-    const EK_ENTITY_TYPE_EVENT: i64 = 0;
-
-    let store_class = event_store_class();
-
-    let status_raw: i64 = unsafe {
-        msg_send![store_class, authorizationStatusForEntityType: EK_ENTITY_TYPE_EVENT]
-    };
+#[specta::specta]
+pub async fn get_calendar_authorization_status(
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+) -> Result<CalendarAuthorizationStatus> {
+    let status = NativeCalendar::authorization_status()?;
+    Ok(with_calendar_access(status, &caldav, &ics).await)
+}
 
-    let status = match status_raw {
-        0 => CalendarAuthorizationStatus::NotDetermined,
-        1 => CalendarAuthorizationStatus::Restricted,
-        2 => CalendarAuthorizationStatus::Denied,
-        3 => CalendarAuthorizationStatus::Authorized,
-        // 4 => CalendarAuthorizationStatus::WriteOnly, // EKAuthorizationStatusWriteOnly - Handle if needed
-        _ => return Err(format!("Unknown calendar authorization status: {}", status_raw)),
+#[command]
+#[specta::specta]
+pub async fn request_calendar_access<R: Runtime>(app_handle: AppHandle<R>) -> Result<CalendarAuthorizationStatus> {
+    // The platform call blocks until the user answered the prompt
+    let handle = app_handle.clone();
+    let status = match task::spawn_blocking(move || handle.state::<CalendarState>().calendar.request_access()).await {
+        Ok(result) => result?,
+        Err(e) => return Err(Error::internal("Failed to run blocking task for calendar access result.").with_details(e)),
     };
-
-    Ok(status)
+    Ok(with_calendar_access(status, &app_handle.state::<CalDavState>(), &app_handle.state::<IcsState>()).await)
 }
 
+// Events of the native, CalDAV and .ics calendars among `calendar_ids`, by start date
 #[command]
-pub async fn request_calendar_access(state: State<'_, CalendarState>) -> Result<CalendarAuthorizationStatus, String> {
-    const EK_ENTITY_TYPE_EVENT: i64 = 0;
-
-    let store = get_store_ptr(&state)?;
-
-    // Check current status first (optional but good practice)
-    let current_status = get_calendar_authorization_status()?;
-    if current_status == CalendarAuthorizationStatus::Authorized {
-        return Ok(current_status);
-    }
-    if current_status != CalendarAuthorizationStatus::NotDetermined {
-        return Ok(current_status);
-    }
-
-    let (tx, rx) = mpsc::channel::<bool>(); // Use mpsc channel
-
-    let completion_block = ConcreteBlock::new(move |granted: objc::runtime::BOOL, error: id| -> () {
-        let granted_bool = granted == objc::runtime::YES;
-        let tx_clone = tx.clone(); // Clone the sender for use in the closure
-        if error != nil {
-            let description: id = unsafe { msg_send![error, localizedDescription] };
-            let description_str = unsafe {
-                let c_str = cocoa::foundation::NSString::UTF8String(description);
-                std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned()
-            };
-            // Send false on error
-            let _ = tx_clone.send(false);
-        } else {
-            let _ = tx_clone.send(granted_bool); // Use the clone
+#[specta::specta]
+pub async fn get_calendar_events(
+    state: State<'_, CalendarState>,
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+    calendar_ids: Vec<String>,
+    start_date_iso: String,
+    end_date_iso: String,
+) -> Result<Vec<EventInfo>> {
+    let (mut native_ids, mut remote_ids, mut file_ids) = (Vec::new(), Vec::new(), Vec::new());
+    for id in calendar_ids {
+        match EventStore::of(&id) {
+            EventStore::Native => native_ids.push(id),
+            EventStore::CalDav => remote_ids.push(id),
+            EventStore::IcsFile => file_ids.push(id),
         }
-    }).copy();
-
-    unsafe {
-        let _: () = msg_send![store,
-            requestFullAccessToEventsWithCompletion: completion_block
-        ];
     }
 
-    match task::spawn_blocking(move || rx.recv()).await {
-        Ok(Ok(granted)) => Ok(CalendarAuthorizationStatus::Authorized),
-        Ok(Err(_)) => Err("Calendar access completion handler channel closed unexpectedly.".to_string()),
-        Err(_) => Err("Failed to run blocking task for calendar access result.".to_string()),
+    let mut events = Vec::new();
+    if !native_ids.is_empty() {
+        events = state.calendar.events(&native_ids, &start_date_iso, &end_date_iso)?;
     }
+    if !remote_ids.is_empty() {
+        events.extend(caldav.events(&remote_ids, &start_date_iso, &end_date_iso).await?);
+    }
+    if !file_ids.is_empty() {
+        events.extend(ics.events(&file_ids, &start_date_iso, &end_date_iso)?);
+    }
+    events.sort_by(|a, b| a.start_date.cmp(&b.start_date));
+    Ok(events)
 }
 
+// Native calendars followed by those of CalDAV accounts and imported .ics
+// files. Without access to the native ones, only the others are listed if
+// there are any.
 #[command]
-pub fn get_calendar_events(
-    state: State<CalendarState>,
-    calendar_ids: Vec<String>,
-    start_date_iso: String,
-    end_date_iso: String,
-) -> Result<Vec<EventInfo>, String> {
-    let _pool = unsafe { NSAutoreleasePool::new(nil) };
-
-    // Check auth status
-    match get_calendar_authorization_status()? {
-        CalendarAuthorizationStatus::Authorized => (),
-        _ => return Err("Calendar access not authorized.".to_string()),
+#[specta::specta]
+pub async fn get_calendars(
+    state: State<'_, CalendarState>,
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+) -> Result<Vec<CalendarInfo>> {
+    let native = state.calendar.calendars();
+    if !caldav.has_accounts().await && !ics.has_calendars() {
+        return native;
     }
+    let mut calendars = native.unwrap_or_else(|e| {
+        eprintln!("Listing only CalDAV and .ics calendars: {}", e);
+        Vec::new()
+    });
+    calendars.extend(caldav.calendars().await);
+    calendars.extend(ics.calendars());
+    Ok(calendars)
+}
 
-    let store = get_store_ptr(&state)?;
-
-    unsafe {
-        // Convert dates
-        let start_date_ns = iso_string_to_nsdate(&start_date_iso)
-            .ok_or_else(|| format!("Invalid start date format: {}", start_date_iso))?;
-        let end_date_ns = iso_string_to_nsdate(&end_date_iso)
-            .ok_or_else(|| format!("Invalid end date format: {}", end_date_iso))?;
-
-        // Get calendar objects
-        let calendars_nsarray = if calendar_ids.is_empty() {
-            // If no specific IDs are provided, fetch from all accessible event calendars
-            const EK_ENTITY_TYPE_EVENT: i64 = 0;
-            let all_calendars: id = msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT];
-            if all_calendars == nil { NSArray::array(nil) } else { all_calendars } // Use empty array if nil
-        } else {
-            let calendar_objs = get_calendars_by_ids(store, &calendar_ids);
-            if calendar_objs.is_empty() {
-                // Return empty vec if no matching calendars found or provided IDs were invalid
-                return Ok(Vec::new());
-            }
-            NSArray::arrayWithObjects(nil, &calendar_objs)
-        };
-
-        if calendars_nsarray == nil || NSArray::count(calendars_nsarray) == 0 {
-            return Ok(Vec::new()); // No calendars to search in
-        }
-
-        // Create predicate
-        // Equivalent to: [store predicateForEventsWithStartDate:startDate endDate:endDate calendars:calendars];
-        let predicate: id = msg_send![store, predicateForEventsWithStartDate:start_date_ns endDate:end_date_ns calendars:calendars_nsarray];
-
-        // Fetch events
-        // Equivalent to: [store eventsMatchingPredicate:predicate];
-        let events_nsarray: id = msg_send![store, eventsMatchingPredicate:predicate];
-
-        if events_nsarray == nil {
-            return Ok(Vec::new());
-        }
-
-        let mut events_vec = Vec::new();
-        let count: NSUInteger = NSArray::count(events_nsarray);
-
-        for i in 0..count {
-            let event: id = NSArray::objectAtIndex(events_nsarray, i);
-
-            // Extract event properties
-            let identifier: id = msg_send![event, eventIdentifier];
-            let title: id = msg_send![event, title];
-            let notes: id = msg_send![event, notes];
-            let start_date: id = msg_send![event, startDate];
-            let end_date: id = msg_send![event, endDate];
-            let calendar: id = msg_send![event, calendar];
-            let calendar_id: id = msg_send![calendar, calendarIdentifier];
-            let location: id = msg_send![event, location];
-            let is_all_day: ObjcBOOL = msg_send![event, isAllDay];
-            let url_nsurl: id = msg_send![event, URL]; // Get NSURL
-            let participants_nsarray: id = msg_send![event, attendees];
-
-            let mut participants = Vec::new();
-            if participants_nsarray != nil {
-                let participants_count: NSUInteger = NSArray::count(participants_nsarray);
-                for j in 0..participants_count {
-                    let participant: id = NSArray::objectAtIndex(participants_nsarray, j);
-                    let name: id = msg_send![participant, name];
-                    let participant_info = ParticipantInfo {
-                        name: if name != nil { Some(nsstring_to_string(name)) } else { None },
-                    };
-                    participants.push(participant_info);
-                }
-            }
-
-            let event_info = EventInfo {
-                identifier: nsstring_to_string(identifier),
-                title: nsstring_to_string(title),
-                notes: if notes != nil { Some(nsstring_to_string(notes)) } else { None },
-                // Correctly handle NSURL -> NSString -> Rust String
-                url: if url_nsurl != nil {
-                    let url_nsstring: id = msg_send![url_nsurl, absoluteString];
-                    if url_nsstring != nil {
-                         Some(nsstring_to_string(url_nsstring))
-                    } else {
-                        None // absoluteString returned nil
-                    }
-                } else {
-                    None // URL property was nil
-                },
-                participants,
-                start_date: nsdate_to_iso_string(start_date),
-                end_date: nsdate_to_iso_string(end_date),
-                calendar_id: nsstring_to_string(calendar_id),
-                location: if location != nil { Some(nsstring_to_string(location)) } else { None },
-                is_all_day: is_all_day == YES,
-            };
-            events_vec.push(event_info);
-        }
-        Ok(events_vec)
+#[command]
+#[specta::specta]
+pub async fn create_calendar_event(
+    state: State<'_, CalendarState>,
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+    event: EventDraft,
+) -> Result<EventInfo> {
+    event.validate()?;
+    match EventStore::of(&event.calendar_id) {
+        EventStore::Native => state.calendar.create_event(&event),
+        EventStore::CalDav => caldav.create_event(&event).await,
+        EventStore::IcsFile => ics.create_event(&event),
     }
 }
 
+// Edits the event `event_id` of `calendar_id`, moving it to
+// `event.calendar_id` if that is another calendar. Changes to a recurring
+// event apply to all of its occurrences: `occurrence_date` is the start of
+// the one `event` was edited from, and the series moves as far as it did.
 #[command]
-pub fn get_calendars(state: State<CalendarState>) -> Result<Vec<CalendarInfo>, String> {
-    let _pool = unsafe { NSAutoreleasePool::new(nil) }; // Manage memory
-    const EK_ENTITY_TYPE_EVENT: i64 = 0;
-
-    // Check auth status first
-    match get_calendar_authorization_status()? {
-        CalendarAuthorizationStatus::Authorized => (),
-        status => {
-            return Err("Calendar access not authorized.".to_string());
-        }
+#[specta::specta]
+pub async fn update_calendar_event(
+    state: State<'_, CalendarState>,
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+    calendar_id: String,
+    event_id: String,
+    occurrence_date: String,
+    event: EventDraft,
+) -> Result<EventInfo> {
+    event.validate()?;
+    let occurrence_date = parse_rfc3339(&occurrence_date)?;
+    let store = EventStore::of(&calendar_id);
+    if calendar_id != event.calendar_id
+        && (store != EventStore::Native || EventStore::of(&event.calendar_id) != store)
+    {
+        // Only EventKit moves events between calendars; elsewhere the event
+        // is created in the new calendar first, so nothing is lost on failure
+        let moved = create_calendar_event(state.clone(), caldav.clone(), ics.clone(), event).await?;
+        delete_calendar_event(state, caldav, ics, calendar_id, event_id).await?;
+        return Ok(moved);
     }
-
-    let store = get_store_ptr(&state)?;
-
-    let calendars_nsarray: id = unsafe { msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT] };
-
-    if calendars_nsarray == nil {
-        return Err("Empty list!!!!.".to_string());
+    match store {
+        EventStore::Native => state.calendar.update_event(&event_id, occurrence_date, &event),
+        EventStore::CalDav => caldav.update_event(&event_id, occurrence_date, &event).await,
+        EventStore::IcsFile => ics.update_event(&event_id, occurrence_date, &event),
     }
+}
 
-    let mut calendars_vec = Vec::new();
-    let count: NSUInteger = unsafe { NSArray::count(calendars_nsarray) };
-
-    for i in 0..count {
-        unsafe {
-            let calendar: id = NSArray::objectAtIndex(calendars_nsarray, i);
-            if calendar == nil {
-                continue;
-            }
-
-            // Get properties
-            let identifier: id = msg_send![calendar, calendarIdentifier];
-            let title: id = msg_send![calendar, title];
-            let color: id = msg_send![calendar, color]; // This is NSColor
-
-            let identifier_str = nsstring_to_string(identifier);
-            let title_str = nsstring_to_string(title);
-            let color_hex = nscolor_to_hex(color);
-
-            let calendar_info = CalendarInfo {
-                identifier: identifier_str,
-                title: title_str,
-                color: color_hex,
-            };
-            calendars_vec.push(calendar_info);
-        }
+// Deletes the event and, for recurring events, all of its occurrences
+#[command]
+#[specta::specta]
+pub async fn delete_calendar_event(
+    state: State<'_, CalendarState>,
+    caldav: State<'_, CalDavState>,
+    ics: State<'_, IcsState>,
+    calendar_id: String,
+    event_id: String,
+) -> Result<()> {
+    match EventStore::of(&calendar_id) {
+        EventStore::Native => state.calendar.delete_event(&event_id),
+        EventStore::CalDav => caldav.delete_event(&calendar_id, &event_id).await,
+        EventStore::IcsFile => ics.delete_event(&calendar_id, &event_id),
     }
-    Ok(calendars_vec)
 }
@@ -1,20 +1,15 @@
 use tauri::AppHandle;
-use tauri_nspanel::ManagerExt;
 
-use crate::MAIN_WINDOW_LABEL;
+use crate::platform::{NativePlatform, PanelPlatform};
 
 #[tauri::command]
+#[specta::specta]
 pub fn show(app_handle: AppHandle) {
-    let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
-
-    panel.show();
+    NativePlatform::show(&app_handle);
 }
 
 #[tauri::command]
+#[specta::specta]
 pub fn hide(app_handle: AppHandle) {
-    let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
-
-    if panel.is_visible() {
-        panel.order_out(None);
-    }
+    NativePlatform::hide(&app_handle);
 }
@@ -0,0 +1,767 @@
+// src-tauri/src/content_index.rs
+//
+// Optional full-text index over plain-text files, the documents `text_extract`
+// understands and the notes directory.
+// Files are tokenized into an inverted index (word -> documents) stored in the
+// app data directory, together with the start of each file's text so search
+// results get their line and snippet without reading the files again. Nothing
+// leaves the machine: the index is only built when the user turns content
+// search on.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use tauri::{command, AppHandle, Manager, Runtime, State};
+use walkdir::WalkDir;
+
+use crate::error::{Error, Result};
+use crate::file_index::{index_roots, is_indexable_name};
+use crate::spotlight_utils::fold_name;
+use crate::text_extract;
+
+const INDEX_FILE_NAME: &str = "content-index.json";
+// Bump when the stored format or the tokenizer changes, older files are dropped
+const INDEX_VERSION: u32 = 3;
+// Plain-text formats whose content is indexed
+const TEXT_EXTENSIONS: [&str; 7] = ["txt", "md", "json", "yml", "yaml", "toml", "ini"];
+// Larger files are most likely logs or data dumps
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+// Documents are compressed and carry images, so they get more room
+const MAX_DOCUMENT_SIZE: u64 = 16 * 1024 * 1024;
+const MAX_INDEXED_DOCUMENTS: usize = 50_000;
+// Files with a NUL byte in their first bytes are treated as binary
+const BINARY_SNIFF_LENGTH: usize = 8192;
+// Shorter words are too common to be useful, longer ones are mostly hashes
+const MIN_TOKEN_LENGTH: usize = 2;
+const MAX_TOKEN_LENGTH: usize = 64;
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+// Characters shown on each side of the first match
+const SNIPPET_CONTEXT: usize = 60;
+const MAX_LINE_LENGTH: usize = 500;
+// Bytes of each document's text kept for snippets, the whole text is indexed
+const MAX_STORED_TEXT: usize = 16 * 1024;
+
+#[derive(Serialize, Deserialize)]
+struct ContentIndex {
+    version: u32,
+    built_at: DateTime<Utc>,
+    roots: Vec<PathBuf>,
+    // Position in this list is the document ID used by the postings
+    documents: Vec<IndexedDocument>,
+    // Folded token -> sorted IDs of the documents containing it
+    postings: BTreeMap<String, Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct IndexedDocument {
+    path: PathBuf,
+    // Start of the extracted text, up to MAX_STORED_TEXT bytes
+    text: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, specta::Type)]
+pub struct ContentMatch {
+    pub path: String,
+    pub display_name: String,
+    pub line_number: usize, // 1-based
+    pub line: String,
+    pub snippet: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, specta::Type)]
+pub struct ContentIndexStats {
+    pub document_count: usize,
+    pub term_count: usize,
+    pub last_build: Option<String>, // ISO 8601
+    pub size_bytes: u64,            // Size of the index file on disk
+    pub is_ready: bool,
+    pub is_building: bool,
+}
+
+pub struct ContentIndexState {
+    // None while content search is off or the index is still loading
+    index: RwLock<Option<ContentIndex>>,
+    index_path: Mutex<Option<PathBuf>>,
+    is_building: AtomicBool,
+    // Bumped by `clear`, a rebuild or load that started before is discarded
+    generation: AtomicU64,
+}
+
+impl ContentIndex {
+    // Stops early once `is_cancelled` returns true
+    fn build(roots: Vec<PathBuf>, is_cancelled: impl Fn() -> bool) -> Self {
+        let mut index = Self {
+            version: INDEX_VERSION,
+            built_at: Utc::now(),
+            roots: roots.clone(),
+            documents: Vec::new(),
+            postings: BTreeMap::new(),
+        };
+
+        'roots: for root in roots {
+            let walker = WalkDir::new(&root)
+                .follow_links(false)
+                .into_iter()
+                .filter_entry(|entry| entry.depth() == 0 || is_indexable_name(&entry.file_name().to_string_lossy()));
+
+            for entry in walker.filter_map(Result::ok) {
+                if index.documents.len() >= MAX_INDEXED_DOCUMENTS || is_cancelled() {
+                    break 'roots;
+                }
+                if entry.file_type().is_file() && is_content_indexable(entry.path()) {
+                    index.add_document(entry.path());
+                }
+            }
+        }
+
+        index
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| Error::io("Failed to read content index").with_details(e))?;
+        let index: ContentIndex =
+            serde_json::from_slice(&data).map_err(|e| Error::io("Failed to parse content index").with_details(e))?;
+
+        if index.version != INDEX_VERSION {
+            return Err(Error::io(format!("Unsupported content index version: {}", index.version)));
+        }
+
+        Ok(index)
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_vec(self).map_err(|e| Error::internal("Failed to serialize content index").with_details(e))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io("Failed to create index directory").with_details(e))?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a truncated index
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, data).map_err(|e| Error::io("Failed to write content index").with_details(e))?;
+        fs::rename(&tmp_path, path).map_err(|e| Error::io("Failed to replace content index").with_details(e))
+    }
+
+    fn add_document(&mut self, path: &Path) {
+        let Some(mut text) = document_text(path) else {
+            return;
+        };
+
+        let id = self.documents.len() as u32;
+        let tokens: BTreeSet<String> = tokenize(&text).collect();
+        if tokens.is_empty() {
+            return;
+        }
+
+        for token in tokens {
+            // Documents are added in ID order, so the lists stay sorted
+            self.postings.entry(token).or_default().push(id);
+        }
+
+        text.truncate(text.floor_char_boundary(MAX_STORED_TEXT));
+        self.documents.push(IndexedDocument {
+            path: path.to_path_buf(),
+            text,
+        });
+    }
+
+    // Documents containing every word of the query. The last word also
+    // matches as a prefix, since the query is typed as we search.
+    fn candidates(&self, tokens: &[String]) -> Vec<u32> {
+        let mut candidates: Option<Vec<u32>> = None;
+
+        for (i, token) in tokens.iter().enumerate() {
+            let documents: Vec<u32> = if i + 1 == tokens.len() {
+                let mut documents: Vec<u32> = self
+                    .postings
+                    .range(token.clone()..)
+                    .take_while(|(key, _)| key.starts_with(token.as_str()))
+                    .flat_map(|(_, ids)| ids.iter().copied())
+                    .collect();
+                documents.sort_unstable();
+                documents.dedup();
+                documents
+            } else {
+                self.postings.get(token).cloned().unwrap_or_default()
+            };
+
+            candidates = Some(match candidates {
+                None => documents,
+                Some(previous) => intersect(&previous, &documents),
+            });
+
+            if candidates.as_ref().is_some_and(|ids| ids.is_empty()) {
+                break;
+            }
+        }
+
+        candidates.unwrap_or_default()
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Vec<ContentMatch> {
+        let tokens: Vec<String> = tokenize(query).collect();
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for id in self.candidates(&tokens) {
+            if matches.len() >= limit {
+                break;
+            }
+
+            // The file may have disappeared since it was indexed
+            let document = &self.documents[id as usize];
+            if document.path.exists() {
+                matches.push(find_match(document, &tokens));
+            }
+        }
+
+        matches
+    }
+}
+
+impl ContentIndexState {
+    pub fn new() -> Self {
+        Self {
+            index: RwLock::new(None),
+            index_path: Mutex::new(None),
+            is_building: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn search(&self, query: &str, limit: usize) -> Vec<ContentMatch> {
+        let index = self.index.read().unwrap();
+        index
+            .as_ref()
+            .map(|index| index.search(query, limit))
+            .unwrap_or_default()
+    }
+
+    pub fn stats(&self) -> ContentIndexStats {
+        let index = self.index.read().unwrap();
+        let size_bytes = self
+            .index_path()
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        ContentIndexStats {
+            document_count: index.as_ref().map(|index| index.documents.len()).unwrap_or(0),
+            term_count: index.as_ref().map(|index| index.postings.len()).unwrap_or(0),
+            last_build: index.as_ref().map(|index| index.built_at.to_rfc3339()),
+            size_bytes,
+            is_ready: index.is_some(),
+            is_building: self.is_building.load(Ordering::SeqCst),
+        }
+    }
+
+    // Indexes the search directories plus the notes directory and persists the
+    // result. Blocks for the whole scan.
+    pub fn rebuild(&self, notes_dir: Option<PathBuf>) -> Result<()> {
+        if self.is_building.swap(true, Ordering::SeqCst) {
+            return Err(Error::busy("Content index is already being rebuilt"));
+        }
+        let result = self.build_and_install(notes_dir);
+        self.is_building.store(false, Ordering::SeqCst);
+        result
+    }
+
+    fn build_and_install(&self, notes_dir: Option<PathBuf>) -> Result<()> {
+        let generation = self.generation.load(Ordering::SeqCst);
+
+        let mut roots = index_roots();
+        if let Some(notes_dir) = notes_dir {
+            if notes_dir.is_dir() && !roots.iter().any(|root| notes_dir.starts_with(root)) {
+                roots.push(notes_dir);
+            }
+        }
+
+        let is_cancelled = || self.generation.load(Ordering::SeqCst) != generation;
+        let index = ContentIndex::build(roots, is_cancelled);
+        if is_cancelled() {
+            return Ok(());
+        }
+
+        let index_path = self.index_path();
+        let result = match &index_path {
+            Some(path) => index.save(path),
+            None => Err(Error::internal("Content index location is not set")),
+        };
+
+        // `clear` holds the lock while it bumps the generation, so it either
+        // runs after this and deletes the new index, or before and this
+        // deletes the file that was just saved
+        let mut current = self.index.write().unwrap();
+        if is_cancelled() {
+            return match index_path {
+                Some(path) => remove_index_file(&path),
+                None => Ok(()),
+            };
+        }
+        *current = Some(index);
+
+        result
+    }
+
+    // Turns content search off and deletes the index from disk. A rebuild
+    // still in progress is discarded.
+    pub fn clear(&self) -> Result<()> {
+        let mut current = self.index.write().unwrap();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *current = None;
+
+        match self.index_path() {
+            Some(path) => remove_index_file(&path),
+            None => Ok(()),
+        }
+    }
+
+    fn index_path(&self) -> Option<PathBuf> {
+        self.index_path.lock().unwrap().clone()
+    }
+}
+
+fn remove_index_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(Error::io("Failed to delete content index").with_details(e))
+        }
+        _ => Ok(()),
+    }
+}
+
+// Loads the index from disk in the background if content search was turned on
+// before. Called once from the setup hook.
+pub fn start<R: Runtime>(app_handle: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let state = app_handle.state::<ContentIndexState>();
+
+        let index_path = match app_handle.path().app_data_dir() {
+            Ok(dir) => dir.join(INDEX_FILE_NAME),
+            Err(e) => {
+                eprintln!("Failed to resolve app data directory for the content index: {}", e);
+                return;
+            }
+        };
+        *state.index_path.lock().unwrap() = Some(index_path.clone());
+
+        if !index_path.exists() {
+            return;
+        }
+
+        let generation = state.generation.load(Ordering::SeqCst);
+        match ContentIndex::load(&index_path) {
+            Ok(index) => {
+                let mut current = state.index.write().unwrap();
+                // A rebuild may have finished in the meantime, or content
+                // search was turned off
+                if current.is_none() && state.generation.load(Ordering::SeqCst) == generation {
+                    *current = Some(index);
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    });
+}
+
+fn is_content_indexable(path: &Path) -> bool {
+    let max_size = if text_extract::is_document(path) {
+        MAX_DOCUMENT_SIZE
+    } else if has_text_extension(path) {
+        MAX_FILE_SIZE
+    } else {
+        return false;
+    };
+
+    fs::metadata(path)
+        .map(|metadata| metadata.len() <= max_size)
+        .unwrap_or(false)
+}
+
+fn has_text_extension(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| TEXT_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+fn document_text(path: &Path) -> Option<String> {
+    if text_extract::is_document(path) {
+        text_extract::extract_text(path)
+    } else {
+        read_text(path)
+    }
+}
+
+// The file's text, or None for unreadable, oversized or binary files
+fn read_text(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut data = Vec::new();
+    file.take(MAX_FILE_SIZE + 1).read_to_end(&mut data).ok()?;
+
+    if data.len() as u64 > MAX_FILE_SIZE {
+        return None;
+    }
+    if data.iter().take(BINARY_SNIFF_LENGTH).any(|byte| *byte == 0) {
+        return None;
+    }
+
+    String::from_utf8(data).ok()
+}
+
+// Lowercased, diacritic free words, the same folding the file name search uses
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(fold_name)
+        .filter(|token| {
+            let length = token.chars().count();
+            (MIN_TOKEN_LENGTH..=MAX_TOKEN_LENGTH).contains(&length)
+        })
+}
+
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (mut i, mut j) = (0, 0);
+    let mut result = Vec::new();
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                result.push(a[i]);
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    result
+}
+
+// Finds the first line containing all query words, or failing that the first
+// one containing any of them. Matches past the stored text show its first line.
+fn find_match(document: &IndexedDocument, tokens: &[String]) -> ContentMatch {
+    let path = &document.path;
+    let mut fallback: Option<(usize, &str, usize)> = None;
+    for (index, line) in document.text.lines().enumerate() {
+        let folded = FoldedLine::new(line);
+        let positions: Vec<Option<usize>> = tokens.iter().map(|token| folded.find(token)).collect();
+
+        if positions.iter().all(Option::is_some) {
+            let first = positions.into_iter().flatten().min().unwrap_or(0);
+            return content_match(path, index, line, first);
+        }
+        if fallback.is_none() {
+            if let Some(first) = positions.into_iter().flatten().min() {
+                fallback = Some((index, line, first));
+            }
+        }
+    }
+
+    let (index, line, first) = fallback.unwrap_or_else(|| {
+        document
+            .text
+            .lines()
+            .enumerate()
+            .find(|(_, line)| !line.trim().is_empty())
+            .map_or((0, "", 0), |(index, line)| (index, line, 0))
+    });
+    content_match(path, index, line, first)
+}
+
+fn content_match(path: &Path, line_index: usize, line: &str, match_char: usize) -> ContentMatch {
+    let chars: Vec<char> = line.chars().collect();
+
+    let start = match_char.saturating_sub(SNIPPET_CONTEXT);
+    let end = std::cmp::min(chars.len(), match_char + SNIPPET_CONTEXT);
+    let mut snippet: String = chars[start..end].iter().collect::<String>().trim().to_string();
+    if start > 0 {
+        snippet = format!("…{}", snippet);
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+
+    ContentMatch {
+        path: path.to_string_lossy().into_owned(),
+        display_name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        line_number: line_index + 1,
+        line: chars.iter().take(MAX_LINE_LENGTH).collect::<String>().trim().to_string(),
+        snippet,
+    }
+}
+
+// A folded line that maps matches back to character positions of the original
+struct FoldedLine {
+    folded: String,
+    // Per byte of `folded`: index of the original character it came from
+    origins: Vec<usize>,
+}
+
+impl FoldedLine {
+    fn new(line: &str) -> Self {
+        let mut folded = String::new();
+        let mut origins = Vec::new();
+
+        for (index, c) in line.chars().enumerate() {
+            let folded_char = fold_name(&c.to_string());
+            origins.extend(std::iter::repeat_n(index, folded_char.len()));
+            folded.push_str(&folded_char);
+        }
+
+        Self { folded, origins }
+    }
+
+    fn find(&self, token: &str) -> Option<usize> {
+        self.folded.find(token).map(|byte| self.origins[byte])
+    }
+}
+
+// Home directory relative path segments, like the `notesDir` setting
+fn notes_dir_path(notes_dir: &[String]) -> Option<PathBuf> {
+    if notes_dir.is_empty() {
+        return None;
+    }
+    dirs::home_dir().map(|home| notes_dir.iter().fold(home, |path, segment| path.join(segment)))
+}
+
+#[command]
+#[specta::specta]
+pub async fn rebuild_content_index<R: Runtime>(
+    app_handle: AppHandle<R>,
+    notes_dir: Vec<String>,
+) -> Result<ContentIndexStats> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<ContentIndexState>();
+        state.rebuild(notes_dir_path(&notes_dir))?;
+        Ok(state.stats())
+    })
+    .await?
+}
+
+#[command]
+#[specta::specta]
+pub fn clear_content_index(state: State<'_, ContentIndexState>) -> Result<()> {
+    state.clear()
+}
+
+#[command]
+#[specta::specta]
+pub async fn search_file_contents<R: Runtime>(
+    app_handle: AppHandle<R>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<ContentMatch>> {
+    // Snippets are searched line by line, keep that off the main thread
+    tauri::async_runtime::spawn_blocking(move || {
+        app_handle
+            .state::<ContentIndexState>()
+            .search(&query, limit.unwrap_or(DEFAULT_SEARCH_LIMIT))
+    })
+    .await
+    .map_err(Error::from)
+}
+
+#[command]
+#[specta::specta]
+pub fn get_content_index_stats(state: State<'_, ContentIndexState>) -> Result<ContentIndexStats> {
+    Ok(state.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::scratch_dir;
+
+    fn tokens(query: &str) -> Vec<String> {
+        tokenize(query).collect()
+    }
+
+    fn document(text: &str) -> IndexedDocument {
+        IndexedDocument {
+            path: PathBuf::from("/notes/todo.md"),
+            text: text.into(),
+        }
+    }
+
+    fn names(index: &ContentIndex, ids: &[u32]) -> Vec<String> {
+        ids.iter()
+            .map(|id| index.documents[*id as usize].path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_into_folded_words() {
+        assert_eq!(tokens("Café-Crème, déjà_vu 42"), ["cafe", "creme", "deja", "vu", "42"]);
+        // Single characters and hash-like words are dropped
+        assert_eq!(tokens(&format!("a b {} ok", "x".repeat(65))), ["ok"]);
+        assert_eq!(tokens(&"x".repeat(64)).len(), 1);
+        assert!(tokens("  ,;  ").is_empty());
+    }
+
+    #[test]
+    fn intersects_sorted_lists() {
+        assert_eq!(intersect(&[1, 3, 5, 7], &[2, 3, 4, 7, 9]), [3, 7]);
+        assert_eq!(intersect(&[1, 2], &[3, 4]), [] as [u32; 0]);
+        assert_eq!(intersect(&[], &[1]), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn finds_documents_with_every_word() {
+        let dir = scratch_dir(
+            "content-candidates",
+            &[
+                ("budget.txt", "Quarterly budget review"),
+                ("minutes.md", "Budget meeting minutes\nreviewed by Anna"),
+                ("photo.png", "budget review"),
+                ("numbers.json", "{\"budgets\": 3}"),
+                (".hidden/notes.txt", "budget review"),
+            ],
+        );
+        let index = ContentIndex::build(vec![dir.clone()], || false);
+
+        assert_eq!(index.documents.len(), 3);
+        let mut found = names(&index, &index.candidates(&tokens("budget review")));
+        found.sort();
+        assert_eq!(found, ["budget.txt", "minutes.md"]);
+        // The last word matches as a prefix, the others don't
+        assert_eq!(index.candidates(&tokens("budg")).len(), 3);
+        assert_eq!(names(&index, &index.candidates(&tokens("review budg"))), ["budget.txt"]);
+        assert!(index.candidates(&tokens("budg review")).is_empty());
+        assert!(index.candidates(&tokens("missing")).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stops_building_when_cancelled() {
+        let dir = scratch_dir("content-cancelled", &[("a.txt", "first"), ("b.txt", "second")]);
+        let index = ContentIndex::build(vec![dir.clone()], || true);
+        assert!(index.documents.is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn searches_stored_text() {
+        let dir = scratch_dir(
+            "content-search",
+            &[("a.txt", "shopping list\nmilk and eggs"), ("b.txt", "eggs benedict"), ("c.txt", "eggs")],
+        );
+        let index = ContentIndex::build(vec![dir.clone()], || false);
+
+        let matches = index.search("eggs", 10);
+        assert_eq!(matches.len(), 3);
+        let first = matches.iter().find(|found| found.display_name == "a.txt").unwrap();
+        assert_eq!(first.line_number, 2);
+        assert_eq!(first.line, "milk and eggs");
+        assert_eq!(index.search("eggs", 2).len(), 2);
+        assert!(index.search("", 10).is_empty());
+
+        // Deleted files are left out without reading the others
+        fs::remove_file(dir.join("b.txt")).unwrap();
+        assert_eq!(index.search("eggs", 10).len(), 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_the_start_of_long_documents() {
+        let dir = scratch_dir("content-long", &[("long.txt", &format!("{}\nneedle", "é".repeat(MAX_STORED_TEXT)))]);
+        let index = ContentIndex::build(vec![dir.clone()], || false);
+
+        let text = &index.documents[0].text;
+        assert!(text.len() <= MAX_STORED_TEXT);
+        assert_eq!(text.chars().count(), MAX_STORED_TEXT / 2);
+        // Still found, shown with the first line
+        let matches = index.search("needle", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].line_number, 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn prefers_lines_with_every_word() {
+        let found = find_match(&document("milk\nbread\nmilk and bread"), &tokens("bread milk"));
+        assert_eq!(found.line_number, 3);
+        assert_eq!(found.snippet, "milk and bread");
+
+        // Otherwise the first line with any of them
+        let found = find_match(&document("eggs\nbread\nmilk"), &tokens("milk bread"));
+        assert_eq!(found.line_number, 2);
+
+        // Or the first line with text
+        let found = find_match(&document("\n  \nTitle\nbody"), &tokens("missing"));
+        assert_eq!((found.line_number, found.line.as_str()), (3, "Title"));
+
+        assert_eq!(found.path, "/notes/todo.md");
+        assert_eq!(found.display_name, "todo.md");
+    }
+
+    #[test]
+    fn cuts_snippets_around_the_match() {
+        let line = format!("{} Crème brûlée {}", "a".repeat(100), "b".repeat(100));
+        let found = find_match(&document(&line), &tokens("brulee"));
+
+        let snippet: Vec<char> = found.snippet.chars().collect();
+        assert_eq!(snippet.first(), Some(&'…'));
+        assert_eq!(snippet.last(), Some(&'…'));
+        // "brûlée" starts at character 107
+        let expected: String = line.chars().skip(107 - SNIPPET_CONTEXT).take(2 * SNIPPET_CONTEXT).collect();
+        assert_eq!(found.snippet, format!("…{}…", expected.trim()));
+
+        // Short lines are shown whole
+        let found = find_match(&document("  just a note  "), &tokens("note"));
+        assert_eq!(found.snippet, "just a note");
+        assert_eq!(found.line, "just a note");
+
+        // Long lines are cut
+        let found = find_match(&document(&"word ".repeat(200)), &tokens("word"));
+        assert_eq!(found.line.chars().count(), MAX_LINE_LENGTH - 1);
+    }
+
+    #[test]
+    fn maps_folded_matches_to_original_characters() {
+        let line = FoldedLine::new("Ærø straße file");
+        assert_eq!(line.find("strasse"), None);
+        assert_eq!(line.find("straße"), Some(4));
+        assert_eq!(line.find("file"), Some(11));
+        assert_eq!(line.find("rø"), Some(1));
+        assert_eq!(FoldedLine::new("Déjà vu, CAFÉ").find("cafe"), Some(9));
+    }
+
+    #[test]
+    fn clear_deletes_the_index() {
+        let dir = scratch_dir("content-clear", &[("a.txt", "hello world")]);
+        let state = ContentIndexState::new();
+        let index_path = dir.join("index").join(INDEX_FILE_NAME);
+        *state.index_path.lock().unwrap() = Some(index_path.clone());
+
+        let index = ContentIndex::build(vec![dir.clone()], || false);
+        index.save(&index_path).unwrap();
+        *state.index.write().unwrap() = Some(ContentIndex::load(&index_path).unwrap());
+        assert_eq!(state.search("hello", 10).len(), 1);
+        assert!(state.stats().is_ready);
+
+        state.clear().unwrap();
+        assert!(!index_path.exists());
+        assert!(state.search("hello", 10).is_empty());
+        assert!(!state.stats().is_ready);
+        assert_eq!(state.generation.load(Ordering::SeqCst), 1);
+        // Nothing left to delete
+        state.clear().unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
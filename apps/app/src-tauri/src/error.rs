@@ -0,0 +1,145 @@
+// src-tauri/src/error.rs
+//
+// The error every command returns. It reaches the webview as
+// `{ code, message, details? }`: `message` is meant for people, `code` is
+// stable so the frontend can tell a missing permission from a missing file
+// without parsing text. It is exported to the TypeScript bindings as
+// `CommandError`, see bindings.rs.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, specta::Type)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // The user denied (or hasn't granted yet) access, e.g. to calendars
+    NotAuthorized,
+    // A file, app, secret or search that doesn't exist (anymore)
+    NotFound,
+    // Malformed input from the caller: a query, a date, an option
+    InvalidArgument,
+    // Not available on this platform
+    Unsupported,
+    // Something that can only run once at a time, e.g. an index rebuild, is running
+    Busy,
+    // Changed by someone else since it was read, e.g. an event on a CalDAV server
+    Conflict,
+    Io,
+    Network,
+    // An OS API (EventKit, NSWorkspace, the keyring, ...) failed
+    Platform,
+    // A bug or a broken invariant in grinta itself
+    Internal,
+}
+
+#[derive(thiserror::Error, Serialize, Deserialize, Clone, Debug, PartialEq, specta::Type)]
+#[error("{message}")]
+#[specta(rename = "CommandError")]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+    // The underlying error, for logs and bug reports rather than display
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn not_authorized(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotAuthorized, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidArgument, message)
+    }
+
+    pub fn unsupported(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Unsupported, message)
+    }
+
+    pub fn busy(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Busy, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Io, message)
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Network, message)
+    }
+
+    pub fn platform(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Platform, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    pub fn with_details(mut self, details: impl Display) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        let code = match error.kind() {
+            std::io::ErrorKind::NotFound => ErrorCode::NotFound,
+            std::io::ErrorKind::PermissionDenied => ErrorCode::NotAuthorized,
+            _ => ErrorCode::Io,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+// Blocking work handed to the async runtime panicked or was cancelled
+impl From<tauri::Error> for Error {
+    fn from(error: tauri::Error) -> Self {
+        Self::internal("Background task failed").with_details(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_message_and_details() {
+        let error = Error::not_authorized("Calendar access not authorized");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "NOT_AUTHORIZED", "message": "Calendar access not authorized" })
+        );
+
+        let error = Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "gone"))
+            .with_details("/tmp/a");
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            serde_json::json!({ "code": "NOT_FOUND", "message": "gone", "details": "/tmp/a" })
+        );
+
+        assert_eq!(
+            serde_json::to_value(ErrorCode::InvalidArgument).unwrap(),
+            "INVALID_ARGUMENT"
+        );
+    }
+}
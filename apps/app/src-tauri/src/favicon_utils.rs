@@ -3,6 +3,9 @@ use tauri::{command, AppHandle, Runtime};
 use tauri_plugin_http::reqwest::{header::{HeaderMap, USER_AGENT, ACCEPT, ACCEPT_LANGUAGE}, Client};
 use url::Url;
 
+use crate::error::{self, Error};
+use crate::icon_render::to_png;
+
 async fn try_fetch_favicon_from_url(
     client: &Client,
     target_url: &Url,
@@ -26,12 +29,35 @@ async fn try_fetch_favicon_from_url(
 }
 
 #[command]
+#[specta::specta]
 pub async fn fetch_favicon<R: Runtime>(
     _app_handle: AppHandle<R>,
     url: String,
-) -> Result<String, String> {
-    let base_url = Url::parse(&url).map_err(|e| format!("Initial URL parse failed: {}", e))?;
-    
+) -> error::Result<String> {
+    let client = build_client().map_err(Error::internal)?;
+    find_favicon_url(&client, &url).await.map_err(Error::network)
+}
+
+// Downloads the favicon of `host` (with an optional port) and converts it to PNG
+pub async fn load_favicon_png(host: &str) -> Result<Vec<u8>, String> {
+    let client = build_client()?;
+    let icon_url = find_favicon_url(&client, &format!("https://{}", host)).await?;
+
+    let response = client
+        .get(&icon_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to download favicon {}: {}", icon_url, e))?;
+    let data = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read favicon {}: {}", icon_url, e))?;
+
+    to_png(&data).ok_or_else(|| format!("Unsupported favicon format: {}", icon_url))
+}
+
+fn build_client() -> Result<Client, String> {
     // Artificial headers
     let mut headers = HeaderMap::new();
     headers.insert(USER_AGENT, "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36".parse().unwrap());
@@ -49,14 +75,18 @@ pub async fn fetch_favicon<R: Runtime>(
     headers.insert("priority", "u=0, i".parse().unwrap());
 
     // Build the client with default headers and timeout
-    let client = Client::builder()
+    Client::builder()
         .default_headers(headers)
         .timeout(std::time::Duration::from_secs(2))
         .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+async fn find_favicon_url(client: &Client, url: &str) -> Result<String, String> {
+    let base_url = Url::parse(url).map_err(|e| format!("Initial URL parse failed: {}", e))?;
 
     // 1. Try the original URL
-    match try_fetch_favicon_from_url(&client, &base_url).await {
+    match try_fetch_favicon_from_url(client, &base_url).await {
         Ok(icon_url) => {
             return Ok(icon_url); // Early return on success
         }
@@ -94,7 +124,7 @@ pub async fn fetch_favicon<R: Runtime>(
 
             // 3. Try www URL if constructed
             if let Some(www_url) = www_url_option {
-                match try_fetch_favicon_from_url(&client, &www_url).await {
+                match try_fetch_favicon_from_url(client, &www_url).await {
                     Ok(icon_url) => {
                         return Ok(icon_url); // Early return on www success
                     }
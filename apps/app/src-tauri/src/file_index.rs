@@ -0,0 +1,684 @@
+// src-tauri/src/file_index.rs
+//
+// Persistent file name index. It covers the same directories as
+// `search_spotlight_apps`, is stored in the app data directory so startup does
+// not rescan, and is kept up to date by a file system watcher.
+
+use chrono::{DateTime, Utc};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager, Runtime, State};
+use walkdir::WalkDir;
+
+use crate::error::{Error, Result};
+use crate::search_query::FileQueryFilter;
+use crate::search_ranking::{sort_by_rank, SearchRanker};
+use crate::spotlight_utils::{
+    content_type_for, fold_name, is_hidden_name, search_scopes, FileSearchRequest,
+    SpotlightAppInfo, MAX_SEARCH_RESULTS,
+};
+
+const INDEX_FILE_NAME: &str = "file-index.json";
+// Bump when the stored format changes, older files are rebuilt
+const INDEX_VERSION: u32 = 1;
+// Upper bound to keep memory in check on very large home directories
+const MAX_INDEXED_ENTRIES: usize = 1_000_000;
+// Directories that are never worth searching through
+const IGNORED_DIRECTORY_NAMES: [&str; 1] = ["node_modules"];
+// Matches that get the full ranking, chosen by how well their names match
+const RANKING_CANDIDATES: usize = MAX_SEARCH_RESULTS * 4;
+// How often pending incremental updates are flushed to disk
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+struct IndexedEntry {
+    is_dir: bool,
+    folded_name: String,
+}
+
+struct FileIndex {
+    roots: Vec<PathBuf>,
+    // Ordered by path so a directory and its descendants are contiguous
+    entries: BTreeMap<PathBuf, IndexedEntry>,
+    last_scan: DateTime<Utc>,
+}
+
+// On-disk representation of the index
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    last_scan: DateTime<Utc>,
+    roots: Vec<PathBuf>,
+    entries: Vec<(PathBuf, bool)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, specta::Type)]
+pub struct FileIndexStats {
+    pub file_count: usize,
+    pub last_scan: Option<String>, // ISO 8601
+    pub size_bytes: u64,           // Size of the index file on disk
+    pub is_ready: bool,
+    pub is_building: bool,
+}
+
+pub struct FileIndexState {
+    // None until the index was loaded from disk or built
+    index: RwLock<Option<FileIndex>>,
+    index_path: Mutex<Option<PathBuf>>,
+    // Also used to watch directories created later on
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    // Watcher events seen while a rebuild walks the roots, replayed on the
+    // new index. Only Some during a rebuild, always locked after `index`.
+    queued_events: Mutex<Option<Vec<Event>>>,
+    is_building: AtomicBool,
+    is_dirty: AtomicBool,
+    // Set once inotify ran out of watches, after which no more are added
+    watch_limit_reached: AtomicBool,
+}
+
+impl FileIndex {
+    fn build(roots: Vec<PathBuf>) -> Self {
+        let mut index = Self {
+            roots: roots.clone(),
+            entries: BTreeMap::new(),
+            last_scan: Utc::now(),
+        };
+
+        for root in roots {
+            index.insert_tree(&root);
+        }
+
+        index
+    }
+
+    fn load(path: &Path, roots: &[PathBuf]) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| Error::io("Failed to read file index").with_details(e))?;
+        let stored: StoredIndex =
+            serde_json::from_slice(&data).map_err(|e| Error::io("Failed to parse file index").with_details(e))?;
+
+        if stored.version != INDEX_VERSION {
+            return Err(Error::io(format!("Unsupported file index version: {}", stored.version)));
+        }
+        if stored.roots != roots {
+            return Err(Error::io("File index was built for different directories"));
+        }
+
+        let entries = stored
+            .entries
+            .into_iter()
+            .map(|(path, is_dir)| {
+                let folded_name = fold_name(&file_name(&path));
+                (path, IndexedEntry { is_dir, folded_name })
+            })
+            .collect();
+
+        Ok(Self {
+            roots: stored.roots,
+            entries,
+            last_scan: stored.last_scan,
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        let stored = StoredIndex {
+            version: INDEX_VERSION,
+            last_scan: self.last_scan,
+            roots: self.roots.clone(),
+            entries: self
+                .entries
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.is_dir))
+                .collect(),
+        };
+
+        let data = serde_json::to_vec(&stored).map_err(|e| Error::internal("Failed to serialize file index").with_details(e))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| Error::io("Failed to create index directory").with_details(e))?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a truncated index
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, data).map_err(|e| Error::io("Failed to write file index").with_details(e))?;
+        fs::rename(&tmp_path, path).map_err(|e| Error::io("Failed to replace file index").with_details(e))
+    }
+
+    // Whether `path` lives under one of the roots without crossing a hidden or
+    // ignored directory
+    fn is_indexable(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| match path.strip_prefix(root) {
+            Ok(relative) => {
+                !relative.as_os_str().is_empty()
+                    && relative
+                        .components()
+                        .all(|component| is_indexable_name(&component.as_os_str().to_string_lossy()))
+            }
+            Err(_) => false,
+        })
+    }
+
+    // Adds `path` and, for directories, everything below it
+    fn insert_tree(&mut self, path: &Path) {
+        let is_root = self.roots.iter().any(|root| root == path);
+        if !is_root && !self.is_indexable(path) {
+            return;
+        }
+
+        let walker = WalkDir::new(path)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || is_indexable_name(&entry.file_name().to_string_lossy()));
+
+        for entry in walker.filter_map(Result::ok) {
+            if entry.depth() == 0 && is_root {
+                continue;
+            }
+            if self.entries.len() >= MAX_INDEXED_ENTRIES {
+                break;
+            }
+
+            self.entries.insert(
+                entry.path().to_path_buf(),
+                IndexedEntry {
+                    is_dir: entry.file_type().is_dir(),
+                    folded_name: fold_name(&entry.file_name().to_string_lossy()),
+                },
+            );
+        }
+    }
+
+    // Removes `path` and everything below it
+    fn remove_tree(&mut self, path: &Path) {
+        let descendants: Vec<PathBuf> = self
+            .entries
+            .range(path.to_path_buf()..)
+            .take_while(|(entry_path, _)| entry_path.starts_with(path))
+            .map(|(entry_path, _)| entry_path.clone())
+            .collect();
+
+        for entry_path in descendants {
+            self.entries.remove(&entry_path);
+        }
+    }
+
+    fn apply_event(&mut self, event: Event) -> bool {
+        match event.kind {
+            EventKind::Create(_) => {
+                for path in &event.paths {
+                    self.insert_tree(path);
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in &event.paths {
+                    self.remove_tree(path);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                self.remove_tree(&event.paths[0]);
+                self.insert_tree(&event.paths[1]);
+            }
+            EventKind::Modify(ModifyKind::Name(_)) => {
+                // Either half of a rename, the file system tells which one it was
+                for path in &event.paths {
+                    if path.exists() {
+                        self.insert_tree(path);
+                    } else {
+                        self.remove_tree(path);
+                    }
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+
+    // Indexed directories at or below `path`, the ones to watch on inotify
+    fn directories_under(&self, path: &Path) -> Vec<PathBuf> {
+        let mut directories: Vec<PathBuf> = self
+            .entries
+            .range(path.to_path_buf()..)
+            .take_while(|(entry_path, _)| entry_path.starts_with(path))
+            .filter(|(_, entry)| entry.is_dir)
+            .map(|(entry_path, _)| entry_path.clone())
+            .collect();
+        if self.roots.iter().any(|root| root == path) {
+            directories.insert(0, path.to_path_buf());
+        }
+        directories
+    }
+
+    fn covers(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    fn search(&self, request: &FileSearchRequest) -> Vec<SpotlightAppInfo> {
+        let filter = FileQueryFilter::new(&request.query, &request.extensions);
+        let mut matches = Vec::new();
+
+        for scope in request.scopes() {
+            let entries = self
+                .entries
+                .range(scope.clone()..)
+                .take_while(|(path, _)| path.starts_with(&scope));
+
+            for (path, entry) in entries {
+                let name = file_name(path);
+                if path == &scope || !filter.matches_folded(path, &name, &entry.folded_name, entry.is_dir) {
+                    continue;
+                }
+
+                matches.push(SpotlightAppInfo {
+                    path: path.to_string_lossy().into_owned(),
+                    content_type: content_type_for(path, entry.is_dir),
+                    display_name: name,
+                    score: 0.0,
+                    match_ranges: Vec::new(),
+                });
+            }
+        }
+
+        // Scopes may overlap, sorting by path puts duplicates next to each other
+        matches.sort_by(|a, b| a.path.cmp(&b.path));
+        matches.dedup_by(|a, b| a.path == b.path);
+
+        // Narrow down by name first, the full ranking reads file metadata
+        let ranker = SearchRanker::new(&request.query);
+        for info in &mut matches {
+            info.score = ranker.name_score(&info.display_name);
+        }
+        sort_by_rank(&mut matches);
+        matches.truncate(RANKING_CANDIDATES);
+
+        for info in &mut matches {
+            ranker.rank(info);
+        }
+        sort_by_rank(&mut matches);
+        matches.truncate(MAX_SEARCH_RESULTS);
+        matches
+    }
+}
+
+impl FileIndexState {
+    pub fn new() -> Self {
+        Self {
+            index: RwLock::new(None),
+            index_path: Mutex::new(None),
+            watcher: Mutex::new(None),
+            queued_events: Mutex::new(None),
+            is_building: AtomicBool::new(false),
+            is_dirty: AtomicBool::new(false),
+            watch_limit_reached: AtomicBool::new(false),
+        }
+    }
+
+    // Answers a search from the index, or None while it is not ready yet
+    pub fn search(&self, request: &FileSearchRequest) -> Option<Vec<SpotlightAppInfo>> {
+        let index = self.index.read().ok()?;
+        let index = index.as_ref()?;
+
+        // `in:` may point outside the indexed directories
+        if !request.scopes().iter().all(|scope| index.covers(scope)) {
+            return None;
+        }
+        Some(index.search(request))
+    }
+
+    pub fn stats(&self) -> FileIndexStats {
+        let index = self.index.read().unwrap();
+        let size_bytes = self
+            .index_path()
+            .and_then(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        FileIndexStats {
+            file_count: index.as_ref().map(|index| index.entries.len()).unwrap_or(0),
+            last_scan: index.as_ref().map(|index| index.last_scan.to_rfc3339()),
+            size_bytes,
+            is_ready: index.is_some(),
+            is_building: self.is_building.load(Ordering::SeqCst),
+        }
+    }
+
+    // Rescans all roots and persists the result. Blocks for the whole scan,
+    // the previous index keeps answering searches meanwhile.
+    pub fn rebuild(&self) -> Result<()> {
+        if self.is_building.swap(true, Ordering::SeqCst) {
+            return Err(Error::busy("File index is already being rebuilt"));
+        }
+
+        *self.queued_events.lock().unwrap() = Some(Vec::new());
+        let index = FileIndex::build(index_roots());
+        let result = self.save_index(&index);
+        self.install(index);
+        self.is_building.store(false, Ordering::SeqCst);
+        // Directories may have been created while nothing watched them
+        self.watch_directories(self.indexed_directories());
+
+        result
+    }
+
+    // Swaps in a freshly built index, after replaying what changed during the build
+    fn install(&self, mut index: FileIndex) {
+        let mut current = self.index.write().unwrap();
+        let queued = self.queued_events.lock().unwrap().take().unwrap_or_default();
+        let mut changed = false;
+        for event in queued {
+            changed |= index.apply_event(event);
+        }
+        self.is_dirty.store(changed, Ordering::SeqCst);
+        *current = Some(index);
+    }
+
+    fn index_path(&self) -> Option<PathBuf> {
+        self.index_path.lock().unwrap().clone()
+    }
+
+    fn save_index(&self, index: &FileIndex) -> Result<()> {
+        match self.index_path() {
+            Some(path) => index.save(&path),
+            None => Err(Error::internal("File index location is not set")),
+        }
+    }
+
+    fn flush(&self) {
+        if !self.is_dirty.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        let index = self.index.read().unwrap();
+        if let Some(index) = index.as_ref() {
+            if let Err(e) = self.save_index(index) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+
+    fn handle_event(&self, event: Event) {
+        let new_directories = {
+            let mut index = self.index.write().unwrap();
+            if let Some(queued) = self.queued_events.lock().unwrap().as_mut() {
+                queued.push(event.clone());
+            }
+            let Some(index) = index.as_mut() else {
+                return;
+            };
+            let is_addition = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_)));
+            let paths = event.paths.clone();
+            if !index.apply_event(event) {
+                return;
+            }
+            self.is_dirty.store(true, Ordering::SeqCst);
+
+            match is_addition && !watches_recursively() {
+                true => paths.iter().flat_map(|path| index.directories_under(path)).collect(),
+                false => Vec::new(),
+            }
+        };
+        self.watch_directories(new_directories);
+    }
+
+    // Every directory that needs its own watch, only the roots with FSEvents
+    fn indexed_directories(&self) -> Vec<PathBuf> {
+        let index = self.index.read().unwrap();
+        let Some(index) = index.as_ref() else {
+            return Vec::new();
+        };
+        match watches_recursively() {
+            true => index.roots.clone(),
+            false => index.roots.iter().flat_map(|root| index.directories_under(root)).collect(),
+        }
+    }
+
+    fn watch_directories(&self, directories: Vec<PathBuf>) {
+        if self.watch_limit_reached.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut watcher = self.watcher.lock().unwrap();
+        let Some(watcher) = watcher.as_mut() else {
+            return;
+        };
+        let mode = match watches_recursively() {
+            true => RecursiveMode::Recursive,
+            false => RecursiveMode::NonRecursive,
+        };
+        for directory in directories {
+            match watcher.watch(&directory, mode) {
+                Ok(()) => {}
+                // Every further watch would fail the same way
+                Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                    self.watch_limit_reached.store(true, Ordering::SeqCst);
+                    eprintln!(
+                        "Stopped watching at {}: {}. Raise fs.inotify.max_user_watches to watch more.",
+                        directory.display(),
+                        e
+                    );
+                    break;
+                }
+                Err(e) => eprintln!("Failed to watch {}: {}", directory.display(), e),
+            }
+        }
+    }
+}
+
+// FSEvents follows a whole tree with a single stream. inotify needs a watch
+// per directory, so only the indexed ones are watched: recursive watches would
+// also cover hidden and ignored trees like .cache or node_modules and run into
+// `max_user_watches`.
+fn watches_recursively() -> bool {
+    cfg!(target_os = "macos")
+}
+
+// Loads (or builds) the index in the background and keeps it up to date.
+// Called once from the setup hook.
+pub fn start<R: Runtime>(app_handle: AppHandle<R>) {
+    std::thread::spawn(move || {
+        let state = app_handle.state::<FileIndexState>();
+
+        let index_path = match app_handle.path().app_data_dir() {
+            Ok(dir) => dir.join(INDEX_FILE_NAME),
+            Err(e) => {
+                eprintln!("Failed to resolve app data directory for the file index: {}", e);
+                return;
+            }
+        };
+        *state.index_path.lock().unwrap() = Some(index_path.clone());
+
+        let roots = index_roots();
+
+        match FileIndex::load(&index_path, &roots) {
+            Ok(index) => *state.index.write().unwrap() = Some(index),
+            Err(_) => {
+                if let Err(e) = state.rebuild() {
+                    eprintln!("Failed to build file index: {}", e);
+                }
+            }
+        }
+
+        let handle_for_watcher = app_handle.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            if let Ok(event) = result {
+                handle_for_watcher.state::<FileIndexState>().handle_event(event);
+            }
+        });
+
+        match watcher {
+            Ok(watcher) => {
+                *state.watcher.lock().unwrap() = Some(watcher);
+                state.watch_directories(state.indexed_directories());
+            }
+            Err(e) => eprintln!("Failed to create file index watcher: {}", e),
+        }
+
+        loop {
+            std::thread::sleep(SAVE_INTERVAL);
+            state.flush();
+        }
+    });
+}
+
+// Directories covering both search modes, without nesting
+pub(crate) fn index_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    for scope in search_scopes(true).into_iter().chain(search_scopes(false)) {
+        if !roots.iter().any(|root| scope.starts_with(root)) {
+            roots.retain(|root| !root.starts_with(&scope));
+            roots.push(scope);
+        }
+    }
+
+    roots.sort();
+    roots
+}
+
+pub(crate) fn is_indexable_name(name: &str) -> bool {
+    !is_hidden_name(name) && !IGNORED_DIRECTORY_NAMES.contains(&name)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[command]
+#[specta::specta]
+pub async fn rebuild_file_index<R: Runtime>(app_handle: AppHandle<R>) -> Result<FileIndexStats> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let state = app_handle.state::<FileIndexState>();
+        state.rebuild()?;
+        Ok(state.stats())
+    })
+    .await?
+}
+
+#[command]
+#[specta::specta]
+pub fn get_file_index_stats(state: State<'_, FileIndexState>) -> Result<FileIndexStats> {
+    Ok(state.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_query::parse_query;
+    use crate::test_utils::{create_entry, scratch_tree};
+    use notify::event::{CreateKind, RemoveKind};
+
+    fn indexed(index: &FileIndex, root: &Path) -> Vec<String> {
+        index
+            .entries
+            .keys()
+            .map(|path| path.strip_prefix(root).unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn event(kind: EventKind, paths: &[PathBuf]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| event.add_path(path.clone()))
+    }
+
+    #[test]
+    fn skips_hidden_and_ignored_directories() {
+        let root = scratch_tree(
+            "index-build",
+            &["notes.txt", "Projects/app/main.rs", "Projects/app/node_modules/dep.js", ".cache/blob"],
+        );
+        let index = FileIndex::build(vec![root.clone()]);
+
+        assert_eq!(indexed(&index, &root), ["Projects", "Projects/app", "Projects/app/main.rs", "notes.txt"]);
+        assert!(index.is_indexable(&root.join("Projects/new.txt")));
+        assert!(!index.is_indexable(&root));
+        assert!(!index.is_indexable(&root.join(".cache/new")));
+        assert!(!index.is_indexable(&root.join("Projects/node_modules")));
+        assert!(!index.is_indexable(Path::new("/elsewhere/new.txt")));
+        assert_eq!(
+            index.directories_under(&root),
+            [root.clone(), root.join("Projects"), root.join("Projects/app")]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn applies_watcher_events() {
+        let root = scratch_tree("index-events", &["Projects/app/main.rs", "Projects/app/lib.rs", "Projects2/x.txt"]);
+        let mut index = FileIndex::build(vec![root.clone()]);
+
+        create_entry(&root.join("Drafts/idea.txt"), "");
+        create_entry(&root.join(".git/HEAD"), "");
+        assert!(index.apply_event(event(EventKind::Create(CreateKind::Folder), &[root.join("Drafts"), root.join(".git")])));
+        // Only the tree itself goes, not its neighbour sharing a name prefix
+        index.remove_tree(&root.join("Projects"));
+        assert_eq!(indexed(&index, &root), ["Drafts", "Drafts/idea.txt", "Projects2", "Projects2/x.txt"]);
+
+        fs::rename(root.join("Drafts"), root.join("Ideas")).unwrap();
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        assert!(index.apply_event(event(rename, &[root.join("Drafts"), root.join("Ideas")])));
+        assert!(index.apply_event(event(EventKind::Remove(RemoveKind::File), &[root.join("Projects2/x.txt")])));
+        assert!(!index.apply_event(event(EventKind::Access(notify::event::AccessKind::Any), &[root.join("Ideas")])));
+        assert_eq!(indexed(&index, &root), ["Ideas", "Ideas/idea.txt", "Projects2"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn searches_within_scopes() {
+        let root = scratch_tree("index-search", &["report.txt", "Old/report draft.txt", "report.png", "Reports/"]);
+        let index = FileIndex::build(vec![root.clone()]);
+        let search = |query: &str| {
+            let request = FileSearchRequest {
+                query: parse_query(query).unwrap(),
+                extensions: vec!["*.txt".to_string()],
+                search_only_in_home: false,
+            };
+            let mut names: Vec<String> = index.search(&request).into_iter().map(|info| info.display_name).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(search(&format!("in:{} report", root.display())), ["Reports", "report draft.txt", "report.txt"]);
+        assert_eq!(search(&format!("in:{} draft", root.join("Old").display())), ["report draft.txt"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn survives_a_save_and_load() {
+        let root = scratch_tree("index-store", &["a.txt", "Folder/b.txt"]);
+        let index = FileIndex::build(vec![root.clone()]);
+        let path = root.join("Store/file-index.json");
+        index.save(&path).unwrap();
+
+        let loaded = FileIndex::load(&path, &[root.clone()]).unwrap();
+        assert_eq!(indexed(&loaded, &root), indexed(&index, &root));
+        assert!(loaded.entries[&root.join("Folder")].is_dir);
+        assert_eq!(loaded.entries[&root.join("a.txt")].folded_name, "a.txt");
+        assert!(FileIndex::load(&path, &[root.join("Folder")]).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn replays_events_from_during_a_rebuild() {
+        let root = scratch_tree("index-rebuild", &["a.txt"]);
+        let state = FileIndexState::new();
+        *state.index.write().unwrap() = Some(FileIndex::build(vec![root.clone()]));
+
+        *state.queued_events.lock().unwrap() = Some(Vec::new());
+        let rebuilt = FileIndex::build(vec![root.clone()]);
+        // Happens after the walk passed by
+        create_entry(&root.join("b.txt"), "");
+        state.handle_event(event(EventKind::Create(CreateKind::File), &[root.join("b.txt")]));
+        state.install(rebuilt);
+
+        let index = state.index.read().unwrap();
+        assert_eq!(indexed(index.as_ref().unwrap(), &root), ["a.txt", "b.txt"]);
+        assert!(state.is_dirty.load(Ordering::SeqCst));
+        assert!(state.queued_events.lock().unwrap().is_none());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,446 @@
+// src-tauri/src/icns.rs
+//
+// ICNS decoder. An .icns file is a list of typed elements, each holding one
+// image in one of several encodings that accumulated over the years:
+//   - PNG or JPEG 2000 files (ic07-ic14, icp4-icp6, ic04/ic05, sb24, icsb...)
+//   - "ARGB" followed by run-length encoded channels (ic04, ic05, icsb)
+//   - run-length encoded RGB with a separate 8-bit mask (is32/s8mk, il32/l8mk,
+//     ih32/h8mk, it32/t8mk, older icp4/icp5)
+//   - 1, 4 and 8-bit images with a 1-bit mask from the pre-OS X era
+//     (ICN#, icl4, icl8 and their small/mini/huge variants)
+// Everything except JPEG 2000 is decoded here. JPEG 2000 goes through the
+// decoder the caller passes in, the platform's image library if it has one:
+// ImageIO on macOS, none on Linux, where no pure Rust decoder is at hand.
+// Without one, those elements (usually ic09/ic10, 512px and up) are skipped
+// and `best_png` falls back to the next best element, typically the 256px or
+// smaller PNG that icon sets carry as well.
+
+// Only app bundles on macOS carry ICNS files, elsewhere this is just tested
+#![cfg_attr(not(target_os = "macos"), allow(dead_code))]
+
+use std::io::BufWriter;
+
+const HEADER_MAGIC: &[u8; 4] = b"icns";
+const PNG_SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+// JP2 container and bare codestream signatures
+const JP2_SIGNATURE: &[u8; 12] = b"\x00\x00\x00\x0CjP  \r\n\x87\n";
+const J2K_SIGNATURE: &[u8; 4] = b"\xFF\x4F\xFF\x51";
+const ARGB_MAGIC: &[u8; 4] = b"ARGB";
+
+// Classic Mac OS 16 color palette used by the 4-bit icons
+const PALETTE_4BIT: [[u8; 3]; 16] = [
+    [0xFF, 0xFF, 0xFF],
+    [0xFC, 0xF3, 0x05],
+    [0xFF, 0x64, 0x02],
+    [0xDD, 0x08, 0x06],
+    [0xF2, 0x08, 0x84],
+    [0x46, 0x00, 0xA5],
+    [0x00, 0x00, 0xD4],
+    [0x02, 0xAB, 0xEA],
+    [0x1F, 0xB7, 0x14],
+    [0x00, 0x64, 0x11],
+    [0x56, 0x2C, 0x05],
+    [0x90, 0x71, 0x3A],
+    [0xC0, 0xC0, 0xC0],
+    [0x80, 0x80, 0x80],
+    [0x40, 0x40, 0x40],
+    [0x00, 0x00, 0x00],
+];
+
+// How an element stores its image
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    // PNG, JPEG 2000 or ARGB, told apart by the data
+    Container,
+    // Run-length encoded RGB, with the mask in the named element
+    Rle24 { mask: [u8; 4], prefix: usize },
+    // PNG, JPEG 2000 or, in older files, run-length encoded RGB
+    ContainerOrRle24 { mask: [u8; 4] },
+    // 1-bit image followed by its 1-bit mask
+    Mono,
+    // 4 or 8-bit palette image, masked by the named 1-bit element
+    Indexed { depth: u8, mask: [u8; 4] },
+}
+
+struct ElementType {
+    os_type: &'static [u8; 4],
+    width: u32,
+    height: u32,
+    encoding: Encoding,
+}
+
+const fn element(os_type: &'static [u8; 4], width: u32, height: u32, encoding: Encoding) -> ElementType {
+    ElementType {
+        os_type,
+        width,
+        height,
+        encoding,
+    }
+}
+
+const ELEMENT_TYPES: [ElementType; 33] = [
+    element(b"ic10", 1024, 1024, Encoding::Container),
+    element(b"ic09", 512, 512, Encoding::Container),
+    element(b"ic14", 512, 512, Encoding::Container),
+    element(b"ic08", 256, 256, Encoding::Container),
+    element(b"ic13", 256, 256, Encoding::Container),
+    element(b"ic07", 128, 128, Encoding::Container),
+    element(b"icp6", 64, 64, Encoding::Container),
+    element(b"ic12", 64, 64, Encoding::Container),
+    element(b"SB24", 48, 48, Encoding::Container),
+    element(b"icsB", 36, 36, Encoding::Container),
+    element(b"ic05", 32, 32, Encoding::Container),
+    element(b"ic11", 32, 32, Encoding::Container),
+    element(b"sb24", 24, 24, Encoding::Container),
+    element(b"icsb", 18, 18, Encoding::Container),
+    element(b"ic04", 16, 16, Encoding::Container),
+    element(b"icp5", 32, 32, Encoding::ContainerOrRle24 { mask: *b"l8mk" }),
+    element(b"icp4", 16, 16, Encoding::ContainerOrRle24 { mask: *b"s8mk" }),
+    element(b"it32", 128, 128, Encoding::Rle24 { mask: *b"t8mk", prefix: 4 }),
+    element(b"ih32", 48, 48, Encoding::Rle24 { mask: *b"h8mk", prefix: 0 }),
+    element(b"il32", 32, 32, Encoding::Rle24 { mask: *b"l8mk", prefix: 0 }),
+    element(b"is32", 16, 16, Encoding::Rle24 { mask: *b"s8mk", prefix: 0 }),
+    element(b"ich8", 48, 48, Encoding::Indexed { depth: 8, mask: *b"ich#" }),
+    element(b"icl8", 32, 32, Encoding::Indexed { depth: 8, mask: *b"ICN#" }),
+    element(b"ics8", 16, 16, Encoding::Indexed { depth: 8, mask: *b"ics#" }),
+    element(b"icm8", 16, 12, Encoding::Indexed { depth: 8, mask: *b"icm#" }),
+    element(b"ich4", 48, 48, Encoding::Indexed { depth: 4, mask: *b"ich#" }),
+    element(b"icl4", 32, 32, Encoding::Indexed { depth: 4, mask: *b"ICN#" }),
+    element(b"ics4", 16, 16, Encoding::Indexed { depth: 4, mask: *b"ics#" }),
+    element(b"icm4", 16, 12, Encoding::Indexed { depth: 4, mask: *b"icm#" }),
+    element(b"ich#", 48, 48, Encoding::Mono),
+    element(b"ICN#", 32, 32, Encoding::Mono),
+    element(b"ics#", 16, 16, Encoding::Mono),
+    element(b"icm#", 16, 12, Encoding::Mono),
+];
+
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut png_data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(BufWriter::new(&mut png_data), self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder
+                .write_header()
+                .map_err(|e| format!("Failed to write PNG header: {}", e))?;
+            writer
+                .write_image_data(&self.pixels)
+                .map_err(|e| format!("Failed to write PNG data: {}", e))?;
+        }
+        Ok(png_data)
+    }
+}
+
+pub struct IconFamily<'a> {
+    elements: Vec<([u8; 4], &'a [u8])>,
+}
+
+impl<'a> IconFamily<'a> {
+    pub fn parse(data: &'a [u8]) -> Result<Self, String> {
+        if data.len() < 8 || &data[0..4] != HEADER_MAGIC {
+            return Err("Not an ICNS file".to_string());
+        }
+        // The header length is not trusted, some tools write it wrong
+        let mut elements = Vec::new();
+        let mut offset = 8;
+
+        while offset + 8 <= data.len() {
+            let os_type: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+            let length = u32::from_be_bytes(data[offset + 4..offset + 8].try_into().unwrap()) as usize;
+            if length < 8 || offset + length > data.len() {
+                break;
+            }
+            elements.push((os_type, &data[offset + 8..offset + length]));
+            offset += length;
+        }
+
+        Ok(Self { elements })
+    }
+
+    fn element(&self, os_type: &[u8; 4]) -> Option<&'a [u8]> {
+        self.elements
+            .iter()
+            .find(|(element_type, _)| element_type == os_type)
+            .map(|(_, data)| *data)
+    }
+
+    // PNG data of the image closest to `pixel_size`: the smallest one at
+    // least that large, otherwise the largest one. Elements that fail to
+    // decode are skipped in favor of the next best.
+    pub fn best_png(&self, pixel_size: u32, decode_jpeg2000: impl Fn(&[u8]) -> Option<Vec<u8>>) -> Option<Vec<u8>> {
+        let mut candidates: Vec<(&ElementType, &[u8])> = ELEMENT_TYPES
+            .iter()
+            .filter_map(|element_type| Some((element_type, self.element(element_type.os_type)?)))
+            .collect();
+
+        // Stable, so at equal size the order of ELEMENT_TYPES (best encoding first) decides
+        candidates.sort_by_key(|(element_type, _)| {
+            let size = element_type.width.max(element_type.height);
+            if size >= pixel_size {
+                (0, size)
+            } else {
+                (1, u32::MAX - size)
+            }
+        });
+
+        candidates
+            .into_iter()
+            .find_map(|(element_type, data)| self.decode_png(element_type, data, &decode_jpeg2000))
+    }
+
+    fn decode_png(
+        &self,
+        element_type: &ElementType,
+        data: &[u8],
+        decode_jpeg2000: &impl Fn(&[u8]) -> Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        if data.starts_with(PNG_SIGNATURE) {
+            return Some(data.to_vec());
+        }
+        if data.starts_with(JP2_SIGNATURE) || data.starts_with(J2K_SIGNATURE) {
+            return decode_jpeg2000(data);
+        }
+        self.decode(element_type, data)?.to_png().ok()
+    }
+
+    fn decode(&self, element_type: &ElementType, data: &[u8]) -> Option<RgbaImage> {
+        let (width, height) = (element_type.width, element_type.height);
+        let pixel_count = (width * height) as usize;
+
+        let pixels = match element_type.encoding {
+            Encoding::Container => {
+                let channels = decode_rle(data.strip_prefix(ARGB_MAGIC)?, pixel_count, 4)?;
+                let (alpha, rgb) = channels.split_at(pixel_count);
+                interleave(rgb, Some(alpha), pixel_count)
+            }
+            Encoding::Rle24 { mask, prefix } => {
+                let rgb = decode_rgb(data.get(prefix..)?, pixel_count)?;
+                interleave(&rgb, self.alpha_mask(&mask, pixel_count), pixel_count)
+            }
+            Encoding::ContainerOrRle24 { mask } => {
+                let rgb = decode_rgb(data, pixel_count)?;
+                interleave(&rgb, self.alpha_mask(&mask, pixel_count), pixel_count)
+            }
+            Encoding::Mono => {
+                let bits = unpack_bits(data, 1, pixel_count)?;
+                let mask = unpack_bits(data.get(pixel_count / 8..)?, 1, pixel_count)?;
+                bits.iter()
+                    .zip(&mask)
+                    .flat_map(|(bit, mask)| {
+                        let value = if *bit == 1 { 0x00 } else { 0xFF };
+                        [value, value, value, if *mask == 1 { 0xFF } else { 0x00 }]
+                    })
+                    .collect()
+            }
+            Encoding::Indexed { depth, mask } => {
+                let indices = unpack_bits(data, depth, pixel_count)?;
+                let mask = self
+                    .element(&mask)
+                    .and_then(|mono| unpack_bits(mono.get(pixel_count / 8..)?, 1, pixel_count));
+                indices
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, index)| {
+                        let [r, g, b] = if depth == 4 {
+                            PALETTE_4BIT[*index as usize]
+                        } else {
+                            palette_8bit(*index)
+                        };
+                        let alpha = match &mask {
+                            Some(mask) if mask[i] == 0 => 0x00,
+                            _ => 0xFF,
+                        };
+                        [r, g, b, alpha]
+                    })
+                    .collect()
+            }
+        };
+
+        Some(RgbaImage { width, height, pixels })
+    }
+
+    // 8-bit alpha channel of a RLE RGB image. Without one the image is opaque.
+    fn alpha_mask(&self, os_type: &[u8; 4], pixel_count: usize) -> Option<&'a [u8]> {
+        self.element(os_type)
+            .filter(|mask| mask.len() >= pixel_count)
+            .map(|mask| &mask[..pixel_count])
+    }
+}
+
+// Planar RGB, either run-length encoded or stored raw as xRGB
+fn decode_rgb(data: &[u8], pixel_count: usize) -> Option<Vec<u8>> {
+    if data.len() == pixel_count * 4 {
+        let mut planes = vec![0; pixel_count * 3];
+        for (i, pixel) in data.chunks_exact(4).enumerate() {
+            planes[i] = pixel[1];
+            planes[pixel_count + i] = pixel[2];
+            planes[pixel_count * 2 + i] = pixel[3];
+        }
+        return Some(planes);
+    }
+    decode_rle(data, pixel_count, 3)
+}
+
+// Apple's PackBits variant, one channel after the other: a header byte below
+// 0x80 is followed by header + 1 literal bytes, otherwise the next byte repeats
+// header - 125 times
+fn decode_rle(data: &[u8], pixel_count: usize, channels: usize) -> Option<Vec<u8>> {
+    let expected = pixel_count * channels;
+    let mut output = Vec::with_capacity(expected);
+    let mut input = data.iter();
+
+    while output.len() < expected {
+        let header = *input.next()? as usize;
+        if header < 0x80 {
+            for _ in 0..=header {
+                output.push(*input.next()?);
+            }
+        } else {
+            let value = *input.next()?;
+            output.extend(std::iter::repeat_n(value, header - 125));
+        }
+    }
+
+    // Runs may not cross channel boundaries, but be lenient about overshoot
+    output.truncate(expected);
+    Some(output)
+}
+
+// Most significant bits first
+fn unpack_bits(data: &[u8], depth: u8, pixel_count: usize) -> Option<Vec<u8>> {
+    let per_byte = (8 / depth) as usize;
+    let bytes = data.get(..pixel_count.div_ceil(per_byte))?;
+    let mask = ((1u16 << depth) - 1) as u8;
+
+    Some(
+        bytes
+            .iter()
+            .flat_map(|byte| (0..per_byte).map(move |i| (byte >> (8 - depth as usize * (i + 1))) & mask))
+            .take(pixel_count)
+            .collect(),
+    )
+}
+
+fn interleave(rgb: &[u8], alpha: Option<&[u8]>, pixel_count: usize) -> Vec<u8> {
+    (0..pixel_count)
+        .flat_map(|i| {
+            [
+                rgb[i],
+                rgb[pixel_count + i],
+                rgb[pixel_count * 2 + i],
+                alpha.map(|alpha| alpha[i]).unwrap_or(0xFF),
+            ]
+        })
+        .collect()
+}
+
+// Classic Mac OS 256 color palette: the 6x6x6 color cube from white down
+// (without black), ramps of red, green, blue and gray, then black
+fn palette_8bit(index: u8) -> [u8; 3] {
+    const CUBE: [u8; 6] = [0xFF, 0xCC, 0x99, 0x66, 0x33, 0x00];
+    const RAMP: [u8; 10] = [0xEE, 0xDD, 0xBB, 0xAA, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+
+    let index = index as usize;
+    match index {
+        0..=214 => [CUBE[index / 36], CUBE[index / 6 % 6], CUBE[index % 6]],
+        215..=224 => [RAMP[index - 215], 0, 0],
+        225..=234 => [0, RAMP[index - 225], 0],
+        235..=244 => [0, 0, RAMP[index - 235]],
+        245..=254 => {
+            let value = RAMP[index - 245];
+            [value, value, value]
+        }
+        _ => [0, 0, 0],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> Vec<u8> {
+        fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("icns").join(name)).unwrap()
+    }
+
+    fn decode(name: &str, pixel_size: u32) -> RgbaImage {
+        let data = fixture(name);
+        let png_data = IconFamily::parse(&data).unwrap().best_png(pixel_size, |_| None).unwrap();
+        let mut reader = png::Decoder::new(&png_data[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        RgbaImage {
+            width: info.width,
+            height: info.height,
+            pixels,
+        }
+    }
+
+    fn pixel(image: &RgbaImage, x: u32, y: u32) -> [u8; 4] {
+        let offset = ((y * image.width + x) * 4) as usize;
+        image.pixels[offset..offset + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn decodes_rle_without_mask_as_opaque() {
+        let image = decode("it32-no-mask.icns", 128);
+        assert_eq!((image.width, image.height), (128, 128));
+        assert_eq!(pixel(&image, 0, 0), [0x20, 0x40, 0x80, 0xFF]);
+        assert_eq!(pixel(&image, 127, 127), [0xF0, 0x40, 0x80, 0xFF]);
+    }
+
+    #[test]
+    fn picks_best_size_and_applies_masks() {
+        // 32 -> il32 with its l8mk mask
+        let image = decode("mixed.icns", 32);
+        assert_eq!((image.width, image.height), (32, 32));
+        assert_eq!(pixel(&image, 0, 0), [0x10, 0x20, 0x30, 0x00]);
+        assert_eq!(pixel(&image, 31, 0), [0x10, 0x20, 0x30, 0xFF]);
+
+        // Nothing larger than 16 below 20, but ic04 ARGB beats is32
+        let image = decode("mixed.icns", 8);
+        assert_eq!((image.width, image.height), (16, 16));
+        assert_eq!(pixel(&image, 3, 3), [0xC0, 0x00, 0x40, 0x80]);
+
+        // Larger than anything decodable -> the largest PNG
+        assert_eq!(decode("mixed.icns", 1024).width, 128);
+    }
+
+    #[test]
+    fn hands_jpeg2000_to_the_given_decoder() {
+        let data = fixture("mixed.icns");
+        let family = IconFamily::parse(&data).unwrap();
+        let marker = b"decoded jpeg 2000".to_vec();
+
+        assert_eq!(family.best_png(512, |_| Some(marker.clone())), Some(marker));
+        // Without a decoder, as on Linux, the 512px JPEG 2000 element is
+        // skipped for the next best one: the 128px ic07 PNG
+        let ic07 = family.element(b"ic07").unwrap();
+        assert_eq!(family.best_png(512, |_| None).as_deref(), Some(ic07));
+        assert_eq!(family.best_png(1024, |_| None).as_deref(), Some(ic07));
+        // Smaller sizes never get to the JPEG 2000 element
+        assert_eq!(decode("mixed.icns", 32).width, 32);
+    }
+
+    #[test]
+    fn decodes_legacy_palette_icons() {
+        let image = decode("legacy.icns", 32);
+        assert_eq!((image.width, image.height), (32, 32));
+        // icl8 index 5 is the cube's (FF, FF, 00), masked by ICN#
+        assert_eq!(pixel(&image, 0, 0), [0xFF, 0xFF, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 0, 31), [0xFF, 0xFF, 0x00, 0x00]);
+
+        // Only ics# at 16: black where the bit is set
+        let image = decode("legacy.icns", 16);
+        assert_eq!(pixel(&image, 0, 0), [0x00, 0x00, 0x00, 0xFF]);
+        assert_eq!(pixel(&image, 1, 0), [0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+}
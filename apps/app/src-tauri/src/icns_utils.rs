@@ -1,20 +1,18 @@
-use base64::{engine::general_purpose, Engine as _};
-use cocoa::base::{id, nil};
-use cocoa::foundation::{NSString, NSString as CocoaNSString};
-use objc::{class, msg_send, sel, sel_impl};
 use plist::{Dictionary, Value};
 use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{BufWriter, Write};
-use std::path::Path;
-use tauri::{command, AppHandle, Runtime};
-
-#[cfg(target_os = "macos")]
-use tauri_icns::{IconFamily, IconType};
-
-// Function to load an ICNS file and convert it to PNG
-#[cfg(target_os = "macos")]
-fn load_icns_file(path: &Path) -> Result<Option<Vec<u8>>, String> {
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager, Runtime, State};
+
+use crate::bundle_localization::{localization_sources, localized_app_names, preferred_languages};
+use crate::error::Result;
+use crate::icns::IconFamily;
+use crate::icon_cache::IconCacheState;
+use crate::icon_render::{resize_png, IconFormat, IconRequest};
+use crate::platform::{AppDiscoveryPlatform, IconPlatform, NativePlatform};
+
+// Function to load an ICNS file and convert its image closest to `pixel_size` to PNG
+fn load_icns_file(path: &Path, pixel_size: u32) -> Result<Option<Vec<u8>>, String> {
     // Try to read the ICNS file
     let icns_data = match fs::read(path) {
         Ok(data) => data,
@@ -27,82 +25,8 @@ fn load_icns_file(path: &Path) -> Result<Option<Vec<u8>>, String> {
         }
     };
 
-    // Parse the ICNS data
-    let icon_family = match IconFamily::read(&icns_data[..]) {
-        Ok(family) => family,
-        Err(e) => return Err(format!("Failed to parse ICNS data: {}", e)),
-    };
-
-    // Try to get the largest icon (preferably 256x256 or 128x128)
-    let icon_types = [
-        IconType::RGBA32_512x512,
-        IconType::RGBA32_256x256,
-        IconType::RGBA32_128x128,
-        IconType::RGB24_128x128,
-        IconType::RGBA32_512x512_2x,
-        IconType::RGBA32_256x256_2x,
-        IconType::RGBA32_128x128_2x,
-        IconType::RGBA32_64x64,
-        IconType::RGBA32_32x32,
-    ];
-
-    // First try our preferred icon types
-    for icon_type in icon_types {
-        match icon_family.get_icon_with_type(icon_type) {
-            Ok(image) => {
-                // Create a buffer to hold the PNG data
-                let mut png_data = Vec::new();
-                let writer = BufWriter::new(&mut png_data);
-
-                // Use the built-in write_png method
-                if let Err(_e) = image.write_png(writer) {
-                    // println!("Error writing PNG for icon type {:?}: {}", icon_type, e);
-                    continue;
-                }
-
-                return Ok(Some(png_data));
-            }
-            Err(_e) => {
-                // println!("Error extracting icon type {:?}: {}", icon_type, e);
-                continue;
-            }
-        }
-    }
-
-    // If we couldn't get any of our preferred types, try to get any available icon type
-    // println!("Trying fallback icon extraction for {}", path.display());
-
-    // Get all available icon types in the family
-    let available_types = icon_family.available_icons();
-    if available_types.is_empty() {
-        return Ok(None);
-    }
-
-    // Try each available type
-    for icon_type in available_types {
-        match icon_family.get_icon_with_type(icon_type) {
-            Ok(image) => {
-                // Create a buffer to hold the PNG data
-                let mut png_data = Vec::new();
-                let writer = BufWriter::new(&mut png_data);
-
-                // Use the built-in write_png method
-                if let Err(_e) = image.write_png(writer) {
-                    // println!("Error writing PNG for fallback icon type {:?}: {}", icon_type, e);
-                    continue;
-                }
-
-                return Ok(Some(png_data));
-            }
-            Err(_e) => {
-                // println!("Error extracting fallback icon type {:?}: {}", icon_type, e);
-                continue;
-            }
-        }
-    }
-
-    // No suitable icon found
-    Ok(None)
+    let icon_family = IconFamily::parse(&icns_data)?;
+    Ok(icon_family.best_png(pixel_size, NativePlatform::decode_jpeg2000))
 }
 
 // Helper function to format a single icon file path
@@ -192,24 +116,179 @@ fn extract_icon_path(plist_dict: Option<&Dictionary>, target_app_path: &str) ->
 }
 
 // Define a struct to hold app information
-#[derive(serde::Serialize, serde::Deserialize)]
-#[cfg(target_os = "macos")]
+#[derive(serde::Serialize, serde::Deserialize, specta::Type)]
 #[allow(non_snake_case)]
 pub struct AppInfo {
     pub base64Image: String,
     pub localizedName: String,
+    #[serde(flatten)]
+    pub metadata: AppMetadata,
+}
+
+// Details of an application from its Info.plist or .desktop file
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AppMetadata {
+    // CFBundleIdentifier, the desktop file ID on Linux
+    pub bundle_id: Option<String>,
+    // CFBundleShortVersionString, X-AppImage-Version on Linux
+    pub version: Option<String>,
+    // LSApplicationCategoryType, Categories on Linux
+    pub categories: Vec<String>,
+    // CFBundleURLSchemes, x-scheme-handler/* MIME types on Linux
+    pub url_schemes: Vec<String>,
+    // CFBundleDocumentTypes, the other MIME types on Linux
+    pub document_types: Vec<DocumentType>,
+    // LSUIElement: no Dock icon or menu bar (menu bar extras, helpers),
+    // NoDisplay on Linux
+    pub ui_element: bool,
+    // LSBackgroundOnly: no user interface at all
+    pub background_only: bool,
+}
+
+// A kind of document the application can open
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentType {
+    pub name: Option<String>,
+    // Editor, Viewer, Shell or None
+    pub role: Option<String>,
+    // Uniform type identifiers, e.g. public.plain-text
+    pub content_types: Vec<String>,
+    pub extensions: Vec<String>,
+    pub mime_types: Vec<String>,
+}
+
+// Name, icon and metadata of an application as the platform layer produces
+// them, before the icon is encoded for the webview
+pub struct AppIcon {
+    pub localized_name: String,
+    // PNG at the requested pixel size
+    pub png: Option<Vec<u8>>,
+    pub metadata: AppMetadata,
+}
+
+// Where an installed application was found
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum AppSource {
+    System,
+    User,
+    Flatpak,
+    Snap,
+    Bundle,
+}
+
+// What a launcher needs to start the application without a .app bundle
+#[derive(serde::Serialize, serde::Deserialize, specta::Type)]
+pub struct AppLaunchInfo {
+    // Command line with the %f/%u/... field codes removed
+    pub exec: Option<String>,
+    // Icon name or absolute path
+    pub icon: Option<String>,
+    pub keywords: Vec<String>,
+    pub terminal: bool,
+    pub source: AppSource,
+}
+
+// `AppInfo` of an installed application plus how to launch it
+#[derive(serde::Serialize, serde::Deserialize, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledApp {
+    pub base64_image: String,
+    pub localized_name: String,
+    pub name: String,
+    // Desktop file ID on Linux, bundle name on macOS
+    pub id: String,
+    pub path: String,
+    // Folder of the application folder the bundle is in, e.g. "Utilities"
+    // (macOS only)
+    pub suite: Option<String>,
+    pub launch: AppLaunchInfo,
+    #[serde(flatten)]
+    pub metadata: AppMetadata,
 }
 
 #[command]
-#[cfg(target_os = "macos")]
+#[specta::specta]
 pub async fn load_app_info<R: Runtime>(
-    _app_handle: AppHandle<R>,
+    app_handle: AppHandle<R>,
+    resources_paths: Vec<String>,
+    size: Option<u32>,
+    scale: Option<u32>,
+    format: Option<IconFormat>,
+    // False leaves `base64Image` empty, for callers that load the icons
+    // through the grinta-icon protocol. They are still rendered into the cache.
+    images: Option<bool>,
+) -> Result<HashMap<String, AppInfo>> {
+    let cache = app_handle.state::<IconCacheState>();
+    let request = IconRequest::new(size, scale, format);
+    let result = NativePlatform::load_app_info(resources_paths, &request, &cache);
+    cache.flush();
+
+    let images = images.unwrap_or(true);
+    Ok(result?
+        .into_iter()
+        .map(|(app_name, app)| {
+            let base64_image = match app.png {
+                Some(png) if images => request.data_url(&png),
+                _ => String::new(),
+            };
+            let app_info = AppInfo {
+                base64Image: base64_image,
+                localizedName: app.localized_name,
+                metadata: app.metadata,
+            };
+            (app_name, app_info)
+        })
+        .collect())
+}
+
+#[command]
+#[specta::specta]
+pub async fn list_installed_apps() -> Result<Vec<InstalledApp>> {
+    NativePlatform::installed_apps()
+}
+
+#[command]
+#[specta::specta]
+pub fn load_extension_icons(
+    extensions: Vec<String>,
+    size: Option<u32>,
+    scale: Option<u32>,
+    format: Option<IconFormat>,
+    cache: State<'_, IconCacheState>,
+) -> Result<HashMap<String, String>> {
+    let request = IconRequest::new(size, scale, format);
+    let result = NativePlatform::load_extension_icons(extensions, &request, &cache);
+    cache.flush();
+
+    Ok(result?
+        .into_iter()
+        .map(|(ext, png)| (ext, request.data_url(&png)))
+        .collect())
+}
+
+// Pixel size of bundle icons when no size is requested
+const BUNDLE_ICON_SIZE: u32 = 512;
+
+// Reads icon and localized name of each .app bundle in `resources_paths`, keyed
+// by app name
+pub(crate) fn read_app_bundles(
     resources_paths: Vec<String>,
-) -> Result<HashMap<String, AppInfo>, String> {
+    request: &IconRequest,
+    cache: &IconCacheState,
+) -> HashMap<String, AppIcon> {
+    let pixel_size = request.pixel_size(BUNDLE_ICON_SIZE);
+    // Cached names are only good for the languages they were picked for
+    let languages = preferred_languages();
+    let cache_key = |app_path: &str| format!("{}#{}", app_path, languages.join(","));
     let mut result = HashMap::new();
     let mut app_paths = Vec::new();
     let mut app_names = HashMap::new();
+    let mut app_metadata = HashMap::new();
     let mut icns_paths = HashMap::new();
+    let mut info_paths = HashMap::new();
 
     for resources_path in resources_paths {
         let resources_dir = Path::new(&resources_path);
@@ -228,46 +307,43 @@ pub async fn load_app_info<R: Runtime>(
             .replace(".app", "");
 
         let app_path_str = app_path.to_string_lossy().to_string();
+
+        // Cheap to parse, so metadata is read fresh even when the icon is cached
+        let info_plist = read_info_plist(&app_path_str);
+        let metadata = info_plist
+            .as_ref()
+            .and_then(|(_, plist, _)| plist.as_dictionary())
+            .map(bundle_metadata)
+            .unwrap_or_default();
+
+        if let Some(cached) = cache.get(&cache_key(&app_path_str), pixel_size) {
+            let app_icon = AppIcon {
+                localized_name: cached.name.unwrap_or_else(|| app_name.clone()),
+                png: cached.png,
+                metadata,
+            };
+            result.insert(app_name, app_icon);
+            continue;
+        }
+
         app_paths.push(app_path_str.clone());
         app_names.insert(app_path_str.clone(), app_name);
+        app_metadata.insert(app_path_str.clone(), metadata);
 
-        let info_path = format!("{}/Contents/Info.plist", app_path.display());
-
-        if let Ok(plist) = Value::from_file(&info_path) {
-            if let Some(icon_file_path) = extract_icon_path(plist.as_dictionary(), &format!("{}/Contents/Resources", app_path_str)) {
+        if let Some((info_path, plist, icon_dir)) = info_plist {
+            info_paths.insert(app_path_str.clone(), info_path);
+            if let Some(icon_file_path) = extract_icon_path(plist.as_dictionary(), &icon_dir) {
                 icns_paths.insert(app_path_str.clone(), icon_file_path);
             }
-        } else {
-            // Try to read alias or symlink that might point to an app
-            let symlink_path = format!("{}/WrappedBundle", app_path_str.clone());
-
-            if let Ok(metadata) = fs::symlink_metadata(&symlink_path) {
-                if metadata.file_type().is_symlink() {
-                    if let Ok(target_path) = fs::read_link(&symlink_path) {
-                        // If the target is an app bundle, use that path for the Info.plist
-                        let target_str = target_path.to_string_lossy().to_string();
-                        let target_app_path = if !target_path.is_absolute() {
-                            // Resolve the relative path against the symlink itself
-                            Path::new(&app_path_str).join(&target_path).to_string_lossy().to_string()
-                        } else {
-                            target_str
-                        };
-                        
-                        let info_path = format!("{}/Info.plist", target_app_path);
-
-                        if let Ok(plist) = Value::from_file(&info_path) {
-                            if let Some(icon_file_path) = extract_icon_path(plist.as_dictionary(), &target_app_path) {
-                                icns_paths.insert(app_path_str.clone(), icon_file_path);
-                            }
-                        }
-                    }
-                }
-            }
         }
     }
 
-    // Get all localized names at once using mdls
-    let localized_names = get_localized_app_names(&app_paths);
+    // Localized names of the bundles that were not cached
+    let localized_names = if info_paths.is_empty() {
+        HashMap::new()
+    } else {
+        localized_app_names(&info_paths, &languages)
+    };
 
     for app_path_str in app_paths {
         let app_name = match app_names.get(&app_path_str) {
@@ -282,198 +358,166 @@ pub async fn load_app_info<R: Runtime>(
             .unwrap_or_else(|| app_name.clone());
 
         // Check if we have an icon path for this app
-        if let Some(icns_path) = icns_paths.get(&app_path_str) {
-            if Path::new(icns_path).extension().and_then(|e| e.to_str()) == Some("png") {
+        let png_data = icns_paths.get(&app_path_str).and_then(|icns_path| {
+            let png = if Path::new(icns_path).extension().and_then(|e| e.to_str()) == Some("png") {
                 // If file is a png, load it directly
-                match fs::read(icns_path) {
-                    Ok(png_data) => {
-                        // Convert PNG data to base64
-                        let base64_png = general_purpose::STANDARD.encode(&png_data);
-                        let data_url = format!("data:image/png;base64,{}", base64_png);
-
-                        let app_info = AppInfo {
-                            base64Image: data_url,
-                            localizedName: localized_name,
-                        };
-                        result.insert(app_name, app_info);
-                    },
-                    Err(_) => {
-                        let app_info = AppInfo {
-                            base64Image: String::new(),
-                            localizedName: localized_name.clone(),
-                        };
-                        result.insert(app_name, app_info);
-                    }
-                }
+                fs::read(icns_path).ok()
             } else {
-                match load_icns_file(&Path::new(icns_path)) {
-                    Ok(Some(png_data)) => {
-                        // Convert PNG data to base64
-                        let base64_png = general_purpose::STANDARD.encode(&png_data);
-                        let data_url = format!("data:image/png;base64,{}", base64_png);
-
-                        let app_info = AppInfo {
-                            base64Image: data_url,
-                            localizedName: localized_name,
-                        };
-                        result.insert(app_name, app_info);
-                    },
-                    Ok(None) => {
-                        // No suitable icon found, create AppInfo with empty image
-                        let app_info = AppInfo {
-                            base64Image: String::new(),
-                            localizedName: localized_name.clone(),
-                        };
-                        result.insert(app_name, app_info);
-                    },
-                    Err(_) => {
-                        let app_info = AppInfo {
-                            base64Image: String::new(),
-                            localizedName: localized_name.clone(),
-                        };
-                        result.insert(app_name, app_info);
-                    }
-                }
-            }
-        } else {
-            // No icon path, create AppInfo with empty image
-            let app_info = AppInfo {
-                base64Image: String::new(),
-                localizedName: localized_name,
-            };
-            result.insert(app_name, app_info);
+                load_icns_file(Path::new(icns_path), pixel_size).ok().flatten()
+            }?;
+            // ICNS files jump from 256 to 512 pixels, anything in between is resampled
+            resize_png(&png, pixel_size).or(Some(png))
+        });
+
+        // Reinstalling or updating an app touches at least one of these
+        let mut sources = vec![PathBuf::from(&app_path_str)];
+        if let Some(info_path) = info_paths.get(&app_path_str) {
+            sources.push(PathBuf::from(info_path));
+            sources.extend(localization_sources(Path::new(info_path)));
         }
+        sources.extend(icns_paths.get(&app_path_str).map(PathBuf::from));
+        cache.put(
+            &cache_key(&app_path_str),
+            &sources,
+            pixel_size,
+            Some(localized_name.clone()),
+            png_data.as_deref(),
+        );
+
+        let app_icon = AppIcon {
+            localized_name,
+            png: png_data,
+            metadata: app_metadata.remove(&app_path_str).unwrap_or_default(),
+        };
+        result.insert(app_name, app_icon);
     }
 
-    Ok(result)
+    result
 }
 
-// Define a struct to hold app information for non-macOS platforms
-#[derive(serde::Serialize, serde::Deserialize)]
-#[cfg(not(target_os = "macos"))]
-pub struct AppInfo {
-    pub base64Image: String,
-    pub localizedName: String,
-}
-
-// Function to get localized app names using mdls
-#[cfg(target_os = "macos")]
-fn get_localized_app_names(app_paths: &[String]) -> HashMap<String, String> {
-    let mut localized_names = HashMap::new();
-
-    if app_paths.is_empty() {
-        return localized_names;
+// Info.plist path, contents and icon directory of a bundle
+fn read_info_plist(app_path: &str) -> Option<(String, Value, String)> {
+    let info_path = format!("{}/Contents/Info.plist", app_path);
+    if let Ok(plist) = Value::from_file(&info_path) {
+        return Some((info_path, plist, format!("{}/Contents/Resources", app_path)));
     }
 
-    // Build the mdls command with all app paths
-    let mut command = std::process::Command::new("mdls");
-    command.arg("-name").arg("kMDItemDisplayName");
-
-    // Add all app paths to the command
-    for path in app_paths {
-        command.arg(path);
+    // Try to read alias or symlink that might point to an app
+    let symlink_path = format!("{}/WrappedBundle", app_path);
+    if !fs::symlink_metadata(&symlink_path).ok()?.file_type().is_symlink() {
+        return None;
     }
+    let target_path = fs::read_link(&symlink_path).ok()?;
+    // If the target is an app bundle, use that path for the Info.plist
+    let target_app_path = if !target_path.is_absolute() {
+        // Resolve the relative path against the symlink itself
+        Path::new(app_path).join(&target_path).to_string_lossy().to_string()
+    } else {
+        target_path.to_string_lossy().to_string()
+    };
 
-    // Execute the command
-    if let Ok(output) = command.output() {
-        if output.status.success() {
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            let lines: Vec<&str> = output_str.lines().collect();
-
-            // Process the output lines
-            let mut current_app_index = 0;
-            for line in lines {
-                const PREFIX: &str = "kMDItemDisplayName = \"";
-                if !line.starts_with(PREFIX) {
-                    continue;
-                }
+    let info_path = format!("{}/Info.plist", target_app_path);
+    let plist = Value::from_file(&info_path).ok()?;
+    Some((info_path, plist, target_app_path))
+}
 
-                let name = &line[PREFIX.len()..line.len() - 1];
+// Metadata of the bundle at `app_path`, empty when it has no Info.plist
+pub(crate) fn app_bundle_metadata(app_path: &str) -> AppMetadata {
+    read_info_plist(app_path)
+        .and_then(|(_, plist, _)| plist.as_dictionary().map(bundle_metadata))
+        .unwrap_or_default()
+}
 
-                if current_app_index < app_paths.len() {
-                    localized_names.insert(app_paths[current_app_index].clone(), name.to_string());
-                }
+// Metadata fields of an Info.plist
+fn bundle_metadata(info: &Dictionary) -> AppMetadata {
+    let string = |dict: &Dictionary, key: &str| dict.get(key).and_then(Value::as_string).map(str::to_string);
+    let strings = |dict: &Dictionary, key: &str| -> Vec<String> {
+        dict.get(key)
+            .and_then(Value::as_array)
+            .map(|values| values.iter().filter_map(Value::as_string).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+    let dictionaries = |key: &str| -> Vec<&Dictionary> {
+        info.get(key)
+            .and_then(Value::as_array)
+            .map(|values| values.iter().filter_map(Value::as_dictionary).collect())
+            .unwrap_or_default()
+    };
 
-                current_app_index += 1;
+    let mut url_schemes: Vec<String> = Vec::new();
+    for url_type in dictionaries("CFBundleURLTypes") {
+        for scheme in strings(url_type, "CFBundleURLSchemes") {
+            if !url_schemes.contains(&scheme) {
+                url_schemes.push(scheme);
             }
         }
-    } else {
-        println!("Failed to execute mdls command");
     }
 
-    localized_names
+    let document_types = dictionaries("CFBundleDocumentTypes")
+        .into_iter()
+        .map(|document_type| DocumentType {
+            name: string(document_type, "CFBundleTypeName"),
+            role: string(document_type, "CFBundleTypeRole"),
+            content_types: strings(document_type, "LSItemContentTypes"),
+            extensions: strings(document_type, "CFBundleTypeExtensions"),
+            mime_types: strings(document_type, "CFBundleTypeMIMETypes"),
+        })
+        .collect();
+
+    AppMetadata {
+        bundle_id: string(info, "CFBundleIdentifier"),
+        version: string(info, "CFBundleShortVersionString").or_else(|| string(info, "CFBundleVersion")),
+        categories: string(info, "LSApplicationCategoryType").into_iter().collect(),
+        url_schemes,
+        document_types,
+        ui_element: plist_flag(info.get("LSUIElement")),
+        background_only: plist_flag(info.get("LSBackgroundOnly")),
+    }
 }
 
-// Fallback implementations for non-macOS platforms
-#[command]
-#[cfg(not(target_os = "macos"))]
-pub async fn load_app_info<R: Runtime>(
-    _app_handle: AppHandle<R>,
-    _icns_paths: Vec<String>,
-) -> Result<HashMap<String, AppInfo>, String> {
-    Ok(HashMap::new())
+// Boolean keys are also written as strings ("1", "YES") or integers
+fn plist_flag(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Boolean(flag)) => *flag,
+        Some(Value::String(text)) => matches!(text.to_lowercase().as_str(), "1" | "yes" | "true"),
+        Some(Value::Integer(number)) => number.as_signed().is_some_and(|number| number != 0),
+        _ => false,
+    }
 }
 
-#[tauri::command]
-pub fn load_extension_icons(extensions: Vec<String>) -> Result<HashMap<String, String>, String> {
-    let mut icon_map = HashMap::new();
-
-    for ext in extensions {
-        let icon = unsafe {
-            // Special case for folder icon
-            if ext == "folder" {
-                let ns_image_class = class!(NSImage);
-                // NSImage.folderName in Swift is "NSFolder" in Objective-C
-                let folder_name = NSString::alloc(nil).init_str("NSFolder");
-                msg_send![ns_image_class, imageNamed:folder_name]
-            } else {
-                // Regular file extension icon
-                let workspace = class!(NSWorkspace);
-                let shared_workspace: id = msg_send![workspace, sharedWorkspace];
-                let ns_string = CocoaNSString::alloc(nil).init_str(&ext);
-                let icon: id = msg_send![shared_workspace, iconForFileType:ns_string];
-                icon
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_metadata_from_info_plist() {
+        let app_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/bundles/Editor.app");
+        let metadata = app_bundle_metadata(&app_path.to_string_lossy());
+
+        assert_eq!(metadata.bundle_id.as_deref(), Some("app.grinta.fixtures.editor"));
+        assert_eq!(metadata.version.as_deref(), Some("2.4.1"));
+        assert_eq!(metadata.categories, ["public.app-category.developer-tools"]);
+        assert_eq!(metadata.url_schemes, ["editor", "editor-insiders"]);
+        assert!(metadata.ui_element);
+        assert!(!metadata.background_only);
+
+        assert_eq!(metadata.document_types.len(), 2);
+        assert_eq!(
+            metadata.document_types[0],
+            DocumentType {
+                name: Some("Plain Text".to_string()),
+                role: Some("Editor".to_string()),
+                content_types: vec!["public.plain-text".to_string()],
+                extensions: vec!["txt".to_string(), "text".to_string()],
+                mime_types: vec!["text/plain".to_string()],
             }
-        };
-
-        if icon.is_null() {
-            continue;
-        }
-
-        let tiff_data: id = unsafe { msg_send![icon, TIFFRepresentation] };
-        if tiff_data.is_null() {
-            continue;
-        }
-
-        let bitmap_rep: id = unsafe {
-            let bitmap_rep_class = class!(NSBitmapImageRep);
-            let bitmap_rep: id = msg_send![bitmap_rep_class, alloc];
-            let bitmap_rep: id = msg_send![bitmap_rep, initWithData:tiff_data];
-            bitmap_rep
-        };
-
-        if bitmap_rep.is_null() {
-            continue;
-        }
-
-        let png_data: id = unsafe {
-            let properties: id = msg_send![class!(NSDictionary), dictionary];
-            msg_send![bitmap_rep, representationUsingType:4 properties:properties]
-        };
-
-        if png_data.is_null() {
-            continue;
-        }
-
-        let png_bytes = unsafe {
-            let length = msg_send![png_data, length];
-            std::slice::from_raw_parts(msg_send![png_data, bytes], length)
-        };
-
-        let base64_icon = general_purpose::STANDARD.encode(png_bytes);
-        let data_url = format!("data:image/png;base64,{}", base64_icon);
-        icon_map.insert(ext, data_url);
+        );
+        assert_eq!(metadata.document_types[1].extensions, ["md"]);
     }
 
-    Ok(icon_map)
+    #[test]
+    fn bundles_without_info_plist_have_no_metadata() {
+        let app_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/bundles/Missing.app");
+        assert_eq!(app_bundle_metadata(&app_path.to_string_lossy()), AppMetadata::default());
+    }
 }
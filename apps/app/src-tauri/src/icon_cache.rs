@@ -0,0 +1,535 @@
+// src-tauri/src/icon_cache.rs
+//
+// On-disk cache for the icons `load_app_info` and `load_extension_icons`
+// produce. Decoding ICNS files, rasterizing SVGs and reading localized names
+// from InfoPlist.strings is slow enough to be noticeable with hundreds of
+// apps, so results are stored as PNG files in the app data directory, one per
+// size, next to a manifest.
+// Every entry remembers the modification times of the files it was made from
+// (Info.plist and the InfoPlist.strings files, the icon file, the .desktop
+// file) and is dropped as soon as one of them changes.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{command, AppHandle, Manager, Runtime, State};
+
+use crate::error::Result;
+
+const CACHE_DIR_NAME: &str = "icon-cache";
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+// Bump when the stored format changes, older caches are dropped
+const CACHE_VERSION: u32 = 1;
+// Least recently used entries are evicted above this size
+const MAX_CACHE_BYTES: u64 = 64 * 1024 * 1024;
+// Entries without source files (e.g. system file type icons) can't be
+// validated, so nothing is kept longer than this
+const MAX_ENTRY_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+// How long `flush_later` waits for more changes
+const FLUSH_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct SourceStamp {
+    path: PathBuf,
+    // Milliseconds since the epoch, None when the file could not be read
+    modified: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedImage {
+    file_name: String,
+    size_bytes: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    sources: Vec<SourceStamp>,
+    name: Option<String>,
+    // Icon size in pixels -> PNG file, None when there is no icon at all
+    images: BTreeMap<u32, Option<CachedImage>>,
+    created_at: u64,
+    last_used: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    entries: HashMap<String, CacheEntry>,
+}
+
+struct IconCache {
+    directory: PathBuf,
+    manifest: Manifest,
+    // Whether the manifest changed since it was last written
+    dirty: bool,
+    // MAX_CACHE_BYTES, smaller in tests
+    max_bytes: u64,
+}
+
+// What the cache knows about a key at one size
+pub struct CachedIcon {
+    #[allow(dead_code)] // Only app bundles cache their (slow to resolve) names
+    pub name: Option<String>,
+    pub png: Option<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, specta::Type)]
+pub struct IconCacheStats {
+    pub entry_count: usize,
+    pub size_bytes: u64,
+}
+
+pub struct IconCacheState {
+    // None until `start` resolved the app data directory
+    cache: Mutex<Option<IconCache>>,
+    // Whether a `flush_later` is waiting to run
+    flush_pending: AtomicBool,
+}
+
+impl SourceStamp {
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified_millis(path),
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        modified_millis(&self.path) == self.modified
+    }
+}
+
+impl CacheEntry {
+    fn size_bytes(&self) -> u64 {
+        self.images.values().flatten().map(|image| image.size_bytes).sum()
+    }
+
+    fn is_valid(&self, now: u64) -> bool {
+        now.saturating_sub(self.created_at) < MAX_ENTRY_AGE.as_secs()
+            && self.sources.iter().all(SourceStamp::is_current)
+    }
+}
+
+impl IconCache {
+    fn open(directory: PathBuf) -> Self {
+        let manifest = fs::read(directory.join(MANIFEST_FILE_NAME))
+            .ok()
+            .and_then(|data| serde_json::from_slice::<Manifest>(&data).ok())
+            .filter(|manifest| manifest.version == CACHE_VERSION);
+
+        let mut cache = Self {
+            directory,
+            manifest: Manifest {
+                version: CACHE_VERSION,
+                entries: HashMap::new(),
+            },
+            dirty: false,
+            max_bytes: MAX_CACHE_BYTES,
+        };
+
+        match manifest {
+            Some(manifest) => cache.manifest = manifest,
+            // Unknown or broken manifest, its images can't be trusted either
+            None => cache.remove_all_files(),
+        }
+
+        cache
+    }
+
+    fn get(&mut self, key: &str, size: u32) -> Option<CachedIcon> {
+        let now = now_secs();
+        if !self.manifest.entries.get(key)?.is_valid(now) {
+            self.remove(key);
+            return None;
+        }
+
+        let entry = self.manifest.entries.get_mut(key)?;
+        let png = match entry.images.get(&size)? {
+            Some(image) => Some(fs::read(self.directory.join(&image.file_name)).ok()?),
+            None => None,
+        };
+        entry.last_used = now;
+        self.dirty = true;
+
+        Some(CachedIcon {
+            name: entry.name.clone(),
+            png,
+        })
+    }
+
+    fn put(&mut self, key: &str, sources: &[PathBuf], size: u32, name: Option<String>, png: Option<&[u8]>) {
+        let stamps: Vec<SourceStamp> = sources.iter().map(|path| SourceStamp::new(path)).collect();
+        // Other sizes of the same sources stay, anything older is replaced
+        if self.manifest.entries.get(key).is_some_and(|entry| entry.sources != stamps) {
+            self.remove(key);
+        }
+
+        let image = match png {
+            Some(png) => {
+                let file_name = format!("{:016x}-{}.png", key_hash(key), size);
+                if let Err(e) = fs::create_dir_all(&self.directory)
+                    .and_then(|_| fs::write(self.directory.join(&file_name), png))
+                {
+                    eprintln!("Failed to write icon cache file: {}", e);
+                    return;
+                }
+                Some(CachedImage {
+                    file_name,
+                    size_bytes: png.len() as u64,
+                })
+            }
+            None => None,
+        };
+
+        let now = now_secs();
+        let entry = self.manifest.entries.entry(key.to_string()).or_insert_with(|| CacheEntry {
+            sources: stamps,
+            name: None,
+            images: BTreeMap::new(),
+            created_at: now,
+            last_used: now,
+        });
+        entry.name = name;
+        entry.images.insert(size, image);
+        entry.last_used = now;
+        self.dirty = true;
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(entry) = self.manifest.entries.remove(key) {
+            for image in entry.images.values().flatten() {
+                let _ = fs::remove_file(self.directory.join(&image.file_name));
+            }
+            self.dirty = true;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.manifest.entries.clear();
+        self.remove_all_files();
+        self.dirty = false;
+    }
+
+    fn remove_all_files(&self) {
+        if self.directory.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.directory) {
+                eprintln!("Failed to clear icon cache: {}", e);
+            }
+        }
+    }
+
+    fn size_bytes(&self) -> u64 {
+        self.manifest.entries.values().map(CacheEntry::size_bytes).sum()
+    }
+
+    // Evicts least recently used entries above the size cap and writes the manifest
+    fn flush(&mut self) {
+        let mut size_bytes = self.size_bytes();
+        if size_bytes > self.max_bytes {
+            let mut keys: Vec<(u64, String)> = self
+                .manifest
+                .entries
+                .iter()
+                .map(|(key, entry)| (entry.last_used, key.clone()))
+                .collect();
+            keys.sort();
+
+            for (_, key) in keys {
+                if size_bytes <= self.max_bytes {
+                    break;
+                }
+                size_bytes -= self.manifest.entries[&key].size_bytes();
+                self.remove(&key);
+            }
+        }
+
+        if !self.dirty {
+            return;
+        }
+
+        let result = serde_json::to_vec(&self.manifest)
+            .map_err(|e| e.to_string())
+            .and_then(|data| {
+                fs::create_dir_all(&self.directory)
+                    .and_then(|_| fs::write(self.directory.join(MANIFEST_FILE_NAME), data))
+                    .map_err(|e| e.to_string())
+            });
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(e) => eprintln!("Failed to save icon cache: {}", e),
+        }
+    }
+}
+
+impl IconCacheState {
+    pub fn new() -> Self {
+        Self {
+            cache: Mutex::new(None),
+            flush_pending: AtomicBool::new(false),
+        }
+    }
+
+    // The cached icon of `key` at `size`, if its source files are unchanged
+    pub fn get(&self, key: &str, size: u32) -> Option<CachedIcon> {
+        self.cache.lock().unwrap().as_mut()?.get(key, size)
+    }
+
+    // Stores the icon (or the fact that there is none) made from `sources`
+    pub fn put(&self, key: &str, sources: &[PathBuf], size: u32, name: Option<String>, png: Option<&[u8]>) {
+        if let Some(cache) = self.cache.lock().unwrap().as_mut() {
+            cache.put(key, sources, size, name, png);
+        }
+    }
+
+    // Persists the changes of the last `get`/`put` calls, once per batch
+    pub fn flush(&self) {
+        if let Some(cache) = self.cache.lock().unwrap().as_mut() {
+            cache.flush();
+        }
+    }
+
+    pub fn clear(&self) {
+        if let Some(cache) = self.cache.lock().unwrap().as_mut() {
+            cache.clear();
+        }
+    }
+
+    pub fn stats(&self) -> IconCacheStats {
+        let cache = self.cache.lock().unwrap();
+        IconCacheStats {
+            entry_count: cache.as_ref().map(|cache| cache.manifest.entries.len()).unwrap_or(0),
+            size_bytes: cache.as_ref().map(IconCache::size_bytes).unwrap_or(0),
+        }
+    }
+}
+
+// Opens the cache in the app data directory. Called once from the setup hook,
+// before that every lookup misses.
+pub fn start<R: Runtime>(app_handle: &AppHandle<R>) {
+    match app_handle.path().app_data_dir() {
+        Ok(dir) => {
+            let cache = IconCache::open(dir.join(CACHE_DIR_NAME));
+            *app_handle.state::<IconCacheState>().cache.lock().unwrap() = Some(cache);
+        }
+        Err(e) => eprintln!("Failed to resolve app data directory for the icon cache: {}", e),
+    }
+}
+
+// Flushes the cache shortly after the first of a burst of single icon lookups
+// (e.g. `<img>` requests to the grinta-icon protocol), so the manifest is
+// written once per burst rather than once per icon.
+pub fn flush_later<R: Runtime>(app_handle: &AppHandle<R>) {
+    let state = app_handle.state::<IconCacheState>();
+    if state.flush_pending.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(FLUSH_DELAY);
+        let state = app_handle.state::<IconCacheState>();
+        state.flush_pending.store(false, Ordering::SeqCst);
+        state.flush();
+    });
+}
+
+fn modified_millis(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|age| age.as_millis() as u64)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|age| age.as_secs())
+        .unwrap_or(0)
+}
+
+fn key_hash(key: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[command]
+#[specta::specta]
+pub fn clear_icon_cache(state: State<'_, IconCacheState>) -> Result<()> {
+    state.clear();
+    Ok(())
+}
+
+#[command]
+#[specta::specta]
+pub fn get_icon_cache_stats(state: State<'_, IconCacheState>) -> Result<IconCacheStats> {
+    Ok(state.stats())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::scratch_dir;
+    use std::fs::File;
+
+    fn png(cache: &mut IconCache, key: &str, size: u32) -> Option<Option<Vec<u8>>> {
+        cache.get(key, size).map(|cached| cached.png)
+    }
+
+    fn image_files(directory: &Path) -> usize {
+        fs::read_dir(directory)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "png"))
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn stores_icons_per_size() {
+        let dir = scratch_dir("icon-cache-sizes", &[]);
+        let mut cache = IconCache::open(dir.join("cache"));
+
+        cache.put("app", &[], 32, Some("App".into()), Some(b"small"));
+        cache.put("app", &[], 64, Some("App".into()), Some(b"large"));
+        cache.put("blank", &[], 32, None, None);
+
+        assert_eq!(png(&mut cache, "app", 32), Some(Some(b"small".to_vec())));
+        assert_eq!(png(&mut cache, "app", 64), Some(Some(b"large".to_vec())));
+        assert_eq!(cache.get("app", 32).unwrap().name.as_deref(), Some("App"));
+        // Known to have no icon, unlike sizes and keys never stored
+        assert_eq!(png(&mut cache, "blank", 32), Some(None));
+        assert_eq!(png(&mut cache, "app", 128), None);
+        assert_eq!(png(&mut cache, "missing", 32), None);
+        assert_eq!(cache.size_bytes(), 10);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn drops_entries_whose_sources_changed() {
+        let dir = scratch_dir("icon-cache-sources", &[]);
+        let source = dir.join("Info.plist");
+        fs::write(&source, "v1").unwrap();
+        let mut cache = IconCache::open(dir.join("cache"));
+
+        cache.put("app", &[source.clone()], 32, None, Some(b"icon"));
+        assert!(cache.get("app", 32).is_some());
+
+        let modified = SystemTime::now() - Duration::from_secs(60);
+        File::options().write(true).open(&source).unwrap().set_modified(modified).unwrap();
+        assert!(cache.get("app", 32).is_none());
+        assert!(cache.manifest.entries.is_empty());
+        assert_eq!(image_files(&dir.join("cache")), 0);
+
+        // Deleted sources count as changed too
+        cache.put("app", &[source.clone()], 32, None, Some(b"icon"));
+        fs::remove_file(&source).unwrap();
+        assert!(cache.get("app", 32).is_none());
+
+        // Stored again from other sources, the old sizes go
+        cache.put("other", &[], 32, None, Some(b"old"));
+        cache.put("other", &[dir.clone()], 64, None, Some(b"new"));
+        assert!(cache.get("other", 32).is_none());
+        assert!(cache.get("other", 64).is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn expires_old_entries() {
+        let dir = scratch_dir("icon-cache-age", &[]);
+        let mut cache = IconCache::open(dir.join("cache"));
+
+        cache.put("extension:pdf", &[], 32, None, Some(b"icon"));
+        cache.manifest.entries.get_mut("extension:pdf").unwrap().created_at -= MAX_ENTRY_AGE.as_secs() - 60;
+        assert!(cache.get("extension:pdf", 32).is_some());
+
+        cache.manifest.entries.get_mut("extension:pdf").unwrap().created_at -= 60;
+        assert!(cache.get("extension:pdf", 32).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn evicts_least_recently_used_entries_on_flush() {
+        let dir = scratch_dir("icon-cache-eviction", &[]);
+        let mut cache = IconCache::open(dir.join("cache"));
+        cache.max_bytes = 10;
+
+        for (key, last_used) in [("a", 3), ("b", 1), ("c", 2)] {
+            cache.put(key, &[], 32, None, Some(b"1234"));
+            cache.manifest.entries.get_mut(key).unwrap().last_used = last_used;
+        }
+        assert_eq!(cache.size_bytes(), 12);
+        cache.flush();
+
+        let mut keys: Vec<&String> = cache.manifest.entries.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["a", "c"]);
+        assert_eq!(image_files(&dir.join("cache")), 2);
+
+        // Lookups count as use
+        cache.put("d", &[], 32, None, Some(b"1234"));
+        cache.manifest.entries.get_mut("d").unwrap().last_used = 0;
+        cache.manifest.entries.get_mut("c").unwrap().last_used = 0;
+        cache.get("c", 32).unwrap();
+        cache.flush();
+        assert!(cache.manifest.entries.contains_key("c"));
+        assert!(!cache.manifest.entries.contains_key("d"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn flushes_the_manifest_for_the_next_start() {
+        let dir = scratch_dir("icon-cache-flush", &[]);
+        let mut cache = IconCache::open(dir.join("cache"));
+        cache.put("app", &[], 32, Some("App".into()), Some(b"icon"));
+        assert!(!dir.join("cache").join(MANIFEST_FILE_NAME).exists());
+        cache.flush();
+        assert!(!cache.dirty);
+
+        let mut reopened = IconCache::open(dir.join("cache"));
+        assert_eq!(png(&mut reopened, "app", 32), Some(Some(b"icon".to_vec())));
+        assert_eq!(reopened.get("app", 32).unwrap().name.as_deref(), Some("App"));
+
+        // Caches of another version are thrown away with their images
+        let manifest_path = dir.join("cache").join(MANIFEST_FILE_NAME);
+        let manifest = fs::read_to_string(&manifest_path).unwrap();
+        fs::write(&manifest_path, manifest.replace("\"version\":1", "\"version\":0")).unwrap();
+        let mut outdated = IconCache::open(dir.join("cache"));
+        assert!(outdated.get("app", 32).is_none());
+        assert!(!dir.join("cache").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clears_entries_and_files() {
+        let dir = scratch_dir("icon-cache-clear", &[]);
+        let state = IconCacheState::new();
+        // Before `start` nothing is cached
+        state.put("app", &[], 32, None, Some(b"icon"));
+        assert!(state.get("app", 32).is_none());
+
+        *state.cache.lock().unwrap() = Some(IconCache::open(dir.join("cache")));
+        state.put("app", &[], 32, None, Some(b"icon"));
+        state.flush();
+        assert_eq!(state.stats().entry_count, 1);
+        assert_eq!(state.stats().size_bytes, 4);
+
+        state.clear();
+        assert!(state.get("app", 32).is_none());
+        assert_eq!(state.stats().entry_count, 0);
+        assert!(!dir.join("cache").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
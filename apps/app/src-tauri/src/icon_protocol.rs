@@ -0,0 +1,333 @@
+// src-tauri/src/icon_protocol.rs
+//
+// The grinta-icon URI scheme. Lets the webview load icons with plain `<img>`
+// tags instead of base64 data URLs inside JSON:
+//
+//   grinta-icon://app/<bundle or .desktop path>?size=32&scale=2&format=webp
+//   grinta-icon://ext/pdf
+//   grinta-icon://favicon/<host>
+//
+// Windows and `convertFileSrc` put the route into the path instead
+// (`http://grinta-icon.localhost/app%2F...`), both forms are accepted.
+// Icons come from the icon cache and are rendered into it on a miss.
+
+use percent_encoding::percent_decode_str;
+use std::path::{Component, Path};
+use tauri::http::{header, Request, Response, StatusCode, Uri};
+use tauri::{AppHandle, Manager, Runtime, UriSchemeContext, UriSchemeResponder};
+
+use crate::favicon_utils::load_favicon_png;
+use crate::icon_cache::{self, IconCacheState};
+use crate::icon_render::{resize_png, IconFormat, IconRequest};
+use crate::platform::{IconPlatform, NativePlatform};
+
+pub const SCHEME: &str = "grinta-icon";
+
+// Pixel size of favicons when no size is requested
+const FAVICON_SIZE: u32 = 32;
+// App and file type icons change with app updates, favicons hardly ever
+const ICON_MAX_AGE: u32 = 60 * 60;
+const FAVICON_MAX_AGE: u32 = 24 * 60 * 60;
+
+enum IconRoute {
+    App(String),
+    Extension(String),
+    Favicon(String),
+}
+
+pub fn handle<R: Runtime>(
+    ctx: UriSchemeContext<'_, R>,
+    request: Request<Vec<u8>>,
+    responder: UriSchemeResponder,
+) {
+    let app_handle = ctx.app_handle().clone();
+    let uri = request.uri().clone();
+
+    tauri::async_runtime::spawn(async move {
+        let response = match parse_route(&uri) {
+            Some(route) => {
+                let icon_request = parse_icon_request(&uri);
+                let max_age = match route {
+                    IconRoute::Favicon(_) => FAVICON_MAX_AGE,
+                    _ => ICON_MAX_AGE,
+                };
+                match load_icon(&app_handle, route, icon_request).await {
+                    Some(png) => {
+                        let (mime_type, data) = icon_request.encode(&png);
+                        Response::builder()
+                            .status(StatusCode::OK)
+                            .header(header::CONTENT_TYPE, mime_type)
+                            .header(header::CACHE_CONTROL, format!("max-age={}", max_age))
+                            .body(data)
+                    }
+                    None => error_response(StatusCode::NOT_FOUND, "No icon"),
+                }
+            }
+            None => error_response(StatusCode::BAD_REQUEST, "Unknown icon URL"),
+        };
+
+        match response {
+            Ok(response) => responder.respond(response),
+            Err(e) => eprintln!("Failed to build icon response for {}: {}", uri, e),
+        }
+    });
+}
+
+async fn load_icon<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    route: IconRoute,
+    request: IconRequest,
+) -> Option<Vec<u8>> {
+    let png = match route {
+        IconRoute::Favicon(host) => load_favicon(app_handle, &host, &request).await,
+        route => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let cache = app_handle.state::<IconCacheState>();
+                match route {
+                    IconRoute::App(path) => {
+                        NativePlatform::load_app_info(vec![path], &request, &cache)
+                            .ok()?
+                            .into_values()
+                            .next()?
+                            .png
+                    }
+                    IconRoute::Extension(ext) => {
+                        NativePlatform::load_extension_icons(vec![ext.clone()], &request, &cache)
+                            .ok()?
+                            .remove(&ext)
+                    }
+                    IconRoute::Favicon(_) => None,
+                }
+            })
+            .await
+            .ok()?
+        }
+    };
+
+    icon_cache::flush_later(app_handle);
+    png
+}
+
+// Favicons have no source files to check, the cache expires them by age.
+// Failed downloads are not cached, the site may just be unreachable right now.
+async fn load_favicon<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    host: &str,
+    request: &IconRequest,
+) -> Option<Vec<u8>> {
+    let key = format!("favicon:{}", host);
+    let pixel_size = request.pixel_size(FAVICON_SIZE);
+    if let Some(cached) = app_handle.state::<IconCacheState>().get(&key, pixel_size) {
+        return cached.png;
+    }
+
+    let png = match load_favicon_png(host).await {
+        Ok(png) => png,
+        Err(e) => {
+            eprintln!("{}", e);
+            return None;
+        }
+    };
+    let png = resize_png(&png, pixel_size).unwrap_or(png);
+    app_handle
+        .state::<IconCacheState>()
+        .put(&key, &[], pixel_size, None, Some(&png));
+    Some(png)
+}
+
+fn parse_route(uri: &Uri) -> Option<IconRoute> {
+    let path = uri.path();
+    let path = percent_decode_str(path.strip_prefix('/').unwrap_or(path))
+        .decode_utf8()
+        .ok()?;
+    let target = match uri.host() {
+        Some(host) if host != "localhost" && !host.ends_with(".localhost") => {
+            format!("{}/{}", host, path)
+        }
+        _ => path.into_owned(),
+    };
+
+    let (route, value) = target.split_once('/')?;
+    if value.is_empty() {
+        return None;
+    }
+    match route {
+        "app" if is_app_path(value) => Some(IconRoute::App(value.to_string())),
+        "ext" => Some(IconRoute::Extension(value.to_string())),
+        "favicon" => Some(IconRoute::Favicon(value.to_string())),
+        _ => None,
+    }
+}
+
+// Any page can point an `<img>` here, so app icons are only read from .app
+// bundles and from .desktop files in an `applications` directory
+fn is_app_path(value: &str) -> bool {
+    let path = Path::new(value);
+    if !path.is_absolute() || value.split('/').any(|segment| segment == "..") {
+        return false;
+    }
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("app") => true,
+        Some("desktop") => path.parent().is_some_and(|parent| {
+            parent
+                .components()
+                .any(|c| c == Component::Normal("applications".as_ref()))
+        }),
+        _ => false,
+    }
+}
+
+// `size`, `scale` and `format` query parameters, as `load_app_info` takes them
+fn parse_icon_request(uri: &Uri) -> IconRequest {
+    let mut size = None;
+    let mut scale = None;
+    let mut format = None;
+    for (name, value) in url::form_urlencoded::parse(uri.query().unwrap_or("").as_bytes()) {
+        match name.as_ref() {
+            "size" => size = value.parse().ok(),
+            "scale" => scale = value.parse().ok(),
+            "format" => {
+                format = match value.as_ref() {
+                    "png" => Some(IconFormat::Png),
+                    "webp" => Some(IconFormat::Webp),
+                    _ => None,
+                }
+            }
+            _ => {}
+        }
+    }
+    IconRequest::new(size, scale, format)
+}
+
+fn error_response(
+    status: StatusCode,
+    message: &str,
+) -> Result<Response<Vec<u8>>, tauri::http::Error> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(uri: &str) -> Option<(&'static str, String)> {
+        match parse_route(&uri.parse().unwrap())? {
+            IconRoute::App(path) => Some(("app", path)),
+            IconRoute::Extension(ext) => Some(("ext", ext)),
+            IconRoute::Favicon(host) => Some(("favicon", host)),
+        }
+    }
+
+    fn request(uri: &str) -> IconRequest {
+        parse_icon_request(&uri.parse().unwrap())
+    }
+
+    #[test]
+    fn parses_both_url_forms() {
+        let expected = Some(("app", "/Applications/Safari.app".to_string()));
+        assert_eq!(
+            route("grinta-icon://app//Applications/Safari.app?size=32"),
+            expected
+        );
+        assert_eq!(
+            route("grinta-icon://localhost/app%2F%2FApplications%2FSafari.app"),
+            expected
+        );
+        assert_eq!(
+            route("http://grinta-icon.localhost/app%2F%2FApplications%2FSafari.app"),
+            expected
+        );
+
+        assert_eq!(
+            route("grinta-icon://ext/pdf"),
+            Some(("ext", "pdf".to_string()))
+        );
+        assert_eq!(
+            route("grinta-icon://localhost/favicon%2Fexample.com"),
+            Some(("favicon", "example.com".to_string()))
+        );
+        assert_eq!(
+            route("grinta-icon://app//Applications/My%20App.app"),
+            Some(("app", "/Applications/My App.app".to_string()))
+        );
+        assert_eq!(
+            route("grinta-icon://app//usr/share/applications/kde4/okular.desktop"),
+            Some((
+                "app",
+                "/usr/share/applications/kde4/okular.desktop".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_routes() {
+        assert_eq!(route("grinta-icon://file//etc/passwd"), None);
+        assert_eq!(route("grinta-icon://ext/"), None);
+        assert_eq!(route("grinta-icon://localhost/app"), None);
+        assert_eq!(route("grinta-icon://localhost/"), None);
+        // Invalid UTF-8
+        assert_eq!(route("grinta-icon://localhost/ext%2F%FF"), None);
+    }
+
+    #[test]
+    fn rejects_paths_that_are_not_apps() {
+        for uri in [
+            // Relative
+            "grinta-icon://app/Applications/Safari.app",
+            // Parent directory segments, plain and encoded
+            "grinta-icon://app//Applications/../Users/me/Secret.app",
+            "grinta-icon://localhost/app%2F%2FApplications%2F..%2F..%2Fetc%2Fx.app",
+            // Not a bundle or desktop file
+            "grinta-icon://app//etc/passwd",
+            "grinta-icon://app//Users/me/Documents/report.pdf",
+            "grinta-icon://app//Applications/Safari.app.png",
+            // Desktop files outside an applications directory
+            "grinta-icon://app//home/me/Downloads/evil.desktop",
+            "grinta-icon://app//home/me/applications.desktop",
+        ] {
+            assert_eq!(route(uri), None, "{}", uri);
+        }
+    }
+
+    #[test]
+    fn parses_icon_requests() {
+        let icon = request("grinta-icon://ext/pdf?size=32&scale=2&format=webp");
+        assert_eq!(
+            (icon.size, icon.scale, icon.format),
+            (Some(32), 2, IconFormat::Webp)
+        );
+
+        let icon = request("grinta-icon://ext/pdf");
+        assert_eq!(
+            (icon.size, icon.scale, icon.format),
+            (None, 1, IconFormat::Png)
+        );
+    }
+
+    #[test]
+    fn ignores_bad_sizes() {
+        for query in [
+            "size=abc",
+            "size=-32",
+            "size=",
+            "size=1e3",
+            "size=99999999999",
+        ] {
+            let icon = request(&format!("grinta-icon://ext/pdf?{}", query));
+            assert_eq!(icon.size, None, "{}", query);
+        }
+
+        // Out of range sizes and scales are clamped
+        let icon = request("grinta-icon://ext/pdf?size=0&scale=99&format=gif");
+        assert_eq!(
+            (icon.size, icon.scale, icon.format),
+            (Some(1), 4, IconFormat::Png)
+        );
+        assert_eq!(request("grinta-icon://ext/pdf?size=5000").size, Some(1024));
+    }
+}
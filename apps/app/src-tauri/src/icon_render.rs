@@ -0,0 +1,293 @@
+// src-tauri/src/icon_render.rs
+//
+// Sizing and encoding of icons before they cross IPC. Callers ask for the
+// size they draw at (in CSS pixels) and the display scale; icons are resampled
+// to exactly that many device pixels so a 32px list row doesn't receive a
+// 512px image, and can be sent as lossless WebP, which is usually smaller
+// than PNG.
+
+use base64::{engine::general_purpose, Engine as _};
+use image::imageops::FilterType;
+use image::{ImageFormat, RgbaImage};
+use serde::Deserialize;
+use std::io::Cursor;
+
+const MAX_SIZE: u32 = 1024;
+const MAX_SCALE: u32 = 4;
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum IconFormat {
+    #[default]
+    Png,
+    Webp,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct IconRequest {
+    // None keeps the platform's native size
+    pub size: Option<u32>,
+    pub scale: u32,
+    pub format: IconFormat,
+}
+
+impl IconRequest {
+    pub fn new(size: Option<u32>, scale: Option<u32>, format: Option<IconFormat>) -> Self {
+        Self {
+            size: size.map(|size| size.clamp(1, MAX_SIZE)),
+            scale: scale.unwrap_or(1).clamp(1, MAX_SCALE),
+            format: format.unwrap_or_default(),
+        }
+    }
+
+    // Logical size to look icons up at
+    pub fn size_or(&self, native: u32) -> u32 {
+        self.size.unwrap_or(native)
+    }
+
+    // Device pixels to render at
+    pub fn pixel_size(&self, native: u32) -> u32 {
+        match self.size {
+            Some(size) => (size * self.scale).min(MAX_SIZE),
+            None => native,
+        }
+    }
+
+    // MIME type and data of the PNG in the requested format
+    pub fn encode(&self, png: &[u8]) -> (&'static str, Vec<u8>) {
+        match self.format {
+            IconFormat::Webp => match encode_webp(png) {
+                Some(webp) => ("image/webp", webp),
+                // Keep the PNG when WebP encoding fails
+                None => ("image/png", png.to_vec()),
+            },
+            IconFormat::Png => ("image/png", png.to_vec()),
+        }
+    }
+
+    pub fn data_url(&self, png: &[u8]) -> String {
+        let (mime_type, data) = self.encode(png);
+        format!(
+            "data:{};base64,{}",
+            mime_type,
+            general_purpose::STANDARD.encode(data)
+        )
+    }
+}
+
+// Scales the PNG so its longer side is `pixel_size`, keeping the aspect ratio.
+// PNGs that already have the size are returned as they are.
+pub fn resize_png(png: &[u8], pixel_size: u32) -> Option<Vec<u8>> {
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .ok()?
+        .to_rgba8();
+    let (width, height) = image.dimensions();
+    if width.max(height) == pixel_size {
+        return Some(png.to_vec());
+    }
+
+    let (target_width, target_height) = if width >= height {
+        (pixel_size, (height * pixel_size / width).max(1))
+    } else {
+        ((width * pixel_size / height).max(1), pixel_size)
+    };
+
+    // Filtering straight alpha bleeds the color of transparent pixels into
+    // the edges, so the colors are weighted by alpha while resampling
+    let mut image = image;
+    premultiply(&mut image);
+    let mut resized =
+        image::imageops::resize(&image, target_width, target_height, FilterType::Lanczos3);
+    unpremultiply(&mut resized);
+
+    encode(&resized, ImageFormat::Png)
+}
+
+// Converts an ICO, JPEG, GIF, WebP or PNG image (e.g. a favicon) to PNG. ICO
+// files contribute their largest image.
+pub fn to_png(data: &[u8]) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(data).ok()?.to_rgba8();
+    encode(&image, ImageFormat::Png)
+}
+
+fn encode_webp(png: &[u8]) -> Option<Vec<u8>> {
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)
+        .ok()?
+        .to_rgba8();
+    encode(&image, ImageFormat::WebP)
+}
+
+fn encode(image: &RgbaImage, format: ImageFormat) -> Option<Vec<u8>> {
+    let mut data = Cursor::new(Vec::new());
+    image.write_to(&mut data, format).ok()?;
+    Some(data.into_inner())
+}
+
+fn premultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+fn unpremultiply(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn png_of(image: &RgbaImage) -> Vec<u8> {
+        encode(image, ImageFormat::Png).unwrap()
+    }
+
+    fn decode(data: &[u8]) -> RgbaImage {
+        image::load_from_memory(data).unwrap().to_rgba8()
+    }
+
+    // Opaque red on the left, transparent green on the right
+    fn half_transparent(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| {
+            if x < width / 2 {
+                Rgba([255, 0, 0, 255])
+            } else {
+                Rgba([0, 255, 0, 0])
+            }
+        })
+    }
+
+    #[test]
+    fn resizes_keeping_the_aspect_ratio() {
+        let png = png_of(&RgbaImage::from_pixel(64, 32, Rgba([10, 20, 30, 255])));
+
+        assert_eq!(decode(&resize_png(&png, 16).unwrap()).dimensions(), (16, 8));
+        assert_eq!(
+            decode(&resize_png(&png, 128).unwrap()).dimensions(),
+            (128, 64)
+        );
+        let tall = png_of(&RgbaImage::new(10, 200));
+        assert_eq!(
+            decode(&resize_png(&tall, 20).unwrap()).dimensions(),
+            (1, 20)
+        );
+
+        // Already the right size
+        assert_eq!(resize_png(&png, 64).unwrap(), png);
+        assert!(resize_png(b"not a png", 16).is_none());
+    }
+
+    #[test]
+    fn keeps_colors_when_resizing() {
+        let color = Rgba([200, 100, 50, 255]);
+        let png = png_of(&RgbaImage::from_pixel(64, 64, color));
+        let resized = decode(&resize_png(&png, 24).unwrap());
+
+        assert!(resized.pixels().all(|pixel| *pixel == color));
+    }
+
+    #[test]
+    fn transparent_pixels_dont_bleed_into_edges() {
+        let png = png_of(&half_transparent(64, 64));
+        let resized = decode(&resize_png(&png, 16).unwrap());
+
+        for pixel in resized.pixels().filter(|pixel| pixel[3] > 0) {
+            assert_eq!(pixel[1], 0, "green bled into {:?}", pixel);
+            assert!(pixel[0] >= 250, "red faded in {:?}", pixel);
+        }
+        // The edge itself is partly transparent
+        assert!(resized.pixels().any(|pixel| pixel[3] > 0 && pixel[3] < 255));
+    }
+
+    #[test]
+    fn premultiplies_and_back() {
+        let original = RgbaImage::from_fn(256, 4, |x, y| {
+            let alpha = [255, 128, 1, 0][y as usize];
+            Rgba([x as u8, 255 - x as u8, 128, alpha])
+        });
+        let mut image = original.clone();
+
+        premultiply(&mut image);
+        assert_eq!(image.get_pixel(200, 0), original.get_pixel(200, 0));
+        assert_eq!(*image.get_pixel(200, 1), Rgba([100, 28, 64, 128]));
+        assert_eq!(*image.get_pixel(200, 3), Rgba([0, 0, 0, 0]));
+
+        unpremultiply(&mut image);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            let expected = original.get_pixel(x, y);
+            match pixel[3] {
+                // Opaque pixels survive unchanged, half transparent ones within rounding
+                255 => assert_eq!(pixel, expected),
+                128 => {
+                    for channel in 0..3 {
+                        assert!(
+                            pixel[channel].abs_diff(expected[channel]) <= 1,
+                            "{:?} {:?}",
+                            pixel,
+                            expected
+                        );
+                    }
+                }
+                // Colors of (nearly) transparent pixels are lost
+                _ => assert_eq!(pixel[3], expected[3]),
+            }
+        }
+    }
+
+    #[test]
+    fn encodes_lossless_webp() {
+        let image = half_transparent(32, 32);
+        let png = png_of(&image);
+        let request = IconRequest::new(Some(32), None, Some(IconFormat::Webp));
+
+        let (mime_type, webp) = request.encode(&png);
+        assert_eq!(mime_type, "image/webp");
+        assert_eq!(image::guess_format(&webp).unwrap(), ImageFormat::WebP);
+        let decoded = decode(&webp);
+        // Lossless, only the color of transparent pixels may change
+        for (pixel, expected) in decoded.pixels().zip(image.pixels()) {
+            if expected[3] == 0 {
+                assert_eq!(pixel[3], 0);
+            } else {
+                assert_eq!(pixel, expected);
+            }
+        }
+
+        // PNG requests and undecodable data stay PNG
+        assert_eq!(
+            IconRequest::new(None, None, None).encode(&png),
+            ("image/png", png.clone())
+        );
+        assert_eq!(request.encode(b"broken"), ("image/png", b"broken".to_vec()));
+        assert!(request
+            .data_url(&png)
+            .starts_with("data:image/webp;base64,"));
+    }
+
+    #[test]
+    fn clamps_requested_sizes() {
+        let request = IconRequest::new(Some(32), Some(2), None);
+        assert_eq!((request.size_or(512), request.pixel_size(512)), (32, 64));
+
+        let request = IconRequest::new(Some(4096), Some(10), None);
+        assert_eq!((request.size, request.scale), (Some(MAX_SIZE), MAX_SCALE));
+        assert_eq!(request.pixel_size(512), MAX_SIZE);
+
+        let request = IconRequest::new(None, Some(0), None);
+        assert_eq!(
+            (request.size_or(512), request.pixel_size(512), request.scale),
+            (512, 512, 1)
+        );
+    }
+}
@@ -0,0 +1,1924 @@
+// src-tauri/src/ics.rs
+//
+// iCalendar (RFC 5545) files: reads .ics files and subscribed feeds saved to
+// disk into the `EventInfo`s calendar mode shows, and writes events back out.
+// Events are added, edited and removed in place, keeping what grinta doesn't
+// understand (alarms, recurrence rules, custom properties) as it was.
+// Covers VEVENT with summary, description, URL, location and attendees,
+// all-day events (DATE values), times in the zones a VTIMEZONE defines and
+// recurring events (see recurrence.rs). Times in a zone the file doesn't
+// define are read as local time, like floating times. Plain text parsing, so
+// this works without EventKit.
+
+use chrono::{
+    DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Utc,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, Manager, Runtime, State};
+
+use crate::calendar_utils::{CalendarInfo, EventDraft, EventInfo, ParticipantInfo};
+use crate::error::{Error, Result};
+use crate::recurrence::{resolve_local, RecurrenceRule, RecurrenceSet};
+
+const PRODUCT_ID: &str = "-//Grinta//Grinta Calendar//EN";
+// Lines longer than this many bytes are folded
+const MAX_LINE_LENGTH: usize = 75;
+// For calendars that don't set X-APPLE-CALENDAR-COLOR
+pub(crate) const DEFAULT_COLOR: &str = "#8E8E93";
+// Paths of the imported calendars, in the app data directory
+const STORE_FILE_NAME: &str = "ics_calendars.json";
+
+// BEGIN:<name> … END:<name>, with its properties and nested components
+#[derive(Debug, Default)]
+struct Component {
+    name: String,
+    properties: Vec<Property>,
+    components: Vec<Component>,
+}
+
+impl Component {
+    fn property(&self, name: &str) -> Option<&Property> {
+        self.properties
+            .iter()
+            .find(|property| property.name == name)
+    }
+
+    fn text(&self, name: &str) -> Option<String> {
+        self.property(name)
+            .map(|property| unescape_text(&property.value))
+            .filter(|text| !text.is_empty())
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Component> {
+        self.components
+            .iter()
+            .filter(move |component| component.name == name)
+    }
+
+    fn write(&self, lines: &mut Vec<String>) {
+        lines.push(format!("BEGIN:{}", self.name));
+        lines.extend(self.properties.iter().map(Property::line));
+        for component in &self.components {
+            component.write(lines);
+        }
+        lines.push(format!("END:{}", self.name));
+    }
+}
+
+#[derive(Debug)]
+struct Property {
+    // Uppercase, like parameter names
+    name: String,
+    params: Vec<(String, String)>,
+    // Raw, still escaped
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // The content line, unfolded
+    fn line(&self) -> String {
+        let mut line = self.name.clone();
+        for (name, value) in &self.params {
+            if value.contains([':', ';', ',']) {
+                line.push_str(&format!(";{}=\"{}\"", name, value));
+            } else {
+                line.push_str(&format!(";{}={}", name, value));
+            }
+        }
+        line.push(':');
+        line.push_str(&self.value);
+        line
+    }
+}
+
+// A DATE or DATE-TIME value
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DateValue {
+    Date(NaiveDate),
+    Utc(NaiveDateTime),
+    // Floating, or in a zone the file doesn't define
+    Local(NaiveDateTime),
+    // In the VTIMEZONE at this index of the calendar's zones
+    Zoned(NaiveDateTime, usize),
+}
+
+impl DateValue {
+    fn wall_clock(self) -> NaiveDateTime {
+        match self {
+            DateValue::Date(date) => date.and_time(NaiveTime::MIN),
+            DateValue::Utc(date_time)
+            | DateValue::Local(date_time)
+            | DateValue::Zoned(date_time, _) => date_time,
+        }
+    }
+
+    // Whether both are in the same zone, so the wall-clock time between them
+    // is the length of an event
+    fn same_zone(self, other: DateValue) -> bool {
+        match (self, other) {
+            (DateValue::Zoned(_, zone), DateValue::Zoned(_, other)) => zone == other,
+            _ => std::mem::discriminant(&self) == std::mem::discriminant(&other),
+        }
+    }
+}
+
+// STANDARD or DAYLIGHT part of a VTIMEZONE: from its onsets on, local time is
+// `offset_to` ahead of UTC
+#[derive(Debug)]
+struct Observance {
+    // Onsets are in the wall-clock time before the change, `offset_from`
+    start: NaiveDateTime,
+    offset_from: FixedOffset,
+    offset_to: FixedOffset,
+    rule: Option<RecurrenceRule>,
+    dates: Vec<NaiveDateTime>,
+}
+
+impl Observance {
+    // The last time this observance took effect at or before `local`
+    fn last_onset(&self, local: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut onsets = vec![self.start];
+        onsets.extend(&self.dates);
+        if let Some(rule) = &self.rule {
+            // Wall-clock onsets, so the rule is expanded in UTC
+            onsets.extend(
+                rule.occurrences(&self.start.and_utc())
+                    .map(|onset| onset.naive_utc())
+                    .take_while(|onset| *onset <= local)
+                    .last(),
+            );
+        }
+        onsets.into_iter().filter(|onset| *onset <= local).max()
+    }
+}
+
+#[derive(Debug)]
+struct TimeZoneDefinition {
+    id: String,
+    observances: Vec<Observance>,
+}
+
+impl TimeZoneDefinition {
+    fn from_component(component: &Component) -> Option<Self> {
+        let id = component.property("TZID")?.value.clone();
+        let observances = component
+            .components
+            .iter()
+            .filter(|child| child.name == "STANDARD" || child.name == "DAYLIGHT")
+            .filter_map(|child| {
+                let offset_to = parse_utc_offset(&child.property("TZOFFSETTO")?.value)?;
+                Some(Observance {
+                    start: parse_date_time(&child.property("DTSTART")?.value)?.0,
+                    offset_from: child
+                        .property("TZOFFSETFROM")
+                        .and_then(|property| parse_utc_offset(&property.value))
+                        .unwrap_or(offset_to),
+                    offset_to,
+                    rule: child
+                        .property("RRULE")
+                        .and_then(|property| RecurrenceRule::parse(&property.value).ok()),
+                    dates: child
+                        .properties
+                        .iter()
+                        .filter(|property| property.name == "RDATE")
+                        .flat_map(|property| property.value.split(','))
+                        .filter_map(|date| Some(parse_date_time(date)?.0))
+                        .collect(),
+                })
+            })
+            .collect();
+        Some(Self { id, observances })
+    }
+
+    fn utc_offset(&self, utc: NaiveDateTime) -> FixedOffset {
+        let latest = self
+            .observances
+            .iter()
+            .filter_map(|observance| {
+                let onset = observance.last_onset(utc + observance.offset_from)?;
+                Some((onset - observance.offset_from, observance))
+            })
+            .max_by_key(|(onset, _)| *onset);
+        match latest {
+            Some((_, observance)) => observance.offset_to,
+            // Before the first onset the zone used the offset it started from
+            None => self
+                .observances
+                .iter()
+                .min_by_key(|observance| observance.start)
+                .map_or(Utc.fix(), |observance| observance.offset_from),
+        }
+    }
+}
+
+// A VTIMEZONE as a chrono time zone, so recurrences expand in it like in any
+// other zone
+#[derive(Clone, Debug)]
+pub struct IcsTimeZone(Arc<TimeZoneDefinition>);
+
+#[derive(Clone, Debug)]
+pub struct IcsOffset {
+    zone: IcsTimeZone,
+    offset: FixedOffset,
+}
+
+impl Offset for IcsOffset {
+    fn fix(&self) -> FixedOffset {
+        self.offset
+    }
+}
+
+impl TimeZone for IcsTimeZone {
+    type Offset = IcsOffset;
+
+    fn from_offset(offset: &IcsOffset) -> Self {
+        offset.zone.clone()
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<IcsOffset> {
+        self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+    }
+
+    // A wall-clock time is valid with an offset if that offset is in effect
+    // at the instant it gives: none in DST gaps, two in repeated hours
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<IcsOffset> {
+        let mut offsets: Vec<FixedOffset> = self
+            .0
+            .observances
+            .iter()
+            .flat_map(|observance| [observance.offset_from, observance.offset_to])
+            .filter(|offset| self.0.utc_offset(*local - *offset) == *offset)
+            .collect();
+        offsets.sort_by_key(|offset| std::cmp::Reverse(offset.local_minus_utc()));
+        offsets.dedup();
+
+        let offset = |offset: FixedOffset| IcsOffset {
+            zone: self.clone(),
+            offset,
+        };
+        match offsets[..] {
+            [] if self.0.observances.is_empty() => LocalResult::Single(offset(Utc.fix())),
+            [] => LocalResult::None,
+            [single] => LocalResult::Single(offset(single)),
+            [earliest, latest, ..] => LocalResult::Ambiguous(offset(earliest), offset(latest)),
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> IcsOffset {
+        self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+    }
+
+    fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> IcsOffset {
+        IcsOffset {
+            zone: self.clone(),
+            offset: self.0.utc_offset(*utc),
+        }
+    }
+}
+
+// How long each occurrence of an event lasts: wall-clock time when start and
+// end are in the same zone (a day-long event lasts 23 hours when clocks go
+// forward), exact time otherwise
+#[derive(Clone, Copy, Debug)]
+enum EventLength {
+    WallClock(Duration),
+    Exact(Duration),
+}
+
+#[derive(Debug)]
+pub struct IcsEvent {
+    pub uid: Option<String>,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub location: Option<String>,
+    // Display names, the address for attendees without one
+    pub attendees: Vec<String>,
+    pub start: DateValue,
+    pub end: Option<DateValue>,
+    pub duration: Option<Duration>,
+    // Set on events that replace one occurrence of the recurring event with
+    // the same UID
+    pub recurrence_id: Option<DateValue>,
+    pub rules: Vec<RecurrenceRule>,
+    // RDATE and EXDATE
+    pub dates: Vec<DateValue>,
+    pub exceptions: Vec<DateValue>,
+}
+
+impl IcsEvent {
+    fn from_component(component: &Component, zones: &[IcsTimeZone]) -> Option<Self> {
+        let date_value = |name: &str| {
+            component
+                .property(name)
+                .and_then(|property| parse_date_value(&property.value, property, zones))
+        };
+        let date_values = |name: &str| -> Vec<DateValue> {
+            component
+                .properties
+                .iter()
+                .filter(|property| property.name == name)
+                .flat_map(|property| {
+                    // PERIOD values (start/end) only contribute their start
+                    property.value.split(',').filter_map(|value| {
+                        let start = value.split('/').next().unwrap_or(value);
+                        parse_date_value(start, property, zones)
+                    })
+                })
+                .collect()
+        };
+
+        Some(Self {
+            uid: component.text("UID"),
+            summary: component.text("SUMMARY"),
+            description: component.text("DESCRIPTION"),
+            url: component.text("URL"),
+            location: component.text("LOCATION"),
+            attendees: component
+                .properties
+                .iter()
+                .filter(|property| property.name == "ATTENDEE")
+                .filter_map(attendee_name)
+                .collect(),
+            start: date_value("DTSTART")?,
+            end: date_value("DTEND"),
+            duration: component
+                .property("DURATION")
+                .and_then(|property| parse_duration(&property.value)),
+            recurrence_id: date_value("RECURRENCE-ID"),
+            rules: component
+                .properties
+                .iter()
+                .filter(|property| property.name == "RRULE")
+                .filter_map(|property| match RecurrenceRule::parse(&property.value) {
+                    Ok(rule) => Some(rule),
+                    // The event still shows up once
+                    Err(e) => {
+                        eprintln!("Ignoring recurrence rule {}: {}", property.value, e);
+                        None
+                    }
+                })
+                .collect(),
+            dates: date_values("RDATE"),
+            exceptions: date_values("EXDATE"),
+        })
+    }
+
+    pub fn is_all_day(&self) -> bool {
+        matches!(self.start, DateValue::Date(_))
+    }
+}
+
+#[derive(Debug)]
+pub struct IcsCalendar {
+    // X-WR-CALNAME
+    pub name: Option<String>,
+    // X-APPLE-CALENDAR-COLOR, #RRGGBB
+    pub color: Option<String>,
+    pub events: Vec<IcsEvent>,
+    zones: Vec<IcsTimeZone>,
+}
+
+impl IcsCalendar {
+    pub fn parse(content: &str) -> Result<Self> {
+        let calendar = parse_calendar_component(content)?;
+        let zones = zones(&calendar);
+        let events = calendar
+            .children("VEVENT")
+            .filter_map(|component| IcsEvent::from_component(component, &zones))
+            .collect();
+
+        Ok(Self {
+            name: calendar.text("X-WR-CALNAME"),
+            color: calendar
+                .text("X-APPLE-CALENDAR-COLOR")
+                .and_then(|color| normalize_color(&color)),
+            events,
+            zones,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).map_err(|e| Error::from(e).with_details(path.display()))?;
+        Self::parse(&content).map_err(|e| e.with_details(path.display()))
+    }
+
+    pub fn calendar_info(&self, identifier: &str) -> CalendarInfo {
+        let title = self.name.clone().unwrap_or_else(|| {
+            Path::new(identifier)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_else(|| identifier.to_string())
+        });
+        CalendarInfo {
+            identifier: identifier.to_string(),
+            title,
+            color: self
+                .color
+                .clone()
+                .unwrap_or_else(|| DEFAULT_COLOR.to_string()),
+        }
+    }
+
+    pub fn to_utc<Tz: TimeZone>(&self, value: DateValue, local: &Tz) -> DateTime<Utc> {
+        to_utc(value, &self.zones, local)
+    }
+
+    // All-day events end at midnight after their last day, events without an
+    // end or duration take no time
+    fn event_length<Tz: TimeZone>(&self, event: &IcsEvent, local: &Tz) -> EventLength {
+        match (event.end, event.duration) {
+            (Some(end), _) if end.same_zone(event.start) => {
+                EventLength::WallClock(end.wall_clock() - event.start.wall_clock())
+            }
+            (Some(end), _) => {
+                EventLength::Exact(self.to_utc(end, local) - self.to_utc(event.start, local))
+            }
+            (None, Some(duration)) if event.is_all_day() => EventLength::WallClock(duration),
+            (None, Some(duration)) => EventLength::Exact(duration),
+            (None, None) if event.is_all_day() => EventLength::WallClock(Duration::days(1)),
+            (None, None) => EventLength::Exact(Duration::zero()),
+        }
+    }
+
+    // Starts and ends of the occurrences of `event` that overlap
+    // `start..end`, leaving out the ones in `overridden`
+    fn occurrences<Tz: TimeZone>(
+        &self,
+        event: &IcsEvent,
+        overridden: &[DateTime<Utc>],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        local: &Tz,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        match event.start {
+            DateValue::Utc(date_time) => {
+                self.expand(event, date_time.and_utc(), overridden, start, end, local)
+            }
+            DateValue::Zoned(date_time, zone) => {
+                let first = resolve_local(&self.zones[zone], date_time);
+                self.expand(event, first, overridden, start, end, local)
+            }
+            DateValue::Date(_) | DateValue::Local(_) => {
+                let first = resolve_local(local, event.start.wall_clock());
+                self.expand(event, first, overridden, start, end, local)
+            }
+        }
+    }
+
+    fn expand<Tz: TimeZone, Local: TimeZone>(
+        &self,
+        event: &IcsEvent,
+        first: DateTime<Tz>,
+        overridden: &[DateTime<Utc>],
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        local: &Local,
+    ) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let length = self.event_length(event, local);
+        let mut set = RecurrenceSet::new(first);
+        set.rules = event.rules.clone();
+        set.dates = event
+            .dates
+            .iter()
+            .map(|date| self.to_utc(*date, local))
+            .collect();
+        set.exceptions = event
+            .exceptions
+            .iter()
+            .map(|date| self.to_utc(*date, local))
+            .chain(overridden.iter().copied())
+            .collect();
+
+        // Occurrences that started before `start` can still overlap it. Events
+        // too long to represent are left out.
+        let (EventLength::WallClock(longest) | EventLength::Exact(longest)) = length;
+        let Some(after) = start
+            .checked_sub_signed(longest.max(Duration::zero()))
+            .and_then(|after| after.checked_sub_signed(Duration::days(1)))
+        else {
+            return Vec::new();
+        };
+        set.between(after, end)
+            .into_iter()
+            .filter_map(|occurrence| {
+                let occurrence_end = match length {
+                    EventLength::WallClock(length) => {
+                        let wall_clock = occurrence.naive_local().checked_add_signed(length)?;
+                        resolve_local(&occurrence.timezone(), wall_clock).with_timezone(&Utc)
+                    }
+                    EventLength::Exact(length) => {
+                        occurrence.with_timezone(&Utc).checked_add_signed(length)?
+                    }
+                };
+                let occurrence = occurrence.with_timezone(&Utc);
+                Some((occurrence, occurrence_end.max(occurrence)))
+            })
+            .filter(|(occurrence_start, occurrence_end)| {
+                *occurrence_start < end && (*occurrence_end > start || *occurrence_start >= start)
+            })
+            .collect()
+    }
+
+    // Events overlapping `start..end`, sorted by start. Recurring events are
+    // expanded into one `EventInfo` per occurrence, all with the event's UID
+    // like EventKit does.
+    pub fn events_between<Tz: TimeZone>(
+        &self,
+        calendar_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        local: &Tz,
+    ) -> Vec<EventInfo> {
+        // Occurrences replaced by an event with a RECURRENCE-ID
+        let mut overridden: HashMap<&str, Vec<DateTime<Utc>>> = HashMap::new();
+        for event in &self.events {
+            if let (Some(uid), Some(recurrence_id)) = (&event.uid, event.recurrence_id) {
+                overridden
+                    .entry(uid.as_str())
+                    .or_default()
+                    .push(self.to_utc(recurrence_id, local));
+            }
+        }
+
+        let mut events: Vec<(DateTime<Utc>, EventInfo)> = Vec::new();
+        for (index, event) in self.events.iter().enumerate() {
+            let replaced = match (&event.uid, event.recurrence_id) {
+                (Some(uid), None) => overridden.get(uid.as_str()).map(Vec::as_slice),
+                _ => None,
+            };
+            for (event_start, event_end) in
+                self.occurrences(event, replaced.unwrap_or_default(), start, end, local)
+            {
+                events.push((
+                    event_start,
+                    event_info(event, index, calendar_id, event_start, event_end),
+                ));
+            }
+        }
+        events.sort_by_key(|(start, _)| *start);
+        events.into_iter().map(|(_, event)| event).collect()
+    }
+}
+
+fn event_info(
+    event: &IcsEvent,
+    index: usize,
+    calendar_id: &str,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> EventInfo {
+    EventInfo {
+        // UID is required, but not every feed has one
+        identifier: event
+            .uid
+            .clone()
+            .unwrap_or_else(|| format!("{}#{}", calendar_id, index)),
+        title: event.summary.clone().unwrap_or_default(),
+        notes: event.description.clone(),
+        url: event.url.clone(),
+        participants: event
+            .attendees
+            .iter()
+            .map(|name| ParticipantInfo {
+                name: Some(name.clone()),
+            })
+            .collect(),
+        start_date: start.to_rfc3339(),
+        end_date: end.to_rfc3339(),
+        calendar_id: calendar_id.to_string(),
+        location: event.location.clone(),
+        is_all_day: event.is_all_day(),
+    }
+}
+
+// Writes `events` as a calendar. All-day events keep the dates they have in
+// the `local` zone, other times are written in UTC.
+pub fn write_calendar<Tz: TimeZone>(
+    events: &[EventInfo],
+    name: Option<&str>,
+    now: DateTime<Utc>,
+    local: &Tz,
+) -> Result<String> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    if let Some(name) = name {
+        lines.push(format!("X-WR-CALNAME:{}", escape_text(name)));
+    }
+
+    for event in events {
+        let start = parse_rfc3339(&event.start_date)?;
+        let end = parse_rfc3339(&event.end_date)?;
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape_text(&event.identifier)));
+        lines.push(format!("DTSTAMP:{}", format_utc(now)));
+        lines.extend(date_lines(start, end, event.is_all_day, None, local));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+        if let Some(notes) = &event.notes {
+            lines.push(format!("DESCRIPTION:{}", escape_text(notes)));
+        }
+        if let Some(location) = &event.location {
+            lines.push(format!("LOCATION:{}", escape_text(location)));
+        }
+        if let Some(url) = &event.url {
+            lines.push(format!("URL:{}", url));
+        }
+        for name in event
+            .participants
+            .iter()
+            .filter_map(|participant| participant.name.as_deref())
+        {
+            lines.push(attendee_line(name));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    Ok(lines.iter().map(|line| fold_line(line)).collect())
+}
+
+// DTSTART and DTEND. All-day events keep the dates they have in the `local`
+// zone and last at least a day, other times are written in `zone` or UTC.
+fn date_lines<Tz: TimeZone>(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    is_all_day: bool,
+    zone: Option<&IcsTimeZone>,
+    local: &Tz,
+) -> [String; 2] {
+    if is_all_day {
+        let start_date = start.with_timezone(local).date_naive();
+        let end_date = end
+            .with_timezone(local)
+            .date_naive()
+            .max(start_date.succ_opt().unwrap_or(start_date));
+        return [
+            format!("DTSTART;VALUE=DATE:{}", start_date.format("%Y%m%d")),
+            format!("DTEND;VALUE=DATE:{}", end_date.format("%Y%m%d")),
+        ];
+    }
+    match zone {
+        Some(zone) => {
+            let format = |date: DateTime<Utc>| {
+                format!(
+                    ";TZID={}:{}",
+                    zone.0.id,
+                    date.with_timezone(zone)
+                        .naive_local()
+                        .format("%Y%m%dT%H%M%S")
+                )
+            };
+            [
+                format!("DTSTART{}", format(start)),
+                format!("DTEND{}", format(end)),
+            ]
+        }
+        None => [
+            format!("DTSTART:{}", format_utc(start)),
+            format!("DTEND:{}", format_utc(end)),
+        ],
+    }
+}
+
+// Properties an `EventDraft` sets, replaced when an event is edited
+const DRAFT_PROPERTIES: [&str; 10] = [
+    "DTSTAMP",
+    "LAST-MODIFIED",
+    "DTSTART",
+    "DTEND",
+    "DURATION",
+    "SUMMARY",
+    "DESCRIPTION",
+    "LOCATION",
+    "URL",
+    "SEQUENCE",
+];
+
+// A UID for a new event, also the name of its CalDAV resource
+pub fn new_event_uid() -> String {
+    format!("{:032x}@grinta", rand::random::<u128>())
+}
+
+fn parse_calendar_component(content: &str) -> Result<Component> {
+    parse_components(content)?
+        .into_iter()
+        .find(|component| component.name == "VCALENDAR")
+        .ok_or_else(|| Error::invalid_argument("No VCALENDAR in the file"))
+}
+
+fn write_component(component: &Component) -> String {
+    let mut lines = Vec::new();
+    component.write(&mut lines);
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+// The VEVENT with `uid` that isn't an override of one of its occurrences
+fn find_event<'a>(calendar: &'a mut Component, uid: &str) -> Result<&'a mut Component> {
+    calendar
+        .components
+        .iter_mut()
+        .find(|component| {
+            component.name == "VEVENT"
+                && component.text("UID").as_deref() == Some(uid)
+                && component.property("RECURRENCE-ID").is_none()
+        })
+        .ok_or_else(|| Error::not_found(format!("No event {}", uid)))
+}
+
+// Sets what `draft` covers on `event`. Times stay in the zone the event
+// started in, so a weekly event keeps its local time across DST changes.
+// Returns how far the start moved in wall-clock time, None for new events
+// and events that switched between all-day and timed.
+fn apply_draft<Tz: TimeZone>(
+    event: &mut Component,
+    draft: &EventDraft,
+    zones: &[IcsTimeZone],
+    now: DateTime<Utc>,
+    local: &Tz,
+) -> Result<Option<Duration>> {
+    let (start, end) = draft.validate()?;
+    let previous_start = event
+        .property("DTSTART")
+        .and_then(|property| parse_date_value(&property.value, property, zones));
+    let zone = event
+        .property("DTSTART")
+        .and_then(|property| property.param("TZID"))
+        .and_then(|id| zones.iter().find(|zone| zone.0.id == id));
+    let shift = previous_start.and_then(|previous| {
+        let start = match previous {
+            DateValue::Date(_) if draft.is_all_day => start
+                .with_timezone(local)
+                .date_naive()
+                .and_time(NaiveTime::MIN),
+            _ if draft.is_all_day => return None,
+            DateValue::Date(_) => return None,
+            DateValue::Utc(_) => start.naive_utc(),
+            DateValue::Zoned(_, zone) => start.with_timezone(&zones[zone]).naive_local(),
+            DateValue::Local(_) => start.with_timezone(local).naive_local(),
+        };
+        Some(start - previous.wall_clock())
+    });
+    // New events start at 0, every edit counts up
+    let sequence = match event
+        .property("SEQUENCE")
+        .and_then(|property| property.value.trim().parse::<u32>().ok())
+    {
+        Some(sequence) => sequence + 1,
+        None if previous_start.is_some() => 1,
+        None => 0,
+    };
+
+    let mut lines = vec![
+        format!("DTSTAMP:{}", format_utc(now)),
+        format!("LAST-MODIFIED:{}", format_utc(now)),
+    ];
+    lines.extend(date_lines(start, end, draft.is_all_day, zone, local));
+    lines.push(format!("SUMMARY:{}", escape_text(draft.title.trim())));
+    let optional = [("DESCRIPTION", &draft.notes), ("LOCATION", &draft.location)];
+    for (name, value) in optional {
+        if let Some(value) = value.as_deref().filter(|value| !value.is_empty()) {
+            lines.push(format!("{}:{}", name, escape_text(value)));
+        }
+    }
+    if let Some(url) = &draft.url {
+        lines.push(format!("URL:{}", url));
+    }
+    lines.push(format!("SEQUENCE:{}", sequence));
+
+    event
+        .properties
+        .retain(|property| !DRAFT_PROPERTIES.contains(&property.name.as_str()));
+    // After UID, where calendar apps put them
+    let position = event
+        .properties
+        .iter()
+        .position(|property| property.name == "UID")
+        .map_or(0, |uid| uid + 1);
+    let properties = lines.iter().filter_map(|line| parse_content_line(line));
+    event.properties.splice(position..position, properties);
+    Ok(shift)
+}
+
+// Moves the EXDATE, RDATE and RECURRENCE-ID values and the UNTIL of the
+// rules of `event` by `shift`, so exceptions still match occurrences after
+// the start of a recurring event changed
+fn shift_recurrence_dates(event: &mut Component, shift: Duration) {
+    let rules = event
+        .properties
+        .iter_mut()
+        .filter(|property| property.name == "RRULE");
+    for rule in rules {
+        rule.value = rule
+            .value
+            .split(';')
+            .map(|part| match part.split_once('=') {
+                Some((name, until)) if name.eq_ignore_ascii_case("UNTIL") => {
+                    shift_date_value(until, shift)
+                        .map_or_else(|| part.to_string(), |until| format!("UNTIL={}", until))
+                }
+                _ => part.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(";");
+    }
+
+    let properties = event
+        .properties
+        .iter_mut()
+        .filter(|property| ["EXDATE", "RDATE", "RECURRENCE-ID"].contains(&property.name.as_str()));
+    for property in properties {
+        property.value = property
+            .value
+            .split(',')
+            .map(|value| shift_date_value(value, shift).unwrap_or_else(|| value.to_string()))
+            .collect::<Vec<_>>()
+            .join(",");
+    }
+}
+
+// 20240325 or 20240325T093000, with a trailing Z for UTC. PERIOD values
+// are left alone.
+fn shift_date_value(value: &str, shift: Duration) -> Option<String> {
+    let value = value.trim();
+    if value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()? + shift;
+        return Some(date.format("%Y%m%d").to_string());
+    }
+    let (date_time, is_utc) = parse_date_time(value)?;
+    let shifted = (date_time + shift).format("%Y%m%dT%H%M%S");
+    Some(match is_utc {
+        true => format!("{}Z", shifted),
+        false => shifted.to_string(),
+    })
+}
+
+fn to_utc<Tz: TimeZone>(value: DateValue, zones: &[IcsTimeZone], local: &Tz) -> DateTime<Utc> {
+    match value {
+        DateValue::Utc(date_time) => date_time.and_utc(),
+        DateValue::Zoned(date_time, zone) => {
+            resolve_local(&zones[zone], date_time).with_timezone(&Utc)
+        }
+        DateValue::Date(_) | DateValue::Local(_) => {
+            resolve_local(local, value.wall_clock()).with_timezone(&Utc)
+        }
+    }
+}
+
+fn zones(calendar: &Component) -> Vec<IcsTimeZone> {
+    calendar
+        .children("VTIMEZONE")
+        .filter_map(TimeZoneDefinition::from_component)
+        .map(|zone| IcsTimeZone(Arc::new(zone)))
+        .collect()
+}
+
+// `content` with a new event `uid`, or a calendar with only that event
+pub fn add_event<Tz: TimeZone>(
+    content: Option<&str>,
+    uid: &str,
+    draft: &EventDraft,
+    now: DateTime<Utc>,
+    local: &Tz,
+) -> Result<String> {
+    let mut calendar = match content {
+        Some(content) => parse_calendar_component(content)?,
+        None => Component {
+            name: "VCALENDAR".to_string(),
+            properties: [
+                "VERSION:2.0",
+                &format!("PRODID:{}", PRODUCT_ID),
+                "CALSCALE:GREGORIAN",
+            ]
+            .iter()
+            .filter_map(|line| parse_content_line(line))
+            .collect(),
+            components: Vec::new(),
+        },
+    };
+    let mut event = Component {
+        name: "VEVENT".to_string(),
+        properties: parse_content_line(&format!("UID:{}", escape_text(uid)))
+            .into_iter()
+            .collect(),
+        components: Vec::new(),
+    };
+    apply_draft(&mut event, draft, &[], now, local)?;
+    calendar.components.push(event);
+    Ok(write_component(&calendar))
+}
+
+// `content` with the event `uid` changed to `draft`, which was edited from
+// its occurrence at `occurrence_date`. Recurring events change as a whole.
+pub fn update_event<Tz: TimeZone>(
+    content: &str,
+    uid: &str,
+    occurrence_date: DateTime<Utc>,
+    draft: &EventDraft,
+    now: DateTime<Utc>,
+    local: &Tz,
+) -> Result<String> {
+    let mut calendar = parse_calendar_component(content)?;
+    let zones = zones(&calendar);
+    let event = find_event(&mut calendar, uid)?;
+    let series_start = event
+        .property("DTSTART")
+        .and_then(|property| parse_date_value(&property.value, property, &zones));
+    let draft = match series_start {
+        Some(start) => draft.for_series(occurrence_date, to_utc(start, &zones, local), local)?,
+        None => draft.clone(),
+    };
+    let shift = apply_draft(event, &draft, &zones, now, local)?;
+
+    if let Some(shift) = shift.filter(|shift| !shift.is_zero()) {
+        let events = calendar.components.iter_mut().filter(|component| {
+            component.name == "VEVENT" && component.text("UID").as_deref() == Some(uid)
+        });
+        for event in events {
+            shift_recurrence_dates(event, shift);
+        }
+    }
+    Ok(write_component(&calendar))
+}
+
+// `content` without the event `uid` and the overrides of its occurrences
+pub fn remove_event(content: &str, uid: &str) -> Result<String> {
+    let mut calendar = parse_calendar_component(content)?;
+    find_event(&mut calendar, uid)?;
+    calendar.components.retain(|component| {
+        component.name != "VEVENT" || component.text("UID").as_deref() != Some(uid)
+    });
+    Ok(write_component(&calendar))
+}
+
+// #RRGGBB from #RRGGBB or Apple's #RRGGBBAA
+pub(crate) fn normalize_color(color: &str) -> Option<String> {
+    let color = color.trim();
+    (color.starts_with('#') && color.len() >= 7 && color.is_char_boundary(7))
+        .then(|| color[..7].to_uppercase())
+}
+
+pub(crate) fn parse_rfc3339(date: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|e| Error::invalid_argument(format!("Invalid date: {}", date)).with_details(e))
+}
+
+fn format_utc(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Participants only have a name, which is an address for attendees read
+// without a CN. Calendar apps write `invalid:nomail` for people without one.
+fn attendee_line(name: &str) -> String {
+    if name.contains('@') && !name.contains(char::is_whitespace) {
+        format!("ATTENDEE:mailto:{}", name)
+    } else {
+        format!("ATTENDEE;CN=\"{}\":invalid:nomail", name.replace('"', "'"))
+    }
+}
+
+fn attendee_name(property: &Property) -> Option<String> {
+    if let Some(name) = property.param("CN").filter(|name| !name.is_empty()) {
+        return Some(name.to_string());
+    }
+    let address = property.value.trim();
+    let address = address
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map_or(address, |_| &address[7..]);
+    (!address.is_empty() && address != "invalid:nomail").then(|| address.to_string())
+}
+
+// Splits content lines longer than 75 bytes, continuation lines start with a
+// space. Never splits inside a UTF-8 sequence.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_length = 0;
+    for c in line.chars() {
+        if line_length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+fn parse_components(content: &str) -> Result<Vec<Component>> {
+    let mut roots = Vec::new();
+    let mut stack: Vec<Component> = Vec::new();
+
+    for line in unfold_lines(content) {
+        let Some(property) = parse_content_line(&line) else {
+            continue;
+        };
+        match property.name.as_str() {
+            "BEGIN" => stack.push(Component {
+                name: property.value.to_uppercase(),
+                ..Component::default()
+            }),
+            "END" => {
+                let component = stack
+                    .pop()
+                    .filter(|component| component.name.eq_ignore_ascii_case(&property.value))
+                    .ok_or_else(|| {
+                        Error::invalid_argument(format!("Unexpected END:{}", property.value))
+                    })?;
+                match stack.last_mut() {
+                    Some(parent) => parent.components.push(component),
+                    None => roots.push(component),
+                }
+            }
+            _ => {
+                if let Some(component) = stack.last_mut() {
+                    component.properties.push(property);
+                }
+            }
+        }
+    }
+
+    if let Some(component) = stack.last() {
+        return Err(Error::invalid_argument(format!(
+            "Missing END:{}",
+            component.name
+        )));
+    }
+    Ok(roots)
+}
+
+// Joins folded lines: a line starting with a space or tab continues the
+// previous one
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// NAME;PARAM=value;PARAM="quoted:value":value
+fn parse_content_line(line: &str) -> Option<Property> {
+    let name_end = line.find([';', ':'])?;
+    let name = line[..name_end].to_uppercase();
+    let mut params = Vec::new();
+    let mut rest = &line[name_end..];
+
+    while let Some(param) = rest.strip_prefix(';') {
+        let (param_name, value) = param.split_once('=')?;
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, remaining) = quoted.split_once('"')?;
+                (value, remaining)
+            }
+            None => {
+                let end = value.find([';', ':']).unwrap_or(value.len());
+                value.split_at(end)
+            }
+        };
+        params.push((param_name.to_uppercase(), value.to_string()));
+        rest = remaining;
+    }
+
+    Some(Property {
+        name,
+        params,
+        value: rest.strip_prefix(':')?.to_string(),
+    })
+}
+
+fn parse_date_value(value: &str, property: &Property, zones: &[IcsTimeZone]) -> Option<DateValue> {
+    let value = value.trim();
+    if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(DateValue::Date);
+    }
+
+    let (date_time, is_utc) = parse_date_time(value)?;
+    if is_utc {
+        return Some(DateValue::Utc(date_time));
+    }
+    let zone = property
+        .param("TZID")
+        .and_then(|id| zones.iter().position(|zone| zone.0.id == id));
+    Some(match zone {
+        Some(zone) => DateValue::Zoned(date_time, zone),
+        None => DateValue::Local(date_time),
+    })
+}
+
+// 20240704T100000, with a trailing Z for UTC
+fn parse_date_time(value: &str) -> Option<(NaiveDateTime, bool)> {
+    let (value, is_utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let date_time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    Some((date_time, is_utc))
+}
+
+// +0100, -0530 or +013045
+fn parse_utc_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    let sign = match value.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let digits = &value[1..];
+    if digits.len() != 4 && digits.len() != 6 {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| digits.get(range)?.parse::<i32>().ok();
+    let seconds = field(0..2)? * 3600 + field(2..4)? * 60 + field(4..6).unwrap_or(0);
+    FixedOffset::east_opt(sign * seconds)
+}
+
+// P1W, P1DT2H, PT45M, -PT15M
+fn parse_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in value.strip_prefix('P')?.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => in_time = true,
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                // Amounts too large for a Duration make the value invalid
+                let part = match (unit, in_time) {
+                    ('W', false) => Duration::try_weeks(amount),
+                    ('D', false) => Duration::try_days(amount),
+                    ('H', true) => Duration::try_hours(amount),
+                    ('M', true) => Duration::try_minutes(amount),
+                    ('S', true) => Duration::try_seconds(amount),
+                    _ => None,
+                }?;
+                duration = duration.checked_add(&part)?;
+            }
+        }
+    }
+    if !number.is_empty() {
+        return None;
+    }
+    duration.checked_mul(sign)
+}
+
+// The calendar an .ics file or saved feed describes; its identifier is the path
+#[command]
+#[specta::specta]
+pub fn get_ics_calendar(path: String) -> Result<CalendarInfo> {
+    Ok(IcsCalendar::read(Path::new(&path))?.calendar_info(&path))
+}
+
+#[command]
+#[specta::specta]
+pub fn get_ics_events(
+    path: String,
+    start_date_iso: String,
+    end_date_iso: String,
+) -> Result<Vec<EventInfo>> {
+    let start = parse_rfc3339(&start_date_iso)?;
+    let end = parse_rfc3339(&end_date_iso)?;
+    let calendar = IcsCalendar::read(Path::new(&path))?;
+    Ok(calendar.events_between(&path, start, end, &chrono::Local))
+}
+
+#[command]
+#[specta::specta]
+pub fn export_ics_file(
+    path: String,
+    events: Vec<EventInfo>,
+    calendar_name: Option<String>,
+) -> Result<()> {
+    let content = write_calendar(
+        &events,
+        calendar_name.as_deref(),
+        Utc::now(),
+        &chrono::Local,
+    )?;
+    fs::write(&path, content).map_err(|e| Error::from(e).with_details(&path))
+}
+
+// .ics files are identified by their absolute path, see `get_ics_calendar`
+pub fn is_ics_calendar(calendar_id: &str) -> bool {
+    let path = Path::new(calendar_id);
+    path.is_absolute()
+        && path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"))
+}
+
+fn read_file(path: &str) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| Error::from(e).with_details(path))
+}
+
+fn write_file(path: &str, content: &str) -> Result<()> {
+    fs::write(path, content).map_err(|e| Error::from(e).with_details(path))
+}
+
+// Adds the event to the file `draft.calendar_id`, creating it if needed
+fn create_file_event(draft: &EventDraft) -> Result<EventInfo> {
+    let path = &draft.calendar_id;
+    let content = match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(Error::from(e).with_details(path)),
+    };
+    let uid = new_event_uid();
+    let content = add_event(content.as_deref(), &uid, draft, Utc::now(), &chrono::Local)?;
+    write_file(path, &content)?;
+    draft.event_info(uid)
+}
+
+fn update_file_event(
+    uid: &str,
+    occurrence_date: DateTime<Utc>,
+    draft: &EventDraft,
+) -> Result<EventInfo> {
+    let path = &draft.calendar_id;
+    let content = update_event(
+        &read_file(path)?,
+        uid,
+        occurrence_date,
+        draft,
+        Utc::now(),
+        &chrono::Local,
+    )?;
+    write_file(path, &content)?;
+    draft.event_info(uid.to_string())
+}
+
+fn delete_file_event(path: &str, uid: &str) -> Result<()> {
+    let content = remove_event(&read_file(path)?, uid)?;
+    write_file(path, &content)
+}
+
+// The .ics files imported into calendar mode, by path
+#[derive(Default)]
+struct IcsStore {
+    paths: Vec<String>,
+    // Unset until `start` ran, and in tests
+    file: Option<PathBuf>,
+}
+
+impl IcsStore {
+    fn load(file: &Path) -> Result<Vec<String>> {
+        let data = fs::read(file)
+            .map_err(|e| Error::io("Failed to read imported calendars").with_details(e))?;
+        serde_json::from_slice(&data)
+            .map_err(|e| Error::io("Failed to parse imported calendars").with_details(e))
+    }
+
+    fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        let data = serde_json::to_vec(&self.paths).map_err(|e| {
+            Error::internal("Failed to serialize imported calendars").with_details(e)
+        })?;
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| Error::io("Failed to create app data directory").with_details(e))?;
+        }
+        fs::write(file, data)
+            .map_err(|e| Error::io("Failed to save imported calendars").with_details(e))
+    }
+}
+
+pub struct IcsState {
+    store: Mutex<IcsStore>,
+}
+
+impl IcsState {
+    pub fn new() -> Self {
+        Self {
+            store: Mutex::new(IcsStore::default()),
+        }
+    }
+
+    pub fn has_calendars(&self) -> bool {
+        !self.store.lock().unwrap().paths.is_empty()
+    }
+
+    // Imported calendars whose file can still be read
+    pub fn calendars(&self) -> Vec<CalendarInfo> {
+        let paths = self.store.lock().unwrap().paths.clone();
+        paths
+            .iter()
+            .filter_map(|path| match IcsCalendar::read(Path::new(path)) {
+                Ok(calendar) => Some(calendar.calendar_info(path)),
+                Err(e) => {
+                    eprintln!("Skipping imported calendar {}: {}", path, e);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // Events of the calendars among `paths` in the range, for
+    // `get_calendar_events`. Files that can't be read are left out.
+    pub fn events(
+        &self,
+        paths: &[String],
+        start_date_iso: &str,
+        end_date_iso: &str,
+    ) -> Result<Vec<EventInfo>> {
+        let start = parse_rfc3339(start_date_iso)?;
+        let end = parse_rfc3339(end_date_iso)?;
+        for path in paths {
+            self.check_imported(path)?;
+        }
+        let mut events = Vec::new();
+        for path in paths {
+            match IcsCalendar::read(Path::new(path)) {
+                Ok(calendar) => {
+                    events.extend(calendar.events_between(path, start, end, &chrono::Local))
+                }
+                Err(e) => eprintln!("Skipping events of {}: {}", path, e),
+            }
+        }
+        Ok(events)
+    }
+
+    pub fn create_event(&self, draft: &EventDraft) -> Result<EventInfo> {
+        self.check_imported(&draft.calendar_id)?;
+        create_file_event(draft)
+    }
+
+    pub fn update_event(
+        &self,
+        uid: &str,
+        occurrence_date: DateTime<Utc>,
+        draft: &EventDraft,
+    ) -> Result<EventInfo> {
+        self.check_imported(&draft.calendar_id)?;
+        update_file_event(uid, occurrence_date, draft)
+    }
+
+    pub fn delete_event(&self, path: &str, uid: &str) -> Result<()> {
+        self.check_imported(path)?;
+        delete_file_event(path, uid)
+    }
+
+    // Only imported files are read or written through calendar ids, not
+    // any .ics file the frontend names
+    fn check_imported(&self, path: &str) -> Result<()> {
+        let store = self.store.lock().unwrap();
+        if store.paths.iter().any(|known| known == path) {
+            Ok(())
+        } else {
+            Err(Error::not_found(format!("No imported calendar {}", path)))
+        }
+    }
+
+    fn import(&self, path: &str) -> Result<CalendarInfo> {
+        if !is_ics_calendar(path) {
+            return Err(Error::invalid_argument(format!(
+                "Not an absolute path to an .ics file: {}",
+                path
+            )));
+        }
+        let info = IcsCalendar::read(Path::new(path))?.calendar_info(path);
+        let mut store = self.store.lock().unwrap();
+        if !store.paths.iter().any(|known| known == path) {
+            store.paths.push(path.to_string());
+            store.save()?;
+        }
+        Ok(info)
+    }
+
+    fn remove(&self, path: &str) -> Result<()> {
+        let mut store = self.store.lock().unwrap();
+        let count = store.paths.len();
+        store.paths.retain(|known| known != path);
+        if store.paths.len() == count {
+            return Err(Error::not_found(format!("No imported calendar {}", path)));
+        }
+        store.save()
+    }
+}
+
+// Loads the calendars imported before. Called once from the setup hook.
+pub fn start<R: Runtime>(app_handle: &AppHandle<R>) {
+    let file = match app_handle.path().app_data_dir() {
+        Ok(dir) => dir.join(STORE_FILE_NAME),
+        Err(e) => {
+            eprintln!(
+                "Failed to resolve app data directory for imported calendars: {}",
+                e
+            );
+            return;
+        }
+    };
+    let state = app_handle.state::<IcsState>();
+    let mut store = state.store.lock().unwrap();
+    if file.exists() {
+        match IcsStore::load(&file) {
+            Ok(paths) => store.paths = paths,
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    store.file = Some(file);
+}
+
+// Adds the .ics file or saved feed at `path` to the calendars of calendar
+// mode. The file is read where it is, so changes to it show up.
+#[command]
+#[specta::specta]
+pub fn import_ics_calendar(state: State<'_, IcsState>, path: String) -> Result<CalendarInfo> {
+    state.import(&path)
+}
+
+// Stops listing the calendar, leaving the file alone
+#[command]
+#[specta::specta]
+pub fn remove_ics_calendar(state: State<'_, IcsState>, path: String) -> Result<()> {
+    state.remove(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::scratch_dir;
+
+    fn read_fixture(name: &str) -> IcsCalendar {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures/calendars")
+            .join(name);
+        IcsCalendar::read(&path).unwrap()
+    }
+
+    fn team_calendar() -> IcsCalendar {
+        read_fixture("team.ics")
+    }
+
+    fn utc(date: &str) -> DateTime<Utc> {
+        parse_rfc3339(date).unwrap()
+    }
+
+    #[test]
+    fn reads_events_and_time_zones() {
+        let calendar = team_calendar();
+        assert_eq!(calendar.name.as_deref(), Some("Team"));
+        assert_eq!(calendar.calendar_info("team.ics").color, "#1BADF8");
+
+        let local = FixedOffset::east_opt(9 * 3600).unwrap();
+        let events = calendar.events_between(
+            "team",
+            utc("2024-01-01T00:00:00Z"),
+            utc("2025-01-01T00:00:00Z"),
+            &local,
+        );
+        let titles: Vec<&str> = events.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            [
+                "Winter sync",
+                "Quarterly planning, Q3",
+                "Team offsite",
+                "Release"
+            ]
+        );
+
+        // Berlin is on CET in January and on CEST in July
+        assert_eq!(events[0].start_date, "2024-01-15T15:00:00+00:00");
+        assert_eq!(events[0].end_date, "2024-01-15T15:45:00+00:00");
+        let planning = &events[1];
+        assert_eq!(planning.start_date, "2024-07-04T08:00:00+00:00");
+        assert_eq!(planning.end_date, "2024-07-04T09:30:00+00:00");
+        assert_eq!(
+            planning.notes.as_deref(),
+            Some("Agenda:\n1. Roadmap\n2. Hiring. This description is long enough to be folded onto a second line.")
+        );
+        assert_eq!(planning.location.as_deref(), Some("Room 4; Berlin office"));
+        assert_eq!(
+            planning.url.as_deref(),
+            Some("https://example.com/meetings/q3")
+        );
+        let participants: Vec<_> = planning
+            .participants
+            .iter()
+            .map(|participant| participant.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(participants, ["Lovelace, Ada", "grace@example.com"]);
+
+        // All-day events span local days
+        let offsite = &events[2];
+        assert!(offsite.is_all_day);
+        assert_eq!(offsite.start_date, "2024-07-09T15:00:00+00:00");
+        assert_eq!(offsite.end_date, "2024-07-11T15:00:00+00:00");
+        assert_eq!(events[3].start_date, events[3].end_date);
+
+        let july = calendar.events_between(
+            "team",
+            utc("2024-07-05T00:00:00Z"),
+            utc("2024-08-01T00:00:00Z"),
+            &local,
+        );
+        assert_eq!(july.len(), 1);
+        assert_eq!(july[0].identifier, "offsite@example.com");
+    }
+
+    #[test]
+    fn round_trips_exported_events() {
+        let calendar = team_calendar();
+        let range = (utc("2024-01-01T00:00:00Z"), utc("2025-01-01T00:00:00Z"));
+        let events = calendar.events_between("team", range.0, range.1, &Utc);
+
+        let exported = write_calendar(&events, Some("Team, exported"), range.0, &Utc).unwrap();
+        assert!(exported
+            .lines()
+            .all(|line| line.len() <= MAX_LINE_LENGTH + 1));
+        assert!(exported.contains("DTSTART;VALUE=DATE:20240710\r\n"));
+        assert!(exported.contains("ATTENDEE:mailto:grace@example.com\r\n"));
+
+        let reread = IcsCalendar::parse(&exported).unwrap();
+        assert_eq!(reread.name.as_deref(), Some("Team, exported"));
+        let reread_events = reread.events_between("team", range.0, range.1, &Utc);
+        for (original, reread) in events.iter().zip(&reread_events) {
+            assert_eq!(original.identifier, reread.identifier);
+            assert_eq!(original.title, reread.title);
+            assert_eq!(original.notes, reread.notes);
+            assert_eq!(original.location, reread.location);
+            assert_eq!(original.start_date, reread.start_date);
+            assert_eq!(original.end_date, reread.end_date);
+            assert_eq!(original.is_all_day, reread.is_all_day);
+            assert_eq!(original.participants.len(), reread.participants.len());
+        }
+        assert_eq!(events.len(), reread_events.len());
+    }
+
+    #[test]
+    fn expands_recurring_events() {
+        let calendar = read_fixture("recurring.ics");
+        let events = calendar.events_between(
+            "recurring",
+            utc("2024-02-01T00:00:00Z"),
+            utc("2024-05-01T00:00:00Z"),
+            &Utc,
+        );
+        let occurrences: Vec<(&str, &str)> = events
+            .iter()
+            .map(|event| (event.title.as_str(), event.start_date.as_str()))
+            .collect();
+        assert_eq!(
+            occurrences,
+            [
+                ("Leap day birthday", "2024-02-29T00:00:00+00:00"),
+                ("Review", "2024-03-01T15:00:00+00:00"),
+                // 09:30 in Berlin, before and after the switch to summer time;
+                // the 27th is excluded and the 3rd moved
+                ("Standup", "2024-03-25T08:30:00+00:00"),
+                ("Review", "2024-03-29T15:00:00+00:00"),
+                ("Standup", "2024-04-01T07:30:00+00:00"),
+                ("Standup (moved)", "2024-04-03T12:00:00+00:00"),
+                ("Standup", "2024-04-08T07:30:00+00:00"),
+                ("Standup", "2024-04-10T07:30:00+00:00"),
+                ("Review", "2024-04-15T15:00:00+00:00"),
+                ("Review", "2024-04-26T15:00:00+00:00"),
+            ]
+        );
+        assert_eq!(events[0].end_date, "2024-03-01T00:00:00+00:00");
+        assert_eq!(events[1].end_date, "2024-03-01T16:00:00+00:00");
+        assert_eq!(events[4].end_date, "2024-04-01T07:45:00+00:00");
+        assert!(events
+            .iter()
+            .filter(|event| event.title.starts_with("Standup"))
+            .all(|event| event.identifier == "standup@example.com"));
+
+        // Occurrences that started before the range but are still going on
+        let during = calendar.events_between(
+            "recurring",
+            utc("2024-03-25T08:40:00Z"),
+            utc("2024-03-25T09:00:00Z"),
+            &Utc,
+        );
+        assert_eq!(during.len(), 1);
+        assert_eq!(during[0].start_date, "2024-03-25T08:30:00+00:00");
+    }
+
+    fn draft(title: &str, start: &str, end: &str, is_all_day: bool) -> EventDraft {
+        EventDraft {
+            calendar_id: "recurring".to_string(),
+            title: title.to_string(),
+            start_date: start.to_string(),
+            end_date: end.to_string(),
+            is_all_day,
+            location: Some("Room 4".to_string()),
+            url: None,
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn edits_events_in_place() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/calendars/recurring.ics");
+        let content = fs::read_to_string(path).unwrap();
+        let now = utc("2024-06-01T12:00:00Z");
+
+        // From 09:30 to 10:00 Berlin time: the exceptions and the end of the
+        // series move along
+        let standup = draft(
+            "Daily standup",
+            "2024-03-25T09:00:00Z",
+            "2024-03-25T09:15:00Z",
+            false,
+        );
+        let first = utc("2024-03-25T08:30:00Z");
+        let content =
+            update_event(&content, "standup@example.com", first, &standup, now, &Utc).unwrap();
+        assert!(content.contains("\r\nDTSTART;TZID=Europe/Berlin:20240325T100000\r\n"));
+        assert!(content.contains("\r\nEXDATE;TZID=Europe/Berlin:20240327T100000\r\n"));
+        assert!(content.contains("\r\nRRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20240410T080000Z\r\n"));
+        assert!(content.contains("\r\nSEQUENCE:1\r\n"));
+
+        let content = remove_event(&content, "review@example.com").unwrap();
+        let offsite = draft(
+            "Offsite",
+            "2024-04-12T00:00:00Z",
+            "2024-04-13T00:00:00Z",
+            true,
+        );
+        let content = add_event(Some(&content), "offsite@grinta", &offsite, now, &Utc).unwrap();
+
+        let calendar = IcsCalendar::parse(&content).unwrap();
+        assert_eq!(calendar.name.as_deref(), Some("Recurring"));
+        let events = calendar.events_between(
+            "recurring",
+            utc("2024-03-20T00:00:00Z"),
+            utc("2024-04-20T00:00:00Z"),
+            &Utc,
+        );
+        let occurrences: Vec<(&str, &str)> = events
+            .iter()
+            .map(|event| (event.title.as_str(), event.start_date.as_str()))
+            .collect();
+        assert_eq!(
+            occurrences,
+            [
+                ("Daily standup", "2024-03-25T09:00:00+00:00"),
+                ("Daily standup", "2024-04-01T08:00:00+00:00"),
+                ("Standup (moved)", "2024-04-03T12:00:00+00:00"),
+                ("Daily standup", "2024-04-08T08:00:00+00:00"),
+                ("Daily standup", "2024-04-10T08:00:00+00:00"),
+                ("Offsite", "2024-04-12T00:00:00+00:00"),
+            ]
+        );
+        assert_eq!(events[0].location.as_deref(), Some("Room 4"));
+        assert_eq!(events[5].end_date, "2024-04-13T00:00:00+00:00");
+        assert!(events[5].is_all_day);
+
+        let error =
+            update_event(&content, "review@example.com", first, &standup, now, &Utc).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::NotFound);
+        let untitled = draft(" ", "2024-04-12T00:00:00Z", "2024-04-13T00:00:00Z", true);
+        let error = add_event(None, "untitled@grinta", &untitled, now, &Utc).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn edits_recurring_events_from_any_occurrence() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/calendars/recurring.ics");
+        let content = fs::read_to_string(path).unwrap();
+        let now = utc("2024-06-01T12:00:00Z");
+
+        // The occurrence of April 1st at 09:30 summer time, moved to 10:00:
+        // the series moves along instead of starting on April 1st
+        let standup = draft(
+            "Standup",
+            "2024-04-01T08:00:00Z",
+            "2024-04-01T08:15:00Z",
+            false,
+        );
+        let occurrence = utc("2024-04-01T07:30:00Z");
+        let content = update_event(
+            &content,
+            "standup@example.com",
+            occurrence,
+            &standup,
+            now,
+            &Utc,
+        )
+        .unwrap();
+        assert!(content.contains("\r\nDTSTART;TZID=Europe/Berlin:20240325T100000\r\n"));
+        assert!(content.contains("\r\nDTEND;TZID=Europe/Berlin:20240325T101500\r\n"));
+        assert!(content.contains("\r\nEXDATE;TZID=Europe/Berlin:20240327T100000\r\n"));
+
+        let birthday = draft(
+            "Leap day birthday",
+            "2024-03-01T00:00:00Z",
+            "2024-03-02T00:00:00Z",
+            true,
+        );
+        let occurrence = utc("2024-02-29T00:00:00Z");
+        let content = update_event(
+            &content,
+            "birthday@example.com",
+            occurrence,
+            &birthday,
+            now,
+            &Utc,
+        )
+        .unwrap();
+        assert!(content.contains("\r\nDTSTART;VALUE=DATE:20200301\r\n"));
+        assert!(content.contains("\r\nDTEND;VALUE=DATE:20200302\r\n"));
+    }
+
+    #[test]
+    fn rejects_unbalanced_components() {
+        let error =
+            IcsCalendar::parse("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nEND:VCALENDAR\r\n").unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::InvalidArgument);
+        assert!(IcsCalendar::parse("hello").is_err());
+    }
+
+    #[test]
+    fn survives_huge_durations() {
+        assert_eq!(
+            parse_duration("-P1W2DT3H4M5S"),
+            Some(-(Duration::days(9) + Duration::seconds(3 * 3600 + 4 * 60 + 5)))
+        );
+        assert_eq!(parse_duration("P99999999999999999D"), None);
+        assert_eq!(parse_duration("P9999999999999999W"), None);
+
+        // Too long to end before the end of time, so left out
+        let calendar = IcsCalendar::parse(
+            "BEGIN:VCALENDAR\r\n\
+             BEGIN:VEVENT\r\nUID:huge\r\nSUMMARY:Huge\r\n\
+             DTSTART:20240410T120000Z\r\nDURATION:P99999999999999999D\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:long\r\nSUMMARY:Long\r\n\
+             DTSTART:20240410T120000Z\r\nDURATION:P100000000D\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:lunch\r\nSUMMARY:Lunch\r\n\
+             DTSTART:20240410T120000Z\r\nDURATION:PT1H\r\nEND:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+        let events = calendar.events_between(
+            "huge",
+            utc("2024-04-10T00:00:00Z"),
+            utc("2024-04-11T00:00:00Z"),
+            &Utc,
+        );
+        let titles: Vec<&str> = events.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, ["Huge", "Lunch"]);
+        assert_eq!(events[0].end_date, events[0].start_date);
+    }
+
+    #[test]
+    fn lists_imported_calendars() {
+        let dir = scratch_dir("ics-import", &[]);
+        let path = dir.join("team.ics");
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/calendars/team.ics"),
+            &path,
+        )
+        .unwrap();
+        let path = path.to_string_lossy().into_owned();
+        let state = IcsState::new();
+        state.store.lock().unwrap().file = Some(dir.join(STORE_FILE_NAME));
+
+        let info = state.import(&path).unwrap();
+        state.import(&path).unwrap();
+        assert_eq!(info.identifier, path);
+        let calendars = state.calendars();
+        assert_eq!(calendars.len(), 1);
+        assert_eq!(calendars[0].title, info.title);
+        assert_eq!(
+            IcsStore::load(&dir.join(STORE_FILE_NAME)).unwrap(),
+            vec![path.clone()]
+        );
+
+        // Files that went away are skipped, but stay imported
+        fs::remove_file(&path).unwrap();
+        assert!(state.calendars().is_empty());
+        assert!(state.has_calendars());
+
+        state.remove(&path).unwrap();
+        assert!(!state.has_calendars());
+        let error = state.remove(&path).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::NotFound);
+        let error = state.import("relative/team.ics").unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::InvalidArgument);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn lists_created_file_events() {
+        let empty = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nEND:VCALENDAR\r\n";
+        let dir = scratch_dir(
+            "ics-events",
+            &[("personal.ics", empty), ("missing.ics", empty)],
+        );
+        let path = dir.join("personal.ics").to_string_lossy().into_owned();
+        let missing = dir.join("missing.ics").to_string_lossy().into_owned();
+        let state = IcsState::new();
+        state.import(&path).unwrap();
+        state.import(&missing).unwrap();
+        fs::remove_file(&missing).unwrap();
+
+        let mut lunch = draft(
+            "Lunch",
+            "2024-04-10T12:00:00Z",
+            "2024-04-10T13:00:00Z",
+            false,
+        );
+        lunch.calendar_id = path.clone();
+        let created = state.create_event(&lunch).unwrap();
+
+        let paths = [path.clone(), missing];
+        let events = state
+            .events(&paths, "2024-04-10T00:00:00Z", "2024-04-11T00:00:00Z")
+            .unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].identifier, created.identifier);
+        assert_eq!(events[0].calendar_id, path);
+        assert_eq!(events[0].title, "Lunch");
+        assert_eq!(events[0].location.as_deref(), Some("Room 4"));
+        let later = state
+            .events(&paths, "2024-04-11T00:00:00Z", "2024-04-12T00:00:00Z")
+            .unwrap();
+        assert!(later.is_empty());
+
+        // Other .ics files can't be read or written through calendar ids
+        let other = dir.join("other.ics").to_string_lossy().into_owned();
+        lunch.calendar_id = other.clone();
+        let error = state.create_event(&lunch).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::NotFound);
+        assert!(!Path::new(&other).exists());
+        let error = state
+            .events(
+                &[other.clone()],
+                "2024-04-10T00:00:00Z",
+                "2024-04-11T00:00:00Z",
+            )
+            .unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::NotFound);
+        let error = state.delete_event(&other, &created.identifier).unwrap_err();
+        assert_eq!(error.code, crate::error::ErrorCode::NotFound);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,42 +1,57 @@
-use cocoa::base::{id, YES};
-use objc::{class, msg_send, sel, sel_impl};
+use platform::{NativePlatform, PanelPlatform};
 use state::CalendarState;
-use tauri::{Listener as _, Manager as _};
-use tauri_nspanel::ManagerExt as _;
 use tauri_plugin_autostart::MacosLauncher;
 use tauri_plugin_deep_link::DeepLinkExt;
-use objc::runtime::Object;
-use objc_id::Id;
-use window::WebviewWindowExt as _;
+mod app_discovery;
+mod bindings;
+mod bundle_localization;
 mod command;
+mod error;
+mod icns;
 mod icns_utils;
+mod icon_cache;
+mod icon_protocol;
+mod icon_render;
 mod spotlight_utils;
 mod theme_utils;
-mod window;
 mod toggle_visibility;
 mod workspace_utils;
 mod favicon_utils;
 mod keyring_utils;
 mod calendar_utils;
+mod caldav;
+mod ics;
+mod recurrence;
+mod file_index;
+mod content_index;
+mod text_extract;
+mod open_with;
+mod platform;
+mod search_query;
+mod search_ranking;
+#[cfg(test)]
+mod test_utils;
 pub mod state;
-use std::sync::Mutex;
 
 pub const MAIN_WINDOW_LABEL: &str = "main";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let store_class = class!(EKEventStore);
-    let store_ptr: *mut Object = unsafe { msg_send![store_class, new] };
-    let event_store_id = unsafe { Id::from_retained_ptr(store_ptr) };
-
     // Create the state
-    let calendar_state = CalendarState {
-        event_store: Mutex::new(event_store_id),
-    };
+    let calendar_state = CalendarState::new();
+
+    let commands = bindings::builder();
+    #[cfg(debug_assertions)]
+    if let Err(e) = commands.export(bindings::typescript(), bindings::BINDINGS_PATH) {
+        eprintln!("Failed to export TypeScript bindings: {}", e);
+    }
+
+    let builder = tauri::Builder::default();
+
+    #[cfg(target_os = "macos")]
+    let builder = builder.plugin(tauri_nspanel::init());
 
-    tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![command::show, command::hide])
-        .plugin(tauri_nspanel::init())
+    builder
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_dialog::init())
@@ -55,92 +70,38 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_macos_permissions::init())
+        .register_asynchronous_uri_scheme_protocol(icon_protocol::SCHEME, icon_protocol::handle)
         .setup(|app| {
-            #[cfg(target_os = "macos")]
-            {
-                // Check if we're running in development mode
-                let is_dev = cfg!(debug_assertions) || std::env::var("NODE_ENV").unwrap_or_default() == "development";
-                if !is_dev {
-                    match initialize_master_key() {
-                        Ok(_) => {
-                            // Master key initialized successfully
-                        },
-                        Err(e) => eprintln!("Failed to initialize master key: {}", e),
-                    }
+            // Check if we're running in development mode
+            let is_dev = cfg!(debug_assertions) || std::env::var("NODE_ENV").unwrap_or_default() == "development";
+            if !is_dev {
+                match initialize_master_key() {
+                    Ok(_) => {
+                        // Master key initialized successfully
+                    },
+                    Err(e) => eprintln!("Failed to initialize master key: {}", e),
                 }
+            }
 
-                app.set_activation_policy(tauri::ActivationPolicy::Accessory);
-
-                let handle = app.app_handle();
-
-                let window = handle.get_webview_window(MAIN_WINDOW_LABEL).unwrap();
-
-
-                // Convert the window to a spotlight panel
-                let panel = window.to_spotlight_panel()?;
-
-                let _ = window.with_webview(|webview| {
-                    let inner = webview.inner();
-                    unsafe {
-                        let _: () = msg_send![inner as id, setInspectable: YES];
-                    }
-                });
-
-                unsafe {
-                    let panel = app.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
-                    let view = panel.content_view();
-
-                    // Create a block to execute our UI updates
-                    use block2::ConcreteBlock;
-
-                    let update_block = ConcreteBlock::new(move || -> () {
-                        let layer: id = msg_send![view as id, layer];
-                        let _: () = msg_send![layer, setCornerRadius: 20.0];
-                        let _: () = msg_send![layer, setMasksToBounds: YES];
-                        // Force layout update - using NSView methods
-                        let _: () = msg_send![view as id, setNeedsDisplay: YES];
-
-                        // Update the layer
-                        let _: () = msg_send![layer, setNeedsDisplay];
-                    });
-
-                    let update_block = update_block.copy();
+            NativePlatform::setup(app)?;
 
-                    // Get the main dispatch queue and schedule the block
-                    let main_queue: id = msg_send![class!(NSOperationQueue), mainQueue];
-                    let _: () = msg_send![main_queue, addOperationWithBlock:update_block];
-                }
+            icon_cache::start(app.handle());
+            file_index::start(app.handle().clone());
+            content_index::start(app.handle().clone());
+            caldav::start(app.handle());
+            ics::start(app.handle());
 
-                handle.listen(
-                    format!("{}_panel_did_resign_key", MAIN_WINDOW_LABEL),
-                    move |_| {
-                        panel.order_out(None);
-                    },
-                );
-            }
             Ok(())
         })
         .manage(spotlight_utils::SpotlightState::new())
         .manage(workspace_utils::WorkspaceState::new())
         .manage(calendar_state)
-        .invoke_handler(tauri::generate_handler![
-            theme_utils::set_vibrancy,
-            theme_utils::set_appearance,
-            icns_utils::load_app_info,
-            icns_utils::load_extension_icons,
-            spotlight_utils::search_spotlight_apps,
-            toggle_visibility::toggle_visibility,
-            workspace_utils::activate_application_by_name,
-            workspace_utils::get_frontmost_application_name,
-            favicon_utils::fetch_favicon,
-            keyring_utils::set_secret,
-            keyring_utils::get_secret,
-            keyring_utils::delete_secret,
-            calendar_utils::get_calendar_authorization_status,
-            calendar_utils::request_calendar_access,
-            calendar_utils::get_calendars,
-            calendar_utils::get_calendar_events,
-        ])
+        .manage(caldav::CalDavState::new())
+        .manage(ics::IcsState::new())
+        .manage(file_index::FileIndexState::new())
+        .manage(content_index::ContentIndexState::new())
+        .manage(icon_cache::IconCacheState::new())
+        .invoke_handler(commands.invoke_handler())
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -0,0 +1,318 @@
+// src-tauri/src/open_with.rs
+//
+// "Open with…" for files: the applications that can open a file, which of
+// them opens it by default, and opening the file with a chosen one.
+// Candidates are the installed apps whose declared document types match the
+// file (CFBundleDocumentTypes on macOS, `MimeType=` of .desktop files on
+// Linux); the default and user added or removed associations come from the
+// platform (LaunchServices, mimeapps.list).
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::command;
+
+use crate::error::{Error, Result};
+use crate::icns_utils::{DocumentType, InstalledApp};
+use crate::platform::{NativePlatform, OpenWithPlatform};
+
+// Types every file conforms to. Apps claiming them (archivers, hex editors)
+// are listed after the ones made for the file type.
+const GENERIC_TYPES: [&str; 5] = [
+    "public.data",
+    "public.item",
+    "public.content",
+    "application/octet-stream",
+    "*/*",
+];
+
+// A file path, or just an extension when asking about a kind of file
+pub struct FileTarget {
+    pub path: Option<PathBuf>,
+    // Lowercase, without the dot
+    pub extension: Option<String>,
+}
+
+impl FileTarget {
+    // Absolute paths are files, anything else an extension ("pdf" or ".pdf")
+    pub fn parse(file: &str) -> Self {
+        if file.contains('/') {
+            let path = PathBuf::from(file);
+            let extension = path
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_lowercase);
+            return Self {
+                path: Some(path),
+                extension,
+            };
+        }
+
+        let extension = file.trim_start_matches('.').to_lowercase();
+        Self {
+            path: None,
+            extension: (!extension.is_empty()).then_some(extension),
+        }
+    }
+}
+
+// Associations of a file type with apps, by bundle ID (desktop file ID on Linux)
+#[derive(Default, Debug)]
+pub struct Associations {
+    pub default: Option<String>,
+    // Apps the user chose for the type without them declaring it
+    pub added: Vec<String>,
+    // Apps the user took off the list
+    pub removed: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenWithApp {
+    pub name: String,
+    pub localized_name: String,
+    // Bundle or .desktop file, as `list_installed_apps` reports it; also
+    // what `open_file_with` and the grinta-icon `app` route take
+    pub path: String,
+    pub bundle_id: Option<String>,
+    // Editor or Viewer, from the best matching document type
+    pub role: Option<String>,
+    pub is_default: bool,
+}
+
+// How well an app's document type matches the file, better compares greater
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TypeMatch {
+    Generic,
+    Extension,
+    ContentType,
+    Associated,
+}
+
+// Apps able to open the file: the default one first, then by how specific
+// their document types are and by name. `is_file_type` tells whether the file
+// is of a content type or MIME type, including the types its own conforms to.
+pub fn applications_for_file(
+    apps: Vec<InstalledApp>,
+    extension: Option<&str>,
+    is_file_type: &dyn Fn(&str) -> bool,
+    associations: &Associations,
+) -> Vec<OpenWithApp> {
+    let mut result: Vec<(TypeMatch, OpenWithApp)> = Vec::new();
+
+    for app in apps {
+        let id = app.metadata.bundle_id.as_deref();
+        let listed_in =
+            |ids: &[String]| id.is_some_and(|id| ids.iter().any(|other| same_id(id, other)));
+        let is_default = listed_in(associations.default.as_slice());
+        if !is_default && listed_in(&associations.removed) {
+            continue;
+        }
+        // The same app can be installed twice, e.g. in /Applications and ~/Applications
+        if result
+            .iter()
+            .any(|(_, other)| listed_in(other.bundle_id.as_slice()))
+        {
+            continue;
+        }
+
+        let best = app
+            .metadata
+            .document_types
+            .iter()
+            .filter_map(|document_type| {
+                let type_match = document_type_match(document_type, extension, is_file_type)?;
+                Some((type_match, document_type.role.clone()))
+            })
+            .max_by_key(|(type_match, _)| *type_match);
+        let associated = is_default || listed_in(&associations.added);
+        let (type_match, role) = match best {
+            Some((_, role)) if associated => (TypeMatch::Associated, role),
+            None if associated => (TypeMatch::Associated, None),
+            Some(best) => best,
+            None => continue,
+        };
+
+        result.push((
+            type_match,
+            OpenWithApp {
+                name: app.name,
+                localized_name: app.localized_name,
+                path: app.path,
+                bundle_id: app.metadata.bundle_id,
+                role,
+                is_default,
+            },
+        ));
+    }
+
+    result.sort_by(|(a_match, a), (b_match, b)| {
+        b.is_default
+            .cmp(&a.is_default)
+            .then(b_match.cmp(a_match))
+            .then_with(|| {
+                a.localized_name
+                    .to_lowercase()
+                    .cmp(&b.localized_name.to_lowercase())
+            })
+    });
+    result.into_iter().map(|(_, app)| app).collect()
+}
+
+// LaunchServices reports bundle IDs in lowercase
+fn same_id(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+fn document_type_match(
+    document_type: &DocumentType,
+    extension: Option<&str>,
+    is_file_type: &dyn Fn(&str) -> bool,
+) -> Option<TypeMatch> {
+    // Declared only to be able to show the icon of the type
+    if document_type.role.as_deref() == Some("None") {
+        return None;
+    }
+
+    let types = document_type
+        .content_types
+        .iter()
+        .chain(&document_type.mime_types);
+    let type_matches = types.filter_map(|content_type| {
+        if GENERIC_TYPES.contains(&content_type.as_str()) {
+            Some(TypeMatch::Generic)
+        } else {
+            is_file_type(content_type).then_some(TypeMatch::ContentType)
+        }
+    });
+    let extension_matches = document_type.extensions.iter().filter_map(|declared| {
+        if declared == "*" {
+            Some(TypeMatch::Generic)
+        } else {
+            extension
+                .is_some_and(|extension| declared.eq_ignore_ascii_case(extension))
+                .then_some(TypeMatch::Extension)
+        }
+    });
+
+    type_matches.chain(extension_matches).max()
+}
+
+#[command]
+#[specta::specta]
+pub async fn get_open_with_apps(file: String) -> Result<Vec<OpenWithApp>> {
+    let target = FileTarget::parse(&file);
+    tauri::async_runtime::spawn_blocking(move || NativePlatform::applications_for_file(&target))
+        .await?
+}
+
+#[command]
+#[specta::specta]
+pub async fn open_file_with(file: String, app_path: String) -> Result<()> {
+    let path = Path::new(&file);
+    if !path.exists() {
+        return Err(Error::not_found(format!("File not found: {}", file)));
+    }
+    NativePlatform::open_with(path, &app_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icns_utils::{app_bundle_metadata, AppLaunchInfo, AppMetadata, AppSource};
+
+    fn app(name: &str, metadata: AppMetadata) -> InstalledApp {
+        InstalledApp {
+            base64_image: String::new(),
+            localized_name: name.to_string(),
+            name: name.to_string(),
+            id: name.to_string(),
+            path: format!("/Applications/{}.app", name),
+            suite: None,
+            launch: AppLaunchInfo {
+                exec: None,
+                icon: None,
+                keywords: Vec::new(),
+                terminal: false,
+                source: AppSource::Bundle,
+            },
+            metadata,
+        }
+    }
+
+    fn apps() -> Vec<InstalledApp> {
+        let editor = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/bundles/Editor.app");
+        let archiver = AppMetadata {
+            bundle_id: Some("archiver".to_string()),
+            document_types: vec![DocumentType {
+                role: Some("Viewer".to_string()),
+                content_types: vec!["public.data".to_string()],
+                ..DocumentType::default()
+            }],
+            ..AppMetadata::default()
+        };
+        let icons_only = AppMetadata {
+            bundle_id: Some("icons-only".to_string()),
+            document_types: vec![DocumentType {
+                role: Some("None".to_string()),
+                extensions: vec!["txt".to_string()],
+                ..DocumentType::default()
+            }],
+            ..AppMetadata::default()
+        };
+        vec![
+            app("Archiver", archiver),
+            app("Icons Only", icons_only),
+            app("Editor", app_bundle_metadata(&editor.to_string_lossy())),
+            app(
+                "Player",
+                AppMetadata {
+                    bundle_id: Some("player".to_string()),
+                    ..AppMetadata::default()
+                },
+            ),
+        ]
+    }
+
+    fn names(apps: &[OpenWithApp]) -> Vec<&str> {
+        apps.iter().map(|app| app.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parses_paths_and_extensions() {
+        let target = FileTarget::parse("/Users/me/Notes.Final.MD");
+        assert_eq!(target.extension.as_deref(), Some("md"));
+        assert!(target.path.is_some());
+        assert_eq!(FileTarget::parse(".PDF").extension.as_deref(), Some("pdf"));
+        assert_eq!(FileTarget::parse("").extension, None);
+    }
+
+    #[test]
+    fn ranks_specific_document_types_first() {
+        let is_text = |content_type: &str| content_type == "public.plain-text";
+        let text = applications_for_file(apps(), Some("txt"), &is_text, &Associations::default());
+
+        assert_eq!(names(&text), ["Editor", "Archiver"]);
+        assert_eq!(text[0].role.as_deref(), Some("Editor"));
+        assert!(text.iter().all(|app| !app.is_default));
+
+        // Extension only match
+        let markdown =
+            applications_for_file(apps(), Some("md"), &|_| false, &Associations::default());
+        assert_eq!(names(&markdown), ["Editor", "Archiver"]);
+        assert_eq!(markdown[0].role.as_deref(), Some("Viewer"));
+    }
+
+    #[test]
+    fn applies_associations() {
+        let associations = Associations {
+            default: Some("player".to_string()),
+            added: Vec::new(),
+            removed: vec!["archiver".to_string()],
+        };
+        let markdown = applications_for_file(apps(), Some("md"), &|_| false, &associations);
+
+        assert_eq!(names(&markdown), ["Player", "Editor"]);
+        assert!(markdown[0].is_default);
+        assert!(!markdown[1].is_default);
+    }
+}
@@ -0,0 +1,700 @@
+// Application discovery through freedesktop .desktop files
+// (https://specifications.freedesktop.org/desktop-entry-spec/latest/)
+
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::{data_dirs, data_home, LinuxPlatform};
+use crate::error::{Error, Result};
+use crate::icns_utils::{AppLaunchInfo, AppMetadata, AppSource, DocumentType, InstalledApp};
+use crate::platform::AppDiscoveryPlatform;
+
+const DESKTOP_ENTRY_GROUP: &str = "[Desktop Entry]";
+const SCHEME_HANDLER_PREFIX: &str = "x-scheme-handler/";
+// Debian alternative for the default terminal, also set up by most other distributions
+const TERMINAL_EMULATOR: &str = "x-terminal-emulator";
+
+impl AppDiscoveryPlatform for LinuxPlatform {
+    fn installed_apps() -> Result<Vec<InstalledApp>> {
+        Ok(listed_apps(&application_dirs(), &locale_candidates(), &current_desktops()))
+    }
+}
+
+// Applications of the .desktop files in `directories`, sorted by name
+fn listed_apps(
+    directories: &[(PathBuf, AppSource)],
+    locales: &[String],
+    current_desktops: &[String],
+) -> Vec<InstalledApp> {
+    // Desktop file IDs seen so far. Directories are scanned by precedence,
+    // so the first file with an ID wins, even a hidden one.
+    let mut seen_ids = HashSet::new();
+    let mut apps = Vec::new();
+
+    for (directory, source) in directories {
+        for (id, path) in desktop_files(directory) {
+            if !seen_ids.insert(id.clone()) {
+                continue;
+            }
+
+            let Some(entry) = DesktopEntry::read(&path) else {
+                continue;
+            };
+            if entry.is_listed(current_desktops) {
+                apps.push(entry.into_installed_app(id, &path, *source, locales));
+            }
+        }
+    }
+
+    apps.sort_by_key(|app| app.localized_name.to_lowercase());
+    apps
+}
+
+// Key/value pairs of the [Desktop Entry] group. Localized keys keep their
+// `[locale]` suffix, e.g. `Name[de]`.
+pub(super) struct DesktopEntry {
+    values: HashMap<String, String>,
+}
+
+impl DesktopEntry {
+    pub(super) fn read(path: &Path) -> Option<Self> {
+        Self::parse(&fs::read_to_string(path).ok()?)
+    }
+
+    fn parse(content: &str) -> Option<Self> {
+        let mut values = HashMap::new();
+        let mut in_entry_group = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                // Actions and other groups follow the main group
+                in_entry_group = line == DESKTOP_ENTRY_GROUP;
+                continue;
+            }
+            if !in_entry_group {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once('=') {
+                values
+                    .entry(key.trim().to_string())
+                    .or_insert_with(|| unescape(value.trim()));
+            }
+        }
+
+        if values.get("Type").map(String::as_str) != Some("Application") {
+            return None;
+        }
+        Some(Self { values })
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    fn get_bool(&self, key: &str) -> bool {
+        self.get(key) == Some("true")
+    }
+
+    // The value for the best matching locale, falling back to the plain key
+    pub(super) fn get_localized(&self, key: &str, locales: &[String]) -> Option<&str> {
+        locales
+            .iter()
+            .find_map(|locale| self.get(&format!("{}[{}]", key, locale)))
+            .or_else(|| self.get(key))
+    }
+
+    fn get_list(&self, key: &str) -> Vec<String> {
+        self.get(key).map(split_list).unwrap_or_default()
+    }
+
+    pub(super) fn icon(&self) -> Option<&str> {
+        self.get("Icon").filter(|icon| !icon.is_empty())
+    }
+
+    // `AppMetadata` of the entry, `id` being its desktop file ID
+    pub(super) fn metadata(&self, id: &str) -> AppMetadata {
+        let (url_schemes, mime_types): (Vec<String>, Vec<String>) = self
+            .get_list("MimeType")
+            .into_iter()
+            .partition(|mime_type| mime_type.starts_with(SCHEME_HANDLER_PREFIX));
+        let url_schemes = url_schemes
+            .into_iter()
+            .map(|mime_type| mime_type[SCHEME_HANDLER_PREFIX.len()..].to_string())
+            .collect();
+        // .desktop files don't group MIME types the way Info.plist does
+        let document_types = if mime_types.is_empty() {
+            Vec::new()
+        } else {
+            vec![DocumentType {
+                mime_types,
+                ..DocumentType::default()
+            }]
+        };
+
+        AppMetadata {
+            bundle_id: Some(id.to_string()),
+            version: self.get("X-AppImage-Version").map(str::to_string),
+            categories: self.get_list("Categories"),
+            url_schemes,
+            document_types,
+            ui_element: self.get_bool("NoDisplay"),
+            background_only: false,
+        }
+    }
+
+    // Command line that opens `file` with the application, from Exec with its
+    // field codes filled in. Terminal applications run in the default terminal.
+    pub(super) fn exec_command(&self, file: &Path, desktop_path: &Path) -> Result<Vec<String>> {
+        let exec = self
+            .get("Exec")
+            .ok_or_else(|| Error::invalid_argument("The application has no command"))?;
+        let file = file.to_string_lossy();
+        let name = self.get("Name").unwrap_or_default();
+        let desktop_path = desktop_path.to_string_lossy();
+
+        let mut command = Vec::new();
+        let mut has_file = false;
+        for arg in split_exec(exec) {
+            if arg == "%i" {
+                if let Some(icon) = self.icon() {
+                    command.push("--icon".to_string());
+                    command.push(icon.to_string());
+                }
+                continue;
+            }
+
+            let mut expanded = String::with_capacity(arg.len());
+            let mut chars = arg.chars();
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    expanded.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    // Most applications take paths for URLs as well
+                    Some('f' | 'F' | 'u' | 'U') => {
+                        expanded.push_str(&file);
+                        has_file = true;
+                    }
+                    Some('c') => expanded.push_str(name),
+                    Some('k') => expanded.push_str(&desktop_path),
+                    Some('%') => expanded.push('%'),
+                    // Deprecated codes expand to nothing
+                    _ => {}
+                }
+            }
+            if !expanded.is_empty() || !arg.starts_with('%') {
+                command.push(expanded);
+            }
+        }
+
+        if command.is_empty() {
+            return Err(Error::invalid_argument("The application has no command"));
+        }
+        // Applications without a file field code don't expect files, but
+        // passing it is what users asking to open the file want
+        if !has_file {
+            command.push(file.into_owned());
+        }
+        if self.get_bool("Terminal") {
+            if !find_executable(TERMINAL_EMULATOR) {
+                return Err(Error::not_found("No terminal emulator found"));
+            }
+            command.splice(0..0, [TERMINAL_EMULATOR.to_string(), "-e".to_string()]);
+        }
+        Ok(command)
+    }
+
+    // Whether the entry belongs in an application list on this desktop
+    fn is_listed(&self, current_desktops: &[String]) -> bool {
+        if self.get_bool("Hidden") || self.get_bool("NoDisplay") {
+            return false;
+        }
+
+        let only_show_in = self.get_list("OnlyShowIn");
+        if !only_show_in.is_empty() && !only_show_in.iter().any(|desktop| current_desktops.contains(desktop)) {
+            return false;
+        }
+
+        let not_show_in = self.get_list("NotShowIn");
+        if not_show_in.iter().any(|desktop| current_desktops.contains(desktop)) {
+            return false;
+        }
+
+        // TryExec names a binary that has to be installed
+        match self.get("TryExec") {
+            Some(try_exec) => find_executable(try_exec),
+            None => true,
+        }
+    }
+
+    fn into_installed_app(self, id: String, path: &Path, source: AppSource, locales: &[String]) -> InstalledApp {
+        let name = self.get("Name").unwrap_or(&id).to_string();
+        let localized_name = self.get_localized("Name", locales).unwrap_or(&name).to_string();
+        let keywords = self
+            .get_localized("Keywords", locales)
+            .map(split_list)
+            .unwrap_or_default();
+
+        InstalledApp {
+            base64_image: String::new(),
+            localized_name,
+            metadata: self.metadata(&id),
+            name,
+            id,
+            path: path.to_string_lossy().into_owned(),
+            suite: None,
+            launch: AppLaunchInfo {
+                exec: self.get("Exec").map(strip_field_codes),
+                icon: self.icon().map(str::to_string),
+                keywords,
+                terminal: self.get_bool("Terminal"),
+                source,
+            },
+        }
+    }
+}
+
+// Directories holding .desktop files, highest precedence first
+fn application_dirs() -> Vec<(PathBuf, AppSource)> {
+    application_dirs_in(data_home(), data_dirs())
+}
+
+fn application_dirs_in(data_home: PathBuf, data_dirs: Vec<PathBuf>) -> Vec<(PathBuf, AppSource)> {
+    let mut candidates = vec![data_home.clone()];
+    // Flatpak and Snap normally add these to XDG_DATA_DIRS through their
+    // profile scripts, which sessions started another way miss
+    candidates.push(data_home.join("flatpak/exports/share"));
+    candidates.extend(data_dirs);
+    candidates.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+    candidates.push(PathBuf::from("/var/lib/snapd/desktop"));
+
+    let mut directories: Vec<(PathBuf, AppSource)> = Vec::new();
+    for data_dir in candidates {
+        let directory = data_dir.join("applications");
+        if !directory.is_dir() || directories.iter().any(|(existing, _)| existing == &directory) {
+            continue;
+        }
+
+        let source = if directory.components().any(|c| c.as_os_str() == "flatpak") {
+            AppSource::Flatpak
+        } else if directory.components().any(|c| c.as_os_str() == "snapd") {
+            AppSource::Snap
+        } else if data_dir == data_home {
+            AppSource::User
+        } else {
+            AppSource::System
+        };
+        directories.push((directory, source));
+    }
+
+    directories
+}
+
+// Desktop file ID of a .desktop file outside of `desktop_files`, e.g. one
+// passed to `load_app_info`
+pub(super) fn desktop_file_id(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let relative = match path.rfind("/applications/") {
+        Some(index) => &path[index + "/applications/".len()..],
+        None => path.rsplit('/').next().unwrap_or_default(),
+    };
+    relative.strip_suffix(".desktop").unwrap_or(relative).replace('/', "-")
+}
+
+// (desktop file ID, path) of every .desktop file below `directory`. The ID is
+// the relative path with `/` replaced by `-`.
+fn desktop_files(directory: &Path) -> Vec<(String, PathBuf)> {
+    let mut files: Vec<(String, PathBuf)> = WalkDir::new(directory)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(directory).ok()?.to_string_lossy().into_owned();
+            let id = relative.strip_suffix(".desktop")?.replace('/', "-");
+            Some((id, entry.into_path()))
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+// `lang_COUNTRY.ENCODING@MODIFIER` of the messages locale, expanded into the
+// lookup order of the spec: lang_COUNTRY@MODIFIER, lang_COUNTRY, lang@MODIFIER, lang
+pub(super) fn locale_candidates() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+
+    expand_locale(&locale)
+}
+
+fn expand_locale(locale: &str) -> Vec<String> {
+    let (locale, modifier) = match locale.split_once('@') {
+        Some((locale, modifier)) => (locale, Some(modifier)),
+        None => (locale, None),
+    };
+    let locale = locale.split('.').next().unwrap_or_default();
+    let (lang, country) = match locale.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (locale, None),
+    };
+
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}@{}", lang, modifier));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+pub(super) fn current_desktops() -> Vec<String> {
+    env::var("XDG_CURRENT_DESKTOP")
+        .map(|desktops| desktops.split(':').map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+// Semicolon separated list, `\;` is a literal semicolon
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(';') => current.push(';'),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            ';' => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+// Escape sequences of string values. `\;` is left for `split_list`.
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+// Arguments of an Exec value. Double quotes keep spaces together, inside them
+// a backslash escapes the next character.
+fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_argument = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                in_argument = true;
+            }
+            '\\' if in_quotes => current.extend(chars.next()),
+            c if c.is_whitespace() && !in_quotes => {
+                if in_argument {
+                    args.push(std::mem::take(&mut current));
+                    in_argument = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_argument = true;
+            }
+        }
+    }
+    if in_argument {
+        args.push(current);
+    }
+
+    args
+}
+
+// Drops the %f, %U, ... placeholders a launcher fills in with files or URLs
+fn strip_field_codes(exec: &str) -> String {
+    let mut result = String::with_capacity(exec.len());
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+        } else if chars.next() == Some('%') {
+            // Deprecated and current field codes alike expand to nothing here
+            result.push('%');
+        }
+    }
+
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn find_executable(name: &str) -> bool {
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return path.is_file();
+    }
+
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(name).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::scratch_dir;
+
+    #[test]
+    fn reads_metadata_from_desktop_entry() {
+        let entry = DesktopEntry::parse(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Editor\n\
+             Categories=Development;TextEditor;\n\
+             MimeType=text/plain;x-scheme-handler/editor;text/markdown;\n\
+             NoDisplay=true\n\
+             X-AppImage-Version=2.4.1\n\
+             [Desktop Action new-window]\n\
+             Name=New Window\n",
+        )
+        .unwrap();
+        let metadata = entry.metadata("org.example.Editor");
+
+        assert_eq!(metadata.bundle_id.as_deref(), Some("org.example.Editor"));
+        assert_eq!(metadata.version.as_deref(), Some("2.4.1"));
+        assert_eq!(metadata.categories, ["Development", "TextEditor"]);
+        assert_eq!(metadata.url_schemes, ["editor"]);
+        assert_eq!(metadata.document_types.len(), 1);
+        assert_eq!(metadata.document_types[0].mime_types, ["text/plain", "text/markdown"]);
+        assert!(metadata.ui_element);
+    }
+
+    #[test]
+    fn fills_in_exec_field_codes() {
+        let entry = DesktopEntry::parse(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Viewer\n\
+             Icon=viewer\n\
+             Exec=\"/opt/My Viewer/viewer\" --title=%c %i --open %U --percent=100%%\n",
+        )
+        .unwrap();
+        let command = entry
+            .exec_command(Path::new("/tmp/a b.pdf"), Path::new("/usr/share/applications/viewer.desktop"))
+            .unwrap();
+
+        assert_eq!(
+            command,
+            [
+                "/opt/My Viewer/viewer",
+                "--title=Viewer",
+                "--icon",
+                "viewer",
+                "--open",
+                "/tmp/a b.pdf",
+                "--percent=100%"
+            ]
+        );
+
+        // Without a field code the file goes last
+        let entry = DesktopEntry::parse("[Desktop Entry]\nType=Application\nExec=viewer --new-window\n").unwrap();
+        let command = entry.exec_command(Path::new("/tmp/a.pdf"), Path::new("viewer.desktop")).unwrap();
+        assert_eq!(command, ["viewer", "--new-window", "/tmp/a.pdf"]);
+    }
+
+    fn entry(lines: &str) -> DesktopEntry {
+        DesktopEntry::parse(&format!("[Desktop Entry]\nType=Application\n{}", lines)).unwrap()
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn falls_back_through_locales() {
+        let entry = entry("Name=Files\nName[de]=Dateien\nName[pt_BR]=Arquivos\nName[sr@latin]=Datoteke\n");
+        let name = |locale: &str| entry.get_localized("Name", &expand_locale(locale)).unwrap().to_string();
+
+        assert_eq!(name("de_AT.UTF-8"), "Dateien");
+        assert_eq!(name("pt_BR.UTF-8"), "Arquivos");
+        assert_eq!(name("pt_PT.UTF-8"), "Files");
+        assert_eq!(name("sr_RS@latin"), "Datoteke");
+        assert_eq!(name("C"), "Files");
+        assert_eq!(name(""), "Files");
+    }
+
+    #[test]
+    fn expands_locales_in_lookup_order() {
+        assert_eq!(expand_locale("sr_RS.UTF-8@latin"), ["sr_RS@latin", "sr_RS", "sr@latin", "sr"]);
+        assert_eq!(expand_locale("de_DE.UTF-8"), ["de_DE", "de"]);
+        assert_eq!(expand_locale("fr"), ["fr"]);
+        assert!(expand_locale("POSIX").is_empty());
+    }
+
+    #[test]
+    fn reads_only_application_entries() {
+        assert!(DesktopEntry::parse("[Desktop Entry]\nType=Link\nURL=https://example.com\n").is_none());
+        assert!(DesktopEntry::parse("Type=Application\n").is_none());
+
+        // Keys of other groups and later duplicates are ignored
+        let entry = DesktopEntry::parse(
+            "# comment\n[Desktop Entry]\nType=Application\nName = Editor \nName=Other\n[Desktop Action new]\nExec=new\n",
+        )
+        .unwrap();
+        assert_eq!(entry.get("Name"), Some("Editor"));
+        assert_eq!(entry.get("Exec"), None);
+    }
+
+    #[test]
+    fn filters_unlisted_entries() {
+        let gnome = strings(&["ubuntu", "GNOME"]);
+        let kde = strings(&["KDE"]);
+
+        assert!(entry("Name=Shown\n").is_listed(&gnome));
+        assert!(!entry("Hidden=true\n").is_listed(&gnome));
+        assert!(!entry("NoDisplay=true\n").is_listed(&gnome));
+        assert!(entry("NoDisplay=false\n").is_listed(&gnome));
+
+        let only_gnome = entry("OnlyShowIn=GNOME;Unity;\n");
+        assert!(only_gnome.is_listed(&gnome));
+        assert!(!only_gnome.is_listed(&kde));
+        assert!(!only_gnome.is_listed(&[]));
+
+        let not_gnome = entry("NotShowIn=GNOME;\n");
+        assert!(!not_gnome.is_listed(&gnome));
+        assert!(not_gnome.is_listed(&kde));
+        assert!(not_gnome.is_listed(&[]));
+
+        let dir = scratch_dir("desktop-try-exec", &[("bin/tool", "")]);
+        let installed = format!("TryExec={}\n", dir.join("bin/tool").display());
+        let missing = format!("TryExec={}\n", dir.join("bin/missing").display());
+        assert!(entry(&installed).is_listed(&gnome));
+        assert!(!entry(&missing).is_listed(&gnome));
+        assert!(!entry("TryExec=grinta-test-missing-binary\n").is_listed(&gnome));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unescapes_values() {
+        let entry = entry("Comment=Tabs\\tand\\slines\\nback\\\\slash \\q\n");
+        assert_eq!(entry.get("Comment"), Some("Tabs\tand lines\nback\\slash \\q"));
+
+        assert_eq!(split_list("One;Two\\;Three;;  Four ;"), ["One", "Two;Three", "Four"]);
+        assert_eq!(split_list(r"a\b;c\"), [r"a\b", r"c\"]);
+        assert!(split_list("").is_empty());
+
+        assert_eq!(
+            split_exec(r#"run "a \"quoted\" arg"  plain "" "back\\slash""#),
+            ["run", r#"a "quoted" arg"#, "plain", "", r"back\slash"]
+        );
+        assert_eq!(strip_field_codes("viewer  %U --done 50%% %k"), "viewer --done 50%");
+    }
+
+    #[test]
+    fn prefers_earlier_data_dirs() {
+        let dir = scratch_dir(
+            "desktop-precedence",
+            &[
+                ("home/applications/editor.desktop", "[Desktop Entry]\nType=Application\nName=Editor (user)\n"),
+                ("home/applications/hidden.desktop", "[Desktop Entry]\nType=Application\nHidden=true\n"),
+                ("local/applications/editor.desktop", "[Desktop Entry]\nType=Application\nName=Editor (local)\n"),
+                ("local/applications/kde/viewer.desktop", "[Desktop Entry]\nType=Application\nName=Viewer\n"),
+                ("usr/applications/hidden.desktop", "[Desktop Entry]\nType=Application\nName=Hidden\n"),
+                ("usr/applications/kde-viewer.desktop", "[Desktop Entry]\nType=Application\nName=Old viewer\n"),
+                ("usr/applications/terminal.desktop", "[Desktop Entry]\nType=Application\nName=Terminal\n"),
+                ("usr/applications/readme.txt", "not an entry"),
+            ],
+        );
+        let directories = application_dirs_in(
+            dir.join("home"),
+            vec![dir.join("local"), dir.join("missing"), dir.join("usr"), dir.join("local")],
+        );
+
+        let expected: Vec<(PathBuf, AppSource)> = vec![
+            (dir.join("home/applications"), AppSource::User),
+            (dir.join("local/applications"), AppSource::System),
+            (dir.join("usr/applications"), AppSource::System),
+        ];
+        assert_eq!(&directories[..3], &expected[..]);
+
+        let apps = listed_apps(&expected, &[], &[]);
+        let summary: Vec<(&str, &str, AppSource)> = apps
+            .iter()
+            .map(|app| (app.id.as_str(), app.localized_name.as_str(), app.launch.source))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("editor", "Editor (user)", AppSource::User),
+                ("terminal", "Terminal", AppSource::System),
+                ("kde-viewer", "Viewer", AppSource::System),
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn derives_desktop_file_ids_from_paths() {
+        assert_eq!(
+            desktop_file_id(Path::new("/usr/share/applications/kde4/okular.desktop")),
+            "kde4-okular"
+        );
+        assert_eq!(desktop_file_id(Path::new("/opt/editor/editor.desktop")), "editor");
+    }
+}
@@ -0,0 +1,54 @@
+// src-tauri/src/platform/linux/calendar.rs
+
+use chrono::{DateTime, Utc};
+
+use crate::calendar_utils::{CalendarAuthorizationStatus, CalendarInfo, EventDraft, EventInfo};
+use crate::error::{Error, Result};
+use crate::platform::CalendarPlatform;
+
+// No system calendar store is available, so access is reported as restricted
+pub struct LinuxCalendar;
+
+impl CalendarPlatform for LinuxCalendar {
+    fn new() -> Self {
+        Self
+    }
+
+    fn authorization_status() -> Result<CalendarAuthorizationStatus> {
+        Ok(CalendarAuthorizationStatus::Restricted)
+    }
+
+    fn request_access(&self) -> Result<CalendarAuthorizationStatus> {
+        Self::authorization_status()
+    }
+
+    fn calendars(&self) -> Result<Vec<CalendarInfo>> {
+        Err(Error::unsupported("Calendars are only available on macOS"))
+    }
+
+    fn events(
+        &self,
+        _calendar_ids: &[String],
+        _start_date_iso: &str,
+        _end_date_iso: &str,
+    ) -> Result<Vec<EventInfo>> {
+        Err(Error::unsupported("Calendars are only available on macOS"))
+    }
+
+    fn create_event(&self, _event: &EventDraft) -> Result<EventInfo> {
+        Err(Error::unsupported("Calendars are only available on macOS"))
+    }
+
+    fn update_event(
+        &self,
+        _event_id: &str,
+        _occurrence_date: DateTime<Utc>,
+        _event: &EventDraft,
+    ) -> Result<EventInfo> {
+        Err(Error::unsupported("Calendars are only available on macOS"))
+    }
+
+    fn delete_event(&self, _event_id: &str) -> Result<()> {
+        Err(Error::unsupported("Calendars are only available on macOS"))
+    }
+}
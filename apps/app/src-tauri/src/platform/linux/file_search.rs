@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use walkdir::WalkDir;
+
+use super::LinuxPlatform;
+use crate::error::{Error, Result};
+use crate::platform::FileSearchPlatform;
+use crate::search_query::FileQueryFilter;
+use crate::spotlight_utils::{
+    content_type_for, is_hidden_name, FileSearchRequest, SearchSession, SpotlightAppInfo,
+};
+
+// Matches are handed to the session once this many piled up, or after the interval
+const BATCH_SIZE: usize = 25;
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+impl FileSearchPlatform for LinuxPlatform {
+    fn search_files(request: FileSearchRequest, session: Arc<SearchSession>) -> Result<()> {
+        let scopes = request.scopes();
+        if scopes.is_empty() {
+            return Err(Error::not_found("Could not resolve any search directory"));
+        }
+
+        let filter = FileQueryFilter::new(&request.query, &request.extensions);
+        let mut seen_paths = HashSet::new();
+        let mut batch: Vec<SpotlightAppInfo> = Vec::new();
+        let mut last_flush = Instant::now();
+
+        'scopes: for scope in scopes {
+            let walker = WalkDir::new(&scope)
+                .follow_links(false)
+                .into_iter()
+                // Never descend into hidden directories
+                .filter_entry(|entry| entry.depth() == 0 || !is_hidden_name(&entry.file_name().to_string_lossy()));
+
+            for entry in walker.filter_map(Result::ok) {
+                if session.should_stop() {
+                    break 'scopes;
+                }
+
+                if batch.len() >= BATCH_SIZE || (!batch.is_empty() && last_flush.elapsed() >= BATCH_INTERVAL) {
+                    session.push_batch(std::mem::take(&mut batch));
+                    last_flush = Instant::now();
+                }
+
+                if entry.depth() == 0 {
+                    continue;
+                }
+
+                let name = entry.file_name().to_string_lossy().into_owned();
+                let is_dir = entry.file_type().is_dir();
+
+                if !filter.matches(entry.path(), &name, is_dir) || !seen_paths.insert(entry.path().to_path_buf()) {
+                    continue;
+                }
+
+                batch.push(SpotlightAppInfo {
+                    path: entry.path().to_string_lossy().into_owned(),
+                    content_type: content_type_for(entry.path(), is_dir),
+                    display_name: name,
+                    score: 0.0,
+                    match_ranges: Vec::new(),
+                });
+            }
+        }
+
+        session.push_batch(batch);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,400 @@
+// Icon lookup following the freedesktop icon theme spec
+// (https://specifications.freedesktop.org/icon-theme-spec/latest/)
+//
+// Resolves an `Icon=` name to a file by walking the current theme, the themes
+// it inherits from and hicolor, then the legacy pixmaps folders. SVG icons are
+// rasterized so every icon reaches the frontend as a PNG.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{config_home, data_dirs, data_home};
+
+const FALLBACK_THEME: &str = "hicolor";
+// Checked in this order within a directory. XPM is not supported.
+const ICON_EXTENSIONS: [&str; 3] = ["png", "svg", "svgz"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum DirectoryType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+// A `[<subdir>]` group of index.theme
+#[derive(Debug)]
+struct ThemeDirectory {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryType,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+}
+
+impl ThemeDirectory {
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.kind {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => self.min_size <= size && size <= self.max_size,
+            DirectoryType::Threshold => self.size.abs_diff(size) <= self.threshold,
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let wanted = size * scale;
+        let (min, max) = match self.kind {
+            DirectoryType::Fixed => (self.size, self.size),
+            DirectoryType::Scalable => (self.min_size, self.max_size),
+            DirectoryType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+
+        let (min, max) = (min * self.scale, max * self.scale);
+        if wanted < min {
+            min - wanted
+        } else {
+            wanted.saturating_sub(max)
+        }
+    }
+}
+
+struct Theme {
+    name: String,
+    parents: Vec<String>,
+    directories: Vec<ThemeDirectory>,
+}
+
+impl Theme {
+    // index.theme is read from the first base directory that has the theme,
+    // its icons may be spread over all of them
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<Self> {
+        let index = base_dirs
+            .iter()
+            .find_map(|base| fs::read_to_string(base.join(name).join("index.theme")).ok())?;
+        Some(Self::parse(name, &index))
+    }
+
+    fn parse(name: &str, index: &str) -> Self {
+        let groups = parse_ini(index);
+        let theme_group = groups.get("Icon Theme");
+        let list = |key: &str| -> Vec<String> {
+            theme_group
+                .and_then(|group| group.get(key))
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut directory_names = list("Directories");
+        directory_names.extend(list("ScaledDirectories"));
+
+        let directories = directory_names
+            .into_iter()
+            .filter_map(|path| {
+                let group = groups.get(&path)?;
+                let number = |key: &str| group.get(key).and_then(|value| value.parse::<u32>().ok());
+
+                let size = number("Size")?;
+                let kind = match group.get("Type").map(String::as_str) {
+                    Some("Fixed") => DirectoryType::Fixed,
+                    Some("Scalable") => DirectoryType::Scalable,
+                    _ => DirectoryType::Threshold,
+                };
+
+                Some(ThemeDirectory {
+                    size,
+                    scale: number("Scale").unwrap_or(1).max(1),
+                    kind,
+                    min_size: number("MinSize").unwrap_or(size),
+                    max_size: number("MaxSize").unwrap_or(size),
+                    threshold: number("Threshold").unwrap_or(2),
+                    path,
+                })
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            parents: list("Inherits"),
+            directories,
+        }
+    }
+}
+
+pub(super) struct IconLookup {
+    base_dirs: Vec<PathBuf>,
+    pixmap_dirs: Vec<PathBuf>,
+    // The current theme and everything it inherits from, in lookup order
+    themes: Vec<Theme>,
+}
+
+impl IconLookup {
+    pub(super) fn new(theme_name: &str, base_dirs: Vec<PathBuf>, pixmap_dirs: Vec<PathBuf>) -> Self {
+        let mut themes = Vec::new();
+        load_theme_chain(theme_name, &base_dirs, &mut themes);
+        // hicolor is searched last even when no theme inherits from it
+        load_theme_chain(FALLBACK_THEME, &base_dirs, &mut themes);
+
+        Self {
+            base_dirs,
+            pixmap_dirs,
+            themes,
+        }
+    }
+
+    // The user's icon theme with the standard search paths
+    pub(super) fn system() -> Self {
+        let data_home = data_home();
+        let data_dirs = data_dirs();
+
+        let home = dirs::home_dir().unwrap_or_default();
+        let mut base_dirs = vec![home.join(".icons"), data_home.join("icons")];
+        base_dirs.extend(data_dirs.iter().map(|dir| dir.join("icons")));
+        // Flatpak apps export their icons next to their .desktop files
+        base_dirs.push(data_home.join("flatpak/exports/share/icons"));
+        base_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/icons"));
+        base_dirs.push(PathBuf::from("/var/lib/snapd/desktop/icons"));
+
+        let mut pixmap_dirs: Vec<PathBuf> = data_dirs.iter().map(|dir| dir.join("pixmaps")).collect();
+        pixmap_dirs.push(PathBuf::from("/usr/share/pixmaps"));
+        pixmap_dirs.dedup();
+
+        Self::new(&current_theme_name(), base_dirs, pixmap_dirs)
+    }
+
+    // Path of the icon best matching `size` (in logical pixels) at `scale`.
+    // `icon` is either a name or an absolute path, as in `Icon=` keys.
+    pub(super) fn find_icon(&self, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let path = Path::new(icon);
+        if path.is_absolute() {
+            return path.is_file().then(|| path.to_path_buf());
+        }
+
+        self.themes
+            .iter()
+            .find_map(|theme| self.find_in_theme(theme, icon, size, scale))
+            .or_else(|| self.find_pixmap(icon))
+    }
+
+    fn find_in_theme(&self, theme: &Theme, icon: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        // An exact size match wins
+        for directory in theme.directories.iter().filter(|d| d.matches_size(size, scale)) {
+            if let Some(path) = self.find_in_directory(theme, directory, icon) {
+                return Some(path);
+            }
+        }
+
+        // Otherwise the closest size, the first directory listed on ties
+        let mut closest: Option<(u32, PathBuf)> = None;
+        for directory in &theme.directories {
+            let distance = directory.size_distance(size, scale);
+            if closest.as_ref().is_some_and(|(best, _)| distance >= *best) {
+                continue;
+            }
+            if let Some(path) = self.find_in_directory(theme, directory, icon) {
+                closest = Some((distance, path));
+            }
+        }
+        closest.map(|(_, path)| path)
+    }
+
+    fn find_in_directory(&self, theme: &Theme, directory: &ThemeDirectory, icon: &str) -> Option<PathBuf> {
+        self.base_dirs.iter().find_map(|base| {
+            let directory = base.join(&theme.name).join(&directory.path);
+            ICON_EXTENSIONS
+                .iter()
+                .map(|extension| directory.join(format!("{}.{}", icon, extension)))
+                .find(|path| path.is_file())
+        })
+    }
+
+    fn find_pixmap(&self, icon: &str) -> Option<PathBuf> {
+        self.pixmap_dirs.iter().find_map(|directory| {
+            ICON_EXTENSIONS
+                .iter()
+                .map(|extension| directory.join(format!("{}.{}", icon, extension)))
+                .find(|path| path.is_file())
+        })
+    }
+}
+
+// Depth first over `Inherits`, each theme once
+fn load_theme_chain(name: &str, base_dirs: &[PathBuf], themes: &mut Vec<Theme>) {
+    if themes.iter().any(|theme| theme.name == name) {
+        return;
+    }
+    let Some(theme) = Theme::load(name, base_dirs) else {
+        return;
+    };
+
+    let parents = theme.parents.clone();
+    themes.push(theme);
+    for parent in parents {
+        load_theme_chain(&parent, base_dirs, themes);
+    }
+}
+
+// The theme GTK is configured with, which most desktops keep in sync with
+// their own setting
+fn current_theme_name() -> String {
+    let config_home = config_home();
+
+    ["gtk-4.0/settings.ini", "gtk-3.0/settings.ini"]
+        .iter()
+        .filter_map(|file| fs::read_to_string(config_home.join(file)).ok())
+        .find_map(|settings| {
+            parse_ini(&settings)
+                .get("Settings")?
+                .get("gtk-icon-theme-name")
+                .cloned()
+        })
+        .unwrap_or_else(|| FALLBACK_THEME.to_string())
+}
+
+// PNG data of the icon file. PNGs are returned as they are, SVGs are
+// rendered at `size` pixels.
+pub(super) fn load_icon_png(path: &Path, size: u32) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("png") => Some(data),
+        Some("svg") | Some("svgz") => rasterize_svg(&data, size),
+        _ => None,
+    }
+}
+
+// Renders the SVG centered into a square of `size` pixels, keeping its aspect ratio
+pub(super) fn rasterize_svg(data: &[u8], size: u32) -> Option<Vec<u8>> {
+    let tree = resvg::usvg::Tree::from_data(data, &resvg::usvg::Options::default()).ok()?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size, size)?;
+
+    let tree_size = tree.size();
+    let scale = size as f32 / tree_size.width().max(tree_size.height());
+    let transform = resvg::tiny_skia::Transform::from_scale(scale, scale).post_translate(
+        (size as f32 - tree_size.width() * scale) / 2.0,
+        (size as f32 - tree_size.height() * scale) / 2.0,
+    );
+
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+    pixmap.encode_png().ok()
+}
+
+// Groups of an ini style file. Later duplicate keys don't override earlier ones.
+fn parse_ini(content: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut groups: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            current = Some(name.to_string());
+            continue;
+        }
+        let (Some(group), Some((key, value))) = (&current, line.split_once('=')) else {
+            continue;
+        };
+        groups
+            .entry(group.clone())
+            .or_default()
+            .entry(key.trim().to_string())
+            .or_insert_with(|| value.trim().to_string());
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("icons")
+    }
+
+    fn lookup() -> IconLookup {
+        let root = fixture_dir();
+        IconLookup::new("Fixture", vec![root.join("icons")], vec![root.join("pixmaps")])
+    }
+
+    fn relative(path: PathBuf) -> String {
+        path.strip_prefix(fixture_dir()).unwrap().to_string_lossy().into_owned()
+    }
+
+    fn png_size(data: &[u8]) -> (u32, u32) {
+        let decoder = png::Decoder::new(data);
+        let reader = decoder.read_info().unwrap();
+        (reader.info().width, reader.info().height)
+    }
+
+    #[test]
+    fn prefers_exact_size_in_current_theme() {
+        let lookup = lookup();
+        assert_eq!(
+            relative(lookup.find_icon("editor", 48, 1).unwrap()),
+            "icons/Fixture/48x48/apps/editor.png"
+        );
+        assert_eq!(
+            relative(lookup.find_icon("editor", 48, 2).unwrap()),
+            "icons/Fixture/48x48@2/apps/editor.png"
+        );
+    }
+
+    #[test]
+    fn falls_back_to_closest_size() {
+        assert_eq!(
+            relative(lookup().find_icon("editor", 40, 1).unwrap()),
+            "icons/Fixture/48x48/apps/editor.png"
+        );
+        assert_eq!(
+            relative(lookup().find_icon("editor", 16, 1).unwrap()),
+            "icons/Fixture/24x24/apps/editor.png"
+        );
+    }
+
+    #[test]
+    fn follows_inherited_themes_then_hicolor_and_pixmaps() {
+        let lookup = lookup();
+        assert_eq!(
+            relative(lookup.find_icon("terminal", 48, 1).unwrap()),
+            "icons/FixtureBase/scalable/apps/terminal.svg"
+        );
+        assert_eq!(
+            relative(lookup.find_icon("viewer", 48, 1).unwrap()),
+            "icons/hicolor/32x32/apps/viewer.png"
+        );
+        assert_eq!(
+            relative(lookup.find_icon("legacy", 48, 1).unwrap()),
+            "pixmaps/legacy.png"
+        );
+        assert!(lookup.find_icon("missing", 48, 1).is_none());
+    }
+
+    #[test]
+    fn rasterizes_svg_at_requested_size() {
+        let path = lookup().find_icon("terminal", 48, 1).unwrap();
+        assert_eq!(png_size(&load_icon_png(&path, 64).unwrap()), (64, 64));
+        assert_eq!(png_size(&load_icon_png(&path, 128).unwrap()), (128, 128));
+    }
+
+    #[test]
+    fn loads_png_as_is() {
+        let path = lookup().find_icon("viewer", 32, 1).unwrap();
+        assert_eq!(load_icon_png(&path, 64).unwrap(), fs::read(&path).unwrap());
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::apps::{desktop_file_id, locale_candidates, DesktopEntry};
+use super::icon_theme::{load_icon_png, IconLookup};
+use super::mime::MimeDatabase;
+use super::LinuxPlatform;
+use crate::error::Result;
+use crate::icns_utils::AppIcon;
+use crate::icon_cache::IconCacheState;
+use crate::icon_render::{resize_png, IconRequest};
+use crate::platform::IconPlatform;
+
+// Size of application icons when no size is requested, in pixels
+const APP_ICON_SIZE: u32 = 128;
+// Size of file type icons when no size is requested, in pixels
+const EXTENSION_ICON_SIZE: u32 = 64;
+// Last resort when neither the type nor its media type has an icon
+const UNKNOWN_ICON: &str = "unknown";
+
+impl IconPlatform for LinuxPlatform {
+    // Accepts .desktop file paths, as returned by `list_installed_apps`
+    fn load_app_info(
+        resources_paths: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, AppIcon>> {
+        let pixel_size = request.pixel_size(APP_ICON_SIZE);
+        let locales = locale_candidates();
+        // Only needed when the cache misses
+        let mut icons: Option<IconLookup> = None;
+        let mut result = HashMap::new();
+
+        for resources_path in resources_paths {
+            let path = Path::new(&resources_path);
+            if path.extension().and_then(|e| e.to_str()) != Some("desktop") {
+                continue;
+            }
+            let Some(entry) = DesktopEntry::read(path) else {
+                continue;
+            };
+
+            let app_name = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string();
+            let localized_name = entry
+                .get_localized("Name", &locales)
+                .unwrap_or(&app_name)
+                .to_string();
+
+            // Names are cheap to read and follow the locale, only the icon is cached
+            let png = match cache.get(&resources_path, pixel_size) {
+                Some(cached) => cached.png,
+                None => {
+                    let icons = icons.get_or_insert_with(IconLookup::system);
+                    let icon_path = entry.icon().and_then(|icon| {
+                        icons.find_icon(icon, request.size_or(APP_ICON_SIZE), request.scale)
+                    });
+                    let png = icon_path
+                        .as_deref()
+                        .and_then(|icon_path| render_icon(icon_path, pixel_size));
+
+                    let mut sources = vec![path.to_path_buf()];
+                    sources.extend(icon_path);
+                    cache.put(&resources_path, &sources, pixel_size, None, png.as_deref());
+                    png
+                }
+            };
+
+            let metadata = entry.metadata(&desktop_file_id(path));
+            result.insert(
+                app_name,
+                AppIcon {
+                    localized_name,
+                    png,
+                    metadata,
+                },
+            );
+        }
+
+        Ok(result)
+    }
+
+    // Extension (or "folder") -> icon PNG, extensions without any icon are left out
+    fn load_extension_icons(
+        extensions: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, Vec<u8>>> {
+        let pixel_size = request.pixel_size(EXTENSION_ICON_SIZE);
+        let mut lookup: Option<(IconLookup, MimeDatabase)> = None;
+        let mut icon_map = HashMap::new();
+
+        for ext in extensions {
+            let key = format!("extension:{}", ext);
+            let png = match cache.get(&key, pixel_size) {
+                Some(cached) => cached.png,
+                None => {
+                    let (icons, mime_database) =
+                        lookup.get_or_insert_with(|| (IconLookup::system(), MimeDatabase::system()));
+                    let icon_path = extension_icon_path(&ext, request, icons, mime_database);
+                    let png = icon_path
+                        .as_deref()
+                        .and_then(|icon_path| render_icon(icon_path, pixel_size));
+
+                    let sources: Vec<PathBuf> = icon_path.into_iter().collect();
+                    cache.put(&key, &sources, pixel_size, None, png.as_deref());
+                    png
+                }
+            };
+
+            if let Some(png) = png {
+                icon_map.insert(ext, png);
+            }
+        }
+
+        Ok(icon_map)
+    }
+
+    // No decoder, ICNS files fall back to their PNG and RLE elements (see icns.rs)
+    fn decode_jpeg2000(_data: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+fn extension_icon_path(
+    ext: &str,
+    request: &IconRequest,
+    icons: &IconLookup,
+    mime_database: &MimeDatabase,
+) -> Option<PathBuf> {
+    let mut icon_names = if ext == "folder" {
+        vec!["folder".to_string(), "inode-directory".to_string()]
+    } else {
+        // mime_guess covers systems without shared-mime-info
+        let mime_type = mime_database
+            .mime_type_for_extension(ext)
+            .map(str::to_string)
+            .or_else(|| mime_guess::from_ext(ext).first_raw().map(str::to_string));
+        mime_type
+            .map(|mime_type| mime_database.icon_names(&mime_type))
+            .unwrap_or_default()
+    };
+    icon_names.push(UNKNOWN_ICON.to_string());
+
+    icon_names
+        .iter()
+        .find_map(|name| icons.find_icon(name, request.size_or(EXTENSION_ICON_SIZE), request.scale))
+}
+
+// Themes rarely ship every size, so bitmaps are resampled to `pixel_size`
+fn render_icon(path: &Path, pixel_size: u32) -> Option<Vec<u8>> {
+    let png = load_icon_png(path, pixel_size)?;
+    resize_png(&png, pixel_size).or(Some(png))
+}
@@ -0,0 +1,230 @@
+// File type lookup through the shared-mime-info database
+// (https://specifications.freedesktop.org/shared-mime-info-spec/latest/)
+//
+// Only the glob rules and the type hierarchy are used: icons and "Open with…"
+// go by extension, so there is no file content to sniff.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{data_dirs, data_home};
+
+// Weight the spec assigns to globs without an explicit one
+const DEFAULT_WEIGHT: u32 = 50;
+
+// A `*.suffix` glob of globs2
+struct SuffixGlob {
+    weight: u32,
+    suffix: String,
+    case_sensitive: bool,
+    mime_type: String,
+}
+
+pub(super) struct MimeDatabase {
+    globs: Vec<SuffixGlob>,
+    // mime type -> icon name, from the `icons` files
+    icons: HashMap<String, String>,
+    // mime type -> fallback icon name, from the `generic-icons` files
+    generic_icons: HashMap<String, String>,
+    // mime type -> the types it is a subclass of, from the `subclasses` files
+    parents: HashMap<String, Vec<String>>,
+}
+
+impl MimeDatabase {
+    // Reads the databases in `mime_dirs`, most important first
+    pub(super) fn new(mime_dirs: &[PathBuf]) -> Self {
+        let mut database = Self {
+            globs: Vec::new(),
+            icons: HashMap::new(),
+            generic_icons: HashMap::new(),
+            parents: HashMap::new(),
+        };
+
+        for directory in mime_dirs {
+            if let Ok(globs) = fs::read_to_string(directory.join("globs2")) {
+                database.globs.extend(parse_globs2(&globs));
+            }
+            if let Ok(icons) = fs::read_to_string(directory.join("icons")) {
+                for (mime_type, icon) in parse_pairs(&icons) {
+                    database.icons.entry(mime_type).or_insert(icon);
+                }
+            }
+            if let Ok(icons) = fs::read_to_string(directory.join("generic-icons")) {
+                for (mime_type, icon) in parse_pairs(&icons) {
+                    database.generic_icons.entry(mime_type).or_insert(icon);
+                }
+            }
+            if let Ok(subclasses) = fs::read_to_string(directory.join("subclasses")) {
+                for line in subclasses.lines().filter(|line| !line.starts_with('#')) {
+                    if let Some((mime_type, parent)) = line.split_once(' ') {
+                        let parents = database.parents.entry(mime_type.to_string()).or_default();
+                        if !parents.iter().any(|existing| existing == parent) {
+                            parents.push(parent.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        database
+    }
+
+    // The databases of $XDG_DATA_HOME and $XDG_DATA_DIRS
+    pub(super) fn system() -> Self {
+        let mut mime_dirs = vec![data_home().join("mime")];
+        mime_dirs.extend(data_dirs().into_iter().map(|dir| dir.join("mime")));
+        Self::new(&mime_dirs)
+    }
+
+    // MIME type of files with this extension, e.g. `pdf` or `tar.gz`. The
+    // heaviest glob wins, then a case sensitive one, then the longest one.
+    pub(super) fn mime_type_for_extension(&self, extension: &str) -> Option<&str> {
+        let file_name = format!("file.{}", extension);
+        let lowercase_name = file_name.to_lowercase();
+
+        self.globs
+            .iter()
+            .filter(|glob| {
+                if glob.case_sensitive {
+                    file_name.ends_with(&glob.suffix)
+                } else {
+                    lowercase_name.ends_with(&glob.suffix)
+                }
+            })
+            // Earlier directories win ties, max_by_key keeps the last maximum
+            .rev()
+            .max_by_key(|glob| (glob.weight, glob.case_sensitive, glob.suffix.len()))
+            .map(|glob| glob.mime_type.as_str())
+    }
+
+    // The MIME type followed by the types it is a subclass of, nearest first.
+    // Every text/* type is plain text as well.
+    pub(super) fn with_parents(&self, mime_type: &str) -> Vec<String> {
+        let mut types = vec![mime_type.to_string()];
+        let mut index = 0;
+        while index < types.len() {
+            for parent in self.parents.get(&types[index]).into_iter().flatten() {
+                if !types.contains(parent) {
+                    types.push(parent.clone());
+                }
+            }
+            index += 1;
+        }
+
+        if mime_type.starts_with("text/") && !types.iter().any(|t| t == "text/plain") {
+            types.push("text/plain".to_string());
+        }
+        types
+    }
+
+    // Icon names to try for the MIME type, best first
+    pub(super) fn icon_names(&self, mime_type: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let candidates = [
+            self.icons.get(mime_type).cloned(),
+            Some(mime_type.replace('/', "-")),
+            self.generic_icons.get(mime_type).cloned(),
+            mime_type.split_once('/').map(|(media, _)| format!("{}-x-generic", media)),
+        ];
+        for name in candidates.into_iter().flatten() {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+// `weight:mimetype:glob[:flags]` lines, keeping simple `*.suffix` globs
+fn parse_globs2(content: &str) -> Vec<SuffixGlob> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, ':');
+            let weight = fields.next()?.parse().unwrap_or(DEFAULT_WEIGHT);
+            let mime_type = fields.next()?;
+            let suffix = fields.next()?.strip_prefix('*')?;
+            let case_sensitive = fields
+                .next()
+                .is_some_and(|flags| flags.split(',').any(|flag| flag == "cs"));
+
+            if !suffix.starts_with('.') || suffix.contains(['*', '?', '[']) {
+                return None;
+            }
+
+            Some(SuffixGlob {
+                weight,
+                suffix: if case_sensitive { suffix.to_string() } else { suffix.to_lowercase() },
+                case_sensitive,
+                mime_type: mime_type.to_string(),
+            })
+        })
+        .collect()
+}
+
+// `mimetype:value` lines
+fn parse_pairs(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (mime_type, value) = line.split_once(':')?;
+            Some((mime_type.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn database() -> MimeDatabase {
+        MimeDatabase::new(&[Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/icons/mime")])
+    }
+
+    #[test]
+    fn maps_extensions_by_weight_and_length() {
+        let database = database();
+        assert_eq!(database.mime_type_for_extension("PDF"), Some("application/pdf"));
+        assert_eq!(database.mime_type_for_extension("gz"), Some("application/gzip"));
+        assert_eq!(
+            database.mime_type_for_extension("tar.gz"),
+            Some("application/x-compressed-tar")
+        );
+        // Case sensitive glob
+        assert_eq!(database.mime_type_for_extension("C"), Some("text/x-c++src"));
+        assert_eq!(database.mime_type_for_extension("c"), Some("text/x-csrc"));
+        assert_eq!(database.mime_type_for_extension("unknown"), None);
+    }
+
+    #[test]
+    fn lists_parent_types_nearest_first() {
+        let database = database();
+        assert_eq!(
+            database.with_parents("text/x-c++src"),
+            ["text/x-c++src", "text/x-csrc", "text/plain"]
+        );
+        assert_eq!(database.with_parents("text/markdown"), ["text/markdown", "text/plain"]);
+        assert_eq!(database.with_parents("image/png"), ["image/png"]);
+    }
+
+    #[test]
+    fn lists_icon_names_with_generic_fallbacks() {
+        assert_eq!(
+            database().icon_names("text/x-csrc"),
+            ["text-x-csrc", "text-x-generic"]
+        );
+        assert_eq!(
+            database().icon_names("application/x-compressed-tar"),
+            [
+                "package-x-compressed",
+                "application-x-compressed-tar",
+                "package-x-generic",
+                "application-x-generic"
+            ]
+        );
+    }
+}
@@ -0,0 +1,60 @@
+// src-tauri/src/platform/linux/mod.rs
+//
+// Freedesktop implementation, used on every target other than macOS.
+
+mod apps;
+mod calendar;
+mod file_search;
+mod icon_theme;
+mod icons;
+mod mime;
+mod open_with;
+mod panel;
+mod workspace;
+
+use std::env;
+use std::path::PathBuf;
+
+pub use calendar::LinuxCalendar;
+
+pub struct LinuxPlatform;
+
+// $XDG_DATA_HOME, ~/.local/share when unset
+fn data_home() -> PathBuf {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".local/share"))
+}
+
+// $XDG_DATA_DIRS, /usr/local/share:/usr/share when unset
+fn data_dirs() -> Vec<PathBuf> {
+    env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string())
+        .split(':')
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+// $XDG_CONFIG_HOME, ~/.config when unset
+fn config_home() -> PathBuf {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"))
+}
+
+// $XDG_CONFIG_DIRS, /etc/xdg when unset
+fn config_dirs() -> Vec<PathBuf> {
+    env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string())
+        .split(':')
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .collect()
+}
@@ -0,0 +1,264 @@
+// "Open with…" through the MIME database, .desktop files and mimeapps.list
+// (https://specifications.freedesktop.org/mime-apps-spec/latest/)
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::apps::{current_desktops, DesktopEntry};
+use super::mime::MimeDatabase;
+use super::{config_dirs, config_home, data_dirs, data_home, LinuxPlatform};
+use crate::error::{Error, Result};
+use crate::open_with::{applications_for_file, Associations, FileTarget, OpenWithApp};
+use crate::platform::{AppDiscoveryPlatform, OpenWithPlatform};
+
+const DEFAULT_GROUP: &str = "[Default Applications]";
+const ADDED_GROUP: &str = "[Added Associations]";
+const REMOVED_GROUP: &str = "[Removed Associations]";
+
+impl OpenWithPlatform for LinuxPlatform {
+    fn applications_for_file(target: &FileTarget) -> Result<Vec<OpenWithApp>> {
+        let mime_types = file_mime_types(target);
+        let apps = Self::installed_apps()?;
+
+        let lists: Vec<MimeAppsList> = mimeapps_files()
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .map(|content| MimeAppsList::parse(&content))
+            .collect();
+        let is_installed = |id: &str| apps.iter().any(|app| app.id == id);
+        let associations = associations(&lists, &mime_types, &is_installed);
+
+        let is_file_type = |mime_type: &str| mime_types.iter().any(|t| t == mime_type);
+        Ok(applications_for_file(
+            apps,
+            target.extension.as_deref(),
+            &is_file_type,
+            &associations,
+        ))
+    }
+
+    fn open_with(file_path: &Path, app_path: &str) -> Result<()> {
+        let desktop_path = Path::new(app_path);
+        let entry = DesktopEntry::read(desktop_path)
+            .ok_or_else(|| Error::invalid_argument(format!("Not an application: {}", app_path)))?;
+        let command = entry.exec_command(file_path, desktop_path)?;
+
+        let mut child = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| Error::io(format!("Failed to start {}", command[0])).with_details(e))?;
+        // Reap the process once the application quits
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+// MIME types of the file, with the types they are a subclass of
+fn file_mime_types(target: &FileTarget) -> Vec<String> {
+    if target.path.as_deref().is_some_and(Path::is_dir) {
+        return vec!["inode/directory".to_string()];
+    }
+
+    // The whole name after the first dot, so `*.tar.gz` globs match
+    let suffix = match &target.path {
+        Some(path) => path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.split_once('.'))
+            .map(|(_, suffix)| suffix.to_string()),
+        None => target.extension.clone(),
+    };
+    let Some(suffix) = suffix else {
+        return Vec::new();
+    };
+
+    let mime_database = MimeDatabase::system();
+    // mime_guess covers systems without shared-mime-info
+    let mime_type = mime_database
+        .mime_type_for_extension(&suffix)
+        .map(str::to_string)
+        .or_else(|| {
+            let extension = target.extension.as_deref()?;
+            mime_guess::from_ext(extension)
+                .first_raw()
+                .map(str::to_string)
+        });
+    mime_type
+        .map(|mime_type| mime_database.with_parents(&mime_type))
+        .unwrap_or_default()
+}
+
+// mimeapps.list files, highest precedence first. Desktop specific files come
+// before the general one of the same directory.
+fn mimeapps_files() -> Vec<PathBuf> {
+    let desktops: Vec<String> = current_desktops()
+        .iter()
+        .map(|desktop| desktop.to_lowercase())
+        .collect();
+
+    let mut directories = vec![config_home()];
+    directories.extend(config_dirs());
+    directories.push(data_home().join("applications"));
+    directories.extend(data_dirs().into_iter().map(|dir| dir.join("applications")));
+
+    let mut files = Vec::new();
+    for directory in directories {
+        for desktop in &desktops {
+            files.push(directory.join(format!("{}-mimeapps.list", desktop)));
+        }
+        files.push(directory.join("mimeapps.list"));
+    }
+    files
+}
+
+#[derive(Clone, Copy)]
+enum Group {
+    Default,
+    Added,
+    Removed,
+}
+
+// The groups of one mimeapps.list, MIME type -> desktop file IDs
+#[derive(Default)]
+struct MimeAppsList {
+    defaults: HashMap<String, Vec<String>>,
+    added: HashMap<String, Vec<String>>,
+    removed: HashMap<String, Vec<String>>,
+}
+
+impl MimeAppsList {
+    fn parse(content: &str) -> Self {
+        let mut list = Self::default();
+        let mut group = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                group = match line {
+                    DEFAULT_GROUP => Some(Group::Default),
+                    ADDED_GROUP => Some(Group::Added),
+                    REMOVED_GROUP => Some(Group::Removed),
+                    _ => None,
+                };
+                continue;
+            }
+
+            let (Some(group), Some((mime_type, ids))) = (group, line.split_once('=')) else {
+                continue;
+            };
+            let ids = ids
+                .split(';')
+                .map(|id| {
+                    id.trim()
+                        .strip_suffix(".desktop")
+                        .unwrap_or(id.trim())
+                        .to_string()
+                })
+                .filter(|id| !id.is_empty());
+            let map = match group {
+                Group::Default => &mut list.defaults,
+                Group::Added => &mut list.added,
+                Group::Removed => &mut list.removed,
+            };
+            map.entry(mime_type.trim().to_string())
+                .or_default()
+                .extend(ids);
+        }
+
+        list
+    }
+}
+
+// Default and user chosen applications for the MIME types (most specific
+// first). Lists are read in order of precedence; an association removed in
+// one list still counts when a list with higher precedence added it.
+fn associations(
+    lists: &[MimeAppsList],
+    mime_types: &[String],
+    is_installed: &dyn Fn(&str) -> bool,
+) -> Associations {
+    let mut associations = Associations::default();
+
+    for mime_type in mime_types {
+        let mut removed: Vec<&String> = Vec::new();
+        for list in lists {
+            for id in list.added.get(mime_type).into_iter().flatten() {
+                if !removed.contains(&id) && !associations.added.contains(id) {
+                    associations.added.push(id.clone());
+                }
+            }
+            if associations.default.is_none() {
+                associations.default = list
+                    .defaults
+                    .get(mime_type)
+                    .into_iter()
+                    .flatten()
+                    .find(|id| !removed.contains(id) && is_installed(id))
+                    .cloned();
+            }
+            removed.extend(list.removed.get(mime_type).into_iter().flatten());
+        }
+
+        for id in removed {
+            if !associations.removed.contains(id) && !associations.added.contains(id) {
+                associations.removed.push(id.clone());
+            }
+        }
+    }
+
+    associations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_associations_by_precedence() {
+        let user = MimeAppsList::parse(
+            "[Default Applications]\n\
+             text/markdown=missing.desktop;org.example.Writer.desktop;\n\
+             [Added Associations]\n\
+             text/markdown=org.example.Notes.desktop;\n\
+             [Removed Associations]\n\
+             text/plain=org.example.Viewer.desktop;\n",
+        );
+        let system = MimeAppsList::parse(
+            "# Distribution defaults\n\
+             [Default Applications]\n\
+             text/plain=org.example.Viewer.desktop;org.example.Editor.desktop\n\
+             [Added Associations]\n\
+             text/plain=org.example.Viewer.desktop;org.example.Pager.desktop;\n",
+        );
+        let is_installed = |id: &str| id != "missing";
+
+        let markdown = ["text/markdown".to_string(), "text/plain".to_string()];
+        let associations = associations(&[user, system], &markdown, &is_installed);
+        assert_eq!(associations.default.as_deref(), Some("org.example.Writer"));
+        assert_eq!(
+            associations.added,
+            ["org.example.Notes", "org.example.Pager"]
+        );
+        assert_eq!(associations.removed, ["org.example.Viewer"]);
+    }
+
+    #[test]
+    fn falls_back_to_the_parent_type_default() {
+        let system =
+            MimeAppsList::parse("[Default Applications]\ntext/plain=org.example.Editor.desktop\n");
+        let associations = associations(
+            &[system],
+            &["text/x-csrc".to_string(), "text/plain".to_string()],
+            &|_| true,
+        );
+        assert_eq!(associations.default.as_deref(), Some("org.example.Editor"));
+    }
+}
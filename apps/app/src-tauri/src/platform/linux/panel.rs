@@ -0,0 +1,40 @@
+use tauri::{App, AppHandle, Manager as _, Runtime};
+
+use super::LinuxPlatform;
+use crate::platform::PanelPlatform;
+use crate::MAIN_WINDOW_LABEL;
+
+// There is no panel concept outside macOS, the main webview window is shown
+// and hidden directly.
+impl PanelPlatform for LinuxPlatform {
+    fn setup<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
+        let window = app.get_webview_window(MAIN_WINDOW_LABEL).unwrap();
+
+        window.set_always_on_top(true)?;
+        window.set_skip_taskbar(true)?;
+
+        Ok(())
+    }
+
+    fn show<R: Runtime>(app_handle: &AppHandle<R>) {
+        if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+    }
+
+    fn hide<R: Runtime>(app_handle: &AppHandle<R>) {
+        if let Some(window) = app_handle.get_webview_window(MAIN_WINDOW_LABEL) {
+            if window.is_visible().unwrap_or(false) {
+                let _ = window.hide();
+            }
+        }
+    }
+
+    fn is_visible<R: Runtime>(app_handle: &AppHandle<R>) -> bool {
+        app_handle
+            .get_webview_window(MAIN_WINDOW_LABEL)
+            .and_then(|window| window.is_visible().ok())
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,13 @@
+use super::LinuxPlatform;
+use crate::error::{Error, Result};
+use crate::platform::WorkspacePlatform;
+
+impl WorkspacePlatform for LinuxPlatform {
+    fn activate_application_by_name(_app_name: &str) -> Result<bool> {
+        Err(Error::unsupported("Activating applications is only available on macOS"))
+    }
+
+    fn frontmost_application_name() -> Result<String> {
+        Err(Error::unsupported("Getting frontmost application name is only available on macOS"))
+    }
+}
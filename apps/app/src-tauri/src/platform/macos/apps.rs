@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use super::MacosPlatform;
+use crate::app_discovery::{find_app_bundles, AppBundle, APPLICATION_DIRS, DEFAULT_MAX_DEPTH};
+use crate::error::Result;
+use crate::icns_utils::{app_bundle_metadata, AppLaunchInfo, AppSource, InstalledApp};
+use crate::platform::AppDiscoveryPlatform;
+
+impl AppDiscoveryPlatform for MacosPlatform {
+    fn installed_apps() -> Result<Vec<InstalledApp>> {
+        let mut apps: Vec<InstalledApp> = find_app_bundles(&APPLICATION_DIRS, DEFAULT_MAX_DEPTH)
+            .into_iter()
+            .map(bundle_app)
+            .collect();
+
+        apps.sort_by_key(|app| app.localized_name.to_lowercase());
+        Ok(apps)
+    }
+}
+
+// Localized names and icons come from `load_app_info`
+fn bundle_app(bundle: AppBundle) -> InstalledApp {
+    let path = Path::new(&bundle.path);
+    let name = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    InstalledApp {
+        base64_image: String::new(),
+        localized_name: name.clone(),
+        id: name.clone(),
+        name,
+        path: bundle.path.clone(),
+        suite: bundle.suite,
+        launch: AppLaunchInfo {
+            exec: None,
+            icon: None,
+            keywords: Vec::new(),
+            terminal: false,
+            source: AppSource::Bundle,
+        },
+        metadata: app_bundle_metadata(&bundle.path),
+    }
+}
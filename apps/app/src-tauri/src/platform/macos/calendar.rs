@@ -0,0 +1,501 @@
+// src-tauri/src/platform/macos/calendar.rs
+
+use block::ConcreteBlock;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use cocoa::appkit::CGFloat;
+use cocoa::base::{id, nil, NO, YES};
+use cocoa::foundation::{NSArray, NSAutoreleasePool, NSString, NSUInteger};
+use objc::runtime::{Class, Object, BOOL as ObjcBOOL};
+use objc::{class, msg_send, sel, sel_impl};
+use objc_id::Id;
+use std::ffi::CStr;
+use std::sync::{mpsc, Mutex};
+
+use crate::calendar_utils::{CalendarAuthorizationStatus, CalendarInfo, EventDraft, EventInfo, ParticipantInfo};
+use crate::error::{Error, Result};
+use crate::platform::CalendarPlatform;
+
+const EK_ENTITY_TYPE_EVENT: i64 = 0;
+// EKSpan: the event only, or also its future occurrences
+const EK_SPAN_THIS_EVENT: i64 = 0;
+const EK_SPAN_FUTURE_EVENTS: i64 = 1;
+
+pub struct EventKitCalendar {
+    // Wrap the Id in a Mutex to ensure Send + Sync
+    event_store: Mutex<Id<Object>>,
+}
+
+impl EventKitCalendar {
+    fn store_ptr(&self) -> Result<id> {
+        let guard = self.event_store.lock().map_err(|e| Error::internal("Failed to lock event store mutex").with_details(e))?;
+        let obj_ref: &Object = &*guard;
+        let ptr = obj_ref as *const Object as id;
+        if ptr.is_null() { // Use is_null() for raw pointers
+            Err(Error::platform("Event store instance in state is nil"))
+        } else {
+            Ok(ptr)
+        }
+    }
+}
+
+fn ensure_authorized() -> Result<()> {
+    match EventKitCalendar::authorization_status()? {
+        CalendarAuthorizationStatus::Authorized => Ok(()),
+        _ => Err(Error::not_authorized("Calendar access not authorized.")),
+    }
+}
+
+fn event_store_class() -> &'static Class {
+    Class::get("EKEventStore").expect("EKEventStore class not found")
+}
+
+// Helper to convert NSString to Rust String
+unsafe fn nsstring_to_string(ns_string: id) -> String {
+    if ns_string == nil {
+        return String::new();
+    }
+    let c_str_ptr: *const std::os::raw::c_char = msg_send![ns_string, UTF8String];
+    if c_str_ptr.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(c_str_ptr).to_string_lossy().into_owned()
+}
+
+// Helper to convert NSColor directly to Hex String using components
+// NSColor -> NSColor (RGB space) -> RGBA components -> Hex
+unsafe fn nscolor_to_hex(ns_color: id) -> String {
+    if ns_color == nil {
+        return "#000000".to_string(); // Default color: Black
+    }
+
+    // Get the device RGB color space
+    // Use class method deviceRGBColorSpace which is simpler
+    let device_rgb_space: id = msg_send![class!(NSColorSpace), deviceRGBColorSpace];
+
+    if device_rgb_space == nil {
+        return "#808080".to_string(); // Fallback: Gray
+    }
+
+    // Convert the original color to the device RGB color space
+    let color_rgb: id = msg_send![ns_color, colorUsingColorSpace: device_rgb_space];
+
+    if color_rgb == nil {
+        // This can happen with pattern colors, etc.
+        return "#808080".to_string(); // Fallback: Gray
+    }
+
+    // Get the RGBA components directly from the NSColor object
+    let red: CGFloat = msg_send![color_rgb, redComponent];
+    let green: CGFloat = msg_send![color_rgb, greenComponent];
+    let blue: CGFloat = msg_send![color_rgb, blueComponent];
+    // let alpha: CGFloat = msg_send![color_rgb, alphaComponent]; // We don't need alpha for #RRGGBB
+
+    // Convert CGFloat (0.0-1.0) to u8 (0-255), clamping values
+    let r_u8 = (red.max(0.0).min(1.0) * 255.0).round() as u8;
+    let g_u8 = (green.max(0.0).min(1.0) * 255.0).round() as u8;
+    let b_u8 = (blue.max(0.0).min(1.0) * 255.0).round() as u8;
+
+    // Format as hex string
+    format!("#{:02X}{:02X}{:02X}", r_u8, g_u8, b_u8)
+}
+
+// Autoreleased NSString
+unsafe fn string_to_nsstring(string: &str) -> id {
+    NSString::alloc(nil).init_str(string).autorelease()
+}
+
+unsafe fn optional_nsstring(string: &Option<String>) -> id {
+    match string.as_deref().filter(|string| !string.is_empty()) {
+        Some(string) => string_to_nsstring(string),
+        None => nil,
+    }
+}
+
+unsafe fn nserror_message(error: id) -> String {
+    if error == nil {
+        return "Unknown EventKit error".to_string();
+    }
+    let description: id = msg_send![error, localizedDescription];
+    nsstring_to_string(description)
+}
+
+unsafe fn datetime_utc_to_nsdate(dt: DateTime<Utc>) -> id {
+    let timestamp = dt.timestamp() as f64;
+    let nsdate: id = msg_send![class!(NSDate), dateWithTimeIntervalSince1970: timestamp];
+    nsdate
+}
+
+unsafe fn nsdate_to_datetime_utc(nsdate: id) -> Option<DateTime<Utc>> {
+    if nsdate == nil {
+        return None;
+    }
+    let timestamp: f64 = msg_send![nsdate, timeIntervalSince1970];
+    NaiveDateTime::from_timestamp_opt(timestamp as i64, (timestamp.fract() * 1_000_000_000.0) as u32)
+        .map(|naive| DateTime::<Utc>::from_naive_utc_and_offset(naive, Utc))
+}
+
+// Helper to convert NSDate to ISO 8601 string (UTC)
+unsafe fn nsdate_to_iso_string(nsdate: id) -> String {
+    nsdate_to_datetime_utc(nsdate)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| "".to_string())
+}
+
+// Helper to parse ISO 8601 string to NSDate
+// Assumes input string is UTC or has offset
+unsafe fn iso_string_to_nsdate(iso_string: &str) -> Option<id> {
+    DateTime::parse_from_rfc3339(iso_string)
+        .ok()
+        .map(|dt| datetime_utc_to_nsdate(dt.with_timezone(&Utc)))
+}
+
+unsafe fn get_calendars_by_ids(store: id, calendar_ids: &[String]) -> Vec<id> {
+    let all_calendars_nsarray: id = msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT];
+    if all_calendars_nsarray == nil {
+        return Vec::new();
+    }
+
+    let mut matching_calendars = Vec::new();
+    let count: NSUInteger = NSArray::count(all_calendars_nsarray);
+
+    for i in 0..count {
+        let calendar: id = NSArray::objectAtIndex(all_calendars_nsarray, i);
+        if calendar == nil {
+            continue;
+        }
+
+        let identifier_nsstring: id = msg_send![calendar, calendarIdentifier];
+        let identifier_str = nsstring_to_string(identifier_nsstring);
+        if calendar_ids.contains(&identifier_str) {
+            matching_calendars.push(calendar);
+        }
+    }
+    matching_calendars
+}
+
+unsafe fn event_to_info(event: id) -> EventInfo {
+    let identifier: id = msg_send![event, eventIdentifier];
+    let title: id = msg_send![event, title];
+    let notes: id = msg_send![event, notes];
+    let start_date: id = msg_send![event, startDate];
+    let end_date: id = msg_send![event, endDate];
+    let calendar: id = msg_send![event, calendar];
+    let calendar_id: id = msg_send![calendar, calendarIdentifier];
+    let location: id = msg_send![event, location];
+    let is_all_day: ObjcBOOL = msg_send![event, isAllDay];
+    let url_nsurl: id = msg_send![event, URL]; // Get NSURL
+    let participants_nsarray: id = msg_send![event, attendees];
+
+    let mut participants = Vec::new();
+    if participants_nsarray != nil {
+        let participants_count: NSUInteger = NSArray::count(participants_nsarray);
+        for j in 0..participants_count {
+            let participant: id = NSArray::objectAtIndex(participants_nsarray, j);
+            let name: id = msg_send![participant, name];
+            let participant_info = ParticipantInfo {
+                name: if name != nil { Some(nsstring_to_string(name)) } else { None },
+            };
+            participants.push(participant_info);
+        }
+    }
+
+    EventInfo {
+        identifier: nsstring_to_string(identifier),
+        title: nsstring_to_string(title),
+        notes: if notes != nil { Some(nsstring_to_string(notes)) } else { None },
+        // Correctly handle NSURL -> NSString -> Rust String
+        url: if url_nsurl != nil {
+            let url_nsstring: id = msg_send![url_nsurl, absoluteString];
+            if url_nsstring != nil {
+                 Some(nsstring_to_string(url_nsstring))
+            } else {
+                None // absoluteString returned nil
+            }
+        } else {
+            None // URL property was nil
+        },
+        participants,
+        start_date: nsdate_to_iso_string(start_date),
+        end_date: nsdate_to_iso_string(end_date),
+        calendar_id: nsstring_to_string(calendar_id),
+        location: if location != nil { Some(nsstring_to_string(location)) } else { None },
+        is_all_day: is_all_day == YES,
+    }
+}
+
+// Copies `draft` onto `event`, checking that its calendar takes changes
+unsafe fn apply_draft(store: id, event: id, draft: &EventDraft) -> Result<()> {
+    let (start, end) = draft.validate()?;
+    let calendar: id = msg_send![store, calendarWithIdentifier: string_to_nsstring(&draft.calendar_id)];
+    if calendar == nil {
+        return Err(Error::not_found(format!("No calendar {}", draft.calendar_id)));
+    }
+    let allows_modifications: ObjcBOOL = msg_send![calendar, allowsContentModifications];
+    if allows_modifications != YES {
+        let title: id = msg_send![calendar, title];
+        return Err(Error::not_authorized(format!("The calendar {} is read-only", nsstring_to_string(title))));
+    }
+
+    // EventKit ends all-day events on their last day, not at the midnight after it
+    let end = if draft.is_all_day { (end - Duration::seconds(1)).max(start) } else { end };
+    // NSURL takes only encoded URLs, which is how the url crate writes them
+    let url: id = match &draft.url {
+        Some(url) => {
+            let encoded = url::Url::parse(url).map(String::from).unwrap_or_else(|_| url.clone());
+            let ns_url: id = msg_send![class!(NSURL), URLWithString: string_to_nsstring(&encoded)];
+            if ns_url == nil {
+                return Err(Error::invalid_argument(format!("Invalid URL: {}", url)));
+            }
+            ns_url
+        }
+        None => nil,
+    };
+
+    let _: () = msg_send![event, setCalendar: calendar];
+    let _: () = msg_send![event, setTitle: string_to_nsstring(draft.title.trim())];
+    let _: () = msg_send![event, setStartDate: datetime_utc_to_nsdate(start)];
+    let _: () = msg_send![event, setEndDate: datetime_utc_to_nsdate(end)];
+    let _: () = msg_send![event, setAllDay: if draft.is_all_day { YES } else { NO }];
+    let _: () = msg_send![event, setLocation: optional_nsstring(&draft.location)];
+    let _: () = msg_send![event, setNotes: optional_nsstring(&draft.notes)];
+    let _: () = msg_send![event, setURL: url];
+    Ok(())
+}
+
+unsafe fn save_event(store: id, event: id, span: i64) -> Result<()> {
+    let mut error: id = nil;
+    let saved: ObjcBOOL = msg_send![store, saveEvent: event span: span commit: YES error: &mut error];
+    if saved != YES {
+        return Err(Error::platform(format!("Failed to save the event: {}", nserror_message(error))));
+    }
+    Ok(())
+}
+
+// For recurring events this is the first occurrence
+unsafe fn event_with_identifier(store: id, event_id: &str) -> Result<id> {
+    let event: id = msg_send![store, eventWithIdentifier: string_to_nsstring(event_id)];
+    if event == nil {
+        return Err(Error::not_found(format!("No event {}", event_id)));
+    }
+    Ok(event)
+}
+
+impl CalendarPlatform for EventKitCalendar {
+    fn new() -> Self {
+        let store_class = class!(EKEventStore);
+        let store_ptr: *mut Object = unsafe { msg_send![store_class, new] };
+        let event_store_id = unsafe { Id::from_retained_ptr(store_ptr) };
+
+        Self {
+            event_store: Mutex::new(event_store_id),
+        }
+    }
+
+    fn authorization_status() -> Result<CalendarAuthorizationStatus> {
+        let store_class = event_store_class();
+
+        let status_raw: i64 = unsafe {
+            msg_send![store_class, authorizationStatusForEntityType: EK_ENTITY_TYPE_EVENT]
+        };
+
+        let status = match status_raw {
+            0 => CalendarAuthorizationStatus::NotDetermined,
+            1 => CalendarAuthorizationStatus::Restricted,
+            2 => CalendarAuthorizationStatus::Denied,
+            3 => CalendarAuthorizationStatus::Authorized,
+            // 4 => CalendarAuthorizationStatus::WriteOnly, // EKAuthorizationStatusWriteOnly - Handle if needed
+            _ => return Err(Error::platform(format!("Unknown calendar authorization status: {}", status_raw))),
+        };
+
+        Ok(status)
+    }
+
+    fn request_access(&self) -> Result<CalendarAuthorizationStatus> {
+        let store = self.store_ptr()?;
+
+        // Check current status first (optional but good practice)
+        let current_status = Self::authorization_status()?;
+        if current_status != CalendarAuthorizationStatus::NotDetermined {
+            return Ok(current_status);
+        }
+
+        let (tx, rx) = mpsc::channel::<bool>(); // Use mpsc channel
+
+        let completion_block = ConcreteBlock::new(move |granted: objc::runtime::BOOL, error: id| -> () {
+            // Send false on error
+            let granted_bool = error == nil && granted == objc::runtime::YES;
+            let _ = tx.send(granted_bool);
+        }).copy();
+
+        unsafe {
+            let _: () = msg_send![store,
+                requestFullAccessToEventsWithCompletion: completion_block
+            ];
+        }
+
+        match rx.recv() {
+            Ok(true) => Ok(CalendarAuthorizationStatus::Authorized),
+            Ok(false) => Ok(CalendarAuthorizationStatus::Denied),
+            Err(_) => Err(Error::internal("Calendar access completion handler channel closed unexpectedly.")),
+        }
+    }
+
+    fn calendars(&self) -> Result<Vec<CalendarInfo>> {
+        let _pool = unsafe { NSAutoreleasePool::new(nil) }; // Manage memory
+
+        // Check auth status first
+        match Self::authorization_status()? {
+            CalendarAuthorizationStatus::Authorized => (),
+            _ => return Err(Error::not_authorized("Calendar access not authorized.")),
+        }
+
+        let store = self.store_ptr()?;
+
+        let calendars_nsarray: id = unsafe { msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT] };
+
+        if calendars_nsarray == nil {
+            return Err(Error::platform("EventKit returned no calendar list"));
+        }
+
+        let mut calendars_vec = Vec::new();
+        let count: NSUInteger = unsafe { NSArray::count(calendars_nsarray) };
+
+        for i in 0..count {
+            unsafe {
+                let calendar: id = NSArray::objectAtIndex(calendars_nsarray, i);
+                if calendar == nil {
+                    continue;
+                }
+
+                // Get properties
+                let identifier: id = msg_send![calendar, calendarIdentifier];
+                let title: id = msg_send![calendar, title];
+                let color: id = msg_send![calendar, color]; // This is NSColor
+
+                let identifier_str = nsstring_to_string(identifier);
+                let title_str = nsstring_to_string(title);
+                let color_hex = nscolor_to_hex(color);
+
+                let calendar_info = CalendarInfo {
+                    identifier: identifier_str,
+                    title: title_str,
+                    color: color_hex,
+                };
+                calendars_vec.push(calendar_info);
+            }
+        }
+        Ok(calendars_vec)
+    }
+
+    fn events(
+        &self,
+        calendar_ids: &[String],
+        start_date_iso: &str,
+        end_date_iso: &str,
+    ) -> Result<Vec<EventInfo>> {
+        let _pool = unsafe { NSAutoreleasePool::new(nil) };
+
+        // Check auth status
+        match Self::authorization_status()? {
+            CalendarAuthorizationStatus::Authorized => (),
+            _ => return Err(Error::not_authorized("Calendar access not authorized.")),
+        }
+
+        let store = self.store_ptr()?;
+
+        unsafe {
+            // Convert dates
+            let start_date_ns = iso_string_to_nsdate(start_date_iso)
+                .ok_or_else(|| Error::invalid_argument(format!("Invalid start date format: {}", start_date_iso)))?;
+            let end_date_ns = iso_string_to_nsdate(end_date_iso)
+                .ok_or_else(|| Error::invalid_argument(format!("Invalid end date format: {}", end_date_iso)))?;
+
+            // Get calendar objects
+            let calendars_nsarray = if calendar_ids.is_empty() {
+                // If no specific IDs are provided, fetch from all accessible event calendars
+                let all_calendars: id = msg_send![store, calendarsForEntityType: EK_ENTITY_TYPE_EVENT];
+                if all_calendars == nil { NSArray::array(nil) } else { all_calendars } // Use empty array if nil
+            } else {
+                let calendar_objs = get_calendars_by_ids(store, calendar_ids);
+                if calendar_objs.is_empty() {
+                    // Return empty vec if no matching calendars found or provided IDs were invalid
+                    return Ok(Vec::new());
+                }
+                NSArray::arrayWithObjects(nil, &calendar_objs)
+            };
+
+            if calendars_nsarray == nil || NSArray::count(calendars_nsarray) == 0 {
+                return Ok(Vec::new()); // No calendars to search in
+            }
+
+            // Create predicate
+            // Equivalent to: [store predicateForEventsWithStartDate:startDate endDate:endDate calendars:calendars];
+            let predicate: id = msg_send![store, predicateForEventsWithStartDate:start_date_ns endDate:end_date_ns calendars:calendars_nsarray];
+
+            // Fetch events
+            // Equivalent to: [store eventsMatchingPredicate:predicate];
+            let events_nsarray: id = msg_send![store, eventsMatchingPredicate:predicate];
+
+            if events_nsarray == nil {
+                return Ok(Vec::new());
+            }
+
+            let mut events_vec = Vec::new();
+            let count: NSUInteger = NSArray::count(events_nsarray);
+
+            for i in 0..count {
+                let event: id = NSArray::objectAtIndex(events_nsarray, i);
+                events_vec.push(event_to_info(event));
+            }
+            Ok(events_vec)
+        }
+    }
+
+    fn create_event(&self, event: &EventDraft) -> Result<EventInfo> {
+        let _pool = unsafe { NSAutoreleasePool::new(nil) };
+        ensure_authorized()?;
+        let store = self.store_ptr()?;
+
+        unsafe {
+            let ek_event: id = msg_send![class!(EKEvent), eventWithEventStore: store];
+            apply_draft(store, ek_event, event)?;
+            save_event(store, ek_event, EK_SPAN_THIS_EVENT)?;
+            Ok(event_to_info(ek_event))
+        }
+    }
+
+    fn update_event(&self, event_id: &str, occurrence_date: DateTime<Utc>, event: &EventDraft) -> Result<EventInfo> {
+        let _pool = unsafe { NSAutoreleasePool::new(nil) };
+        ensure_authorized()?;
+        let store = self.store_ptr()?;
+
+        unsafe {
+            let ek_event = event_with_identifier(store, event_id)?;
+            let start_date: id = msg_send![ek_event, startDate];
+            let event = match nsdate_to_datetime_utc(start_date) {
+                Some(series_start) => event.for_series(occurrence_date, series_start, &chrono::Local)?,
+                None => event.clone(),
+            };
+            apply_draft(store, ek_event, &event)?;
+            // From the first occurrence on, so the whole series
+            save_event(store, ek_event, EK_SPAN_FUTURE_EVENTS)?;
+            Ok(event_to_info(ek_event))
+        }
+    }
+
+    fn delete_event(&self, event_id: &str) -> Result<()> {
+        let _pool = unsafe { NSAutoreleasePool::new(nil) };
+        ensure_authorized()?;
+        let store = self.store_ptr()?;
+
+        unsafe {
+            let ek_event = event_with_identifier(store, event_id)?;
+            let mut error: id = nil;
+            let removed: ObjcBOOL = msg_send![store, removeEvent: ek_event span: EK_SPAN_FUTURE_EVENTS commit: YES error: &mut error];
+            if removed != YES {
+                return Err(Error::platform(format!("Failed to delete the event: {}", nserror_message(error))));
+            }
+            Ok(())
+        }
+    }
+}
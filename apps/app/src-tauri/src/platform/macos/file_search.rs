@@ -0,0 +1,321 @@
+use cocoa::base::{id, nil, YES};
+use cocoa::foundation::{NSString as CocoaNSString, NSUInteger};
+use core_foundation::runloop::{CFRunLoopGetCurrent, CFRunLoopStop};
+use objc::{class, msg_send, sel, sel_impl};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use super::MacosPlatform;
+use crate::error::{Error, Result};
+use crate::platform::FileSearchPlatform;
+use crate::search_query::{predicate_format, FileQuery};
+use crate::spotlight_utils::{
+    FileSearchRequest, SearchSession, SpotlightAppInfo, MAX_SEARCH_RESULTS, SEARCH_TIMEOUT,
+};
+
+// How often the waiting thread checks for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// A simple wrapper for NSAutoreleasePool to be shared (unsafe: not thread-safe, but used to make it work)
+struct SharedAutoreleasePool {
+    pool: id,
+}
+
+// Mark the wrapper as Send (unsafe, but for our purposes)
+unsafe impl Send for SharedAutoreleasePool {}
+
+impl SharedAutoreleasePool {
+    fn new() -> Self {
+        let pool: id = unsafe { msg_send![class!(NSAutoreleasePool), new] };
+        SharedAutoreleasePool { pool }
+    }
+}
+
+impl Drop for SharedAutoreleasePool {
+    fn drop(&mut self) {
+        unsafe { let _: () = msg_send![self.pool, drain]; }
+    }
+}
+
+extern "C" {
+    static kMDItemPath: id;
+    static kMDItemDisplayName: id;
+    static kMDItemContentType: id;
+}
+
+impl FileSearchPlatform for MacosPlatform {
+    fn search_files(request: FileSearchRequest, session: Arc<SearchSession>) -> Result<()> {
+        let _shared_pool = SharedAutoreleasePool::new();
+
+        // Signalled from the notification block once gathering finished
+        let (tx, rx) = mpsc::channel::<()>();
+        let session_for_thread = session.clone();
+
+        // Spawn a thread to handle the Objective-C run loop
+        std::thread::spawn(move || {
+            unsafe {
+                // Number of query results already handed to the session, shared by both blocks
+                let reported = Rc::new(Cell::new(0usize));
+
+                let progress_session = session_for_thread.clone();
+                let progress_reported = reported.clone();
+                let progress_block = block::ConcreteBlock::new(move |notification: id| -> () {
+                    let query_obj: id = msg_send![notification, object];
+
+                    report_new_results(query_obj, &progress_session, &progress_reported);
+
+                    if progress_session.should_stop() {
+                        let _: () = msg_send![query_obj, stopQuery];
+                    }
+                });
+                let progress_block = progress_block.copy();
+
+                let finish_session = session_for_thread;
+                let finish_block = block::ConcreteBlock::new(move |notification: id| -> () {
+                    let query_obj: id = msg_send![notification, object];
+
+                    report_new_results(query_obj, &finish_session, &reported);
+
+                    // Signal completion
+                    let _ = tx.send(());
+
+                    let _: () = msg_send![query_obj, stopQuery];
+                    CFRunLoopStop(CFRunLoopGetCurrent());
+                });
+                let finish_block = finish_block.copy();
+
+                // Register for the notifications
+                let progress_notification_name =
+                    CocoaNSString::alloc(nil).init_str("NSMetadataQueryGatheringProgressNotification");
+                let finish_notification_name =
+                    CocoaNSString::alloc(nil).init_str("NSMetadataQueryDidFinishGatheringNotification");
+                let notification_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+                let main_queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+
+                let query_obj = create_metadata_query(&request);
+
+                let _: () = msg_send![notification_center,
+                    addObserverForName: progress_notification_name
+                    object: query_obj
+                    queue: main_queue
+                    usingBlock: progress_block
+                ];
+                let _: () = msg_send![notification_center,
+                    addObserverForName: finish_notification_name
+                    object: query_obj
+                    queue: main_queue
+                    usingBlock: finish_block
+                ];
+
+                // Start the query
+                let _: () = msg_send![query_obj, startQuery];
+                // Run the run loop until the query completes
+                use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoopRunInMode};
+                CFRunLoopRunInMode(kCFRunLoopDefaultMode, SEARCH_TIMEOUT.as_secs_f64(), 0);
+            }
+        });
+
+        // Wake up regularly so cancelled and timed out searches return right away
+        loop {
+            match rx.recv_timeout(POLL_INTERVAL) {
+                Ok(()) => return Ok(()),
+                Err(RecvTimeoutError::Timeout) if session.should_stop() => return Ok(()),
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(Error::platform("Spotlight search ended without reporting results"))
+                }
+            }
+        }
+    }
+}
+
+// Hands the results gathered since the last call over to the session
+unsafe fn report_new_results(query_obj: id, session: &SearchSession, reported: &Cell<usize>) {
+    // Keep the result list stable while reading it
+    let _: () = msg_send![query_obj, disableUpdates];
+
+    let query_results: id = msg_send![query_obj, results];
+    let count: usize = msg_send![query_results, count];
+    let end = std::cmp::min(count, MAX_SEARCH_RESULTS);
+
+    let mut batch = Vec::new();
+    for i in reported.get()..end {
+        let item: id = msg_send![query_results, objectAtIndex: i];
+        if let Some(info) = metadata_item_info(item) {
+            batch.push(info);
+        }
+    }
+    reported.set(std::cmp::max(reported.get(), end));
+
+    let _: () = msg_send![query_obj, enableUpdates];
+
+    session.push_batch(batch);
+}
+
+unsafe fn metadata_item_info(item: id) -> Option<SpotlightAppInfo> {
+    let path_value: id = msg_send![item, valueForAttribute: kMDItemPath];
+    let name_value: id = msg_send![item, valueForAttribute: kMDItemDisplayName];
+    let content_type_value: id = msg_send![item, valueForAttribute: kMDItemContentType];
+
+    if path_value == nil || name_value == nil || content_type_value == nil {
+        return None;
+    }
+
+    let path_str: *const i8 = msg_send![path_value, UTF8String];
+    let name_str: *const i8 = msg_send![name_value, UTF8String];
+    let content_type_str: *const i8 = msg_send![content_type_value, UTF8String];
+
+    let path = std::ffi::CStr::from_ptr(path_str)
+        .to_string_lossy()
+        .into_owned();
+
+    let name = std::ffi::CStr::from_ptr(name_str)
+        .to_string_lossy()
+        .into_owned();
+
+    let content_type = std::ffi::CStr::from_ptr(content_type_str)
+        .to_string_lossy()
+        .into_owned();
+
+    Some(SpotlightAppInfo {
+        path,
+        display_name: name,
+        content_type,
+        score: 0.0,
+        match_ranges: Vec::new(),
+    })
+}
+
+// Helper function to create sort descriptors array
+unsafe fn create_sort_descriptors() -> id {
+    let sort_key: id =
+        msg_send![class!(NSString), stringWithUTF8String:"kMDItemDisplayName".as_ptr()];
+    let sort_descriptor: id = msg_send![
+        class!(NSSortDescriptor),
+        sortDescriptorWithKey:sort_key
+        ascending:YES
+    ];
+    msg_send![class!(NSArray), arrayWithObject:sort_descriptor]
+}
+
+// Helper function to create value list attributes array
+unsafe fn create_value_list_attributes() -> id {
+    let value_list_attributes: id = msg_send![class!(NSMutableArray), array];
+    let display_name_attr: id =
+        msg_send![class!(NSString), stringWithUTF8String:"kMDItemDisplayName".as_ptr()];
+    let path_attr: id = msg_send![class!(NSString), stringWithUTF8String:"kMDItemPath".as_ptr()];
+    let content_type_attr: id =
+        msg_send![class!(NSString), stringWithUTF8String:"kMDItemContentType".as_ptr()];
+
+    let _: () = msg_send![value_list_attributes, addObject:display_name_attr];
+    let _: () = msg_send![value_list_attributes, addObject:path_attr];
+    let _: () = msg_send![value_list_attributes, addObject:content_type_attr];
+    value_list_attributes
+}
+
+// Helper function to create search scope array
+unsafe fn create_search_scope(search_only_in_home: bool) -> id {
+    let file_manager: id = msg_send![class!(NSFileManager), defaultManager];
+    let user_domain_mask: NSUInteger = 1; // NSUserDomainMask
+
+    // Get home directory
+    let home_url: id = msg_send![file_manager, homeDirectoryForCurrentUser];
+    let home_path: id = msg_send![home_url, path];
+
+    if search_only_in_home {
+        msg_send![class!(NSMutableArray), arrayWithObject: home_path]
+    } else {
+        // Standard directories to search
+        let directories = [
+            (15, "NSDownloadsDirectory"),
+            (9, "NSDocumentDirectory"),
+            (12, "NSDesktopDirectory"),
+        ];
+
+        let computer_scope: id =
+            msg_send![class!(NSString), stringWithUTF8String:"kMDQueryScopeComputer".as_ptr()];
+        let scope_array: id = msg_send![class!(NSMutableArray), arrayWithObject: computer_scope];
+
+        for (directory_constant, _name) in directories.iter() {
+            let urls: id = msg_send![
+                file_manager,
+                URLsForDirectory: *directory_constant as NSUInteger
+                inDomains: user_domain_mask
+            ];
+            let count: NSUInteger = msg_send![urls, count];
+            if urls != nil && count > 0 {
+                let url: id = msg_send![urls, firstObject];
+                if url != nil {
+                    let path: id = msg_send![url, path]; // Use path instead of relativePath
+                    if path != nil {
+                        // Check if path is not nil and not empty before adding
+                        let path_str = if path != nil {
+                            let ns_string: id = msg_send![path, description];
+                            let utf8_str: *const std::os::raw::c_char =
+                                msg_send![ns_string, UTF8String];
+                            if !utf8_str.is_null() {
+                                std::ffi::CStr::from_ptr(utf8_str).to_str().unwrap_or("")
+                            } else {
+                                ""
+                            }
+                        } else {
+                            ""
+                        };
+                        if !path_str.is_empty() {
+                            let _: () = msg_send![scope_array, addObject: path];
+                        }
+                    }
+                }
+            }
+        }
+        // Explicitly add home directory if not already covered or desired
+        // let _: () = msg_send![scope_array, addObject: home_path];
+
+        scope_array
+    }
+}
+
+unsafe fn create_metadata_query(request: &FileSearchRequest) -> id {
+    let query_obj: id = msg_send![class!(NSMetadataQuery), new];
+
+    // `in:` terms replace the default scopes
+    let query_scopes = request.query.scopes();
+    let scope_array = if query_scopes.is_empty() {
+        create_search_scope(request.search_only_in_home)
+    } else {
+        let scope_array: id = msg_send![class!(NSMutableArray), array];
+        for scope in query_scopes {
+            let path = CocoaNSString::alloc(nil).init_str(&scope.to_string_lossy());
+            let _: () = msg_send![scope_array, addObject: path];
+        }
+        scope_array
+    };
+    let _: () = msg_send![query_obj, setSearchScopes: scope_array];
+
+    let predicate: id = create_spotlight_predicate(&request.query, &request.extensions);
+    let _: () = msg_send![query_obj, setPredicate: predicate];
+
+    let sort_descriptors_array = create_sort_descriptors();
+    let _: () = msg_send![query_obj, setSortDescriptors:sort_descriptors_array];
+
+    let value_list_attributes_array = create_value_list_attributes();
+    let _: () = msg_send![query_obj, setValueListAttributes:value_list_attributes_array];
+
+    query_obj
+}
+
+fn create_spotlight_predicate(query: &FileQuery, allowed_extensions: &[String]) -> id {
+    unsafe {
+        let predicate_str = CocoaNSString::alloc(nil).init_str(&predicate_format(query, allowed_extensions, SystemTime::now()));
+        let predicate: id = msg_send![class!(NSPredicate), predicateWithFormat: predicate_str];
+        let _: () = msg_send![predicate_str, release];
+
+        // Try to retain the predicate to prevent it from being released too early
+        let _: () = msg_send![predicate, retain];
+
+        predicate
+    }
+}
@@ -0,0 +1,153 @@
+use cocoa::base::{id, nil};
+use cocoa::foundation::{NSString, NSString as CocoaNSString};
+use objc::{class, msg_send, sel, sel_impl};
+use std::collections::HashMap;
+
+use super::MacosPlatform;
+use crate::error::Result;
+use crate::icns_utils::{read_app_bundles, AppIcon};
+use crate::icon_cache::IconCacheState;
+use crate::icon_render::{resize_png, IconRequest};
+use crate::platform::IconPlatform;
+
+// Pixel size of file type icons when no size is requested, the first
+// representation of NSWorkspace icons
+const EXTENSION_ICON_SIZE: u32 = 32;
+
+impl IconPlatform for MacosPlatform {
+    fn load_app_info(
+        resources_paths: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, AppIcon>> {
+        Ok(read_app_bundles(resources_paths, request, cache))
+    }
+
+    fn load_extension_icons(
+        extensions: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, Vec<u8>>> {
+        let pixel_size = request.pixel_size(EXTENSION_ICON_SIZE);
+        let mut icon_map = HashMap::new();
+
+        for ext in extensions {
+            // System icons have no file to check, the cache expires them by age
+            let key = format!("extension:{}", ext);
+            if let Some(cached) = cache.get(&key, pixel_size) {
+                if let Some(png) = cached.png {
+                    icon_map.insert(ext, png);
+                }
+                continue;
+            }
+
+            let icon = unsafe {
+                // Special case for folder icon
+                if ext == "folder" {
+                    let ns_image_class = class!(NSImage);
+                    // NSImage.folderName in Swift is "NSFolder" in Objective-C
+                    let folder_name = NSString::alloc(nil).init_str("NSFolder");
+                    msg_send![ns_image_class, imageNamed:folder_name]
+                } else {
+                    // Regular file extension icon
+                    let workspace = class!(NSWorkspace);
+                    let shared_workspace: id = msg_send![workspace, sharedWorkspace];
+                    let ns_string = CocoaNSString::alloc(nil).init_str(&ext);
+                    let icon: id = msg_send![shared_workspace, iconForFileType:ns_string];
+                    icon
+                }
+            };
+
+            if icon.is_null() {
+                continue;
+            }
+
+            let tiff_data: id = unsafe { msg_send![icon, TIFFRepresentation] };
+            if tiff_data.is_null() {
+                continue;
+            }
+
+            let bitmap_rep = unsafe { best_bitmap_rep(tiff_data, pixel_size) };
+
+            if bitmap_rep.is_null() {
+                continue;
+            }
+
+            let png_data: id = unsafe {
+                let properties: id = msg_send![class!(NSDictionary), dictionary];
+                msg_send![bitmap_rep, representationUsingType:4 properties:properties]
+            };
+
+            if png_data.is_null() {
+                continue;
+            }
+
+            let png_bytes: &[u8] = unsafe {
+                let length = msg_send![png_data, length];
+                std::slice::from_raw_parts(msg_send![png_data, bytes], length)
+            };
+            let png = resize_png(png_bytes, pixel_size).unwrap_or_else(|| png_bytes.to_vec());
+
+            cache.put(&key, &[], pixel_size, None, Some(&png));
+            icon_map.insert(ext, png);
+        }
+
+        Ok(icon_map)
+    }
+
+    // NSBitmapImageRep reads JPEG 2000 through ImageIO and writes it back as PNG
+    fn decode_jpeg2000(data: &[u8]) -> Option<Vec<u8>> {
+        unsafe {
+            let ns_data: id = msg_send![class!(NSData), dataWithBytes:data.as_ptr() as *const std::ffi::c_void length:data.len()];
+            if ns_data.is_null() {
+                return None;
+            }
+
+            let bitmap_rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+            let bitmap_rep: id = msg_send![bitmap_rep, initWithData:ns_data];
+            if bitmap_rep.is_null() {
+                return None;
+            }
+
+            let properties: id = msg_send![class!(NSDictionary), dictionary];
+            let png_data: id = msg_send![bitmap_rep, representationUsingType:4 properties:properties];
+            let png_bytes = if png_data.is_null() {
+                None
+            } else {
+                let length: usize = msg_send![png_data, length];
+                let bytes: *const u8 = msg_send![png_data, bytes];
+                Some(std::slice::from_raw_parts(bytes, length).to_vec())
+            };
+
+            let _: () = msg_send![bitmap_rep, release];
+            png_bytes
+        }
+    }
+}
+
+// The TIFF of an NSImage holds one bitmap per size (16 to 1024 pixels for
+// most icons). Picks the smallest one at least `pixel_size` wide, or the
+// largest one, so resampling only ever scales down.
+unsafe fn best_bitmap_rep(tiff_data: id, pixel_size: u32) -> id {
+    let reps: id = msg_send![class!(NSBitmapImageRep), imageRepsWithData:tiff_data];
+    if reps.is_null() {
+        return nil;
+    }
+
+    let count: usize = msg_send![reps, count];
+    let mut best: Option<(isize, id)> = None;
+    for index in 0..count {
+        let rep: id = msg_send![reps, objectAtIndex:index];
+        let width: isize = msg_send![rep, pixelsWide];
+        let better = match best {
+            None => true,
+            Some((best_width, _)) if best_width < pixel_size as isize => width > best_width,
+            Some((best_width, _)) => width >= pixel_size as isize && width < best_width,
+        };
+        if better {
+            best = Some((width, rep));
+        }
+    }
+
+    best.map(|(_, rep)| rep).unwrap_or(nil)
+}
@@ -0,0 +1,14 @@
+// src-tauri/src/platform/macos/mod.rs
+
+mod apps;
+mod calendar;
+mod file_search;
+mod icons;
+mod open_with;
+mod panel;
+mod window;
+mod workspace;
+
+pub use calendar::EventKitCalendar;
+
+pub struct MacosPlatform;
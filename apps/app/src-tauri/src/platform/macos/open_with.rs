@@ -0,0 +1,100 @@
+use core_foundation::base::TCFType;
+use core_foundation::string::{CFString, CFStringRef};
+use std::path::Path;
+use std::process::Command;
+
+use super::MacosPlatform;
+use crate::error::{Error, Result};
+use crate::open_with::{applications_for_file, Associations, FileTarget, OpenWithApp};
+use crate::platform::{AppDiscoveryPlatform, OpenWithPlatform};
+
+// kLSRolesAll
+const ROLES_ALL: u32 = 0xFFFF_FFFF;
+const FOLDER_TYPE: &str = "public.folder";
+
+#[link(name = "CoreServices", kind = "framework")]
+extern "C" {
+    static kUTTagClassFilenameExtension: CFStringRef;
+    fn UTTypeCreatePreferredIdentifierForTag(
+        tag_class: CFStringRef,
+        tag: CFStringRef,
+        conforming_to: CFStringRef,
+    ) -> CFStringRef;
+    fn UTTypeConformsTo(content_type: CFStringRef, conforms_to: CFStringRef) -> u8;
+    fn LSCopyDefaultRoleHandlerForContentType(content_type: CFStringRef, role: u32) -> CFStringRef;
+}
+
+impl OpenWithPlatform for MacosPlatform {
+    fn applications_for_file(target: &FileTarget) -> Result<Vec<OpenWithApp>> {
+        let content_type = content_type(target);
+        let associations = Associations {
+            default: content_type.as_ref().and_then(default_handler),
+            ..Associations::default()
+        };
+
+        // Document types name the most general type they handle, so the file
+        // matches when its type conforms to it (public.python-script to
+        // public.plain-text)
+        let is_file_type = |declared: &str| {
+            content_type.as_ref().is_some_and(|content_type| unsafe {
+                UTTypeConformsTo(
+                    content_type.as_concrete_TypeRef(),
+                    CFString::new(declared).as_concrete_TypeRef(),
+                ) != 0
+            })
+        };
+
+        Ok(applications_for_file(
+            Self::installed_apps()?,
+            target.extension.as_deref(),
+            &is_file_type,
+            &associations,
+        ))
+    }
+
+    fn open_with(file_path: &Path, app_path: &str) -> Result<()> {
+        let status = Command::new("open")
+            .arg("-a")
+            .arg(app_path)
+            .arg(file_path)
+            .status()
+            .map_err(|e| Error::platform("Failed to run open").with_details(e))?;
+        if !status.success() {
+            return Err(Error::platform(format!(
+                "Could not open {} with {}",
+                file_path.display(),
+                app_path
+            )));
+        }
+        Ok(())
+    }
+}
+
+// Uniform type identifier of the file, from its extension
+fn content_type(target: &FileTarget) -> Option<CFString> {
+    if target.path.as_deref().is_some_and(Path::is_dir) {
+        return Some(CFString::new(FOLDER_TYPE));
+    }
+
+    let extension = CFString::new(target.extension.as_deref()?);
+    unsafe {
+        let content_type = UTTypeCreatePreferredIdentifierForTag(
+            kUTTagClassFilenameExtension,
+            extension.as_concrete_TypeRef(),
+            std::ptr::null(),
+        );
+        (!content_type.is_null()).then(|| CFString::wrap_under_create_rule(content_type))
+    }
+}
+
+// Bundle ID of the app that opens files of the type
+fn default_handler(content_type: &CFString) -> Option<String> {
+    unsafe {
+        let bundle_id =
+            LSCopyDefaultRoleHandlerForContentType(content_type.as_concrete_TypeRef(), ROLES_ALL);
+        if bundle_id.is_null() {
+            return None;
+        }
+        Some(CFString::wrap_under_create_rule(bundle_id).to_string())
+    }
+}
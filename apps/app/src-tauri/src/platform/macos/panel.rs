@@ -0,0 +1,83 @@
+use cocoa::base::{id, YES};
+use objc::{class, msg_send, sel, sel_impl};
+use tauri::{App, AppHandle, Listener as _, Manager as _, Runtime};
+use tauri_nspanel::ManagerExt as _;
+
+use super::window::WebviewWindowExt as _;
+use super::MacosPlatform;
+use crate::platform::PanelPlatform;
+use crate::MAIN_WINDOW_LABEL;
+
+impl PanelPlatform for MacosPlatform {
+    fn setup<R: Runtime>(app: &mut App<R>) -> tauri::Result<()> {
+        app.set_activation_policy(tauri::ActivationPolicy::Accessory);
+
+        let handle = app.app_handle();
+
+        let window = handle.get_webview_window(MAIN_WINDOW_LABEL).unwrap();
+
+        // Convert the window to a spotlight panel
+        let panel = window.to_spotlight_panel()?;
+
+        let _ = window.with_webview(|webview| {
+            let inner = webview.inner();
+            unsafe {
+                let _: () = msg_send![inner as id, setInspectable: YES];
+            }
+        });
+
+        unsafe {
+            let panel = app.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
+            let view = panel.content_view();
+
+            // Create a block to execute our UI updates
+            use block2::ConcreteBlock;
+
+            let update_block = ConcreteBlock::new(move || -> () {
+                let layer: id = msg_send![view as id, layer];
+                let _: () = msg_send![layer, setCornerRadius: 20.0];
+                let _: () = msg_send![layer, setMasksToBounds: YES];
+                // Force layout update - using NSView methods
+                let _: () = msg_send![view as id, setNeedsDisplay: YES];
+
+                // Update the layer
+                let _: () = msg_send![layer, setNeedsDisplay];
+            });
+
+            let update_block = update_block.copy();
+
+            // Get the main dispatch queue and schedule the block
+            let main_queue: id = msg_send![class!(NSOperationQueue), mainQueue];
+            let _: () = msg_send![main_queue, addOperationWithBlock:update_block];
+        }
+
+        handle.listen(
+            format!("{}_panel_did_resign_key", MAIN_WINDOW_LABEL),
+            move |_| {
+                panel.order_out(None);
+            },
+        );
+
+        Ok(())
+    }
+
+    fn show<R: Runtime>(app_handle: &AppHandle<R>) {
+        let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
+
+        panel.show();
+    }
+
+    fn hide<R: Runtime>(app_handle: &AppHandle<R>) {
+        let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
+
+        if panel.is_visible() {
+            panel.order_out(None);
+        }
+    }
+
+    fn is_visible<R: Runtime>(app_handle: &AppHandle<R>) -> bool {
+        let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
+
+        panel.is_visible()
+    }
+}
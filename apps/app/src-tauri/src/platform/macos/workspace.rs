@@ -0,0 +1,92 @@
+use cocoa::base::{id, nil};
+use objc::{class, msg_send, sel, sel_impl};
+
+use super::MacosPlatform;
+use crate::error::{Error, Result};
+use crate::platform::WorkspacePlatform;
+
+impl WorkspacePlatform for MacosPlatform {
+    fn activate_application_by_name(app_name: &str) -> Result<bool> {
+        unsafe {
+            // Create an autorelease pool
+            let pool: id = msg_send![class!(NSAutoreleasePool), new];
+
+            // Get the shared workspace
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+
+            // Get running applications
+            let running_apps: id = msg_send![workspace, runningApplications];
+            let count: usize = msg_send![running_apps, count];
+
+            // Find and activate the app
+            let mut activated = false;
+
+            for i in 0..count {
+                let app: id = msg_send![running_apps, objectAtIndex: i];
+                let name: id = msg_send![app, localizedName];
+
+                if name != nil {
+                    let name_str: *const i8 = msg_send![name, UTF8String];
+                    let localized_name = std::ffi::CStr::from_ptr(name_str)
+                        .to_string_lossy()
+                        .into_owned();
+
+                    if localized_name == app_name {
+                        // Activate the app
+                        let options = 0; // NSApplicationActivateIgnoringOtherApps = 1 << 0
+                        let success: bool = msg_send![app, activateWithOptions: options];
+
+                        activated = success;
+                        break;
+                    }
+                }
+            }
+
+            // Release the pool
+            let _: () = msg_send![pool, drain];
+
+            if activated {
+                Ok(true)
+            } else {
+                Err(Error::not_found(format!("Application '{}' not found or could not be activated", app_name)))
+            }
+        }
+    }
+
+    fn frontmost_application_name() -> Result<String> {
+        unsafe {
+            // Create an autorelease pool
+            let pool: id = msg_send![class!(NSAutoreleasePool), new];
+
+            // Get the shared workspace
+            let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+
+            // Get the frontmost application
+            let frontmost_app: id = msg_send![workspace, frontmostApplication];
+
+            if frontmost_app == nil {
+                let _: () = msg_send![pool, drain];
+                return Err(Error::not_found("No frontmost application found"));
+            }
+
+            // Get the localized name of the frontmost application
+            let name: id = msg_send![frontmost_app, localizedName];
+
+            if name == nil {
+                let _: () = msg_send![pool, drain];
+                return Err(Error::platform("Could not get name of frontmost application"));
+            }
+
+            // Convert the name to a Rust string
+            let name_str: *const i8 = msg_send![name, UTF8String];
+            let localized_name = std::ffi::CStr::from_ptr(name_str)
+                .to_string_lossy()
+                .into_owned();
+
+            // Release the pool
+            let _: () = msg_send![pool, drain];
+
+            Ok(localized_name)
+        }
+    }
+}
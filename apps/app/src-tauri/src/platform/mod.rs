@@ -0,0 +1,110 @@
+// src-tauri/src/platform/mod.rs
+//
+// Platform abstraction layer. Every OS specific capability the commands rely on
+// is described by a trait below, and each supported OS provides one
+// implementation of it. Commands only talk to `NativePlatform` and
+// `NativeCalendar`, so they compile the same way on every target.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{App, AppHandle, Runtime};
+
+use crate::calendar_utils::{CalendarAuthorizationStatus, CalendarInfo, EventDraft, EventInfo};
+use crate::error::Result;
+use crate::icns_utils::{AppIcon, InstalledApp};
+use crate::icon_cache::IconCacheState;
+use crate::icon_render::IconRequest;
+use crate::open_with::{FileTarget, OpenWithApp};
+use crate::spotlight_utils::{FileSearchRequest, SearchSession};
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+pub use macos::{EventKitCalendar as NativeCalendar, MacosPlatform as NativePlatform};
+
+#[cfg(not(target_os = "macos"))]
+mod linux;
+#[cfg(not(target_os = "macos"))]
+pub use linux::{LinuxCalendar as NativeCalendar, LinuxPlatform as NativePlatform};
+
+// The launcher window: a non-activating NSPanel on macOS, a plain window elsewhere
+pub trait PanelPlatform {
+    // Called once from the Tauri setup hook
+    fn setup<R: Runtime>(app: &mut App<R>) -> tauri::Result<()>;
+    fn show<R: Runtime>(app_handle: &AppHandle<R>);
+    fn hide<R: Runtime>(app_handle: &AppHandle<R>);
+    fn is_visible<R: Runtime>(app_handle: &AppHandle<R>) -> bool;
+}
+
+// Running applications
+pub trait WorkspacePlatform {
+    fn activate_application_by_name(app_name: &str) -> Result<bool>;
+    fn frontmost_application_name() -> Result<String>;
+}
+
+// File search backend used by `search_spotlight_apps`
+pub trait FileSearchPlatform {
+    // Blocks until the search finished or `session.should_stop()`, handing
+    // matches over in batches as they come in
+    fn search_files(request: FileSearchRequest, session: Arc<SearchSession>) -> Result<()>;
+}
+
+// Application and file type icons as PNGs, rendered at the size of `request`.
+// Implementations look in `cache` first and store what they had to compute;
+// the caller flushes it and encodes the icons in the requested format.
+pub trait IconPlatform {
+    fn load_app_info(
+        resources_paths: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, AppIcon>>;
+    fn load_extension_icons(
+        extensions: Vec<String>,
+        request: &IconRequest,
+        cache: &IconCacheState,
+    ) -> Result<HashMap<String, Vec<u8>>>;
+    // PNG data of a JPEG 2000 image inside an ICNS file, None without a decoder
+    fn decode_jpeg2000(data: &[u8]) -> Option<Vec<u8>>;
+}
+
+// Installed applications, from .desktop files on Linux and .app bundles on macOS
+pub trait AppDiscoveryPlatform {
+    // Sorted by localized name
+    fn installed_apps() -> Result<Vec<InstalledApp>>;
+}
+
+// "Open with…": applications for a file and launching one of them
+pub trait OpenWithPlatform {
+    // Ranked by `open_with::applications_for_file`, the default one first
+    fn applications_for_file(target: &FileTarget) -> Result<Vec<OpenWithApp>>;
+    // `app_path` is a path `applications_for_file` returned
+    fn open_with(file_path: &Path, app_path: &str) -> Result<()>;
+}
+
+// Calendar provider. Unlike the other traits it carries state (the EventKit
+// store on macOS), so an instance lives in `CalendarState`.
+pub trait CalendarPlatform: Send + Sync + Sized {
+    fn new() -> Self;
+    fn authorization_status() -> Result<CalendarAuthorizationStatus>;
+    // Blocks until the user answered the permission prompt
+    fn request_access(&self) -> Result<CalendarAuthorizationStatus>;
+    fn calendars(&self) -> Result<Vec<CalendarInfo>>;
+    fn events(
+        &self,
+        calendar_ids: &[String],
+        start_date_iso: &str,
+        end_date_iso: &str,
+    ) -> Result<Vec<EventInfo>>;
+    // Changes to a recurring event apply to all of its occurrences, see
+    // `EventDraft::for_series`
+    fn create_event(&self, event: &EventDraft) -> Result<EventInfo>;
+    fn update_event(
+        &self,
+        event_id: &str,
+        occurrence_date: DateTime<Utc>,
+        event: &EventDraft,
+    ) -> Result<EventInfo>;
+    fn delete_event(&self, event_id: &str) -> Result<()>;
+}
@@ -0,0 +1,1042 @@
+// src-tauri/src/recurrence.rs
+//
+// Recurring events (RFC 5545 RRULE, RDATE, EXDATE). EventKit expands
+// recurrences itself; calendars read in Rust (.ics files, CalDAV) go through
+// here. Rules are expanded in wall-clock time of the event's zone and only
+// then resolved to instants, so a weekly 10:00 meeting stays at 10:00 across
+// DST changes. Supports FREQ from MINUTELY to YEARLY with INTERVAL, COUNT,
+// UNTIL, BYMONTH, BYMONTHDAY, BYYEARDAY, BYDAY, BYHOUR, BYMINUTE, BYSECOND,
+// BYSETPOS and WKST. SECONDLY and BYWEEKNO are rejected as unsupported.
+
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Timelike, Utc, Weekday,
+};
+use std::ops::RangeInclusive;
+
+use crate::error::{Error, Result};
+
+// Periods in a row without an occurrence after which a rule is taken to be
+// exhausted, e.g. FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30
+const MAX_EMPTY_PERIODS: u32 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frequency {
+    Minutely,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// A BYDAY entry: every Monday (MO), or the 2nd or last one of the month or
+// year (2MO, -1MO)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeekdayNum {
+    pub nth: Option<i32>,
+    pub weekday: Weekday,
+}
+
+// UNTIL is a date for all-day events, UTC for events in a time zone and
+// floating for floating ones
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Until {
+    Date(NaiveDate),
+    Floating(NaiveDateTime),
+    Utc(DateTime<Utc>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_month: Vec<u32>,
+    // Negative values count from the end of the month, like in BYYEARDAY and BYSETPOS
+    pub by_month_day: Vec<i32>,
+    pub by_year_day: Vec<i32>,
+    pub by_day: Vec<WeekdayNum>,
+    pub by_hour: Vec<u32>,
+    pub by_minute: Vec<u32>,
+    pub by_second: Vec<u32>,
+    pub by_set_pos: Vec<i32>,
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    // The value of an RRULE property: FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE
+    pub fn parse(rule: &str) -> Result<Self> {
+        let invalid =
+            |part: &str| Error::invalid_argument(format!("Invalid recurrence rule part: {}", part));
+
+        let mut frequency = None;
+        let mut parsed = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_year_day: Vec::new(),
+            by_day: Vec::new(),
+            by_hour: Vec::new(),
+            by_minute: Vec::new(),
+            by_second: Vec::new(),
+            by_set_pos: Vec::new(),
+            week_start: Weekday::Mon,
+        };
+
+        for part in rule.trim().split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=').ok_or_else(|| invalid(part))?;
+            let value = value.to_ascii_uppercase();
+            match name.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "MINUTELY" => Frequency::Minutely,
+                        "HOURLY" => Frequency::Hourly,
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        "SECONDLY" => {
+                            return Err(Error::unsupported("FREQ=SECONDLY is not supported"))
+                        }
+                        _ => return Err(invalid(part)),
+                    })
+                }
+                "INTERVAL" => {
+                    parsed.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| invalid(part))?
+                }
+                "COUNT" => parsed.count = Some(value.parse().map_err(|_| invalid(part))?),
+                "UNTIL" => parsed.until = Some(parse_until(&value).ok_or_else(|| invalid(part))?),
+                "BYMONTH" => parsed.by_month = parse_numbers(&value, 1..=12, false, part)?,
+                "BYMONTHDAY" => parsed.by_month_day = parse_numbers(&value, 1..=31, true, part)?,
+                "BYYEARDAY" => parsed.by_year_day = parse_numbers(&value, 1..=366, true, part)?,
+                "BYHOUR" => parsed.by_hour = parse_numbers(&value, 0..=23, false, part)?,
+                "BYMINUTE" => parsed.by_minute = parse_numbers(&value, 0..=59, false, part)?,
+                "BYSECOND" => parsed.by_second = parse_numbers(&value, 0..=59, false, part)?,
+                "BYSETPOS" => parsed.by_set_pos = parse_numbers(&value, 1..=366, true, part)?,
+                "BYDAY" => {
+                    parsed.by_day = value
+                        .split(',')
+                        .map(|day| parse_weekday_num(day).ok_or_else(|| invalid(part)))
+                        .collect::<Result<_>>()?
+                }
+                "WKST" => parsed.week_start = parse_weekday(&value).ok_or_else(|| invalid(part))?,
+                "BYWEEKNO" => return Err(Error::unsupported("BYWEEKNO is not supported")),
+                // Extensions (X-NAME=...)
+                _ => {}
+            }
+        }
+
+        parsed.frequency = frequency.ok_or_else(|| invalid(rule))?;
+        Ok(parsed)
+    }
+
+    // `start`, then the occurrences the rule generates after it, in order.
+    // RFC 5545 counts DTSTART as the first occurrence even if the rule
+    // wouldn't generate it.
+    pub fn occurrences<Tz: TimeZone>(&self, start: &DateTime<Tz>) -> Occurrences<'_, Tz> {
+        Occurrences {
+            rule: self,
+            zone: start.timezone(),
+            start: start.naive_local(),
+            period: 0,
+            pending: vec![start.naive_local()].into_iter(),
+            emitted: 0,
+            empty_periods: 0,
+            finished: false,
+        }
+    }
+
+    fn is_within_until<Tz: TimeZone>(&self, occurrence: &DateTime<Tz>) -> bool {
+        match self.until {
+            None => true,
+            Some(Until::Date(until)) => occurrence.naive_local().date() <= until,
+            Some(Until::Floating(until)) => occurrence.naive_local() <= until,
+            Some(Until::Utc(until)) => occurrence.with_timezone(&Utc) <= until,
+        }
+    }
+
+    // Wall-clock times of the `index`th period (year, month, week, ...) after
+    // the one `start` is in. None once the dates overflow.
+    fn period(&self, start: NaiveDateTime, index: u32) -> Option<Vec<NaiveDateTime>> {
+        let step = i64::from(index) * i64::from(self.interval);
+        let date = start.date();
+
+        let days = match self.frequency {
+            Frequency::Yearly => {
+                let year = i32::try_from(i64::from(date.year()) + step).ok()?;
+                self.year_days(year, date)
+            }
+            Frequency::Monthly => {
+                let month = date
+                    .with_day(1)?
+                    .checked_add_months(Months::new(u32::try_from(step).ok()?))?;
+                if !self.by_month.is_empty() && !self.by_month.contains(&month.month()) {
+                    Vec::new()
+                } else {
+                    self.month_days(month.year(), month.month(), date)
+                }
+            }
+            Frequency::Weekly => {
+                let days_into_week = (date.weekday().num_days_from_monday() + 7
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let week = date
+                    .checked_sub_signed(Duration::days(days_into_week.into()))?
+                    .checked_add_signed(Duration::try_weeks(step)?)?;
+                week.iter_days()
+                    .take(7)
+                    .filter(|day| match self.by_day.is_empty() {
+                        true => day.weekday() == date.weekday(),
+                        false => self
+                            .by_day
+                            .iter()
+                            .any(|by_day| by_day.weekday == day.weekday()),
+                    })
+                    .filter(|day| self.by_month.is_empty() || self.by_month.contains(&day.month()))
+                    .collect()
+            }
+            Frequency::Daily => {
+                let day = date.checked_add_signed(Duration::try_days(step)?)?;
+                self.matches_day(day).then_some(day).into_iter().collect()
+            }
+            // BYHOUR (and BYMINUTE under MINUTELY) limit, smaller units expand
+            Frequency::Hourly => {
+                let time = start.checked_add_signed(Duration::try_hours(step)?)?;
+                if !self.matches_day(time.date()) || !matches(&self.by_hour, time.hour()) {
+                    return Some(Vec::new());
+                }
+                return Some(self.select_positions(self.expand_times(
+                    time.date(),
+                    &[time.hour()],
+                    &values_or(&self.by_minute, time.minute()),
+                    &values_or(&self.by_second, time.second()),
+                )));
+            }
+            Frequency::Minutely => {
+                let time = start.checked_add_signed(Duration::try_minutes(step)?)?;
+                if !self.matches_day(time.date())
+                    || !matches(&self.by_hour, time.hour())
+                    || !matches(&self.by_minute, time.minute())
+                {
+                    return Some(Vec::new());
+                }
+                return Some(self.select_positions(self.expand_times(
+                    time.date(),
+                    &[time.hour()],
+                    &[time.minute()],
+                    &values_or(&self.by_second, time.second()),
+                )));
+            }
+        };
+
+        let time = start.time();
+        let hours = values_or(&self.by_hour, time.hour());
+        let minutes = values_or(&self.by_minute, time.minute());
+        let seconds = values_or(&self.by_second, time.second());
+        let mut candidates: Vec<NaiveDateTime> = days
+            .into_iter()
+            .flat_map(|day| self.expand_times(day, &hours, &minutes, &seconds))
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+        Some(self.select_positions(candidates))
+    }
+
+    fn expand_times(
+        &self,
+        day: NaiveDate,
+        hours: &[u32],
+        minutes: &[u32],
+        seconds: &[u32],
+    ) -> Vec<NaiveDateTime> {
+        let mut times = Vec::with_capacity(hours.len() * minutes.len() * seconds.len());
+        for hour in hours {
+            for minute in minutes {
+                for second in seconds {
+                    times.extend(
+                        NaiveTime::from_hms_opt(*hour, *minute, *second)
+                            .map(|time| day.and_time(time)),
+                    );
+                }
+            }
+        }
+        times.sort();
+        times
+    }
+
+    fn year_days(&self, year: i32, start: NaiveDate) -> Vec<NaiveDate> {
+        if !self.by_year_day.is_empty() {
+            let length = days_in_year(year);
+            let mut days: Vec<NaiveDate> = self
+                .by_year_day
+                .iter()
+                .filter_map(|day| resolve_index(*day, length))
+                .filter_map(|day| NaiveDate::from_yo_opt(year, day))
+                .filter(|day| self.by_month.is_empty() || self.by_month.contains(&day.month()))
+                .filter(|day| self.by_month_day.is_empty() || self.matches_month_day(*day))
+                .filter(|day| self.by_day.is_empty() || self.matches_weekday(*day))
+                .collect();
+            days.sort();
+            return days;
+        }
+
+        // BYDAY alone picks weekdays of the whole year: 20MO is the 20th
+        // Monday of the year
+        if !self.by_day.is_empty() && self.by_month.is_empty() && self.by_month_day.is_empty() {
+            let Some(first) = NaiveDate::from_ymd_opt(year, 1, 1) else {
+                return Vec::new();
+            };
+            let year: Vec<NaiveDate> = first
+                .iter_days()
+                .take(days_in_year(year) as usize)
+                .collect();
+            return self.weekdays_in(&year);
+        }
+
+        let months = if !self.by_month.is_empty() {
+            self.by_month.clone()
+        } else if !self.by_month_day.is_empty() {
+            (1..=12).collect()
+        } else {
+            vec![start.month()]
+        };
+        let mut days: Vec<NaiveDate> = months
+            .into_iter()
+            .flat_map(|month| self.month_days(year, month, start))
+            .collect();
+        days.sort();
+        days
+    }
+
+    fn month_days(&self, year: i32, month: u32, start: NaiveDate) -> Vec<NaiveDate> {
+        let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+            return Vec::new();
+        };
+        let length = days_in_month(first);
+
+        let mut days: Vec<NaiveDate> = if !self.by_month_day.is_empty() {
+            // BYDAY only limits BYMONTHDAY: Friday the 13th
+            self.by_month_day
+                .iter()
+                .filter_map(|day| resolve_index(*day, length))
+                .filter_map(|day| first.with_day(day))
+                .filter(|day| self.by_day.is_empty() || self.matches_weekday(*day))
+                .collect()
+        } else if !self.by_day.is_empty() {
+            let month: Vec<NaiveDate> = first.iter_days().take(length as usize).collect();
+            self.weekdays_in(&month)
+        } else {
+            // Months without the start's day (the 31st) are skipped
+            first.with_day(start.day()).into_iter().collect()
+        };
+        days.sort();
+        days.dedup();
+        days
+    }
+
+    // The BYDAY days among `span`, nth ones counted within it
+    fn weekdays_in(&self, span: &[NaiveDate]) -> Vec<NaiveDate> {
+        let mut days = Vec::new();
+        for by_day in &self.by_day {
+            let matching: Vec<NaiveDate> = span
+                .iter()
+                .copied()
+                .filter(|day| day.weekday() == by_day.weekday)
+                .collect();
+            match by_day.nth {
+                None => days.extend(matching),
+                Some(nth) => days.extend(
+                    resolve_index(nth, matching.len() as u32)
+                        .map(|index| matching[index as usize - 1]),
+                ),
+            }
+        }
+        days.sort();
+        days.dedup();
+        days
+    }
+
+    // BYMONTH, BYMONTHDAY, BYYEARDAY and BYDAY as filters, for DAILY and shorter periods
+    fn matches_day(&self, day: NaiveDate) -> bool {
+        (self.by_month.is_empty() || self.by_month.contains(&day.month()))
+            && (self.by_month_day.is_empty() || self.matches_month_day(day))
+            && (self.by_year_day.is_empty()
+                || self.by_year_day.iter().any(|year_day| {
+                    resolve_index(*year_day, days_in_year(day.year())) == Some(day.ordinal())
+                }))
+            && (self.by_day.is_empty() || self.matches_weekday(day))
+    }
+
+    fn matches_month_day(&self, day: NaiveDate) -> bool {
+        let length = days_in_month(day);
+        self.by_month_day
+            .iter()
+            .any(|month_day| resolve_index(*month_day, length) == Some(day.day()))
+    }
+
+    fn matches_weekday(&self, day: NaiveDate) -> bool {
+        self.by_day
+            .iter()
+            .any(|by_day| by_day.weekday == day.weekday())
+    }
+
+    fn select_positions(&self, candidates: Vec<NaiveDateTime>) -> Vec<NaiveDateTime> {
+        if self.by_set_pos.is_empty() {
+            return candidates;
+        }
+        let mut selected: Vec<NaiveDateTime> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|position| resolve_index(*position, candidates.len() as u32))
+            .map(|index| candidates[index as usize - 1])
+            .collect();
+        selected.sort();
+        selected.dedup();
+        selected
+    }
+}
+
+pub struct Occurrences<'a, Tz: TimeZone> {
+    rule: &'a RecurrenceRule,
+    zone: Tz,
+    start: NaiveDateTime,
+    // Index of the next period to expand
+    period: u32,
+    pending: std::vec::IntoIter<NaiveDateTime>,
+    emitted: u32,
+    empty_periods: u32,
+    finished: bool,
+}
+
+impl<Tz: TimeZone> Iterator for Occurrences<'_, Tz> {
+    type Item = DateTime<Tz>;
+
+    fn next(&mut self) -> Option<DateTime<Tz>> {
+        while !self.finished {
+            if let Some(local) = self.pending.next() {
+                let occurrence = resolve_local(&self.zone, local);
+                if self.rule.count.is_some_and(|count| self.emitted >= count)
+                    || !self.rule.is_within_until(&occurrence)
+                {
+                    self.finished = true;
+                    return None;
+                }
+                self.emitted += 1;
+                return Some(occurrence);
+            }
+
+            let Some(mut candidates) = self.rule.period(self.start, self.period) else {
+                self.finished = true;
+                return None;
+            };
+            self.period += 1;
+            candidates.retain(|candidate| *candidate > self.start);
+            if candidates.is_empty() {
+                self.empty_periods += 1;
+                self.finished = self.empty_periods >= MAX_EMPTY_PERIODS;
+            } else {
+                self.empty_periods = 0;
+            }
+            self.pending = candidates.into_iter();
+        }
+        None
+    }
+}
+
+// DTSTART with the RRULEs, RDATEs and EXDATEs of an event
+#[derive(Clone, Debug)]
+pub struct RecurrenceSet<Tz: TimeZone> {
+    pub start: DateTime<Tz>,
+    pub rules: Vec<RecurrenceRule>,
+    pub dates: Vec<DateTime<Utc>>,
+    pub exceptions: Vec<DateTime<Utc>>,
+}
+
+impl<Tz: TimeZone> RecurrenceSet<Tz> {
+    pub fn new(start: DateTime<Tz>) -> Self {
+        Self {
+            start,
+            rules: Vec::new(),
+            dates: Vec::new(),
+            exceptions: Vec::new(),
+        }
+    }
+
+    // Starts of the occurrences in `after..before`, in order
+    pub fn between(&self, after: DateTime<Utc>, before: DateTime<Utc>) -> Vec<DateTime<Tz>> {
+        let zone = self.start.timezone();
+        let mut starts: Vec<DateTime<Tz>> = self
+            .rules
+            .iter()
+            .flat_map(|rule| {
+                rule.occurrences(&self.start)
+                    .take_while(|occurrence| occurrence.with_timezone(&Utc) < before)
+            })
+            .collect();
+        if self.rules.is_empty() {
+            starts.push(self.start.clone());
+        }
+        starts.extend(self.dates.iter().map(|date| date.with_timezone(&zone)));
+
+        starts.retain(|occurrence| {
+            let occurrence = occurrence.with_timezone(&Utc);
+            occurrence >= after && occurrence < before && !self.exceptions.contains(&occurrence)
+        });
+        starts.sort_by_key(|occurrence| occurrence.with_timezone(&Utc));
+        starts.dedup_by_key(|occurrence| occurrence.with_timezone(&Utc));
+        starts
+    }
+}
+
+// `local` in `zone`. Times a DST change skips are read with the offset from
+// before the change, so 02:30 on the night clocks jump to 03:00 is 03:30
+// (RFC 5545, 3.3.5); repeated times are the first of the two.
+pub fn resolve_local<Tz: TimeZone>(zone: &Tz, local: NaiveDateTime) -> DateTime<Tz> {
+    match zone.from_local_datetime(&local) {
+        LocalResult::Single(date) | LocalResult::Ambiguous(date, _) => date,
+        LocalResult::None => {
+            let offset = zone.offset_from_utc_datetime(&(local - Duration::days(1)));
+            zone.from_utc_datetime(&(local - chrono::Offset::fix(&offset)))
+        }
+    }
+}
+
+pub fn parse_weekday(day: &str) -> Option<Weekday> {
+    Some(match day {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+// MO, 2MO, +2MO, -1SU
+fn parse_weekday_num(day: &str) -> Option<WeekdayNum> {
+    let (nth, weekday) = day.trim().split_at(day.trim().len().checked_sub(2)?);
+    let nth = match nth {
+        "" => None,
+        nth => Some(
+            nth.trim_start_matches('+')
+                .parse()
+                .ok()
+                .filter(|nth: &i32| *nth != 0 && nth.abs() <= 53)?,
+        ),
+    };
+    Some(WeekdayNum {
+        nth,
+        weekday: parse_weekday(weekday)?,
+    })
+}
+
+// 20240131, 20240131T235959 or 20240131T235959Z
+fn parse_until(value: &str) -> Option<Until> {
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(Until::Date);
+    }
+    match value.strip_suffix('Z') {
+        Some(value) => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(|until| Until::Utc(until.and_utc())),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .ok()
+            .map(Until::Floating),
+    }
+}
+
+// Comma separated values in `range`, or in its negation when `signed`
+fn parse_numbers<T: TryFrom<i32>>(
+    value: &str,
+    range: RangeInclusive<i32>,
+    signed: bool,
+    part: &str,
+) -> Result<Vec<T>> {
+    value
+        .split(',')
+        .map(|number| {
+            number
+                .trim()
+                .trim_start_matches('+')
+                .parse::<i32>()
+                .ok()
+                .filter(|number| range.contains(number) || (signed && range.contains(&-number)))
+                .and_then(|number| T::try_from(number).ok())
+                .ok_or_else(|| {
+                    Error::invalid_argument(format!("Invalid recurrence rule part: {}", part))
+                })
+        })
+        .collect()
+}
+
+// 1-based index of the `index`th (or, when negative, last but `-index - 1`)
+// of `length` items
+fn resolve_index(index: i32, length: u32) -> Option<u32> {
+    let length = i64::from(length);
+    let index = i64::from(index);
+    let resolved = if index < 0 { length + 1 + index } else { index };
+    (1..=length).contains(&resolved).then_some(resolved as u32)
+}
+
+fn values_or(values: &[u32], default: u32) -> Vec<u32> {
+    match values.is_empty() {
+        true => vec![default],
+        false => values.to_vec(),
+    }
+}
+
+fn matches(values: &[u32], value: u32) -> bool {
+    values.is_empty() || values.contains(&value)
+}
+
+fn days_in_month(day: NaiveDate) -> u32 {
+    let first = day.with_day(1).unwrap_or(day);
+    first
+        .checked_add_months(Months::new(1))
+        .map_or(31, |next| (next - first).num_days() as u32)
+}
+
+fn days_in_year(year: i32) -> u32 {
+    match NaiveDate::from_ymd_opt(year, 2, 29) {
+        Some(_) => 366,
+        None => 365,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn at(date: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn expand(rule: &str, start: &str, limit: usize) -> Vec<String> {
+        let rule = RecurrenceRule::parse(rule).unwrap();
+        rule.occurrences(&at(start).and_utc())
+            .take(limit)
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    // Fixed-rule zone with European DST: +1, +2 from the last Sunday of March
+    // 01:00 UTC to the last Sunday of October 01:00 UTC
+    #[derive(Clone, Debug)]
+    struct Cet;
+
+    impl Cet {
+        fn offset(utc: NaiveDateTime) -> FixedOffset {
+            let last_sunday = |month: u32| {
+                let last = NaiveDate::from_ymd_opt(utc.year(), month + 1, 1)
+                    .unwrap()
+                    .pred_opt()
+                    .unwrap();
+                last - Duration::days(last.weekday().num_days_from_sunday().into())
+            };
+            let summer_start = last_sunday(3).and_hms_opt(1, 0, 0).unwrap();
+            let summer_end = last_sunday(10).and_hms_opt(1, 0, 0).unwrap();
+            let hours = if utc >= summer_start && utc < summer_end {
+                2
+            } else {
+                1
+            };
+            FixedOffset::east_opt(hours * 3600).unwrap()
+        }
+    }
+
+    impl TimeZone for Cet {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            Cet
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let valid: Vec<FixedOffset> = [2, 1]
+                .into_iter()
+                .map(|hours| FixedOffset::east_opt(hours * 3600).unwrap())
+                .filter(|offset| Self::offset(*local - *offset) == *offset)
+                .collect();
+            match valid[..] {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(offset),
+                [earliest, latest, ..] => LocalResult::Ambiguous(earliest, latest),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            Self::offset(utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            Self::offset(*utc)
+        }
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rule = RecurrenceRule::parse("FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,2MO;BYSETPOS=1;WKST=SU;UNTIL=20241231T235959Z;X-FOO=1").unwrap();
+        assert_eq!(rule.frequency, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(
+            rule.by_day,
+            [
+                WeekdayNum {
+                    nth: Some(-1),
+                    weekday: Weekday::Fri
+                },
+                WeekdayNum {
+                    nth: Some(2),
+                    weekday: Weekday::Mon
+                },
+            ]
+        );
+        assert_eq!(rule.week_start, Weekday::Sun);
+        assert_eq!(
+            rule.until,
+            Some(Until::Utc(
+                at("2024-12-31 23:59").and_utc() + Duration::seconds(59)
+            ))
+        );
+        assert_eq!(
+            RecurrenceRule::parse("freq=daily;until=20240131")
+                .unwrap()
+                .until,
+            Some(Until::Date(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()))
+        );
+
+        for invalid in [
+            "",
+            "INTERVAL=2",
+            "FREQ=FORTNIGHTLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;BYMONTH=13",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=DAILY;COUNT=many",
+            "FREQ=DAILY;BYHOUR",
+        ] {
+            let error = RecurrenceRule::parse(invalid).unwrap_err();
+            assert_eq!(
+                error.code,
+                crate::error::ErrorCode::InvalidArgument,
+                "{}",
+                invalid
+            );
+        }
+        for unsupported in ["FREQ=SECONDLY", "FREQ=YEARLY;BYWEEKNO=20"] {
+            let error = RecurrenceRule::parse(unsupported).unwrap_err();
+            assert_eq!(
+                error.code,
+                crate::error::ErrorCode::Unsupported,
+                "{}",
+                unsupported
+            );
+        }
+    }
+
+    #[test]
+    fn expands_daily_and_weekly_rules() {
+        assert_eq!(
+            expand("FREQ=DAILY;COUNT=3", "2024-02-28 09:00", 10),
+            ["2024-02-28 09:00", "2024-02-29 09:00", "2024-03-01 09:00"]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=DAILY;INTERVAL=10;UNTIL=20240130T090000Z",
+                "2024-01-01 09:00",
+                10
+            ),
+            ["2024-01-01 09:00", "2024-01-11 09:00", "2024-01-21 09:00"]
+        );
+        // Weekdays only
+        assert_eq!(
+            expand("FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", "2024-01-05 09:00", 3),
+            ["2024-01-05 09:00", "2024-01-08 09:00", "2024-01-09 09:00"]
+        );
+        assert_eq!(
+            expand("FREQ=WEEKLY;BYDAY=TU,TH;COUNT=5", "2024-01-02 10:00", 10),
+            [
+                "2024-01-02 10:00",
+                "2024-01-04 10:00",
+                "2024-01-09 10:00",
+                "2024-01-11 10:00",
+                "2024-01-16 10:00"
+            ]
+        );
+        assert_eq!(
+            expand("FREQ=WEEKLY", "2024-01-03 10:00", 3),
+            ["2024-01-03 10:00", "2024-01-10 10:00", "2024-01-17 10:00"]
+        );
+    }
+
+    #[test]
+    fn week_start_decides_which_days_share_a_week() {
+        // RFC 5545 example: starting on a Tuesday, every other week
+        assert_eq!(
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;COUNT=4;BYDAY=TU,SU;WKST=MO",
+                "1997-08-05 09:00",
+                10
+            ),
+            [
+                "1997-08-05 09:00",
+                "1997-08-10 09:00",
+                "1997-08-19 09:00",
+                "1997-08-24 09:00"
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;COUNT=4;BYDAY=TU,SU;WKST=SU",
+                "1997-08-05 09:00",
+                10
+            ),
+            [
+                "1997-08-05 09:00",
+                "1997-08-17 09:00",
+                "1997-08-19 09:00",
+                "1997-08-31 09:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_monthly_rules() {
+        // The 31st only exists in some months
+        assert_eq!(
+            expand("FREQ=MONTHLY;COUNT=4", "2024-01-31 12:00", 10),
+            [
+                "2024-01-31 12:00",
+                "2024-03-31 12:00",
+                "2024-05-31 12:00",
+                "2024-07-31 12:00"
+            ]
+        );
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYMONTHDAY=1,-1", "2024-01-01 08:00", 4),
+            [
+                "2024-01-01 08:00",
+                "2024-01-31 08:00",
+                "2024-02-01 08:00",
+                "2024-02-29 08:00"
+            ]
+        );
+        // First Friday and last Sunday
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYDAY=1FR,-1SU", "2024-01-05 18:00", 4),
+            [
+                "2024-01-05 18:00",
+                "2024-01-28 18:00",
+                "2024-02-02 18:00",
+                "2024-02-25 18:00"
+            ]
+        );
+        // Friday the 13th
+        assert_eq!(
+            expand("FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13", "2024-01-01 00:00", 4),
+            [
+                "2024-01-01 00:00",
+                "2024-09-13 00:00",
+                "2024-12-13 00:00",
+                "2025-06-13 00:00"
+            ]
+        );
+        // Last workday of the month
+        assert_eq!(
+            expand(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+                "2024-03-29 17:00",
+                3
+            ),
+            ["2024-03-29 17:00", "2024-04-30 17:00", "2024-05-31 17:00"]
+        );
+        assert_eq!(
+            expand("FREQ=MONTHLY;INTERVAL=3;BYDAY=2WE", "2024-01-10 09:00", 3),
+            ["2024-01-10 09:00", "2024-04-10 09:00", "2024-07-10 09:00"]
+        );
+    }
+
+    #[test]
+    fn expands_yearly_rules() {
+        // Leap days
+        assert_eq!(
+            expand("FREQ=YEARLY;COUNT=3", "2024-02-29 00:00", 10),
+            ["2024-02-29 00:00", "2028-02-29 00:00", "2032-02-29 00:00"]
+        );
+        // US Thanksgiving
+        assert_eq!(
+            expand("FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", "2023-11-23 15:00", 3),
+            ["2023-11-23 15:00", "2024-11-28 15:00", "2025-11-27 15:00"]
+        );
+        // The 20th Monday of the year
+        assert_eq!(
+            expand("FREQ=YEARLY;BYDAY=20MO", "1997-05-19 09:00", 3),
+            ["1997-05-19 09:00", "1998-05-18 09:00", "1999-05-17 09:00"]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=YEARLY;BYYEARDAY=1,100,-1;COUNT=4",
+                "2024-01-01 09:00",
+                10
+            ),
+            [
+                "2024-01-01 09:00",
+                "2024-04-09 09:00",
+                "2024-12-31 09:00",
+                "2025-01-01 09:00"
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=YEARLY;BYMONTH=1,7;BYMONTHDAY=15",
+                "2024-01-15 09:00",
+                3
+            ),
+            ["2024-01-15 09:00", "2024-07-15 09:00", "2025-01-15 09:00"]
+        );
+        // Never matches: stops instead of looping forever
+        assert_eq!(
+            expand(
+                "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30",
+                "2024-01-01 09:00",
+                10
+            ),
+            ["2024-01-01 09:00"]
+        );
+    }
+
+    #[test]
+    fn expands_times_within_periods() {
+        assert_eq!(
+            expand(
+                "FREQ=DAILY;BYHOUR=9,17;BYMINUTE=0,30;COUNT=5",
+                "2024-01-01 09:00",
+                10
+            ),
+            [
+                "2024-01-01 09:00",
+                "2024-01-01 09:30",
+                "2024-01-01 17:00",
+                "2024-01-01 17:30",
+                "2024-01-02 09:00"
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=HOURLY;INTERVAL=3;BYHOUR=9,12,15",
+                "2024-01-01 09:15",
+                4
+            ),
+            [
+                "2024-01-01 09:15",
+                "2024-01-01 12:15",
+                "2024-01-01 15:15",
+                "2024-01-02 09:15"
+            ]
+        );
+        assert_eq!(
+            expand(
+                "FREQ=MINUTELY;INTERVAL=20;BYHOUR=9;COUNT=4",
+                "2024-01-01 09:00",
+                10
+            ),
+            [
+                "2024-01-01 09:00",
+                "2024-01-01 09:20",
+                "2024-01-01 09:40",
+                "2024-01-02 09:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_start_as_the_first_occurrence() {
+        // Wednesday start for a Monday rule
+        assert_eq!(
+            expand("FREQ=WEEKLY;BYDAY=MO;COUNT=3", "2024-01-03 10:00", 10),
+            ["2024-01-03 10:00", "2024-01-08 10:00", "2024-01-15 10:00"]
+        );
+    }
+
+    #[test]
+    fn keeps_wall_clock_time_across_dst_changes() {
+        let rule = RecurrenceRule::parse("FREQ=WEEKLY;COUNT=3").unwrap();
+        let start = resolve_local(&Cet, at("2024-03-24 10:00"));
+        let occurrences: Vec<String> = rule
+            .occurrences(&start)
+            .map(|date| {
+                date.with_timezone(&Utc)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            occurrences,
+            ["2024-03-24 09:00", "2024-03-31 08:00", "2024-04-07 08:00"]
+        );
+
+        // 02:30 doesn't exist on 2024-03-31
+        let skipped = resolve_local(&Cet, at("2024-03-31 02:30"));
+        assert_eq!(skipped.naive_local(), at("2024-03-31 03:30"));
+        // and 02:30 is repeated on 2024-10-27
+        let repeated = resolve_local(&Cet, at("2024-10-27 02:30"));
+        assert_eq!(
+            repeated.with_timezone(&Utc).naive_utc(),
+            at("2024-10-27 00:30")
+        );
+
+        // UNTIL in UTC against local occurrences
+        let rule = RecurrenceRule::parse("FREQ=DAILY;UNTIL=20240702T080000Z").unwrap();
+        let start = resolve_local(&Cet, at("2024-07-01 10:00"));
+        assert_eq!(rule.occurrences(&start).count(), 2);
+    }
+
+    #[test]
+    fn combines_rules_dates_and_exceptions() {
+        let start = at("2024-01-01 09:00").and_utc();
+        let mut set = RecurrenceSet::new(start);
+        set.rules
+            .push(RecurrenceRule::parse("FREQ=WEEKLY").unwrap());
+        set.dates.push(at("2024-01-10 14:00").and_utc());
+        set.exceptions.push(at("2024-01-15 09:00").and_utc());
+
+        let occurrences: Vec<String> = set
+            .between(
+                at("2024-01-05 00:00").and_utc(),
+                at("2024-01-29 09:00").and_utc(),
+            )
+            .iter()
+            .map(|date| date.format("%Y-%m-%d %H:%M").to_string())
+            .collect();
+        assert_eq!(
+            occurrences,
+            ["2024-01-08 09:00", "2024-01-10 14:00", "2024-01-22 09:00"]
+        );
+
+        // Without rules the start is the only occurrence besides RDATEs
+        let single = RecurrenceSet::new(start);
+        assert_eq!(single.between(start, start + Duration::days(1)), [start]);
+        assert!(single
+            .between(start + Duration::hours(1), start + Duration::days(1))
+            .is_empty());
+    }
+}
@@ -0,0 +1,695 @@
+// src-tauri/src/search_query.rs
+//
+// Query language of the file search. A query like
+//
+//     report ext:pdf modified:<7d -draft "q3 numbers" in:~/Documents
+//
+// is parsed into a `FileQuery` once per search. The macOS backend translates it
+// into an NSPredicate with `predicate_format`, the index and the Linux backend
+// evaluate it through `FileQueryFilter`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::spotlight_utils::{fold_name, is_hidden_name};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileQuery {
+    pub terms: Vec<QueryTerm>,
+}
+
+// A single whitespace separated part of the query, `-` in front negates it
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryTerm {
+    pub negated: bool,
+    pub filter: QueryFilter,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QueryFilter {
+    // Plain word or quoted phrase the display name has to contain
+    Text(String),
+    // `ext:pdf`, lowercased and without the leading dot
+    Extension(String),
+    // `kind:folder` or `kind:file`
+    Kind(FileKind),
+    // `modified:<7d`, compares the time since the last modification
+    Modified(Comparison, Duration),
+    // `size:>10mb`, in bytes
+    Size(Comparison, u64),
+    // `in:~/Projects`, the entry lives somewhere below this directory
+    In(PathBuf),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileKind {
+    Folder,
+    File,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less,
+    Greater,
+}
+
+impl Comparison {
+    pub fn holds<T: PartialOrd>(self, value: T, bound: T) -> bool {
+        match self {
+            Comparison::Less => value < bound,
+            Comparison::Greater => value > bound,
+        }
+    }
+}
+
+impl FileQuery {
+    // Directories of the positive `in:` terms, searched instead of the default scopes
+    pub fn scopes(&self) -> Vec<PathBuf> {
+        self.terms
+            .iter()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.filter {
+                QueryFilter::In(path) => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // Whether the query picks the kind of entries itself. Without `ext:` or
+    // `kind:` the search falls back to the allowed extensions and folders.
+    pub fn restricts_kind(&self) -> bool {
+        self.terms.iter().any(|term| {
+            !term.negated && matches!(term.filter, QueryFilter::Extension(_) | QueryFilter::Kind(_))
+        })
+    }
+
+    fn needs_metadata(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|term| matches!(term.filter, QueryFilter::Modified(..) | QueryFilter::Size(..)))
+    }
+}
+
+pub fn parse_query(input: &str) -> Result<FileQuery, String> {
+    let mut terms = Vec::new();
+
+    for token in tokenize(input)? {
+        let filter = if token.quoted {
+            QueryFilter::Text(token.text)
+        } else {
+            parse_filter(&token.text)?
+        };
+
+        terms.push(QueryTerm {
+            negated: token.negated,
+            filter,
+        });
+    }
+
+    Ok(FileQuery { terms })
+}
+
+struct Token {
+    text: String,
+    negated: bool,
+    // Quoted phrases are always plain text, `"ext:pdf"` searches for that name
+    quoted: bool,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        // A lone `-` is searched for as is
+        let negated = c == '-' && chars.clone().nth(1).is_some_and(|next| !next.is_whitespace());
+        if negated {
+            chars.next();
+        }
+
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut phrase = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => phrase.push(c),
+                    None => return Err("Unterminated quote in search query".to_string()),
+                }
+            }
+            if !phrase.is_empty() {
+                tokens.push(Token {
+                    text: phrase,
+                    negated,
+                    quoted: true,
+                });
+            }
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+        tokens.push(Token {
+            text: word,
+            negated,
+            quoted: false,
+        });
+    }
+
+    Ok(tokens)
+}
+
+fn parse_filter(body: &str) -> Result<QueryFilter, String> {
+    let Some((key, value)) = body.split_once(':') else {
+        return Ok(QueryFilter::Text(body.to_string()));
+    };
+
+    let filter = match key.to_lowercase().as_str() {
+        "ext" => {
+            let extension = value.trim_start_matches('.').to_lowercase();
+            if extension.is_empty() {
+                return Err("Missing extension after \"ext:\"".to_string());
+            }
+            QueryFilter::Extension(extension)
+        }
+        "kind" => match value.to_lowercase().as_str() {
+            "folder" | "dir" | "directory" => QueryFilter::Kind(FileKind::Folder),
+            "file" => QueryFilter::Kind(FileKind::File),
+            _ => return Err(format!("Unknown kind \"{}\", use folder or file", value)),
+        },
+        "modified" => {
+            let (comparison, amount) = split_comparison(key, value)?;
+            QueryFilter::Modified(comparison, parse_age(amount)?)
+        }
+        "size" => {
+            let (comparison, amount) = split_comparison(key, value)?;
+            QueryFilter::Size(comparison, parse_size(amount)?)
+        }
+        "in" => {
+            if value.is_empty() {
+                return Err("Missing directory after \"in:\"".to_string());
+            }
+            QueryFilter::In(expand_directory(value))
+        }
+        // Not a filter, e.g. a name containing a colon
+        _ => QueryFilter::Text(body.to_string()),
+    };
+
+    Ok(filter)
+}
+
+fn split_comparison<'a>(key: &str, value: &'a str) -> Result<(Comparison, &'a str), String> {
+    if let Some(rest) = value.strip_prefix('<') {
+        Ok((Comparison::Less, rest))
+    } else if let Some(rest) = value.strip_prefix('>') {
+        Ok((Comparison::Greater, rest))
+    } else {
+        Err(format!("Expected < or > after \"{}:\"", key))
+    }
+}
+
+// Splits "10mb" into (10.0, "mb")
+fn split_number(value: &str) -> Option<(f64, String)> {
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let number = value[..unit_start].parse::<f64>().ok()?;
+    Some((number, value[unit_start..].to_lowercase()))
+}
+
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid age \"{}\", e.g. 30min, 12h, 7d, 2w, 6mo or 1y", value);
+    let (number, unit) = split_number(value).ok_or_else(invalid)?;
+
+    let unit_seconds = match unit.as_str() {
+        "s" => 1.0,
+        "min" => 60.0,
+        "h" => 60.0 * 60.0,
+        "d" | "" => 24.0 * 60.0 * 60.0,
+        "w" => 7.0 * 24.0 * 60.0 * 60.0,
+        "mo" => 30.0 * 24.0 * 60.0 * 60.0,
+        "y" => 365.0 * 24.0 * 60.0 * 60.0,
+        _ => return Err(invalid()),
+    };
+
+    // Ages beyond what a Duration holds are as good as a typo
+    Duration::try_from_secs_f64(number * unit_seconds).map_err(|_| invalid())
+}
+
+fn parse_size(value: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid size \"{}\", e.g. 500kb, 10mb or 1gb", value);
+    let (number, unit) = split_number(value).ok_or_else(invalid)?;
+
+    let unit_bytes: f64 = match unit.as_str() {
+        "b" | "" => 1.0,
+        "kb" | "k" => 1024.0,
+        "mb" | "m" => 1024.0 * 1024.0,
+        "gb" | "g" => 1024.0 * 1024.0 * 1024.0,
+        "tb" | "t" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(invalid()),
+    };
+
+    Ok((number * unit_bytes) as u64)
+}
+
+// `~` stands for the home directory, relative paths are resolved against it too
+fn expand_directory(value: &str) -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_default();
+
+    if value == "~" {
+        home
+    } else if let Some(rest) = value.strip_prefix("~/") {
+        home.join(rest)
+    } else if Path::new(value).is_absolute() {
+        PathBuf::from(value)
+    } else {
+        home.join(value)
+    }
+}
+
+// Evaluates a `FileQuery` against file system entries, the counterpart of the
+// NSPredicate built on macOS. Without `ext:` or `kind:` terms an entry has to
+// match one of the extension globs or be a folder without a dot in its name.
+// Hidden entries stand in for Spotlight's system files and never match.
+pub struct FileQueryFilter {
+    query: FileQuery,
+    extension_patterns: Vec<String>,
+    // Folded once here instead of for every entry
+    folded_texts: Vec<Option<String>>,
+}
+
+impl FileQueryFilter {
+    pub fn new(query: &FileQuery, extensions: &[String]) -> Self {
+        let folded_texts = query
+            .terms
+            .iter()
+            .map(|term| match &term.filter {
+                QueryFilter::Text(text) => Some(fold_name(text)),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            query: query.clone(),
+            extension_patterns: extensions.iter().map(|ext| ext.to_lowercase()).collect(),
+            folded_texts,
+        }
+    }
+
+    pub fn matches(&self, path: &Path, name: &str, is_dir: bool) -> bool {
+        self.matches_folded(path, name, &fold_name(name), is_dir)
+    }
+
+    // Same as `matches`, for callers that keep the folded name around
+    pub fn matches_folded(&self, path: &Path, name: &str, folded_name: &str, is_dir: bool) -> bool {
+        if is_hidden_name(name) {
+            return false;
+        }
+
+        let lowercase_name = name.to_lowercase();
+
+        if !self.query.restricts_kind() {
+            let matches_extension = self
+                .extension_patterns
+                .iter()
+                .any(|pattern| glob_match(pattern, &lowercase_name));
+
+            let is_plain_folder = is_dir && !name.contains('.');
+            if !matches_extension && !is_plain_folder {
+                return false;
+            }
+        }
+
+        // Positive `ext:` and `in:` terms are alternatives, everything else must hold
+        let mut extension_terms = (0, false);
+        let mut directory_terms = (0, false);
+        // Only read from disk for `size:` and `modified:`
+        let metadata = if self.query.needs_metadata() {
+            match fs::metadata(path) {
+                Ok(metadata) => Some(metadata),
+                Err(_) => return false,
+            }
+        } else {
+            None
+        };
+
+        for (term, folded_text) in self.query.terms.iter().zip(&self.folded_texts) {
+            let holds = match &term.filter {
+                QueryFilter::Text(_) => folded_text
+                    .as_deref()
+                    .is_none_or(|text| folded_name.contains(text)),
+                QueryFilter::Extension(extension) => {
+                    !is_dir && lowercase_name.ends_with(&format!(".{}", extension))
+                }
+                QueryFilter::Kind(FileKind::Folder) => is_dir,
+                QueryFilter::Kind(FileKind::File) => !is_dir,
+                QueryFilter::Modified(comparison, age) => metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                    .is_some_and(|elapsed| comparison.holds(elapsed, *age)),
+                QueryFilter::Size(comparison, bytes) => {
+                    // Folders have no meaningful size
+                    !is_dir
+                        && metadata
+                            .as_ref()
+                            .is_some_and(|metadata| comparison.holds(metadata.len(), *bytes))
+                }
+                QueryFilter::In(directory) => path.starts_with(directory),
+            };
+
+            if term.negated {
+                if holds {
+                    return false;
+                }
+                continue;
+            }
+
+            match &term.filter {
+                QueryFilter::Extension(_) => {
+                    extension_terms.0 += 1;
+                    extension_terms.1 |= holds;
+                }
+                QueryFilter::In(_) => {
+                    directory_terms.0 += 1;
+                    directory_terms.1 |= holds;
+                }
+                _ if !holds => return false,
+                _ => {}
+            }
+        }
+
+        (extension_terms.0 == 0 || extension_terms.1) && (directory_terms.0 == 0 || directory_terms.1)
+    }
+}
+
+// Matches `*` and `?` wildcards, like NSPredicate's LIKE operator
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last star swallow one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// Seconds between 1970 and 2001, NSDate's reference date
+#[cfg(any(target_os = "macos", test))]
+const NS_DATE_REFERENCE_OFFSET: f64 = 978_307_200.0;
+
+// Translates the query into NSPredicate format syntax for Spotlight, with
+// `modified:` relative to `now`
+#[cfg(any(target_os = "macos", test))]
+pub fn predicate_format(query: &FileQuery, allowed_extensions: &[String], now: SystemTime) -> String {
+    let mut clauses = Vec::new();
+
+    // Without `ext:` or `kind:` terms, match the allowed extensions, or folders
+    // (which can't have dots)
+    if !query.restricts_kind() {
+        let mut kind_clauses: Vec<String> = allowed_extensions
+            .iter()
+            .map(|ext| format!("kMDItemDisplayName LIKE[c] {}", quote(ext)))
+            .collect();
+        kind_clauses.push(
+            "(kMDItemContentType == 'public.folder' AND NOT (kMDItemDisplayName CONTAINS[cd] '.'))"
+                .to_string(),
+        );
+        clauses.push(format!("({})", kind_clauses.join(" OR ")));
+    }
+
+    let mut extension_clauses = Vec::new();
+
+    for term in &query.terms {
+        let clause = match &term.filter {
+            QueryFilter::Text(text) => format!("kMDItemDisplayName CONTAINS[cd] {}", quote(text)),
+            // Files only, like the index. Bundles such as .app aren't 'public.folder'.
+            QueryFilter::Extension(extension) => format!(
+                "(kMDItemContentType != 'public.folder' AND kMDItemFSName LIKE[c] {})",
+                quote(&format!("*.{}", like_literal(extension)))
+            ),
+            QueryFilter::Kind(FileKind::Folder) => "kMDItemContentType == 'public.folder'".to_string(),
+            QueryFilter::Kind(FileKind::File) => "kMDItemContentType != 'public.folder'".to_string(),
+            QueryFilter::Modified(comparison, age) => {
+                // Modified less than 7 days ago means after the date 7 days ago
+                let operator = match comparison {
+                    Comparison::Less => ">",
+                    Comparison::Greater => "<",
+                };
+                // Ages reaching back before 1970 mean any date since then
+                let reference_date = now
+                    .checked_sub(*age)
+                    .and_then(|date| date.duration_since(SystemTime::UNIX_EPOCH).ok())
+                    .unwrap_or_default()
+                    .as_secs_f64()
+                    - NS_DATE_REFERENCE_OFFSET;
+                format!(
+                    "kMDItemFSContentChangeDate {} CAST({:.0}, 'NSDate')",
+                    operator, reference_date
+                )
+            }
+            QueryFilter::Size(comparison, bytes) => {
+                let operator = match comparison {
+                    Comparison::Less => "<",
+                    Comparison::Greater => ">",
+                };
+                format!(
+                    "(kMDItemContentType != 'public.folder' AND kMDItemFSSize {} {})",
+                    operator, bytes
+                )
+            }
+            QueryFilter::In(directory) => {
+                let mut prefix = directory.to_string_lossy().into_owned();
+                if !prefix.ends_with('/') {
+                    prefix.push('/');
+                }
+                format!("kMDItemPath BEGINSWITH {}", quote(&prefix))
+            }
+        };
+
+        if term.negated {
+            clauses.push(format!("NOT ({})", clause));
+            continue;
+        }
+
+        // Positive `ext:` terms are alternatives. Positive `in:` terms already
+        // became the search scopes.
+        match &term.filter {
+            QueryFilter::Extension(_) => extension_clauses.push(clause),
+            QueryFilter::In(_) => {}
+            _ => clauses.push(clause),
+        }
+    }
+
+    if !extension_clauses.is_empty() {
+        clauses.push(format!("({})", extension_clauses.join(" OR ")));
+    }
+
+    // Exclude system files
+    clauses.push("NOT (kMDItemSupportFileType == 'MDSystemFile')".to_string());
+
+    clauses.join(" AND ")
+}
+
+// Escapes LIKE's `*` and `?` wildcards in user input
+#[cfg(any(target_os = "macos", test))]
+fn like_literal(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('*', "\\*")
+        .replace('?', "\\?")
+}
+
+// Single quoted NSPredicate string literal
+#[cfg(any(target_os = "macos", test))]
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(negated: bool, filter: QueryFilter) -> QueryTerm {
+        QueryTerm { negated, filter }
+    }
+
+    #[test]
+    fn parses_plain_words_and_phrases() {
+        let query = parse_query(r#"annual "q3 numbers"  report"#).unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(false, QueryFilter::Text("annual".into())),
+                term(false, QueryFilter::Text("q3 numbers".into())),
+                term(false, QueryFilter::Text("report".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_filters() {
+        let query = parse_query("ext:.PDF kind:folder modified:<7d size:>10mb").unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(false, QueryFilter::Extension("pdf".into())),
+                term(false, QueryFilter::Kind(FileKind::Folder)),
+                term(false, QueryFilter::Modified(Comparison::Less, Duration::from_secs(7 * 86_400))),
+                term(false, QueryFilter::Size(Comparison::Greater, 10 * 1024 * 1024)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_exclusions() {
+        let query = parse_query(r#"-draft -ext:tmp -"old copy" - "#).unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(true, QueryFilter::Text("draft".into())),
+                term(true, QueryFilter::Extension("tmp".into())),
+                term(true, QueryFilter::Text("old copy".into())),
+                term(false, QueryFilter::Text("-".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_directories() {
+        let home = dirs::home_dir().unwrap_or_default();
+        let query = parse_query("in:~/Projects in:/tmp in:Notes").unwrap();
+        assert_eq!(
+            query.scopes(),
+            vec![home.join("Projects"), PathBuf::from("/tmp"), home.join("Notes")]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_filters_and_unknown_keys_as_text() {
+        let query = parse_query(r#""ext:pdf" note:1"#).unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                term(false, QueryFilter::Text("ext:pdf".into())),
+                term(false, QueryFilter::Text("note:1".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_filters() {
+        assert!(parse_query("size:10mb").is_err());
+        assert!(parse_query("size:>lots").is_err());
+        assert!(parse_query("modified:<7x").is_err());
+        assert!(parse_query("modified:<99999999999999999y").is_err());
+        assert!(parse_query("kind:socket").is_err());
+        assert!(parse_query("ext:").is_err());
+        assert!(parse_query(r#""unterminated"#).is_err());
+    }
+
+    #[test]
+    fn filters_names() {
+        let extensions = vec!["*.pdf".to_string(), "*.txt".to_string()];
+        let path = Path::new("/nonexistent");
+        let filter = |query: &str| FileQueryFilter::new(&parse_query(query).unwrap(), &extensions);
+
+        assert!(filter("resume").matches(path, "Résumé.pdf", false));
+        assert!(filter("").matches(path, "Projects", true));
+        assert!(!filter("").matches(path, "photo.png", false));
+        assert!(!filter("").matches(path, ".hidden.pdf", false));
+        assert!(!filter("-draft").matches(path, "Draft 2.txt", false));
+        assert!(filter("ext:png").matches(path, "photo.png", false));
+        assert!(filter("ext:png ext:jpg").matches(path, "photo.jpg", false));
+        assert!(!filter("ext:png").matches(path, "notes.txt", false));
+        // `ext:` only matches files
+        assert!(!filter("ext:png").matches(path, "screenshots.png", true));
+        assert!(filter("ext:png").matches(path, "screenshots.png", false));
+        assert!(filter("kind:folder").matches(path, "my.app", true));
+        assert!(!filter("kind:file").matches(path, "Projects", true));
+        assert!(filter("in:/nonexistent").matches(path, "a.txt", false));
+        assert!(!filter("-in:/nonexistent").matches(path, "a.txt", false));
+        // Entries that can't be read never match size or date filters
+        assert!(!filter("size:>0").matches(path, "a.txt", false));
+    }
+
+    fn predicate(query: &str) -> String {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        predicate_format(&parse_query(query).unwrap(), &["*.pdf".to_string()], now)
+    }
+
+    const SYSTEM_FILES: &str = "NOT (kMDItemSupportFileType == 'MDSystemFile')";
+
+    #[test]
+    fn translates_queries_to_predicates() {
+        assert_eq!(
+            predicate("report"),
+            format!(
+                "(kMDItemDisplayName LIKE[c] '*.pdf' OR (kMDItemContentType == 'public.folder' AND NOT (kMDItemDisplayName CONTAINS[cd] '.'))) AND kMDItemDisplayName CONTAINS[cd] 'report' AND {}",
+                SYSTEM_FILES
+            )
+        );
+        assert_eq!(
+            predicate("ext:png ext:jpg -in:/tmp"),
+            format!(
+                "NOT (kMDItemPath BEGINSWITH '/tmp/') AND ((kMDItemContentType != 'public.folder' AND kMDItemFSName LIKE[c] '*.png') OR (kMDItemContentType != 'public.folder' AND kMDItemFSName LIKE[c] '*.jpg')) AND {}",
+                SYSTEM_FILES
+            )
+        );
+    }
+
+    #[test]
+    fn escapes_user_input_in_predicates() {
+        assert!(predicate(r#""it's" kind:file"#).contains(r"kMDItemDisplayName CONTAINS[cd] 'it\'s'"));
+        assert!(predicate(r"C:\notes kind:file").contains(r"kMDItemDisplayName CONTAINS[cd] 'C:\\notes'"));
+        // CONTAINS has no wildcards, LIKE's are escaped
+        assert!(predicate("a*b? kind:file").contains("kMDItemDisplayName CONTAINS[cd] 'a*b?'"));
+        assert!(predicate("ext:p*f").contains(r"kMDItemFSName LIKE[c] '*.p\\*f'"));
+        assert!(predicate("ext:t?t").contains(r"kMDItemFSName LIKE[c] '*.t\\?t'"));
+    }
+
+    #[test]
+    fn translates_date_and_size_clauses() {
+        // 1_000_000_000 - 7 days - 978_307_200 seconds since 2001
+        assert_eq!(
+            predicate("modified:<7d kind:file"),
+            format!(
+                "kMDItemFSContentChangeDate > CAST(21088000, 'NSDate') AND kMDItemContentType != 'public.folder' AND {}",
+                SYSTEM_FILES
+            )
+        );
+        assert!(predicate("modified:>1d kind:file").contains("kMDItemFSContentChangeDate < CAST(21606400, 'NSDate')"));
+        assert!(predicate("modified:<500000000000y").contains("kMDItemFSContentChangeDate > CAST(-978307200, 'NSDate')"));
+        assert!(predicate("size:>1kb kind:file")
+            .contains("(kMDItemContentType != 'public.folder' AND kMDItemFSSize > 1024)"));
+    }
+}
@@ -0,0 +1,462 @@
+// src-tauri/src/search_ranking.rs
+//
+// Ranking stage of the file search. Every result gets a score from how well
+// its name matches the text of the query (prefix, word boundary, fuzzy
+// closeness), how deep it is nested and how recently it was modified or
+// opened. The matched parts of the name are returned as ranges so the
+// frontend can highlight them without matching again.
+
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use unicode_normalization::char::is_combining_mark;
+
+use crate::search_query::{FileQuery, QueryFilter};
+use crate::spotlight_utils::{fold_name, SpotlightAppInfo};
+
+// Weights of the name signals, per query word
+const PREFIX_SCORE: f64 = 100.0;
+const WORD_BOUNDARY_SCORE: f64 = 60.0;
+const CONTAINS_SCORE: f64 = 30.0;
+// Characters of the query found in order, but not next to each other
+const SUBSEQUENCE_SCORE: f64 = 10.0;
+// The name without its extension equals the query
+const EXACT_NAME_SCORE: f64 = 50.0;
+// Scaled by how close the name is to the query, by edit distance
+const FUZZY_SCORE: f64 = 20.0;
+
+// Penalty per directory level below the search scope depth
+const DEPTH_PENALTY: f64 = 1.5;
+// Levels that are not penalized, e.g. /Users/name/Documents
+const FREE_DEPTH: usize = 4;
+const MAX_DEPTH_PENALTY: f64 = 20.0;
+
+// Recency boosts decay exponentially with these half-lives
+const MODIFIED_SCORE: f64 = 25.0;
+const MODIFIED_HALF_LIFE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+const OPENED_SCORE: f64 = 15.0;
+const OPENED_HALF_LIFE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+// Part of the display name that matched the query, in UTF-16 code units so it
+// can be used with JavaScript string offsets directly
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, specta::Type)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+pub struct SearchRanker {
+    // Folded text terms of the query, excluded ones don't count
+    words: Vec<String>,
+    now: SystemTime,
+}
+
+impl SearchRanker {
+    pub fn new(query: &FileQuery) -> Self {
+        let words = query
+            .terms
+            .iter()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.filter {
+                QueryFilter::Text(text) => Some(fold_name(text)),
+                _ => None,
+            })
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        Self {
+            words,
+            now: SystemTime::now(),
+        }
+    }
+
+    // Scores the result and fills in its match ranges
+    pub fn rank(&self, info: &mut SpotlightAppInfo) {
+        let (name_score, match_ranges) = self.score_name(&info.display_name);
+
+        info.score = name_score + self.score_path(Path::new(&info.path));
+        info.match_ranges = match_ranges;
+    }
+
+    // Name signals only, cheap enough to run over every indexed entry
+    pub fn name_score(&self, name: &str) -> f64 {
+        self.score_name(name).0
+    }
+
+    fn score_name(&self, name: &str) -> (f64, Vec<MatchRange>) {
+        if self.words.is_empty() {
+            return (0.0, Vec::new());
+        }
+
+        let folded = FoldedName::new(name);
+        let mut score = 0.0;
+        let mut ranges = Vec::new();
+
+        for word in &self.words {
+            if let Some((word_score, range)) = folded.best_substring_match(word) {
+                score += word_score;
+                ranges.push(range);
+            } else if let Some(positions) = folded.subsequence_match(word) {
+                // Fewer gaps between the matched characters rank higher
+                let span = positions.last().unwrap() - positions.first().unwrap() + 1;
+                score += SUBSEQUENCE_SCORE * word.chars().count() as f64 / span as f64;
+                ranges.extend(positions.into_iter().map(|index| folded.range(index, index + 1)));
+            }
+        }
+
+        let query = self.words.join(" ");
+        let stem = fold_name(name_stem(name));
+        if stem == query {
+            score += EXACT_NAME_SCORE;
+        }
+
+        let longest = std::cmp::max(stem.chars().count(), query.chars().count()).max(1);
+        score += FUZZY_SCORE * (1.0 - levenshtein(&stem, &query) as f64 / longest as f64);
+
+        (score, merge_ranges(ranges))
+    }
+
+    fn score_path(&self, path: &Path) -> f64 {
+        let depth = path.components().count().saturating_sub(FREE_DEPTH);
+        let mut score = -(depth as f64 * DEPTH_PENALTY).min(MAX_DEPTH_PENALTY);
+
+        if let Ok(metadata) = fs::metadata(path) {
+            if let Ok(modified) = metadata.modified() {
+                score += MODIFIED_SCORE * self.decay(modified, MODIFIED_HALF_LIFE);
+            }
+            // Access times are only updated lazily on most systems, but good
+            // enough to tell recently opened files apart
+            if let Ok(accessed) = metadata.accessed() {
+                score += OPENED_SCORE * self.decay(accessed, OPENED_HALF_LIFE);
+            }
+        }
+
+        score
+    }
+
+    fn decay(&self, time: SystemTime, half_life: Duration) -> f64 {
+        let age = self.now.duration_since(time).unwrap_or_default();
+        0.5_f64.powf(age.as_secs_f64() / half_life.as_secs_f64())
+    }
+}
+
+// Best first, ties broken by name like the Spotlight sort order
+pub fn sort_by_rank(results: &mut [SpotlightAppInfo]) {
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.display_name.to_lowercase().cmp(&b.display_name.to_lowercase()))
+    });
+}
+
+// A folded name that remembers where each folded character came from
+struct FoldedName {
+    folded: Vec<char>,
+    // Per folded character: UTF-16 range of the original character
+    origins: Vec<(usize, usize)>,
+    // Per folded character: whether a word starts there
+    boundaries: Vec<bool>,
+}
+
+impl FoldedName {
+    fn new(name: &str) -> Self {
+        let mut folded = Vec::new();
+        let mut origins: Vec<(usize, usize)> = Vec::new();
+        let mut boundaries = Vec::new();
+
+        let mut offset = 0;
+        let mut previous: Option<char> = None;
+        for c in name.chars() {
+            if is_combining_mark(c) {
+                // Marks of decomposed (NFD) names, as macOS often stores them,
+                // belong to the character before them
+                let start = offset;
+                offset += c.len_utf16();
+                for origin in origins.iter_mut().rev().take_while(|origin| origin.1 == start) {
+                    origin.1 = offset;
+                }
+                continue;
+            }
+
+            let is_boundary = match previous {
+                None => true,
+                Some(previous) => {
+                    !previous.is_alphanumeric()
+                        || (previous.is_lowercase() && c.is_uppercase())
+                        || (previous.is_alphabetic() && c.is_numeric())
+                }
+            };
+
+            let start = offset;
+            offset += c.len_utf16();
+            for (i, folded_char) in fold_name(&c.to_string()).chars().enumerate() {
+                folded.push(folded_char);
+                origins.push((start, offset));
+                boundaries.push(is_boundary && i == 0);
+            }
+            previous = Some(c);
+        }
+
+        Self {
+            folded,
+            origins,
+            boundaries,
+        }
+    }
+
+    // UTF-16 range of the original name covering folded characters start..end
+    fn range(&self, start: usize, end: usize) -> MatchRange {
+        MatchRange {
+            start: self.origins[start].0,
+            end: self.origins[end - 1].1,
+        }
+    }
+
+    // The best scoring occurrence: a prefix, then a word start, then anywhere
+    fn best_substring_match(&self, word: &str) -> Option<(f64, MatchRange)> {
+        let word: Vec<char> = word.chars().collect();
+        if word.len() > self.folded.len() {
+            return None;
+        }
+
+        let mut best: Option<(f64, usize)> = None;
+        for start in 0..=(self.folded.len() - word.len()) {
+            if self.folded[start..start + word.len()] != word[..] {
+                continue;
+            }
+
+            let score = if start == 0 {
+                PREFIX_SCORE
+            } else if self.boundaries[start] {
+                WORD_BOUNDARY_SCORE
+            } else {
+                CONTAINS_SCORE
+            };
+
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, start));
+            }
+        }
+
+        best.map(|(score, start)| (score, self.range(start, start + word.len())))
+    }
+
+    // Positions of the word's characters found in order, each as early as possible
+    fn subsequence_match(&self, word: &str) -> Option<Vec<usize>> {
+        let mut positions = Vec::new();
+        let mut next = 0;
+
+        for c in word.chars().filter(|c| !c.is_whitespace()) {
+            let position = next + self.folded[next..].iter().position(|folded| *folded == c)?;
+            positions.push(position);
+            next = position + 1;
+        }
+
+        if positions.is_empty() {
+            None
+        } else {
+            Some(positions)
+        }
+    }
+}
+
+fn name_stem(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(index) if index > 0 => &name[..index],
+        _ => name,
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous_row[j] + usize::from(a_char != *b_char);
+            row[j + 1] = substitution.min(previous_row[j + 1] + 1).min(row[j] + 1);
+        }
+        previous_row = row;
+    }
+
+    previous_row[b.len()]
+}
+
+// Sorted, with overlapping and touching ranges joined
+fn merge_ranges(mut ranges: Vec<MatchRange>) -> Vec<MatchRange> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<MatchRange> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_query::parse_query;
+    use std::fs::{File, FileTimes};
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn ranker(query: &str) -> SearchRanker {
+        SearchRanker::new(&parse_query(query).unwrap())
+    }
+
+    fn range(start: usize, end: usize) -> MatchRange {
+        MatchRange { start, end }
+    }
+
+    #[test]
+    fn ranks_prefix_over_word_boundary_over_substring_over_fuzzy() {
+        let ranker = ranker("report");
+        let names = [
+            "report-draft.pdf",
+            "annual report.pdf",
+            "myreport.pdf",
+            "r-e-p-o-r-t.txt",
+            "reprot.doc",
+            "invoice.pdf",
+        ];
+        let scores: Vec<f64> = names.iter().map(|name| ranker.name_score(name)).collect();
+
+        for pair in scores.windows(2) {
+            assert!(pair[0] > pair[1], "{names:?} scored {scores:?}");
+        }
+    }
+
+    #[test]
+    fn boosts_exact_names() {
+        let ranker = ranker("report");
+        assert!(ranker.name_score("Report.pdf") > ranker.name_score("reports.pdf"));
+        assert_eq!(ranker.name_score("Report.pdf"), ranker.name_score("report.txt"));
+    }
+
+    #[test]
+    fn returns_match_ranges() {
+        assert_eq!(ranker("report").score_name("Annual Report.pdf").1, vec![range(7, 13)]);
+        // Word starts inside camel case and after digits count as boundaries
+        assert_eq!(ranker("report").score_name("myReport.pdf").1, vec![range(2, 8)]);
+        // Subsequence matches highlight every matched character
+        assert_eq!(
+            ranker("rpt").score_name("receipt.pdf").1,
+            vec![range(0, 1), range(5, 7)]
+        );
+        // Ranges are UTF-16 offsets of the original name
+        assert_eq!(ranker("cafe").score_name("😀 Café.txt").1, vec![range(3, 7)]);
+        // Combining marks of decomposed names go with their letter
+        assert_eq!(ranker("cafe").score_name("Cafe\u{301}.txt").1, vec![range(0, 5)]);
+        assert_eq!(
+            ranker("menu").score_name("Cafe\u{301}Menu.txt").1,
+            vec![range(5, 9)]
+        );
+        assert_eq!(ranker("anything").score_name("").1, vec![]);
+        assert_eq!(ranker("ext:pdf").score_name("report.pdf"), (0.0, vec![]));
+    }
+
+    #[test]
+    fn merges_the_ranges_of_query_words() {
+        // Overlapping
+        assert_eq!(ranker("rep port").score_name("report.pdf").1, vec![range(0, 6)]);
+        // Touching
+        assert_eq!(ranker("re port").score_name("report.pdf").1, vec![range(0, 6)]);
+        // Apart
+        assert_eq!(
+            ranker("annual report").score_name("annual-report.pdf").1,
+            vec![range(0, 6), range(7, 13)]
+        );
+    }
+
+    #[test]
+    fn merges_ranges() {
+        assert_eq!(
+            merge_ranges(vec![range(8, 10), range(0, 2), range(1, 4), range(4, 5)]),
+            vec![range(0, 5), range(8, 10)]
+        );
+        assert_eq!(merge_ranges(vec![range(0, 6), range(2, 3)]), vec![range(0, 6)]);
+        assert_eq!(merge_ranges(vec![]), vec![]);
+    }
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("report", "report"), 0);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("repotr", "report"), 2);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn penalizes_deeply_nested_paths() {
+        let ranker = ranker("report");
+        // Paths that don't exist have no recency boost
+        assert_eq!(ranker.score_path(Path::new("/missing/b/report.pdf")), 0.0);
+        assert_eq!(ranker.score_path(Path::new("/missing/b/c/report.pdf")), -1.5);
+        assert_eq!(ranker.score_path(Path::new("/missing/b/c/d/e/report.pdf")), -4.5);
+
+        let deep = format!("/missing{}/report.pdf", "/dir".repeat(40));
+        assert_eq!(ranker.score_path(Path::new(&deep)), -MAX_DEPTH_PENALTY);
+    }
+
+    #[test]
+    fn boosts_recently_modified_and_opened_files() {
+        let dir = std::env::temp_dir().join(format!("grinta-ranking-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let now = SystemTime::now();
+        let touch = |name: &str, modified: SystemTime, accessed: SystemTime| {
+            let path = dir.join(name);
+            let file = File::create(&path).unwrap();
+            file.set_times(FileTimes::new().set_modified(modified).set_accessed(accessed))
+                .unwrap();
+            path
+        };
+        let fresh = touch("fresh.txt", now, now);
+        let modified = touch("modified.txt", now - MODIFIED_HALF_LIFE, now - 365 * DAY);
+        let stale = touch("stale.txt", now - 365 * DAY, now - 365 * DAY);
+
+        let ranker = SearchRanker {
+            words: vec!["report".into()],
+            now,
+        };
+        let depth = ranker.score_path(&dir.join("missing.txt"));
+        let boost = |path: &Path| ranker.score_path(path) - depth;
+
+        assert!((boost(&fresh) - (MODIFIED_SCORE + OPENED_SCORE)).abs() < 0.01);
+        // Half of the modified boost after one half-life
+        assert!((boost(&modified) - MODIFIED_SCORE / 2.0).abs() < 0.01);
+        assert!(boost(&stale) < 0.01);
+
+        // Times in the future count as now
+        assert_eq!(ranker.decay(now + DAY, OPENED_HALF_LIFE), 1.0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sorts_by_score_then_name() {
+        let info = |name: &str, score: f64| SpotlightAppInfo {
+            path: format!("/tmp/{name}"),
+            display_name: name.into(),
+            content_type: String::new(),
+            score,
+            match_ranges: vec![],
+        };
+        let mut results = vec![info("b", 1.0), info("C", 2.0), info("a", 1.0), info("d", 0.5)];
+        sort_by_rank(&mut results);
+
+        let names: Vec<&str> = results.iter().map(|info| info.display_name.as_str()).collect();
+        assert_eq!(names, ["C", "a", "b", "d"]);
+    }
+}
@@ -1,420 +1,449 @@
-use cocoa::base::{id, nil, YES};
-use cocoa::foundation::{NSString as CocoaNSString, NSUInteger};
-use core_foundation::runloop::{CFRunLoopGetCurrent, CFRunLoopStop};
-use objc::{class, msg_send, sel, sel_impl};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::{command, Emitter, Listener, Runtime, State, Window};
-use tokio::sync::oneshot;
-
-#[allow(non_snake_case)]
-#[derive(serde::Serialize, serde::Deserialize, Clone)]
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, Runtime, State, Window};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::{Error, Result};
+use crate::file_index::FileIndexState;
+use crate::platform::{FileSearchPlatform, NativePlatform};
+use crate::search_query::{parse_query, FileQuery};
+use crate::search_ranking::{sort_by_rank, MatchRange, SearchRanker};
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, specta::Type)]
+#[serde(rename_all = "camelCase")]
 pub struct SpotlightAppInfo {
     pub path: String,
     pub display_name: String,
     pub content_type: String,
+    // Set by the ranking stage, higher is better
+    #[serde(default)]
+    pub score: f64,
+    #[serde(default)]
+    pub match_ranges: Vec<MatchRange>,
 }
 
-// State to store ongoing queries
-#[allow(dead_code)]
-pub struct SpotlightState {
-    queries: Mutex<HashMap<String, Arc<Mutex<Vec<SpotlightAppInfo>>>>>,
+// Parameters of a single file search, handed over to the platform backend
+pub struct FileSearchRequest {
+    pub query: FileQuery,
+    pub extensions: Vec<String>,
+    pub search_only_in_home: bool,
 }
 
-#[allow(dead_code)]
-impl SpotlightState {
-    pub fn new() -> Self {
-        Self {
-            queries: Mutex::new(HashMap::new()),
+impl FileSearchRequest {
+    // `in:` terms replace the default search scopes
+    pub fn scopes(&self) -> Vec<PathBuf> {
+        let query_scopes = self.query.scopes();
+        if query_scopes.is_empty() {
+            search_scopes(self.search_only_in_home)
+        } else {
+            query_scopes
         }
     }
 }
 
-// A simple wrapper for NSAutoreleasePool to be shared (unsafe: not thread-safe, but used to make it work)
-struct SharedAutoreleasePool {
-    pool: id,
-}
+// Maximum number of results a single search returns
+pub const MAX_SEARCH_RESULTS: usize = 100;
 
-// Mark the wrapper as Send (unsafe, but for our purposes)
-unsafe impl Send for SharedAutoreleasePool {}
+// Searches still gathering after this long are stopped with partial results
+pub const SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
 
-impl SharedAutoreleasePool {
-    fn new() -> Self {
-        let pool: id = unsafe { msg_send![class!(NSAutoreleasePool), new] };
-        SharedAutoreleasePool { pool }
-    }
+// Emitted with a `FileSearchBatch` whenever a session gathered new results
+pub const FILE_SEARCH_BATCH_EVENT: &str = "file-search-batch";
+// Emitted with a `FileSearchComplete` once a session stopped
+pub const FILE_SEARCH_COMPLETE_EVENT: &str = "file-search-complete";
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, specta::Type)]
+pub struct FileSearchBatch {
+    pub search_id: String,
+    pub results: Vec<SpotlightAppInfo>,
 }
 
-impl Drop for SharedAutoreleasePool {
-    fn drop(&mut self) {
-        unsafe { let _: () = msg_send![self.pool, drain]; }
-    }
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, PartialEq, specta::Type)]
+#[serde(rename_all = "camelCase")]
+pub enum FileSearchStatus {
+    Finished,
+    Cancelled,
+    TimedOut,
+    Failed,
 }
 
-extern "C" {
-    static kMDItemPath: id;
-    static kMDItemDisplayName: id;
-    static kMDItemContentType: id;
+#[derive(serde::Serialize, serde::Deserialize, Clone, specta::Type)]
+pub struct FileSearchComplete {
+    pub search_id: String,
+    pub status: FileSearchStatus,
+    pub error: Option<Error>,
 }
 
-#[cfg(target_os = "macos")]
-#[command]
-#[allow(dead_code)]
-pub async fn search_spotlight_apps<R: Runtime>(
-    window: Window<R>,
-    state: State<'_, SpotlightState>,
-    query: Option<String>,
-    extensions: Vec<String>,
-    search_only_in_home: bool,
-) -> Result<Vec<SpotlightAppInfo>, String> {
-    let query_id = format!(
-        "query_{}",
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_millis()
-    );
-
-    // Execute synchronous operations within an autorelease pool scope
-    let rx = {
-        let _shared_pool = SharedAutoreleasePool::new();
-
-        // Create a shared results vector
-        let results = Arc::new(Mutex::new(Vec::new()));
-
-        // Store the results in the state
-        {
-            let mut queries = state.queries.lock().unwrap();
-            queries.insert(query_id.clone(), results.clone());
+type BatchListener = Box<dyn Fn(&str, &[SpotlightAppInfo]) + Send + Sync>;
+
+// A single running search. Backends hand over results in batches and poll
+// `should_stop` so cancelled or timed out searches end early.
+pub struct SearchSession {
+    pub id: String,
+    started_at: Instant,
+    cancelled: AtomicBool,
+    results: Mutex<Vec<SpotlightAppInfo>>,
+    ranker: SearchRanker,
+    on_batch: Option<BatchListener>,
+}
+
+impl SearchSession {
+    fn new(id: String, query: &FileQuery, on_batch: Option<BatchListener>) -> Self {
+        Self {
+            id,
+            started_at: Instant::now(),
+            cancelled: AtomicBool::new(false),
+            results: Mutex::new(Vec::new()),
+            ranker: SearchRanker::new(query),
+            on_batch,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.started_at.elapsed() >= SEARCH_TIMEOUT
+    }
+
+    pub fn should_stop(&self) -> bool {
+        self.is_cancelled() || self.is_timed_out() || self.result_count() >= MAX_SEARCH_RESULTS
+    }
+
+    pub fn result_count(&self) -> usize {
+        self.results.lock().map(|results| results.len()).unwrap_or(0)
+    }
+
+    // Ranks and stores new results, then forwards them to the listener. Results
+    // of a cancelled session and paths reported before are dropped.
+    pub fn push_batch(&self, batch: Vec<SpotlightAppInfo>) {
+        if self.is_cancelled() || batch.is_empty() {
+            return;
         }
 
-        // Create a oneshot channel to wait for the event
-        let (tx, rx) = oneshot::channel();
-
-        // Set up an event listener for the completion event
-        let event_name = "spotlight-search-complete";
-        let query_id_for_listener = query_id.clone();
-        let tx = Arc::new(Mutex::new(Some(tx)));
-        let tx_clone = tx.clone();
-
-        let _listener = window.listen(event_name, move |event| {
-            // Parse the payload as a string
-            let payload_str = event.payload().to_string();
-            // Remove quotes if present (JSON string)
-            let clean_payload = payload_str.trim_matches('"');
-            if clean_payload == query_id_for_listener {
-                // Take the sender out of the Option to consume it
-                if let Some(sender) = tx_clone.lock().unwrap().take() {
-                    let _ = sender.send(());
+        let added = {
+            let mut results = match self.results.lock() {
+                Ok(results) => results,
+                Err(_) => return,
+            };
+
+            let mut added = Vec::new();
+            for mut info in batch {
+                if results.len() >= MAX_SEARCH_RESULTS {
+                    break;
+                }
+                if !results.iter().any(|existing| existing.path == info.path) {
+                    self.ranker.rank(&mut info);
+                    results.push(info.clone());
+                    added.push(info);
                 }
             }
-        });
-
-        // Clone the window and query_id for the thread
-        let window_clone = window.clone();
-        let query_id_clone = query_id.clone();
-        let results_clone = results.clone();
-
-        // Spawn a thread to handle the Objective-C run loop
-        std::thread::spawn(move || {
-            unsafe {
-                // Create a block with captured variables from our context
-                let window_for_block = window_clone.clone();
-                let query_id_for_block = query_id_clone.clone();
-                let results_for_block = results_clone.clone();
-
-                // Use the block API to handle results directly
-                let block = block::ConcreteBlock::new(move |notification: id| -> () {
-                    // Get the query object
-                    let query_obj: id = msg_send![notification, object];
-
-                    // Process the results
-                    let query_results: id = msg_send![query_obj, results];
-                    let count: usize = msg_send![query_results, count];
-
-                    // Store the results
-                    if let Ok(mut results_vec) = results_for_block.lock() {
-                        for i in 0..std::cmp::min(count, 100) {
-                            let item: id = msg_send![query_results, objectAtIndex: i];
-
-                            let path_value: id = msg_send![item, valueForAttribute: kMDItemPath];
-                            let name_value: id = msg_send![item, valueForAttribute: kMDItemDisplayName];
-                            let content_type_value: id =
-                                msg_send![item, valueForAttribute: kMDItemContentType];
-
-                            if path_value != nil && name_value != nil && content_type_value != nil {
-                                let path_str: *const i8 = msg_send![path_value, UTF8String];
-                                let name_str: *const i8 = msg_send![name_value, UTF8String];
-                                let content_type_str: *const i8 =
-                                    msg_send![content_type_value, UTF8String];
-
-                                let path = std::ffi::CStr::from_ptr(path_str)
-                                    .to_string_lossy()
-                                    .into_owned();
-
-                                let name = std::ffi::CStr::from_ptr(name_str)
-                                    .to_string_lossy()
-                                    .into_owned();
-
-                                let content_type = std::ffi::CStr::from_ptr(content_type_str)
-                                    .to_string_lossy()
-                                    .into_owned();
-
-                                results_vec.push(SpotlightAppInfo {
-                                    path,
-                                    display_name: name,
-                                    content_type,
-                                });
-                            }
-                        }
-                    }
-
-                    // Signal completion by emitting event
-                    let _ =
-                        window_for_block.emit("spotlight-search-complete", query_id_for_block.clone());
-
-                    let _: () = msg_send![query_obj, stopQuery];
-                    CFRunLoopStop(CFRunLoopGetCurrent());
-                });
-
-                let block = block.copy();
-
-                // Register for the notification
-                let notification_name =
-                    CocoaNSString::alloc(nil).init_str("NSMetadataQueryDidFinishGatheringNotification");
-                let notification_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
-
-                let query_obj = create_metadata_query(search_only_in_home, query, Some(extensions));
-
-                let _: () = msg_send![notification_center,
-                    addObserverForName: notification_name
-                    object: query_obj
-                    queue: {let main_queue: id = msg_send![class!(NSOperationQueue), mainQueue]; main_queue}
-                    usingBlock: block
-                ];
-
-                // Start the query
-                let _: () = msg_send![query_obj, startQuery];
-                // Run the run loop until the query completes
-                use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoopRunInMode};
-                CFRunLoopRunInMode(kCFRunLoopDefaultMode, 5.0, 0);
+            added
+        };
+
+        if let Some(on_batch) = &self.on_batch {
+            if !added.is_empty() {
+                on_batch(&self.id, &added);
             }
-        });
+        }
+    }
 
-        rx
-    };
-    let _ = rx.await;
-
-    // After await, the shared autorelease pool will be dropped when going out of scope
-    let results_vec = {
-        let mut queries = state.queries.lock().unwrap();
-        let results_arc = queries
-            .get(&query_id)
-            .cloned()
-            .unwrap_or_else(|| Arc::new(Mutex::new(Vec::new())));
-        queries.remove(&query_id); // Now remove it after we've cloned it
-        let results_vec = results_arc.lock().unwrap().clone();
-        results_vec
-    };
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    fn status(&self) -> FileSearchStatus {
+        if self.is_cancelled() {
+            FileSearchStatus::Cancelled
+        } else if self.is_timed_out() {
+            FileSearchStatus::TimedOut
+        } else {
+            FileSearchStatus::Finished
+        }
+    }
 
-    Ok(results_vec)
+    // All results gathered so far, best ranked first
+    fn sorted_results(&self) -> Vec<SpotlightAppInfo> {
+        let mut results = self.results.lock().map(|results| results.clone()).unwrap_or_default();
+        sort_by_rank(&mut results);
+        results
+    }
 }
 
-// Helper function to create sort descriptors array
-unsafe fn create_sort_descriptors() -> id {
-    let sort_key: id =
-        msg_send![class!(NSString), stringWithUTF8String:"kMDItemDisplayName".as_ptr()];
-    let sort_descriptor: id = msg_send![
-        class!(NSSortDescriptor),
-        sortDescriptorWithKey:sort_key
-        ascending:YES
-    ];
-    msg_send![class!(NSArray), arrayWithObject:sort_descriptor]
+// Content type reported for folders, the frontend matches on the Spotlight UTI
+pub const FOLDER_CONTENT_TYPE: &str = "public.folder";
+
+pub fn is_hidden_name(name: &str) -> bool {
+    name.starts_with('.')
 }
 
-// Helper function to create value list attributes array
-unsafe fn create_value_list_attributes() -> id {
-    let value_list_attributes: id = msg_send![class!(NSMutableArray), array];
-    let display_name_attr: id =
-        msg_send![class!(NSString), stringWithUTF8String:"kMDItemDisplayName".as_ptr()];
-    let path_attr: id = msg_send![class!(NSString), stringWithUTF8String:"kMDItemPath".as_ptr()];
-    let content_type_attr: id =
-        msg_send![class!(NSString), stringWithUTF8String:"kMDItemContentType".as_ptr()];
-
-    let _: () = msg_send![value_list_attributes, addObject:display_name_attr];
-    let _: () = msg_send![value_list_attributes, addObject:path_attr];
-    let _: () = msg_send![value_list_attributes, addObject:content_type_attr];
-    value_list_attributes
+// Lowercases and strips diacritics, "Résumé" becomes "resume"
+pub fn fold_name(name: &str) -> String {
+    name.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
 }
 
-// Helper function to create search scope array
-unsafe fn create_search_scope(search_only_in_home: bool) -> id {
-    let file_manager: id = msg_send![class!(NSFileManager), defaultManager];
-    let user_domain_mask: NSUInteger = 1; // NSUserDomainMask
+// Mirrors the macOS search scopes: the whole home directory, or the user's
+// Downloads, Documents and Desktop folders.
+pub fn search_scopes(search_only_in_home: bool) -> Vec<PathBuf> {
+    let candidates = if search_only_in_home {
+        vec![dirs::home_dir()]
+    } else {
+        vec![dirs::download_dir(), dirs::document_dir(), dirs::desktop_dir()]
+    };
 
-    // Get home directory
-    let home_url: id = msg_send![file_manager, homeDirectoryForCurrentUser];
-    let home_path: id = msg_send![home_url, path];
+    let mut scopes: Vec<PathBuf> = Vec::new();
+    for scope in candidates.into_iter().flatten() {
+        // XDG directories may point at the home directory itself or at each other
+        if scope.is_dir() && !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    scopes
+}
 
-    if search_only_in_home {
-        msg_send![class!(NSMutableArray), arrayWithObject: home_path]
-    } else {
-        // Standard directories to search
-        let directories = [
-            (15, "NSDownloadsDirectory"),
-            (9, "NSDocumentDirectory"),
-            (12, "NSDesktopDirectory"),
-        ];
-
-        let computer_scope: id =
-            msg_send![class!(NSString), stringWithUTF8String:"kMDQueryScopeComputer".as_ptr()];
-        let scope_array: id = msg_send![class!(NSMutableArray), arrayWithObject: computer_scope];
-
-        for (directory_constant, _name) in directories.iter() {
-            let urls: id = msg_send![
-                file_manager,
-                URLsForDirectory: *directory_constant as NSUInteger
-                inDomains: user_domain_mask
-            ];
-            let count: NSUInteger = msg_send![urls, count];
-            if urls != nil && count > 0 {
-                let url: id = msg_send![urls, firstObject];
-                if url != nil {
-                    let path: id = msg_send![url, path]; // Use path instead of relativePath
-                    if path != nil {
-                        // Check if path is not nil and not empty before adding
-                        let path_str = if path != nil {
-                            let ns_string: id = msg_send![path, description];
-                            let utf8_str: *const std::os::raw::c_char =
-                                msg_send![ns_string, UTF8String];
-                            if !utf8_str.is_null() {
-                                std::ffi::CStr::from_ptr(utf8_str).to_str().unwrap_or("")
-                            } else {
-                                ""
-                            }
-                        } else {
-                            ""
-                        };
-                        if !path_str.is_empty() {
-                            let _: () = msg_send![scope_array, addObject: path];
-                        }
-                    }
-                }
-            }
+pub fn content_type_for(path: &Path, is_dir: bool) -> String {
+    if is_dir {
+        return FOLDER_CONTENT_TYPE.to_string();
+    }
+
+    mime_guess::from_path(path)
+        .first_or_octet_stream()
+        .essence_str()
+        .to_string()
+}
+
+// State to store ongoing queries
+pub struct SpotlightState {
+    queries: Mutex<HashMap<String, Arc<SearchSession>>>,
+    next_query_id: AtomicU64,
+}
+
+impl SpotlightState {
+    pub fn new() -> Self {
+        Self {
+            queries: Mutex::new(HashMap::new()),
+            next_query_id: AtomicU64::new(1),
         }
-        // Explicitly add home directory if not already covered or desired
-        // let _: () = msg_send![scope_array, addObject: home_path];
+    }
+
+    fn create_session(&self, query: &FileQuery, on_batch: Option<BatchListener>) -> Arc<SearchSession> {
+        // A counter instead of a timestamp, so concurrent queries never collide
+        let query_id = format!("query_{}", self.next_query_id.fetch_add(1, Ordering::SeqCst));
+        let session = Arc::new(SearchSession::new(query_id.clone(), query, on_batch));
+
+        self.queries.lock().unwrap().insert(query_id, session.clone());
+        session
+    }
 
-        scope_array
+    fn remove_session(&self, query_id: &str) -> Option<Arc<SearchSession>> {
+        self.queries.lock().unwrap().remove(query_id)
     }
 }
 
-unsafe fn create_metadata_query(
-    search_only_in_home: bool,
-    query: Option<String>,
-    extensions: Option<Vec<String>>,
-) -> id {
-    let query_obj: id = msg_send![class!(NSMetadataQuery), new];
+// Runs a session to completion on the current thread, then drops it from the state
+fn run_search_session<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    request: FileSearchRequest,
+    session: Arc<SearchSession>,
+) -> Result<FileSearchStatus> {
+    // The index answers in milliseconds once it is loaded
+    let result = match app_handle.state::<FileIndexState>().search(&request) {
+        Some(results) => {
+            session.push_batch(results);
+            Ok(())
+        }
+        None => NativePlatform::search_files(request, session.clone()),
+    };
 
-    let scope_array = create_search_scope(search_only_in_home);
-    let _: () = msg_send![query_obj, setSearchScopes: scope_array];
+    app_handle.state::<SpotlightState>().remove_session(&session.id);
 
-    let search_term = query.as_deref().unwrap_or("");
-    // Pass extensions.unwrap_or_default() to handle the Option
-    let predicate: id = create_spotlight_predicate(search_term, extensions.unwrap_or_default());
-    let _: () = msg_send![query_obj, setPredicate: predicate];
+    result.map(|_| session.status())
+}
 
-    let sort_descriptors_array = create_sort_descriptors();
-    let _: () = msg_send![query_obj, setSortDescriptors:sort_descriptors_array];
+#[command]
+#[specta::specta]
+pub async fn search_spotlight_apps<R: Runtime>(
+    window: Window<R>,
+    state: State<'_, SpotlightState>,
+    query: Option<String>,
+    extensions: Vec<String>,
+    search_only_in_home: bool,
+) -> Result<Vec<SpotlightAppInfo>> {
+    let request = FileSearchRequest {
+        query: parse_query(query.as_deref().unwrap_or("")).map_err(Error::invalid_argument)?,
+        extensions,
+        search_only_in_home,
+    };
 
-    let value_list_attributes_array = create_value_list_attributes();
-    let _: () = msg_send![query_obj, setValueListAttributes:value_list_attributes_array];
+    let session = state.create_session(&request.query, None);
+    let session_for_task = session.clone();
+    let app_handle = window.app_handle().clone();
 
-    query_obj
+    // The platform backends block until gathering finished
+    tauri::async_runtime::spawn_blocking(move || {
+        run_search_session(&app_handle, request, session_for_task)
+    })
+    .await??;
+
+    Ok(session.sorted_results())
 }
 
-#[cfg(target_os = "macos")]
-pub fn create_spotlight_predicate(query_text: &str, allowed_extensions: Vec<String>) -> id {
-    use cocoa::foundation::NSString as CocoaNSString;
+// Starts a search in the background and returns its ID right away. Results are
+// streamed as `file-search-batch` events, `file-search-complete` follows last.
+#[command]
+#[specta::specta]
+pub fn start_file_search<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, SpotlightState>,
+    query: Option<String>,
+    extensions: Vec<String>,
+    search_only_in_home: bool,
+) -> Result<String> {
+    let request = FileSearchRequest {
+        query: parse_query(query.as_deref().unwrap_or("")).map_err(Error::invalid_argument)?,
+        extensions,
+        search_only_in_home,
+    };
 
-    unsafe {
-        unsafe fn create_predicate(format: &str) -> id {
-            let predicate_str = CocoaNSString::alloc(nil).init_str(format);
-            let predicate: id = msg_send![class!(NSPredicate), predicateWithFormat: predicate_str];
+    let handle_for_batches = app_handle.clone();
+    let session = state.create_session(&request.query, Some(Box::new(move |search_id, results| {
+        let _ = handle_for_batches.emit(
+            FILE_SEARCH_BATCH_EVENT,
+            FileSearchBatch {
+                search_id: search_id.to_string(),
+                results: results.to_vec(),
+            },
+        );
+    })));
+    let search_id = session.id.clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let search_id = session.id.clone();
+        let (status, error) = match run_search_session(&app_handle, request, session) {
+            Ok(status) => (status, None),
+            Err(e) => (FileSearchStatus::Failed, Some(e)),
+        };
+
+        let _ = app_handle.emit(
+            FILE_SEARCH_COMPLETE_EVENT,
+            FileSearchComplete {
+                search_id,
+                status,
+                error,
+            },
+        );
+    });
+
+    Ok(search_id)
+}
 
-            let _: () = msg_send![predicate_str, release];
-            predicate
+// Stops a running search, e.g. when the next keystroke made it stale.
+// Returns false when the search already finished.
+#[command]
+#[specta::specta]
+pub fn cancel_file_search(state: State<'_, SpotlightState>, search_id: String) -> Result<bool> {
+    match state.remove_session(&search_id) {
+        Some(session) => {
+            session.cancel();
+            Ok(true)
         }
+        None => Ok(false),
+    }
+}
 
-        // Create predicates for each allowed extension
-        let mut allow_predicates: Vec<id> = Vec::with_capacity(allowed_extensions.len());
-        for (_i, ext) in allowed_extensions.iter().enumerate() {
-            let escaped_ext = ext.replace("'", "\\'").replace("\"", "\\\"").replace("\\\\", "\\\\\\\\"); // Escape quotes and backslashes
-            let format_str = format!("kMDItemDisplayName LIKE[c] '{}'", escaped_ext);
-            allow_predicates.push(create_predicate(&format_str));
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::scratch_tree;
+    use std::fs;
+
+    // Names found by the platform backend for `query` within `dir`
+    fn search(dir: &Path, query: &str) -> Vec<String> {
+        let request = FileSearchRequest {
+            query: parse_query(&format!("in:{} {}", dir.display(), query)).unwrap(),
+            extensions: vec!["*.pdf".to_string(), "*.txt".to_string()],
+            search_only_in_home: false,
+        };
+        let session = Arc::new(SearchSession::new("test".to_string(), &request.query, None));
+        NativePlatform::search_files(request, session.clone()).unwrap();
+
+        let mut names: Vec<String> = session
+            .sorted_results()
+            .into_iter()
+            .map(|info| info.display_name)
+            .collect();
+        names.sort();
+        names
+    }
 
-        // Create NSArray from the allow_predicates vector
-        let count = allow_predicates.len();
-        let allow_predicates_array: id =
-            msg_send![class!(NSArray), arrayWithObjects:allow_predicates.as_ptr() count:count];
-
-        // Create OR compound predicate for allowed extensions
-        let allow_compound_predicate: id = msg_send![
-            class!(NSCompoundPredicate),
-            orPredicateWithSubpredicates:allow_predicates_array
-        ];
-
-        // Create search predicate based on query text
-        let escaped_query = query_text.replace("\\", "\\\\").replace("'", "\\'").replace("\"", "\\\""); // Escape quotes
-        let search_format = format!("kMDItemDisplayName CONTAINS[cd] '{}'", escaped_query);
-        let search_predicate: id = create_predicate(&search_format);
-
-        // If we found a folder, it can't have dots
-        let folder_format =
-            "kMDItemContentType == 'public.folder' AND NOT (kMDItemDisplayName CONTAINS[cd] '.')";
-        let folder_predicate: id = create_predicate(folder_format);
-
-        // Create OR compound predicate for extensions or folders
-        let extensions_or_folder_predicates = [allow_compound_predicate, folder_predicate];
-        let extensions_or_folder_array: id = msg_send![class!(NSArray), arrayWithObjects:extensions_or_folder_predicates.as_ptr() count:2];
-        let extensions_or_folder_predicate: id = msg_send![
-            class!(NSCompoundPredicate),
-            orPredicateWithSubpredicates:extensions_or_folder_array
-        ];
-
-        // Create exclude system file predicate
-        let exclude_system_format = "NOT (kMDItemSupportFileType == 'MDSystemFile')";
-        let exclude_system_file_predicate: id = create_predicate(exclude_system_format);
-
-        // Create final AND compound predicate
-        let final_predicates = [
-            search_predicate,
-            extensions_or_folder_predicate,
-            exclude_system_file_predicate,
-        ];
-
-        let final_array: id =
-            msg_send![class!(NSArray),arrayWithObjects:final_predicates.as_ptr() count:3];
-
-        let final_predicate: id =
-            msg_send![class!(NSCompoundPredicate), andPredicateWithSubpredicates:final_array];
-
-        // Try to retain the predicate to prevent it from being released too early
-        let _: () = msg_send![final_predicate, retain];
-
-        final_predicate
+    #[test]
+    fn folds_case_and_diacritics() {
+        assert_eq!(fold_name("Résumé FINAL"), "resume final");
+        assert_eq!(fold_name("Ångström"), "angstrom");
     }
-}
 
-#[cfg(not(target_os = "macos"))]
-#[command]
-pub async fn search_spotlight_apps<R: Runtime>(
-    _window: Window<R>,
-    _state: State<'_, SpotlightState>,
-    _query: Option<String>,
-    _extensions: Vec<String>,
-) -> Result<Vec<SpotlightAppInfo>, String> {
-    Err("Spotlight search is only available on macOS".to_string())
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn matches_allowed_extensions_and_plain_folders() {
+        let dir = scratch_tree(
+            "search-rules",
+            &[
+                "Résumé.PDF",
+                "resume.txt",
+                "resume.png",
+                "Resume Drafts/",
+                "resume.d/",
+                ".resume.pdf",
+                ".cache/resume.pdf",
+                "Archive/old resume.pdf",
+            ],
+        );
+
+        assert_eq!(
+            search(&dir, "RESUME"),
+            ["Resume Drafts", "Résumé.PDF", "old resume.pdf", "resume.txt"]
+        );
+        // `kind:` lifts the extension allow-list
+        assert_eq!(search(&dir, "resume kind:folder"), ["Resume Drafts", "resume.d"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn stops_at_the_result_limit() {
+        let names: Vec<String> = (0..MAX_SEARCH_RESULTS + 20)
+            .map(|i| format!("note {}.txt", i))
+            .collect();
+        let entries: Vec<&str> = names.iter().map(String::as_str).collect();
+        let dir = scratch_tree("search-limit", &entries);
+
+        assert_eq!(search(&dir, "note").len(), MAX_SEARCH_RESULTS);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn drops_results_beyond_the_limit_and_duplicates() {
+        let query = parse_query("").unwrap();
+        let session = SearchSession::new("test".to_string(), &query, None);
+        let info = |i: usize| SpotlightAppInfo {
+            path: format!("/tmp/{}.txt", i),
+            display_name: format!("{}.txt", i),
+            content_type: "text/plain".to_string(),
+            score: 0.0,
+            match_ranges: Vec::new(),
+        };
+
+        session.push_batch((0..10).map(info).collect());
+        session.push_batch((5..MAX_SEARCH_RESULTS + 50).map(info).collect());
+        assert_eq!(session.result_count(), MAX_SEARCH_RESULTS);
+        assert!(session.should_stop());
+    }
 }
@@ -1,10 +1,15 @@
 // src-tauri/src/state.rs
 
-use std::sync::Mutex;
-use objc::runtime::Object;
-use objc_id;
+use crate::platform::{CalendarPlatform, NativeCalendar};
 
 pub struct CalendarState {
-    // Wrap the Id in a Mutex to ensure Send + Sync
-    pub event_store: Mutex<objc_id::Id<Object>>,
+    pub calendar: NativeCalendar,
+}
+
+impl CalendarState {
+    pub fn new() -> Self {
+        Self {
+            calendar: NativeCalendar::new(),
+        }
+    }
 }
@@ -0,0 +1,35 @@
+// src-tauri/src/test_utils.rs
+//
+// Helpers shared by the unit tests of several modules.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// A fresh directory in the system's temp directory with the given files and
+// their contents. Paths ending in a slash become empty folders.
+pub fn scratch_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("grinta-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (path, content) in files {
+        create_entry(&dir.join(path), content);
+    }
+    dir
+}
+
+// Same as `scratch_dir`, with empty files
+pub fn scratch_tree(name: &str, entries: &[&str]) -> PathBuf {
+    let files: Vec<(&str, &str)> = entries.iter().map(|entry| (*entry, "")).collect();
+    scratch_dir(name, &files)
+}
+
+// A file with `content`, or a folder if `path` ends in a slash, along with
+// the folders above it
+pub fn create_entry(path: &Path, content: &str) {
+    if path.to_string_lossy().ends_with('/') {
+        fs::create_dir_all(path).unwrap();
+    } else {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
@@ -0,0 +1,701 @@
+// src-tauri/src/text_extract.rs
+//
+// Text extraction for the document formats the content index understands
+// besides plain text. Office and ebook formats are zip archives of XML, PDF
+// text is read from the (usually deflated) page content streams. Extraction is
+// best effort: anything unexpected yields None and the file is skipped.
+
+use flate2::read::ZlibDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+
+// Formats with an extractor, besides plain text
+pub const DOCUMENT_EXTENSIONS: [&str; 6] = ["docx", "xlsx", "pptx", "odp", "epub", "pdf"];
+
+// Limits against zip bombs and huge documents
+const MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+const MAX_STREAM_SIZE: u64 = 16 * 1024 * 1024;
+// The index only needs the words, not the whole book
+const MAX_TEXT_LENGTH: usize = 1024 * 1024;
+
+pub fn is_document(path: &Path) -> bool {
+    document_extension(path).is_some()
+}
+
+pub fn extract_text(path: &Path) -> Option<String> {
+    let mut text = match document_extension(path)?.as_str() {
+        "docx" => extract_docx(path),
+        "xlsx" => extract_xlsx(path),
+        "pptx" => extract_pptx(path),
+        "odp" => extract_odp(path),
+        "epub" => extract_epub(path),
+        "pdf" => extract_pdf(path),
+        _ => None,
+    }?;
+
+    if text.len() > MAX_TEXT_LENGTH {
+        let mut end = MAX_TEXT_LENGTH;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+
+    Some(text)
+}
+
+fn document_extension(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    DOCUMENT_EXTENSIONS
+        .contains(&extension.as_str())
+        .then_some(extension)
+}
+
+// Word: paragraphs of `w:t` runs
+fn extract_docx(path: &Path) -> Option<String> {
+    let mut archive = open_archive(path)?;
+    let xml = read_entry(&mut archive, "word/document.xml")?;
+    Some(xml_text(&xml, XmlText::Only(&[b"t"]), &[b"p", b"tab", b"br"]))
+}
+
+// Excel: the shared string table plus inline strings of every sheet. Numbers
+// are left out, they are rarely what somebody searches for.
+fn extract_xlsx(path: &Path) -> Option<String> {
+    let mut archive = open_archive(path)?;
+    let mut parts = Vec::new();
+
+    if let Some(xml) = read_entry(&mut archive, "xl/sharedStrings.xml") {
+        parts.push(xml_text(&xml, XmlText::Only(&[b"t"]), &[b"si"]));
+    }
+    for name in sorted_entries(&archive, "xl/worksheets/sheet", ".xml") {
+        if let Some(xml) = read_entry(&mut archive, &name) {
+            parts.push(xml_text(&xml, XmlText::Only(&[b"t"]), &[b"row"]));
+        }
+    }
+
+    joined(parts)
+}
+
+// PowerPoint: slides in order, `a:t` runs grouped into paragraphs
+fn extract_pptx(path: &Path) -> Option<String> {
+    let mut archive = open_archive(path)?;
+    let mut parts = Vec::new();
+
+    for name in sorted_entries(&archive, "ppt/slides/slide", ".xml") {
+        if let Some(xml) = read_entry(&mut archive, &name) {
+            parts.push(xml_text(&xml, XmlText::Only(&[b"t"]), &[b"p"]));
+        }
+    }
+
+    joined(parts)
+}
+
+// OpenDocument presentation: all text of content.xml
+fn extract_odp(path: &Path) -> Option<String> {
+    let mut archive = open_archive(path)?;
+    let xml = read_entry(&mut archive, "content.xml")?;
+    Some(xml_text(&xml, XmlText::All(&[]), &[b"p", b"h", b"tab", b"line-break", b"page"]))
+}
+
+// EPUB: the XHTML documents of the spine, in reading order
+fn extract_epub(path: &Path) -> Option<String> {
+    let mut archive = open_archive(path)?;
+
+    let container = read_entry(&mut archive, "META-INF/container.xml")?;
+    let package_path = first_attribute(&container, b"rootfile", b"full-path")?;
+    let package = read_entry(&mut archive, &package_path)?;
+    let base = Path::new(&package_path).parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let mut parts = Vec::new();
+    for href in spine_documents(&package) {
+        let entry_name = normalize_entry_path(&base.join(&href));
+        if let Some(xhtml) = read_entry(&mut archive, &entry_name) {
+            parts.push(xml_text(
+                &xhtml,
+                XmlText::All(&[b"head", b"script", b"style"]),
+                &[b"p", b"div", b"br", b"li", b"tr", b"h1", b"h2", b"h3", b"h4", b"h5", b"h6"],
+            ));
+        }
+    }
+
+    joined(parts)
+}
+
+// Which text nodes of an XML document to keep
+enum XmlText<'a> {
+    // Only text directly inside these elements
+    Only(&'a [&'a [u8]]),
+    // All text except inside these elements
+    All(&'a [&'a [u8]]),
+}
+
+// Collects text nodes, starting a new line whenever one of `break_tags` opens
+// or closes. Tags are compared by local name, without namespace prefix.
+fn xml_text(xml: &[u8], selection: XmlText, break_tags: &[&[u8]]) -> String {
+    let mut reader = Reader::from_reader(xml);
+    let mut buffer = Vec::new();
+    let mut text = String::new();
+    // Depth inside selected (Only) or excluded (All) elements
+    let mut depth = 0usize;
+
+    let tags = match selection {
+        XmlText::Only(tags) | XmlText::All(tags) => tags,
+    };
+    let keep_text = |depth: usize| match selection {
+        XmlText::Only(_) => depth > 0,
+        XmlText::All(_) => depth == 0,
+    };
+
+    loop {
+        match reader.read_event_into(&mut buffer) {
+            Ok(Event::Start(element)) => {
+                let name = element.local_name();
+                if tags.contains(&name.as_ref()) {
+                    depth += 1;
+                }
+                if break_tags.contains(&name.as_ref()) {
+                    push_break(&mut text);
+                }
+            }
+            Ok(Event::End(element)) => {
+                let name = element.local_name();
+                if tags.contains(&name.as_ref()) {
+                    depth = depth.saturating_sub(1);
+                }
+                if break_tags.contains(&name.as_ref()) {
+                    push_break(&mut text);
+                }
+            }
+            Ok(Event::Empty(element)) if break_tags.contains(&element.local_name().as_ref()) => {
+                push_break(&mut text);
+            }
+            Ok(Event::Text(content)) if keep_text(depth) => {
+                // XHTML entities like &nbsp; aren't known to an XML parser
+                match content.unescape() {
+                    Ok(unescaped) => text.push_str(&unescaped),
+                    Err(_) => text.push_str(&String::from_utf8_lossy(&content)),
+                }
+            }
+            Ok(Event::CData(content)) if keep_text(depth) => {
+                text.push_str(&String::from_utf8_lossy(&content));
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    text.trim().to_string()
+}
+
+fn push_break(text: &mut String) {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+fn first_attribute(xml: &[u8], tag: &[u8], attribute: &[u8]) -> Option<String> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buffer = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buffer) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) if element.local_name().as_ref() == tag => {
+                let value = element
+                    .attributes()
+                    .flatten()
+                    .find(|attr| attr.key.local_name().as_ref() == attribute)?;
+                return value.unescape_value().ok().map(|value| value.into_owned());
+            }
+            Ok(Event::Eof) | Err(_) => return None,
+            _ => {}
+        }
+        buffer.clear();
+    }
+}
+
+// Hrefs of the package's spine items, resolved through the manifest
+fn spine_documents(package: &[u8]) -> Vec<String> {
+    let mut reader = Reader::from_reader(package);
+    let mut buffer = Vec::new();
+    let mut manifest: Vec<(String, String)> = Vec::new();
+    let mut spine: Vec<String> = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buffer) {
+            Ok(Event::Start(element)) | Ok(Event::Empty(element)) => {
+                let attribute = |key: &[u8]| {
+                    element
+                        .attributes()
+                        .flatten()
+                        .find(|attr| attr.key.local_name().as_ref() == key)
+                        .and_then(|attr| attr.unescape_value().ok().map(|value| value.into_owned()))
+                };
+
+                match element.local_name().as_ref() {
+                    b"item" => {
+                        if let (Some(id), Some(href)) = (attribute(b"id"), attribute(b"href")) {
+                            manifest.push((id, href));
+                        }
+                    }
+                    b"itemref" => spine.extend(attribute(b"idref")),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    spine
+        .iter()
+        .filter_map(|idref| manifest.iter().find(|(id, _)| id == idref))
+        .map(|(_, href)| href.split('#').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+// Zip entry names use forward slashes and no `..`
+fn normalize_entry_path(path: &Path) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for component in path.components() {
+        match component.as_os_str().to_string_lossy().as_ref() {
+            "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part.to_string()),
+        }
+    }
+    parts.join("/")
+}
+
+fn open_archive(path: &Path) -> Option<ZipArchive<File>> {
+    ZipArchive::new(File::open(path).ok()?).ok()
+}
+
+fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Option<Vec<u8>> {
+    let entry = archive.by_name(name).ok()?;
+    let mut data = Vec::new();
+    entry.take(MAX_ENTRY_SIZE).read_to_end(&mut data).ok()?;
+    Some(data)
+}
+
+// Entries like `prefix12suffix`, ordered by their number
+fn sorted_entries(archive: &ZipArchive<File>, prefix: &str, suffix: &str) -> Vec<String> {
+    let mut entries: Vec<(u32, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let number = name.strip_prefix(prefix)?.strip_suffix(suffix)?.parse().ok()?;
+            Some((number, name.to_string()))
+        })
+        .collect();
+
+    entries.sort();
+    entries.into_iter().map(|(_, name)| name).collect()
+}
+
+fn joined(parts: Vec<String>) -> Option<String> {
+    let parts: Vec<String> = parts.into_iter().filter(|part| !part.is_empty()).collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("\n"))
+    }
+}
+
+// PDF: the text shown by the page content streams. Covers the common case of
+// simple fonts; text drawn with embedded CID fonts comes out as glyph IDs and is
+// dropped by the printable check, scanned pages have no text layer at all.
+fn extract_pdf(path: &Path) -> Option<String> {
+    let mut data = Vec::new();
+    File::open(path).ok()?.take(MAX_STREAM_SIZE).read_to_end(&mut data).ok()?;
+    if !data.starts_with(b"%PDF") {
+        return None;
+    }
+
+    let mut text = String::new();
+    for (dictionary, stream) in pdf_streams(&data) {
+        // Images and embedded font programs never hold page text, unlike
+        // form XObjects which are drawn like pages
+        if is_image_or_font_program(dictionary) {
+            continue;
+        }
+
+        let content = if contains(dictionary, b"/FlateDecode") {
+            let mut decoded = Vec::new();
+            if ZlibDecoder::new(stream)
+                .take(MAX_STREAM_SIZE)
+                .read_to_end(&mut decoded)
+                .is_err()
+            {
+                continue;
+            }
+            decoded
+        } else if contains(dictionary, b"/Filter") {
+            // Other filters are used for images
+            continue;
+        } else {
+            stream.to_vec()
+        };
+
+        let page_text = content_stream_text(&content);
+        if !page_text.is_empty() {
+            push_break(&mut text);
+            text.push_str(&page_text);
+        }
+    }
+
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+// (dictionary, raw stream data) of every stream object
+fn pdf_streams(data: &[u8]) -> Vec<(&[u8], &[u8])> {
+    let mut streams = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = find(&data[position..], b"stream") {
+        let keyword = position + offset;
+        position = keyword + b"stream".len();
+
+        // Skip the `endstream` keyword itself
+        if keyword >= 3 && &data[keyword - 3..keyword] == b"end" {
+            continue;
+        }
+
+        let dictionary_start = dictionary_start(&data[..keyword]).unwrap_or(keyword);
+        let dictionary = &data[dictionary_start..keyword];
+
+        // The keyword is followed by CRLF or LF
+        let mut start = position;
+        if data.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if data.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+
+        let Some(length) = find(&data[start..], b"endstream") else {
+            break;
+        };
+        streams.push((dictionary, &data[start..start + length]));
+        position = start + length + b"endstream".len();
+    }
+
+    streams
+}
+
+// Start of the dictionary that `data` ends with, found by matching `>>` and `<<`
+// backwards so nested dictionaries like `/DecodeParms << ... >>` are skipped
+fn dictionary_start(data: &[u8]) -> Option<usize> {
+    let mut depth = 0;
+    let mut i = data.len();
+
+    while i >= 2 {
+        match &data[i - 2..i] {
+            b">>" => {
+                depth += 1;
+                i -= 2;
+            }
+            b"<<" if depth > 0 => {
+                depth -= 1;
+                i -= 2;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            // Only whitespace may separate the dictionary from the keyword
+            _ if depth == 0 && !data[i - 1].is_ascii_whitespace() => return None,
+            _ => i -= 1,
+        }
+    }
+
+    None
+}
+
+// Image XObjects and the streams of embedded fonts (`/FontFile` carries
+// `/Length1`, `/FontFile3` a subtype of its own)
+fn is_image_or_font_program(dictionary: &[u8]) -> bool {
+    const SKIPPED_SUBTYPES: [&[u8]; 4] = [b"Image", b"Type1C", b"CIDFontType0C", b"OpenType"];
+
+    if contains(dictionary, b"/Length1") {
+        return true;
+    }
+    let Some(offset) = find(dictionary, b"/Subtype") else {
+        return false;
+    };
+    let rest = &dictionary[offset + b"/Subtype".len()..];
+    let rest = rest.trim_ascii_start();
+    let Some(name) = rest.strip_prefix(b"/") else {
+        return false;
+    };
+    let end = name
+        .iter()
+        .position(|c| c.is_ascii_whitespace() || b"/<>[]()".contains(c))
+        .unwrap_or(name.len());
+    SKIPPED_SUBTYPES.contains(&&name[..end])
+}
+
+// Strings shown by the text operators of a content stream
+fn content_stream_text(content: &[u8]) -> String {
+    let mut text = String::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < content.len() {
+        match content[i] {
+            b'(' => {
+                let (string, next) = literal_string(content, i + 1);
+                pending.push(decode_pdf_string(&string));
+                i = next;
+            }
+            b'<' if content.get(i + 1) != Some(&b'<') => {
+                let end = content[i..].iter().position(|c| *c == b'>').map_or(content.len(), |end| i + end);
+                pending.push(decode_pdf_string(&hex_string(&content[i + 1..end])));
+                i = end + 1;
+            }
+            b'[' | b']' => i += 1,
+            // Large negative adjustments inside TJ arrays are word gaps
+            b'-' if content.get(i + 1).is_some_and(u8::is_ascii_digit) => {
+                let end = content[i + 1..]
+                    .iter()
+                    .position(|c| !c.is_ascii_digit() && *c != b'.')
+                    .map_or(content.len(), |end| i + 1 + end);
+                let amount: f64 = std::str::from_utf8(&content[i + 1..end]).ok().and_then(|n| n.parse().ok()).unwrap_or(0.0);
+                if amount > 200.0 && !pending.is_empty() {
+                    pending.push(" ".to_string());
+                }
+                i = end;
+            }
+            c if c.is_ascii_alphabetic() || c == b'\'' || c == b'"' || c == b'*' => {
+                let end = content[i..]
+                    .iter()
+                    .position(|c| !(c.is_ascii_alphabetic() || *c == b'\'' || *c == b'"' || *c == b'*'))
+                    .map_or(content.len(), |end| i + end);
+
+                match &content[i..end] {
+                    b"Tj" | b"TJ" => text.push_str(&pending.concat()),
+                    b"'" | b"\"" => {
+                        push_break(&mut text);
+                        text.push_str(&pending.concat());
+                    }
+                    b"Td" | b"TD" | b"T*" | b"ET" => push_break(&mut text),
+                    _ => {}
+                }
+                pending.clear();
+                i = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    text.trim().to_string()
+}
+
+// Reads a literal string starting after its opening parenthesis, returns the
+// bytes and the position after the closing one
+fn literal_string(content: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+    let mut bytes = Vec::new();
+    let mut nesting = 0;
+
+    while i < content.len() {
+        match content[i] {
+            b'\\' => {
+                i += 1;
+                match content.get(i) {
+                    Some(b'n') => bytes.push(b'\n'),
+                    Some(b'r') => bytes.push(b'\r'),
+                    Some(b't') => bytes.push(b'\t'),
+                    Some(b'b') => bytes.push(0x08),
+                    Some(b'f') => bytes.push(0x0c),
+                    Some(c) if c.is_ascii_digit() => {
+                        // Up to three octal digits
+                        let end = (i..std::cmp::min(i + 3, content.len()))
+                            .take_while(|j| (b'0'..=b'7').contains(&content[*j]))
+                            .last()
+                            .map_or(i + 1, |last| last + 1);
+                        let value = std::str::from_utf8(&content[i..end])
+                            .ok()
+                            .and_then(|octal| u32::from_str_radix(octal, 8).ok())
+                            .unwrap_or(0);
+                        bytes.push(value as u8);
+                        i = end - 1;
+                    }
+                    // Line continuation
+                    Some(b'\r') | Some(b'\n') => {}
+                    Some(c) => bytes.push(*c),
+                    None => {}
+                }
+            }
+            b'(' => {
+                nesting += 1;
+                bytes.push(b'(');
+            }
+            b')' if nesting == 0 => return (bytes, i + 1),
+            b')' => {
+                nesting -= 1;
+                bytes.push(b')');
+            }
+            c => bytes.push(c),
+        }
+        i += 1;
+    }
+
+    (bytes, i)
+}
+
+fn hex_string(hex: &[u8]) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .iter()
+        .filter_map(|c| (*c as char).to_digit(16).map(|d| d as u8))
+        .collect();
+
+    // An odd final digit is padded with 0
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0))
+        .collect()
+}
+
+// UTF-16 with a byte order mark, otherwise treated as Latin-1 which matches
+// the PDFDoc and WinAnsi encodings for letters
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    let decoded = if bytes.starts_with(&[0xfe, 0xff]) {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        bytes.iter().map(|byte| *byte as char).collect()
+    };
+
+    let printable = decoded.chars().filter(|c| !c.is_control() || c.is_whitespace()).count();
+    if printable < decoded.chars().count() {
+        return String::new();
+    }
+    decoded
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    find(haystack, needle).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures").join("documents").join(name)
+    }
+
+    fn extracted(name: &str) -> String {
+        extract_text(&fixture(name)).unwrap_or_else(|| panic!("no text extracted from {}", name))
+    }
+
+    #[test]
+    fn extracts_docx() {
+        let text = extracted("sample.docx");
+        assert!(text.contains("Quarterly report"));
+        assert!(text.contains("Revenue grew in every region"));
+    }
+
+    #[test]
+    fn extracts_xlsx() {
+        let text = extracted("sample.xlsx");
+        assert!(text.contains("Invoice number"));
+        assert!(text.contains("Inline total"));
+    }
+
+    #[test]
+    fn extracts_pptx_in_slide_order() {
+        let text = extracted("sample.pptx");
+        let first = text.find("Roadmap overview").unwrap();
+        let second = text.find("Next milestones").unwrap();
+        assert!(first < second);
+    }
+
+    #[test]
+    fn extracts_odp() {
+        let text = extracted("sample.odp");
+        assert!(text.contains("Team offsite agenda"));
+    }
+
+    #[test]
+    fn extracts_epub_spine() {
+        let text = extracted("sample.epub");
+        assert!(text.contains("Call me Ishmael"));
+        assert!(text.contains("Chapter two begins"));
+        assert!(!text.contains("color: red"));
+    }
+
+    #[test]
+    fn extracts_pdf_text_layer() {
+        let text = extracted("sample.pdf");
+        assert!(text.contains("Signed rental agreement"));
+        assert!(text.contains("Deposit (refundable)"));
+    }
+
+    #[test]
+    fn extracts_pdf_streams_with_nested_dictionaries() {
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        std::io::Write::write_all(&mut encoder, b"BT /F1 12 Tf (Deflated page text) Tj ET").unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut object = |dictionary: &str, stream: &[u8]| {
+            pdf.extend_from_slice(format!("1 0 obj\n{}\nstream\n", dictionary).as_bytes());
+            pdf.extend_from_slice(stream);
+            pdf.extend_from_slice(b"\nendstream\nendobj\n");
+        };
+        object(
+            "<< /Length 48 /Filter /FlateDecode /DecodeParms << /Predictor 1 /Columns 1 >> >>",
+            &deflated,
+        );
+        object(
+            "<< /Type /XObject /Subtype /Form /BBox [0 0 100 100] /Resources << /Font << /F1 5 0 R >> >> >>",
+            b"BT (Form text) Tj ET",
+        );
+        object("<< /Type /XObject /Subtype /Image /Width 1 /Height 1 >>", b"BT (Image bytes) Tj ET");
+        object("<< /Length1 20 /Length 20 >>", b"BT (Font program) Tj ET");
+        object("<< /Subtype /Type1C /Length 20 >>", b"BT (Compact font) Tj ET");
+
+        let path = std::env::temp_dir().join(format!("grinta-nested-{}.pdf", std::process::id()));
+        std::fs::write(&path, &pdf).unwrap();
+        let text = extract_text(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(text, "Deflated page text\nForm text");
+    }
+
+    #[test]
+    fn finds_the_outer_stream_dictionary() {
+        let data = b"1 0 obj << /A << /B 1 >> /C << >> >>\n";
+        assert_eq!(dictionary_start(data), Some(8));
+        assert_eq!(dictionary_start(b"<< /Length 3 >>"), Some(0));
+        // Not a dictionary right before the keyword
+        assert_eq!(dictionary_start(b"<< /Length 3 >> (a stream"), None);
+        assert_eq!(dictionary_start(b"/B 1 >> >>"), None);
+        assert_eq!(dictionary_start(b""), None);
+    }
+
+    #[test]
+    fn ignores_unknown_and_broken_files() {
+        assert!(extract_text(Path::new("notes.txt")).is_none());
+        assert!(extract_text(&fixture("missing.docx")).is_none());
+    }
+}
@@ -1,14 +1,18 @@
 use tauri::Manager;
 use tauri::command;
+
+use crate::error::{Error, Result};
+#[cfg(target_os = "macos")]
 use window_vibrancy::*;
 
 #[command]
+#[specta::specta]
 #[cfg(target_os = "macos")]
 #[allow(deprecated)]
-pub fn set_vibrancy(app_handle: tauri::AppHandle, material_name: String) -> Result<String, String> {
+pub fn set_vibrancy(app_handle: tauri::AppHandle, material_name: String) -> Result<String> {
     let window = app_handle
         .get_webview_window("main")
-        .ok_or("Window not found")?;
+        .ok_or_else(|| Error::not_found("Window not found"))?;
 
     // Map the string to the appropriate material
     let material = match material_name.as_str() {
@@ -28,22 +32,23 @@ pub fn set_vibrancy(app_handle: tauri::AppHandle, material_name: String) -> Resu
 
     // Clear any existing vibrancy
     if let Err(e) = clear_vibrancy(&window) {
-        return Err(format!("Failed to clear vibrancy: {}", e));
+        return Err(Error::platform("Failed to clear vibrancy").with_details(e));
     }
 
     // Apply new vibrancy
     match apply_vibrancy(&window, material, None, None) {
         Ok(_) => Ok("OK set vibrancy".into()),
-        Err(e) => Err(format!("Failed to apply vibrancy: {}", e)),
+        Err(e) => Err(Error::platform("Failed to apply vibrancy").with_details(e)),
     }
 }
 
 #[command]
+#[specta::specta]
 #[cfg(target_os = "macos")]
-pub fn set_appearance(app_handle: tauri::AppHandle, appearance: String) -> Result<String, String> {
+pub fn set_appearance(app_handle: tauri::AppHandle, appearance: String) -> Result<String> {
     let window = app_handle
         .get_webview_window("main")
-        .ok_or("Window not found")?;
+        .ok_or_else(|| Error::not_found("Window not found"))?;
 
     if let Ok(ns_window_ptr) = window.ns_window() {
         // Map the string to the appropriate appearance
@@ -76,6 +81,33 @@ pub fn set_appearance(app_handle: tauri::AppHandle, appearance: String) -> Resul
 
         Ok("OK set appearance".into())
     } else {
-        Err("Failed to get window handle".into())
+        Err(Error::platform("Failed to get window handle"))
     }
 }
+
+#[command]
+#[specta::specta]
+#[cfg(not(target_os = "macos"))]
+pub fn set_vibrancy(_app_handle: tauri::AppHandle, _material_name: String) -> Result<String> {
+    Err(Error::unsupported("Vibrancy is only available on macOS"))
+}
+
+#[command]
+#[specta::specta]
+#[cfg(not(target_os = "macos"))]
+pub fn set_appearance(app_handle: tauri::AppHandle, appearance: String) -> Result<String> {
+    let window = app_handle
+        .get_webview_window("main")
+        .ok_or_else(|| Error::not_found("Window not found"))?;
+
+    let theme = match appearance.as_str() {
+        "light" => tauri::Theme::Light,
+        _ => tauri::Theme::Dark, // Default to dark
+    };
+
+    window
+        .set_theme(Some(theme))
+        .map_err(|e| Error::platform("Failed to set appearance").with_details(e))?;
+
+    Ok("OK set appearance".into())
+}
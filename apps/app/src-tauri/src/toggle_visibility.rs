@@ -1,17 +1,15 @@
 use tauri::{command, Emitter};
-use tauri_nspanel::ManagerExt;
 
-pub const MAIN_WINDOW_LABEL: &str = "main";
+use crate::error::Result;
+use crate::platform::{NativePlatform, PanelPlatform};
 
 #[command]
-#[cfg(target_os = "macos")]
-pub fn toggle_visibility(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let panel = app_handle.get_webview_panel(MAIN_WINDOW_LABEL).unwrap();
-
-    if panel.is_visible() {
-        panel.order_out(None);
+#[specta::specta]
+pub fn toggle_visibility(app_handle: tauri::AppHandle) -> Result<String> {
+    if NativePlatform::is_visible(&app_handle) {
+        NativePlatform::hide(&app_handle);
     } else {
-        panel.show();
+        NativePlatform::show(&app_handle);
         app_handle.emit("focus", ()).unwrap();
     }
 
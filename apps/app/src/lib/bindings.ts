@@ -0,0 +1,238 @@
+// Generated from the Tauri commands in src-tauri, do not edit.
+// Run `UPDATE_BINDINGS=1 cargo test bindings` in src-tauri to update.
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async show() : Promise<void> {
+    await TAURI_INVOKE("show");
+},
+async hide() : Promise<void> {
+    await TAURI_INVOKE("hide");
+},
+async setVibrancy(materialName: string) : Promise<string> {
+    return await TAURI_INVOKE("set_vibrancy", { materialName });
+},
+async setAppearance(appearance: string) : Promise<string> {
+    return await TAURI_INVOKE("set_appearance", { appearance });
+},
+async loadAppInfo(resourcesPaths: string[], size: number | null, scale: number | null, format: IconFormat | null, images: boolean | null) : Promise<Partial<{ [key in string]: AppInfo }>> {
+    return await TAURI_INVOKE("load_app_info", { resourcesPaths, size, scale, format, images });
+},
+async loadExtensionIcons(extensions: string[], size: number | null, scale: number | null, format: IconFormat | null) : Promise<Partial<{ [key in string]: string }>> {
+    return await TAURI_INVOKE("load_extension_icons", { extensions, size, scale, format });
+},
+async listInstalledApps() : Promise<InstalledApp[]> {
+    return await TAURI_INVOKE("list_installed_apps");
+},
+async findApps(maxDepth: number | null) : Promise<AppBundle[]> {
+    return await TAURI_INVOKE("find_apps", { maxDepth });
+},
+async getOpenWithApps(file: string) : Promise<OpenWithApp[]> {
+    return await TAURI_INVOKE("get_open_with_apps", { file });
+},
+async openFileWith(file: string, appPath: string) : Promise<null> {
+    return await TAURI_INVOKE("open_file_with", { file, appPath });
+},
+async clearIconCache() : Promise<null> {
+    return await TAURI_INVOKE("clear_icon_cache");
+},
+async getIconCacheStats() : Promise<IconCacheStats> {
+    return await TAURI_INVOKE("get_icon_cache_stats");
+},
+async searchSpotlightApps(query: string | null, extensions: string[], searchOnlyInHome: boolean) : Promise<SpotlightAppInfo[]> {
+    return await TAURI_INVOKE("search_spotlight_apps", { query, extensions, searchOnlyInHome });
+},
+async startFileSearch(query: string | null, extensions: string[], searchOnlyInHome: boolean) : Promise<string> {
+    return await TAURI_INVOKE("start_file_search", { query, extensions, searchOnlyInHome });
+},
+async cancelFileSearch(searchId: string) : Promise<boolean> {
+    return await TAURI_INVOKE("cancel_file_search", { searchId });
+},
+async toggleVisibility() : Promise<string> {
+    return await TAURI_INVOKE("toggle_visibility");
+},
+async activateApplicationByName(appName: string) : Promise<boolean> {
+    return await TAURI_INVOKE("activate_application_by_name", { appName });
+},
+async getFrontmostApplicationName() : Promise<string> {
+    return await TAURI_INVOKE("get_frontmost_application_name");
+},
+async fetchFavicon(url: string) : Promise<string> {
+    return await TAURI_INVOKE("fetch_favicon", { url });
+},
+async setSecret(serviceName: string, accountName: string, secret: string) : Promise<null> {
+    return await TAURI_INVOKE("set_secret", { serviceName, accountName, secret });
+},
+async getSecret(serviceName: string, accountName: string) : Promise<string> {
+    return await TAURI_INVOKE("get_secret", { serviceName, accountName });
+},
+async deleteSecret(serviceName: string, accountName: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_secret", { serviceName, accountName });
+},
+async getCalendarAuthorizationStatus() : Promise<CalendarAuthorizationStatus> {
+    return await TAURI_INVOKE("get_calendar_authorization_status");
+},
+async requestCalendarAccess() : Promise<CalendarAuthorizationStatus> {
+    return await TAURI_INVOKE("request_calendar_access");
+},
+async getCalendars() : Promise<CalendarInfo[]> {
+    return await TAURI_INVOKE("get_calendars");
+},
+async getCalendarEvents(calendarIds: string[], startDateIso: string, endDateIso: string) : Promise<EventInfo[]> {
+    return await TAURI_INVOKE("get_calendar_events", { calendarIds, startDateIso, endDateIso });
+},
+async createCalendarEvent(event: EventDraft) : Promise<EventInfo> {
+    return await TAURI_INVOKE("create_calendar_event", { event });
+},
+async updateCalendarEvent(calendarId: string, eventId: string, occurrenceDate: string, event: EventDraft) : Promise<EventInfo> {
+    return await TAURI_INVOKE("update_calendar_event", { calendarId, eventId, occurrenceDate, event });
+},
+async deleteCalendarEvent(calendarId: string, eventId: string) : Promise<null> {
+    return await TAURI_INVOKE("delete_calendar_event", { calendarId, eventId });
+},
+async getIcsCalendar(path: string) : Promise<CalendarInfo> {
+    return await TAURI_INVOKE("get_ics_calendar", { path });
+},
+async getIcsEvents(path: string, startDateIso: string, endDateIso: string) : Promise<EventInfo[]> {
+    return await TAURI_INVOKE("get_ics_events", { path, startDateIso, endDateIso });
+},
+async exportIcsFile(path: string, events: EventInfo[], calendarName: string | null) : Promise<null> {
+    return await TAURI_INVOKE("export_ics_file", { path, events, calendarName });
+},
+async importIcsCalendar(path: string) : Promise<CalendarInfo> {
+    return await TAURI_INVOKE("import_ics_calendar", { path });
+},
+async removeIcsCalendar(path: string) : Promise<null> {
+    return await TAURI_INVOKE("remove_ics_calendar", { path });
+},
+async addCaldavAccount(serverUrl: string, username: string, password: string) : Promise<CalDavAccount> {
+    return await TAURI_INVOKE("add_caldav_account", { serverUrl, username, password });
+},
+async listCaldavAccounts() : Promise<CalDavAccount[]> {
+    return await TAURI_INVOKE("list_caldav_accounts");
+},
+async refreshCaldavCalendars() : Promise<CalDavAccount[]> {
+    return await TAURI_INVOKE("refresh_caldav_calendars");
+},
+async removeCaldavAccount(accountId: string) : Promise<null> {
+    return await TAURI_INVOKE("remove_caldav_account", { accountId });
+},
+async rebuildFileIndex() : Promise<FileIndexStats> {
+    return await TAURI_INVOKE("rebuild_file_index");
+},
+async getFileIndexStats() : Promise<FileIndexStats> {
+    return await TAURI_INVOKE("get_file_index_stats");
+},
+async rebuildContentIndex(notesDir: string[]) : Promise<ContentIndexStats> {
+    return await TAURI_INVOKE("rebuild_content_index", { notesDir });
+},
+async clearContentIndex() : Promise<null> {
+    return await TAURI_INVOKE("clear_content_index");
+},
+async searchFileContents(query: string, limit: number | null) : Promise<ContentMatch[]> {
+    return await TAURI_INVOKE("search_file_contents", { query, limit });
+},
+async getContentIndexStats() : Promise<ContentIndexStats> {
+    return await TAURI_INVOKE("get_content_index_stats");
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type AppBundle = { name: string; path: string; suite: string | null }
+export type AppInfo = ({ bundleId: string | null; version: string | null; categories: string[]; urlSchemes: string[]; documentTypes: DocumentType[]; uiElement: boolean; backgroundOnly: boolean }) & { base64Image: string; localizedName: string }
+export type AppLaunchInfo = { exec: string | null; icon: string | null; keywords: string[]; terminal: boolean; source: AppSource }
+export type AppSource = "system" | "user" | "flatpak" | "snap" | "bundle"
+export type CalDavAccount = { id: string; serverUrl: string; username: string; calendars: CalendarInfo[] }
+export type CalendarAuthorizationStatus = "notDetermined" | "restricted" | "denied" | "authorized"
+export type CalendarInfo = { identifier: string; title: string; color: string }
+export type CommandError = { code: ErrorCode; message: string; details?: string | null }
+export type ContentIndexStats = { document_count: number; term_count: number; last_build: string | null; size_bytes: number; is_ready: boolean; is_building: boolean }
+export type ContentMatch = { path: string; display_name: string; line_number: number; line: string; snippet: string }
+export type DocumentType = { name: string | null; role: string | null; contentTypes: string[]; extensions: string[]; mimeTypes: string[] }
+export type ErrorCode = "NOT_AUTHORIZED" | "NOT_FOUND" | "INVALID_ARGUMENT" | "UNSUPPORTED" | "BUSY" | "CONFLICT" | "IO" | "NETWORK" | "PLATFORM" | "INTERNAL"
+export type EventDraft = { calendarId: string; title: string; startDate: string; endDate: string; isAllDay: boolean; location: string | null; url: string | null; notes: string | null }
+export type EventInfo = { identifier: string; title: string; notes: string | null; url: string | null; participants: ParticipantInfo[]; start_date: string; end_date: string; calendar_id: string; location: string | null; is_all_day: boolean }
+export type FileIndexStats = { file_count: number; last_scan: string | null; size_bytes: number; is_ready: boolean; is_building: boolean }
+export type FileSearchBatch = { search_id: string; results: SpotlightAppInfo[] }
+export type FileSearchComplete = { search_id: string; status: FileSearchStatus; error: CommandError | null }
+export type FileSearchStatus = "finished" | "cancelled" | "timedOut" | "failed"
+export type IconCacheStats = { entry_count: number; size_bytes: number }
+export type IconFormat = "png" | "webp"
+export type InstalledApp = ({ bundleId: string | null; version: string | null; categories: string[]; urlSchemes: string[]; documentTypes: DocumentType[]; uiElement: boolean; backgroundOnly: boolean }) & { base64Image: string; localizedName: string; name: string; id: string; path: string; suite: string | null; launch: AppLaunchInfo }
+export type MatchRange = { start: number; end: number }
+export type OpenWithApp = { name: string; localizedName: string; path: string; bundleId: string | null; role: string | null; isDefault: boolean }
+export type ParticipantInfo = { name: string | null }
+export type RunningAppInfo = { bundle_identifier: string; localized_name: string; is_active: boolean; is_hidden: boolean }
+export type SpotlightAppInfo = { path: string; displayName: string; contentType: string; score?: number; matchRanges?: MatchRange[] }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
@@ -1,14 +1,50 @@
-import {
-  type InvokeArgs,
-  type InvokeOptions,
-  invoke,
-} from "@tauri-apps/api/core";
+import { convertFileSrc } from "@tauri-apps/api/core";
 import { uniq } from "rambda";
-import type {
-  CalendarAuthorizationStatus,
-  CalendarInfo,
-  EventInfo,
-} from "./types/calendar";
+import {
+  type AppBundle,
+  type AppInfo,
+  type CalDavAccount,
+  type CalendarInfo,
+  type CommandError,
+  type ContentIndexStats,
+  type ContentMatch,
+  type ErrorCode,
+  type EventDraft,
+  type EventInfo,
+  type FileIndexStats,
+  type IconCacheStats,
+  type IconFormat,
+  type InstalledApp,
+  type OpenWithApp,
+  type SpotlightAppInfo,
+  commands,
+} from "./bindings";
+import type { CalendarAuthorizationStatus } from "./types/calendar";
+
+// Types generated from the Rust commands, see src-tauri/src/bindings.rs
+export type {
+  AppBundle,
+  AppInfo,
+  AppLaunchInfo,
+  AppSource,
+  CalDavAccount,
+  CommandError,
+  ContentIndexStats,
+  ContentMatch,
+  DocumentType,
+  ErrorCode,
+  EventDraft,
+  FileIndexStats,
+  FileSearchBatch,
+  FileSearchComplete,
+  IconCacheStats,
+  IconFormat,
+  InstalledApp,
+  MatchRange,
+  OpenWithApp,
+  RunningAppInfo,
+  SpotlightAppInfo,
+} from "./bindings";
 
 export const SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS = [
   // Documents
@@ -65,30 +101,105 @@ export const SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS = [
 export type SupportedFileExtension =
   (typeof SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS)[number];
 
-export function setVibrancy(
-  materialName: "dark" | "light",
-): Promise<SpotlightSearchResult[]> {
-  return grintaInvoke("set_vibrancy", { materialName });
+export function setVibrancy(materialName: "dark" | "light"): Promise<string> {
+  return commands.setVibrancy(materialName);
 }
 
-export type AppInfo = { base64Image: string; localizedName: string };
+// From Info.plist on macOS and .desktop files on Linux
+export type AppMetadata = Omit<AppInfo, "base64Image" | "localizedName">;
+
 export type ExtInfo = { base64Image: string; extension: string };
 
+// Icons are rendered at `size` CSS pixels times `scale` device pixels. Without
+// a size the backend keeps the platform's native size.
+export type IconOptions = {
+  size?: number;
+  scale?: number;
+  format?: IconFormat;
+};
+
+// `images: false` leaves `base64Image` empty, for icons loaded with `iconUrl`
 export async function loadAppInfo(
   paths: string[],
+  { size, scale, format, images }: IconOptions & { images?: boolean } = {},
 ): Promise<Record<string, AppInfo>> {
-  return grintaInvoke<Record<string, AppInfo>>("load_app_info", {
-    resourcesPaths: paths,
-  });
+  const appInfo = await commands.loadAppInfo(
+    paths,
+    size ?? null,
+    scale ?? null,
+    format ?? null,
+    images ?? null,
+  );
+  return appInfo as Record<string, AppInfo>;
+}
+
+export async function loadExtensionIcons(
+  extensions: string[],
+  { size, scale, format }: IconOptions = {},
+): Promise<Record<string, string>> {
+  const icons = await commands.loadExtensionIcons(
+    extensions,
+    size ?? null,
+    scale ?? null,
+    format ?? null,
+  );
+  return icons as Record<string, string>;
+}
+
+export type IconRoute = "app" | "ext" | "favicon";
+
+// `<img>` source for an icon served by the grinta-icon protocol. `target` is an
+// app bundle or .desktop path, a file extension or a host.
+export function iconUrl(
+  route: IconRoute,
+  target: string,
+  options: IconOptions = {},
+): string {
+  const url = new URL(convertFileSrc(`${route}/${target}`, "grinta-icon"));
+  for (const [key, value] of Object.entries(options)) {
+    if (value !== undefined) url.searchParams.set(key, String(value));
+  }
+  return url.toString();
+}
+
+export async function listInstalledApps(): Promise<InstalledApp[]> {
+  return commands.listInstalledApps();
+}
+
+// .app bundles in the application folders and their subfolders, down to
+// `maxDepth` levels (2 by default)
+export async function findApps(maxDepth?: number): Promise<AppBundle[]> {
+  return commands.findApps(maxDepth ?? null);
+}
+
+// Applications that can open a file path or an extension ("pdf"), the
+// default one first
+export async function getOpenWithApps(file: string): Promise<OpenWithApp[]> {
+  return commands.getOpenWithApps(file);
+}
+
+export async function openFileWith(
+  file: string,
+  appPath: string,
+): Promise<void> {
+  await commands.openFileWith(file, appPath);
+}
+
+export async function clearIconCache(): Promise<void> {
+  await commands.clearIconCache();
+}
+
+export async function getIconCacheStats(): Promise<IconCacheStats> {
+  return commands.getIconCacheStats();
 }
 
 export async function activateAppByName(name: string): Promise<boolean> {
-  return grintaInvoke("activate_application_by_name", { appName: name });
+  return commands.activateApplicationByName(name);
 }
 
 export async function getLastFocusedWindowName(): Promise<string> {
   try {
-    return await grintaInvoke("get_frontmost_application_name");
+    return await commands.getFrontmostApplicationName();
   } catch (error) {
     console.error("Failed to get frontmost application name:", error);
     return "";
@@ -96,112 +207,123 @@ export async function getLastFocusedWindowName(): Promise<string> {
 }
 
 export async function toggleVisibility(): Promise<string> {
-  return grintaInvoke("toggle_visibility");
+  return commands.toggleVisibility();
 }
 
 export async function searchSpotlightApps(
   query: string,
   additionalExtensions: string[] = [],
   searchOnlyInHome = false,
-) {
-  return await invoke<SpotlightAppInfo[]>("search_spotlight_apps", {
+): Promise<SpotlightAppInfo[]> {
+  return commands.searchSpotlightApps(
+    query,
+    uniq([...SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS, ...additionalExtensions]),
+    searchOnlyInHome,
+  );
+}
+
+export const FILE_SEARCH_BATCH_EVENT = "file-search-batch";
+export const FILE_SEARCH_COMPLETE_EVENT = "file-search-complete";
+
+let activeFileSearchId: string | null = null;
+
+/**
+ * Starts a streaming file search. Results arrive as `file-search-batch` events,
+ * and the previous search is cancelled since its results became stale.
+ */
+export async function startFileSearch(
+  query: string,
+  additionalExtensions: string[] = [],
+  searchOnlyInHome = false,
+): Promise<string> {
+  if (activeFileSearchId) {
+    void cancelFileSearch(activeFileSearchId);
+  }
+  const searchId = await commands.startFileSearch(
     query,
-    extensions: uniq([
-      ...SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS,
-      ...additionalExtensions,
-    ]),
-    searchOnlyInHome: searchOnlyInHome,
-  });
+    uniq([...SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS, ...additionalExtensions]),
+    searchOnlyInHome,
+  );
+  activeFileSearchId = searchId;
+  return searchId;
+}
+
+export async function cancelFileSearch(searchId: string): Promise<boolean> {
+  if (activeFileSearchId === searchId) {
+    activeFileSearchId = null;
+  }
+  return commands.cancelFileSearch(searchId);
 }
 
 export async function fetchFavicon(url: string): Promise<string> {
-  return await invoke<string>("fetch_favicon", { url });
+  return commands.fetchFavicon(url);
 }
 
 export async function requestAccessToUserFolders(): Promise<
-  SpotlightSearchResult[]
+  SpotlightAppInfo[]
 > {
-  return grintaInvoke<SpotlightSearchResult[]>("search_spotlight_apps", {
-    query: "random_request_access_handler",
-    extensions: SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS,
-    searchOnlyInHome: false,
-  });
+  return commands.searchSpotlightApps(
+    "random_request_access_handler",
+    [...SUPPORTED_FILE_INDEXING_FILE_EXTENSIONS],
+    false,
+  );
 }
 
-type SpotlightSearchResult = {
-  display_name: string;
-  path: string;
-  content_type: string;
-};
-
-type SpotlightAppInfo = {
-  display_name: string;
-  path: string;
-  content_type: string;
-};
-
-type GrintaInvokeCommand =
-  | "search_spotlight_apps"
-  | "set_vibrancy"
-  | "load_app_info"
-  | "activate_application_by_name"
-  | "toggle_visibility"
-  | "get_frontmost_application_name"
-  | "fetch_favicon"
-  | "set_secret"
-  | "get_secret"
-  | "delete_secret"
-  | "get_calendar_authorization_status"
-  | "request_calendar_access"
-  | "get_calendars"
-  | "get_calendar_events"
-  | "show"
-  | "hide";
-
-function grintaInvoke<T>(
-  cmd: GrintaInvokeCommand,
-  args?: InvokeArgs,
-  options?: InvokeOptions,
-): Promise<T> {
-  return invoke(cmd, args, options);
+/**
+ * Whether `error` is what a failed command rejects with, optionally with the
+ * given code.
+ */
+export function isCommandError(
+  error: unknown,
+  code?: ErrorCode,
+): error is CommandError {
+  if (typeof error !== "object" || error === null) return false;
+  const { code: errorCode, message } = error as Partial<CommandError>;
+  return (
+    typeof errorCode === "string" &&
+    typeof message === "string" &&
+    (code === undefined || errorCode === code)
+  );
 }
 
 export async function getMasterKey(): Promise<string> {
-  return grintaInvoke("get_secret", {
-    serviceName: "grinta",
-    accountName: "master-key",
-  });
+  return commands.getSecret("grinta", "master-key");
 }
 
+// Null when nothing is stored for the account
 export async function getSecret(
   service: string,
   accountName: string,
 ): Promise<string | null> {
-  return await grintaInvoke("get_secret", {
-    service,
-    account: accountName,
-  });
+  try {
+    return await commands.getSecret(service, accountName);
+  } catch (error) {
+    if (isCommandError(error, "NOT_FOUND")) return null;
+    throw error;
+  }
 }
 
 export async function deleteSecret(
   service: string,
   account: string,
 ): Promise<void> {
-  await grintaInvoke("delete_secret", { service, account });
+  await commands.deleteSecret(service, account);
 }
 
 // --- Calendar Commands ---
 
 export async function getCalendarAuthorizationStatus(): Promise<CalendarAuthorizationStatus> {
-  return await grintaInvoke("get_calendar_authorization_status");
+  const status = await commands.getCalendarAuthorizationStatus();
+  return status as CalendarAuthorizationStatus;
 }
 
 export async function requestCalendarAccess(): Promise<CalendarAuthorizationStatus> {
-  return await grintaInvoke("request_calendar_access");
+  const status = await commands.requestCalendarAccess();
+  return status as CalendarAuthorizationStatus;
 }
 
 export async function getCalendars(): Promise<CalendarInfo[]> {
-  return await grintaInvoke("get_calendars");
+  return await commands.getCalendars();
 }
 
 export async function getCalendarEvents(
@@ -209,19 +331,141 @@ export async function getCalendarEvents(
   startDateIso: string,
   endDateIso: string,
 ): Promise<EventInfo[]> {
-  return await grintaInvoke("get_calendar_events", {
+  return await commands.getCalendarEvents(
     calendarIds,
     startDateIso,
     endDateIso,
-  });
+  );
+}
+
+// Writes to EventKit on macOS, and to the CalDAV server or .ics file of the
+// calendar elsewhere. Editing a recurring event changes the whole series.
+export async function createCalendarEvent(
+  event: EventDraft,
+): Promise<EventInfo> {
+  return await commands.createCalendarEvent(event);
+}
+
+// `calendarId` is where the event is now, `event.calendarId` where it goes.
+// `occurrenceDate` is the start of the occurrence the draft was made from;
+// the series moves as far as that occurrence did.
+export async function updateCalendarEvent(
+  calendarId: string,
+  eventId: string,
+  occurrenceDate: string,
+  event: EventDraft,
+): Promise<EventInfo> {
+  return await commands.updateCalendarEvent(
+    calendarId,
+    eventId,
+    occurrenceDate,
+    event,
+  );
+}
+
+export async function deleteCalendarEvent(
+  calendarId: string,
+  eventId: string,
+): Promise<void> {
+  await commands.deleteCalendarEvent(calendarId, eventId);
+}
+
+// --- iCalendar Files ---
+
+export async function getIcsCalendar(path: string): Promise<CalendarInfo> {
+  return await commands.getIcsCalendar(path);
+}
+
+// Lists the file with the other calendars from now on, reading it in place
+export async function importIcsCalendar(path: string): Promise<CalendarInfo> {
+  return await commands.importIcsCalendar(path);
+}
+
+export async function removeIcsCalendar(path: string): Promise<void> {
+  await commands.removeIcsCalendar(path);
+}
+
+export async function getIcsEvents(
+  path: string,
+  startDateIso: string,
+  endDateIso: string,
+): Promise<EventInfo[]> {
+  return await commands.getIcsEvents(path, startDateIso, endDateIso);
+}
+
+export async function exportIcsFile(
+  path: string,
+  events: EventInfo[],
+  calendarName?: string,
+): Promise<void> {
+  await commands.exportIcsFile(path, events, calendarName ?? null);
+}
+
+// --- CalDAV Accounts ---
+
+// Looks up the calendars of the account, checking the password on the way.
+// `serverUrl` can be the server's root or the user's calendar home.
+export async function addCalDavAccount(
+  serverUrl: string,
+  username: string,
+  password: string,
+): Promise<CalDavAccount> {
+  return await commands.addCaldavAccount(serverUrl, username, password);
+}
+
+export async function listCalDavAccounts(): Promise<CalDavAccount[]> {
+  return await commands.listCaldavAccounts();
+}
+
+// Asks the servers for calendars added, renamed or removed since the last
+// refresh. `getCalendars` lists the calendars as of then.
+export async function refreshCalDavCalendars(): Promise<CalDavAccount[]> {
+  return await commands.refreshCaldavCalendars();
+}
+
+export async function removeCalDavAccount(accountId: string): Promise<void> {
+  await commands.removeCaldavAccount(accountId);
+}
+
+// --- File Index Commands ---
+
+export async function rebuildFileIndex(): Promise<FileIndexStats> {
+  return await commands.rebuildFileIndex();
+}
+
+export async function getFileIndexStats(): Promise<FileIndexStats> {
+  return await commands.getFileIndexStats();
+}
+
+// --- Content Index Commands ---
+
+export async function rebuildContentIndex(
+  notesDir: string[],
+): Promise<ContentIndexStats> {
+  return await commands.rebuildContentIndex(notesDir);
+}
+
+export async function clearContentIndex(): Promise<void> {
+  await commands.clearContentIndex();
+}
+
+export async function searchFileContents(
+  query: string,
+  limit?: number,
+): Promise<ContentMatch[]> {
+  return await commands.searchFileContents(query, limit ?? null);
+}
+
+export async function getContentIndexStats(): Promise<ContentIndexStats> {
+  return await commands.getContentIndexStats();
 }
 
 // --- Internal Commands (potentially less stable) ---
 
 export async function grintaShow(): Promise<void> {
-  await grintaInvoke("show");
+  await commands.show();
 }
 
 export async function grintaHide(): Promise<void> {
-  await grintaInvoke("hide");
+  await commands.hide();
 }
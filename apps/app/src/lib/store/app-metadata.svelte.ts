@@ -1,7 +1,25 @@
-import { type AppInfo, type ExtInfo, loadAppInfo } from "../grinta-invoke";
-import { invoke } from "@tauri-apps/api/core";
+import {
+	type AppInfo,
+	type ExtInfo,
+	type IconOptions,
+	iconUrl,
+	loadAppInfo,
+} from "../grinta-invoke";
 import { COMMAND_HANDLER, type ExecutableCommand } from "@getgrinta/core";
 
+// Size command list icons are drawn at, in CSS pixels
+const ICON_SIZE = 32;
+
+// Rendered for the current display, so retina screens get sharp icons and
+// nothing larger than needed crosses IPC
+function iconOptions(): IconOptions {
+	return {
+		size: ICON_SIZE,
+		scale: Math.ceil(window.devicePixelRatio || 1),
+		format: "webp",
+	};
+}
+
 export class AppMetadataStore {
 	loadingState = $state<Record<string, boolean>>({});
 	appInfo = $state<Record<string, AppInfo>>({});
@@ -10,7 +28,6 @@ export class AppMetadataStore {
 	loading = $state<boolean>(false);
 	initialized = $state<boolean>(false);
 	loadingApps = $state<Set<string>>(new Set());
-	loadingExtensions = $state<Set<string>>(new Set());
 
 	// Initialize the store with app paths but don't load icons yet
 	async initialize() {
@@ -41,13 +58,22 @@ export class AppMetadataStore {
 		this.loadingApps.add(appName);
 
 		try {
-			loadAppInfo([`${resourcePath}`]).then((appInfo) => {
-				// Update the store with the new icon
-				// Only update keys with new values instead of creating a new object
-				for (const [key, value] of Object.entries(appInfo)) {
-					if (value) this.appInfo[key] = value;
-				}
-			});
+			// Only the name comes over IPC, the icon is rendered into the icon
+			// cache on the way and loaded from there by the <img>
+			const options = iconOptions();
+			loadAppInfo([`${resourcePath}`], { ...options, images: false }).then(
+				(appInfo) => {
+					// Only update keys with new values instead of creating a new object
+					for (const [key, value] of Object.entries(appInfo)) {
+						if (value) {
+							this.appInfo[key] = {
+								...value,
+								base64Image: iconUrl("app", resourcePath, options),
+							};
+						}
+					}
+				},
+			);
 			return null;
 		} catch (error) {
 			console.error(`Error loading icon for ${appName}:`, error);
@@ -64,31 +90,22 @@ export class AppMetadataStore {
 			return this.extInfo[extension].base64Image;
 		}
 
-		// Skip if already loading
-		if (this.loadingExtensions.has(extension)) {
-			return null;
-		}
-
-		this.loadingExtensions.add(extension);
-
-		try {
-			const icons = (await invoke("load_extension_icons", {
-				extensions: [extension],
-			})) as Record<string, string>;
-
-			const data: Record<string, ExtInfo> = {};
-			for (const [ext, base64Image] of Object.entries(icons)) {
-				data[ext] = { base64Image, extension: ext };
-			}
+		// Served by the grinta-icon protocol, no IPC round trip needed
+		const base64Image = iconUrl("ext", extension, iconOptions());
+		this.extInfo = { ...this.extInfo, [extension]: { base64Image, extension } };
+		return base64Image;
+	}
 
-			this.extInfo = { ...this.extInfo, ...data };
-			return data[extension]?.base64Image || null;
-		} catch (error) {
-			console.error(`Error loading icon for extension ${extension}:`, error);
-			return null;
-		} finally {
-			this.loadingExtensions.delete(extension);
+	// Favicon of the site at `origin`, served by the grinta-icon protocol
+	loadFavicon(origin: string): string {
+		if (!this.favIcons[origin]) {
+			this.favIcons[origin] = iconUrl(
+				"favicon",
+				new URL(origin).host,
+				iconOptions(),
+			);
 		}
+		return this.favIcons[origin];
 	}
 
 	// Get an icon, loading it if necessary
@@ -3,8 +3,17 @@ import {
   requestCalendarAccess,
   getCalendars,
   getCalendarEvents,
+  createCalendarEvent,
+  updateCalendarEvent,
+  deleteCalendarEvent,
+  isCommandError,
+  refreshCalDavCalendars,
 } from "$lib/grinta-invoke";
-import type { CalendarInfo, EventInfo } from "$lib/types/calendar";
+import type {
+  CalendarInfo,
+  EventDraft,
+  EventInfo,
+} from "$lib/types/calendar";
 import { CalendarAuthorizationStatus } from "$lib/types/calendar";
 
 export class CalendarStore {
@@ -78,6 +87,10 @@ export class CalendarStore {
       console.error("Error fetching calendar events:", err);
       this.error = err.message || "Failed to fetch events.";
       this.events = [];
+      // Access was revoked in System Settings since the last check
+      if (isCommandError(err, "NOT_AUTHORIZED")) {
+        this.authorizationStatus = CalendarAuthorizationStatus.Denied;
+      }
     } finally {
       this.isLoading = false;
     }
@@ -107,6 +120,11 @@ export class CalendarStore {
   }
 
   async refreshAuthorization() {
+    try {
+      await refreshCalDavCalendars();
+    } catch (err) {
+      console.error("Error refreshing CalDAV calendars:", err);
+    }
     await this.checkAuthAndFetchCalendars();
   }
 
@@ -175,6 +193,66 @@ export class CalendarStore {
     await this.#fetchEventsForRange(start, end);
   }
 
+  // Where events created from the search bar go
+  get defaultCalendarId(): string | null {
+    return (
+      this.selectedCalendarIdentifiers.find((identifier) =>
+        this.availableCalendars.some(
+          (calendar) => calendar.identifier === identifier,
+        ),
+      ) ?? null
+    );
+  }
+
+  // The write methods throw the command's error, so callers can tell the
+  // user what went wrong. Events are fetched again afterwards.
+  async createEvent(draft: EventDraft): Promise<EventInfo> {
+    const event = await this.#withAuthorization(() =>
+      createCalendarEvent(draft),
+    );
+    await this.refetchEventsIfAuthorized();
+    return event;
+  }
+
+  async updateEvent(
+    calendarId: string,
+    eventId: string,
+    occurrenceDate: string,
+    draft: EventDraft,
+  ): Promise<EventInfo> {
+    const event = await this.#withAuthorization(() =>
+      updateCalendarEvent(calendarId, eventId, occurrenceDate, draft),
+    );
+    await this.refetchEventsIfAuthorized();
+    return event;
+  }
+
+  async deleteEvent(calendarId: string, eventId: string): Promise<void> {
+    await this.#withAuthorization(() =>
+      deleteCalendarEvent(calendarId, eventId),
+    );
+    this.events = this.events.filter(
+      (event) =>
+        event.identifier !== eventId || event.calendar_id !== calendarId,
+    );
+  }
+
+  async #withAuthorization<T>(write: () => Promise<T>): Promise<T> {
+    try {
+      return await write();
+    } catch (err) {
+      // Read-only calendars fail the same way, but don't revoke access
+      if (
+        isCommandError(err, "NOT_AUTHORIZED") &&
+        (await getCalendarAuthorizationStatus()) !==
+          CalendarAuthorizationStatus.Authorized
+      ) {
+        this.authorizationStatus = CalendarAuthorizationStatus.Denied;
+      }
+      throw err;
+    }
+  }
+
   setSelectedCalendars(identifiers: string[]) {
     this.selectedCalendarIdentifiers = identifiers;
     if (
@@ -1,4 +1,5 @@
 import { goto } from "$app/navigation";
+import { listen } from "@tauri-apps/api/event";
 import { watch } from "@tauri-apps/plugin-fs";
 import { fetch } from "@tauri-apps/plugin-http";
 import { openUrl } from "@tauri-apps/plugin-opener";
@@ -7,7 +8,7 @@ import { Command } from "@tauri-apps/plugin-shell";
 import nlp from "compromise";
 import dates from "compromise-dates";
 import numbers from "compromise-numbers";
-import { matchSorter } from "match-sorter";
+import { matchSorter, rankings } from "match-sorter";
 import { sortBy, uniq } from "rambda";
 import { _ } from "svelte-i18n";
 import { toast } from "svelte-sonner";
@@ -28,14 +29,30 @@ import {
   PluginNotes,
   PluginNlp,
   PluginExactUrl,
+  PluginCalendarEvent,
 } from "@getgrinta/plugin-search";
-import { searchSpotlightApps, toggleVisibility } from "../grinta-invoke";
+import {
+  type AppBundle,
+  type ContentMatch,
+  type FileSearchBatch,
+  type FileSearchComplete,
+  type InstalledApp,
+  type SpotlightAppInfo,
+  FILE_SEARCH_BATCH_EVENT,
+  FILE_SEARCH_COMPLETE_EVENT,
+  findApps,
+  cancelFileSearch,
+  isCommandError,
+  listInstalledApps,
+  searchFileContents,
+  startFileSearch,
+  toggleVisibility,
+} from "../grinta-invoke";
 import { appMetadataStore } from "../store/app-metadata.svelte";
 import {
   extractMeetingInfo,
-  type FileEntry,
-  findApps,
   generateCancellationToken,
+  isMacOS,
   t,
 } from "../utils.svelte";
 import { appStore } from "./app.svelte";
@@ -68,7 +85,7 @@ export type SystemCommand = keyof typeof SYSTEM_COMMAND;
 type HistoryEntry = Omit<ExecutableCommand, "label">;
 
 async function buildAppCommands(
-  apps: FileEntry[],
+  apps: AppBundle[],
 ): Promise<ExecutableCommand[]> {
   return apps.map((app) => {
     const defaultName = app.name.slice(0, -4);
@@ -89,6 +106,23 @@ async function buildAppCommands(
   });
 }
 
+// Applications of .desktop files, their names are localized already
+function buildInstalledAppCommands(apps: InstalledApp[]) {
+  return apps.map((app) =>
+    ExecutableCommandSchema.parse({
+      label: app.name,
+      localizedLabel: app.localizedName,
+      value: app.path,
+      metadata: {
+        path: app.path,
+      },
+      handler: COMMAND_HANDLER.APP,
+      priority: COMMAND_PRIORITY.HIGH,
+      appModes: [APP_MODE.INITIAL],
+    }),
+  );
+}
+
 function buildShortcutCommands(stdout: string) {
   const shortcuts = stdout.split(/\r?\n/);
   return shortcuts.map((shortcut) =>
@@ -117,11 +151,19 @@ export class CommandsStore extends SecureStore<Commands> {
   currentCommand = $derived<ExecutableCommand>(
     this.commands[this.selectedIndex],
   );
-  installedApps = $state<FileEntry[]>([]);
+  installedApps = $state<AppBundle[]>([]);
   appCommands = $state<ExecutableCommand[]>([]);
   shortcutCommands = $state<ExecutableCommand[]>([]);
   webSearchCommands = $state<ExecutableCommand[]>([]);
   spotlightCommands = $state<ExecutableCommand[]>([]);
+  // Streamed results of the current file search, and content index matches
+  fileSearchId: string | null = null;
+  fileSearchResults: SpotlightAppInfo[] = [];
+  contentMatches: ContentMatch[] = [];
+  // The previous query's results stay until the new search reports back
+  private fileSearchStale = false;
+  // Batches that arrived before `startFileSearch` returned the search's ID
+  private earlyFileSearchBatches = new Map<string, SpotlightAppInfo[]>();
   isUpdatingExternalSource = $state<boolean>(false);
   scrollTop = $state<number>(0);
 
@@ -162,6 +204,7 @@ export class CommandsStore extends SecureStore<Commands> {
     }
 
     this.watchForApplicationChanges();
+    await this.listenForFileSearchResults();
 
     setInterval(() => {
       this.buildShortcutCommands();
@@ -176,6 +219,10 @@ export class CommandsStore extends SecureStore<Commands> {
   }
 
   async buildAppCommands() {
+    if (!isMacOS()) {
+      this.appCommands = buildInstalledAppCommands(await listInstalledApps());
+      return;
+    }
     const apps = await findApps();
     this.installedApps = apps;
     this.appCommands = await buildAppCommands(this.installedApps);
@@ -307,9 +354,31 @@ export class CommandsStore extends SecureStore<Commands> {
       return;
     }
 
-    const filteredCommands = matchSorter(commands, appStore.query, {
+    // File search results are already filtered and ranked by the backend, and
+    // filter terms like "ext:pdf" would never match their labels here. They
+    // keep the backend order, after the commands starting with the query and
+    // before the looser matches.
+    const rankedFileCommands = commands.filter(
+      (command) => command.handler === COMMAND_HANDLER.FS_ITEM,
+    );
+    const matchedCommands = matchSorter(
+      commands.filter(
+        (command) => command.handler !== COMMAND_HANDLER.FS_ITEM,
+      ),
+      appStore.query,
+      {
+        keys: ["localizedLabel", "label"],
+      },
+    );
+    const strongMatches = matchSorter(matchedCommands, appStore.query, {
       keys: ["localizedLabel", "label"],
+      threshold: rankings.WORD_STARTS_WITH,
     });
+    const filteredCommands = [
+      ...strongMatches,
+      ...rankedFileCommands,
+      ...matchedCommands.filter((command) => !strongMatches.includes(command)),
+    ];
 
     // Prevent overriding commands
     if (newCommandsToken !== this.buildCommandsToken) {
@@ -340,6 +409,14 @@ export class CommandsStore extends SecureStore<Commands> {
           )) ?? [])
         : [];
 
+    // Same for events typed into the search bar, like "lunch tomorrow 1pm"
+    const eventCommands =
+      appStore.appMode === APP_MODE.INITIAL && calendarStore.defaultCalendarId
+        ? ((await PluginCalendarEvent(
+            this.buildPluginContext(),
+          )?.addSearchResults?.(appStore.query)) ?? [])
+        : [];
+
     let quickSearchCommand: ExecutableCommand | null = null;
     if (appStore.appMode === APP_MODE.INITIAL && appStore.quickSearchMode) {
       const hostname = new URL(appStore.quickSearchMode.searchUrl("")).hostname;
@@ -362,13 +439,16 @@ export class CommandsStore extends SecureStore<Commands> {
     if (quickSearchCommand) {
       this.commands = uniq([
         ...formulaCommands,
+        ...eventCommands,
         ...filteredCommands,
         quickSearchCommand,
       ]).sort((a, b) => this.sortCommands({ prev: a, next: b }));
     } else {
-      this.commands = uniq([...formulaCommands, ...filteredCommands]).sort(
-        (a, b) => this.sortCommands({ prev: a, next: b }),
-      );
+      this.commands = uniq([
+        ...formulaCommands,
+        ...eventCommands,
+        ...filteredCommands,
+      ]).sort((a, b) => this.sortCommands({ prev: a, next: b }));
     }
 
     if (
@@ -388,45 +468,142 @@ export class CommandsStore extends SecureStore<Commands> {
 
       if (appStore.query.length >= 3) {
         this.debouncedSpotlightSearch();
+      } else {
+        this.cancelSpotlightSearch();
       }
     }
   }
 
-  private startSpotlightSearch() {
+  private async listenForFileSearchResults() {
+    await listen<FileSearchBatch>(FILE_SEARCH_BATCH_EVENT, ({ payload }) => {
+      if (payload.search_id !== this.fileSearchId) {
+        if (this.fileSearchId === null) {
+          const early =
+            this.earlyFileSearchBatches.get(payload.search_id) ?? [];
+          this.earlyFileSearchBatches.set(payload.search_id, [
+            ...early,
+            ...payload.results,
+          ]);
+        }
+        return;
+      }
+      this.addFileSearchResults(payload.results);
+    });
+    await listen<FileSearchComplete>(
+      FILE_SEARCH_COMPLETE_EVENT,
+      ({ payload }) => {
+        if (payload.search_id !== this.fileSearchId) return;
+        if (payload.status === "failed") {
+          // Incomplete filters like "size:>" are rejected while typing, keep
+          // the previous results until the query parses again
+          console.warn("File search failed:", payload.error);
+          return;
+        }
+        // Nothing matched the new query
+        if (this.fileSearchStale) this.addFileSearchResults([]);
+      },
+    );
+  }
+
+  // Each search cancels the previous one, its results arrive in batches
+  private async startSpotlightSearch() {
     const spotlightToken = generateCancellationToken();
     this.spotlightSearchToken = spotlightToken;
+    this.fileSearchId = null;
+    this.fileSearchStale = true;
+    this.earlyFileSearchBatches.clear();
 
     const additionalExtensions =
       settingsStore.data.fsSearchAdditionalExtensions;
     const searchOnlyInHome = settingsStore.data.fsSearchOnlyInHome;
 
-    searchSpotlightApps(
-      appStore.query,
-      additionalExtensions,
-      searchOnlyInHome,
-    ).then((result) => {
-      if (spotlightToken !== this.spotlightSearchToken) {
-        return;
-      }
+    if (settingsStore.data.fsContentSearchEnabled) {
+      searchFileContents(appStore.query)
+        .catch(() => [])
+        .then((contentMatches) => {
+          if (spotlightToken !== this.spotlightSearchToken) return;
+          this.contentMatches = contentMatches;
+          this.updateSpotlightCommands();
+        });
+    } else {
+      this.contentMatches = [];
+    }
+
+    try {
+      const searchId = await startFileSearch(
+        appStore.query,
+        additionalExtensions,
+        searchOnlyInHome,
+      );
+      if (spotlightToken !== this.spotlightSearchToken) return;
+      this.fileSearchId = searchId;
+      const early = this.earlyFileSearchBatches.get(searchId);
+      this.earlyFileSearchBatches.clear();
+      if (early) this.addFileSearchResults(early);
+    } catch (error) {
+      console.warn("File search failed:", error);
+    }
+  }
 
-      this.spotlightCommands = result.map((entry) =>
+  private cancelSpotlightSearch() {
+    this.debouncedSpotlightSearch.clear();
+    this.spotlightSearchToken = generateCancellationToken();
+    if (this.fileSearchId) {
+      void cancelFileSearch(this.fileSearchId);
+      this.fileSearchId = null;
+    }
+  }
+
+  private addFileSearchResults(results: SpotlightAppInfo[]) {
+    // Batches are ranked on their own, keep the merged list in score order
+    this.fileSearchResults = sortBy(
+      (entry: SpotlightAppInfo) => -entry.score,
+    )(this.fileSearchStale ? results : [...this.fileSearchResults, ...results]);
+    this.fileSearchStale = false;
+    this.updateSpotlightCommands();
+  }
+
+  private updateSpotlightCommands() {
+    const nameMatchPaths = new Set(
+      this.fileSearchResults.map((entry) => entry.path),
+    );
+    this.spotlightCommands = [
+      ...this.fileSearchResults.map((entry) =>
         ExecutableCommandSchema.parse({
-          label: entry.display_name,
-          localizedLabel: entry.display_name,
+          label: entry.displayName,
+          localizedLabel: entry.displayName,
           value: entry.path,
           path: entry.path,
-          metadata: { contentType: entry.content_type },
+          metadata: {
+            contentType: entry.contentType,
+            searchScore: entry.score,
+            matchRanges: entry.matchRanges,
+          },
           handler: COMMAND_HANDLER.FS_ITEM,
           appModes: [APP_MODE.INITIAL],
         }),
-      );
-
-      this.isUpdatingExternalSource = true;
-      this.buildCommands({
-        isRefresh: true,
-      });
-      this.isUpdatingExternalSource = false;
+      ),
+      // Files matching by content only, after the name matches
+      ...this.contentMatches
+        .filter((entry) => !nameMatchPaths.has(entry.path))
+        .map((entry) =>
+          ExecutableCommandSchema.parse({
+            label: entry.display_name,
+            localizedLabel: entry.display_name,
+            value: entry.path,
+            path: entry.path,
+            metadata: { contentSnippet: entry.snippet },
+            handler: COMMAND_HANDLER.FS_ITEM,
+            appModes: [APP_MODE.INITIAL],
+          }),
+        ),
+    ];
+
+    this.isUpdatingExternalSource = true;
+    this.buildCommands({
+      isRefresh: true,
     });
+    this.isUpdatingExternalSource = false;
   }
 
   sortCommands({
@@ -475,6 +652,7 @@ export class CommandsStore extends SecureStore<Commands> {
       COMMAND_HANDLER.CREATE_NOTE,
       COMMAND_HANDLER.SYSTEM,
       COMMAND_HANDLER.OPEN_CALENDAR,
+      COMMAND_HANDLER.CREATE_EVENT,
     ] as string[];
 
     const shouldRecord =
@@ -595,6 +773,34 @@ export class CommandsStore extends SecureStore<Commands> {
       .with({ handler: COMMAND_HANDLER.OPEN_CALENDAR }, async ({ value }) => {
         return goto(`/calendar/${value}`);
       })
+      .with(
+        { handler: COMMAND_HANDLER.CREATE_EVENT },
+        async ({ value, metadata }) => {
+          const calendarId = calendarStore.defaultCalendarId;
+          const { startTime, endTime, isAllDay } =
+            metadata.calendarSchema ?? {};
+          if (!calendarId || !startTime || !endTime) return;
+          try {
+            const event = await calendarStore.createEvent({
+              calendarId,
+              title: value,
+              startDate: startTime,
+              endDate: endTime,
+              isAllDay: isAllDay ?? false,
+              location: null,
+              url: null,
+              notes: null,
+            });
+            appStore.clearQuery();
+            toast.success(t("toast.eventCreated", { title: event.title }));
+          } catch (error) {
+            const reason = isCommandError(error)
+              ? error.message
+              : String(error);
+            toast.error(t("toast.eventNotCreated", { reason }));
+          }
+        },
+      )
       .otherwise(() => {
         console.log("Run custom plugin handlers");
       });
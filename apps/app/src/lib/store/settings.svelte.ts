@@ -12,7 +12,12 @@ import {
   checkAccessibilityPermission,
   checkFullDiskAccessPermission,
 } from "tauri-plugin-macos-permissions-api";
-import { activateAppByName, getLastFocusedWindowName } from "../grinta-invoke";
+import {
+  activateAppByName,
+  clearContentIndex,
+  getLastFocusedWindowName,
+  rebuildContentIndex,
+} from "../grinta-invoke";
 import { SettingsSchema } from "@getgrinta/core";
 import { z } from "zod/v3";
 import type { CustomQuickLink } from "@getgrinta/core";
@@ -79,6 +84,16 @@ export class SettingsStore extends SecureStore<Settings> {
     this.updateData({ fsSearchAdditionalExtensions: updatedExtensions });
   }
 
+  async setFsContentSearchEnabled(fsContentSearchEnabled: boolean) {
+    this.updateData({ fsContentSearchEnabled });
+    // The index only exists while content search is on
+    if (fsContentSearchEnabled) {
+      await rebuildContentIndex(this.data.notesDir);
+    } else {
+      await clearContentIndex();
+    }
+  }
+
   async setToggleShortcut(toggleShortcut: string) {
     await this.unregisterShortcuts();
     this.updateData({ toggleShortcut });
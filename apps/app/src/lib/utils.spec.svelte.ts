@@ -1,5 +1,9 @@
 import { describe, expect, test } from "bun:test";
-import { generateCancellationToken, highlightText } from "./utils.svelte";
+import {
+	generateCancellationToken,
+	highlightRanges,
+	highlightText,
+} from "./utils.svelte";
 
 describe.skip("highlightText", () => {
 	test("returns full text as non-highlighted when search is empty", () => {
@@ -34,6 +38,38 @@ describe.skip("highlightText", () => {
 	});
 });
 
+describe("highlightRanges", () => {
+	test("highlights the given ranges", () => {
+		const result = highlightRanges("Annual Report.pdf", [
+			{ start: 0, end: 2 },
+			{ start: 7, end: 13 },
+		]);
+		expect(result).toEqual([
+			{ text: "An", highlight: true },
+			{ text: "nual ", highlight: false },
+			{ text: "Report", highlight: true },
+			{ text: ".pdf", highlight: false },
+		]);
+	});
+
+	test("uses UTF-16 offsets", () => {
+		const result = highlightRanges("😀 Café.txt", [{ start: 3, end: 7 }]);
+		expect(result).toEqual([
+			{ text: "😀 ", highlight: false },
+			{ text: "Café", highlight: true },
+			{ text: ".txt", highlight: false },
+		]);
+	});
+
+	test("skips ranges outside of the text", () => {
+		const result = highlightRanges("report", [
+			{ start: 4, end: 2 },
+			{ start: 2, end: 10 },
+		]);
+		expect(result).toEqual([{ text: "report", highlight: false }]);
+	});
+});
+
 describe("generateCancellationToken", () => {
 	test("generates a string of expected length", () => {
 		const token = generateCancellationToken();
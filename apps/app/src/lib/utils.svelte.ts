@@ -1,12 +1,12 @@
 import type { AppType } from "@getgrinta/api";
 import { type UnlistenFn, emit, listen } from "@tauri-apps/api/event";
-import { readDir } from "@tauri-apps/plugin-fs";
 import { fetch } from "@tauri-apps/plugin-http";
 // biome-ignore lint/suspicious/noShadowRestrictedNames: just do it
 import AggregateError from "aggregate-error";
 import { hc } from "hono/client";
 import {
   AppWindowIcon,
+  CalendarPlusIcon,
   ChevronRightIcon,
   CopyIcon,
   FileIcon,
@@ -50,6 +50,11 @@ function escapeRegExp(str: string): string {
   return str.replace(/[.*+?^${}()|[\]\\]/g, "\\$&");
 }
 
+// The webview reports the platform, there is no OS plugin
+export function isMacOS() {
+  return navigator.userAgent.includes("Mac OS X");
+}
+
 export function generateCancellationToken(): string {
   return Math.random().toString(36).substring(2, 12);
 }
@@ -78,6 +83,28 @@ export function highlightText(
   }));
 }
 
+// Highlights ranges matched elsewhere, like the backend's file search ranking.
+// Offsets are UTF-16 code units, sorted and not overlapping.
+export function highlightRanges(
+  text: string,
+  ranges: { start: number; end: number }[],
+): HighlightSegment[] {
+  const segments: HighlightSegment[] = [];
+  let offset = 0;
+  for (const { start, end } of ranges) {
+    if (start < offset || end > text.length || start >= end) continue;
+    if (start > offset) {
+      segments.push({ text: text.slice(offset, start), highlight: false });
+    }
+    segments.push({ text: text.slice(start, end), highlight: true });
+    offset = end;
+  }
+  if (offset < text.length) {
+    segments.push({ text: text.slice(offset), highlight: false });
+  }
+  return segments;
+}
+
 export async function activateWindow() {
   await appStore.appWindow?.show();
   await appStore.appWindow?.setFocus();
@@ -202,6 +229,7 @@ export function getIcon(command: ExecutableCommand) {
     .with(COMMAND_HANDLER.CREATE_NOTE, () => StickyNoteIcon)
     .with(COMMAND_HANDLER.RUN_SHORTCUT, () => Layers2Icon)
     .with(COMMAND_HANDLER.COPY_TO_CLIPBOARD, () => CopyIcon)
+    .with(COMMAND_HANDLER.CREATE_EVENT, () => CalendarPlusIcon)
     .with(COMMAND_HANDLER.FS_ITEM, () => {
       if (command.metadata?.contentType === "public.folder") {
         return FolderIcon;
@@ -211,56 +239,6 @@ export function getIcon(command: ExecutableCommand) {
     .otherwise(() => ChevronRightIcon);
 }
 
-export type FileEntry = {
-  name: string;
-  isDirectory: boolean;
-  isFile: boolean;
-  isSymlink: boolean;
-  path: string;
-};
-
-export async function findApps(): Promise<FileEntry[]> {
-  const apps = (
-    await Promise.all([
-      findAppsInDirectory("/Applications"),
-      findAppsInDirectory("/System/Applications"),
-      findAppsInDirectory(
-        "/System/Volumes/Preboot/Cryptexes/App/System/Applications",
-      ),
-    ])
-  ).flat();
-
-  return apps;
-}
-
-async function findAppsInDirectory(path: string): Promise<FileEntry[]> {
-  const entries = await readDir(path);
-  const apps: FileEntry[] = [];
-
-  for (const entry of entries) {
-    if (entry.isDirectory) {
-      if (entry.name.endsWith(".app")) {
-        apps.push({
-          ...entry,
-          path: `${path}/${entry.name}`,
-        });
-      } else {
-        // Don't recurse into .app directories
-        if (!entry.name.includes(".app/")) {
-          try {
-            const subApps = await findAppsInDirectory(`${path}/${entry.name}`);
-            apps.push(...subApps);
-          } catch {
-            // Permission errors
-          }
-        }
-      }
-    }
-  }
-
-  return apps;
-}
-
 export class ColorModeValue<T = string> {
   lightModeValue = $state<T>("" as T);
   darkModeValue = $state<T>("" as T);
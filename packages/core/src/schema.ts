@@ -24,6 +24,7 @@ export const COMMAND_HANDLER = {
   EMBEDDED_URL: "EMBEDDED_URL",
   FS_ITEM: "FS_ITEM",
   OPEN_CALENDAR: "OPEN_CALENDAR",
+  CREATE_EVENT: "CREATE_EVENT",
 } as const;
 
 export const commandHandlerEnum = z.nativeEnum(COMMAND_HANDLER);
@@ -74,6 +75,12 @@ export const MetadataSchema = z.object({
   ranAt: z.date().optional(),
   updatedAt: z.date().optional(),
   calendarSchema: CalendarSchema.optional(),
+  searchScore: z.number().optional(),
+  // Matching line of a content search result
+  contentSnippet: z.string().optional(),
+  matchRanges: z
+    .array(z.object({ start: z.number(), end: z.number() }))
+    .optional(),
 });
 
 export const COMMAND_PRIORITY = {
@@ -205,6 +212,7 @@ export const SettingsSchema = z.object({
   baseCurrency: z.nativeEnum(BASE_CURRENCY).default(BASE_CURRENCY.USD),
   fsSearchOnlyInHome: z.boolean().default(false),
   fsSearchAdditionalExtensions: z.array(z.string()).default([]),
+  fsContentSearchEnabled: z.boolean().default(false),
   accessibilityPermissions: z.boolean().default(false),
   fsPermissions: z.boolean().default(false),
   selectedCalendarIdentifiers: z.array(z.string()).default([]),
@@ -7,3 +7,4 @@ export { PluginWebSearch } from "./plugin-web-search"
 export { PluginNotes } from "./plugin-notes"
 export { PluginNlp } from "./plugin-nlp"
 export { PluginExactUrl } from "./plugin-exact-url"
+export { PluginCalendarEvent } from "./plugin-calendar-event"
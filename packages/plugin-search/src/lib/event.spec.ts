@@ -0,0 +1,82 @@
+import { describe, expect, it, mock } from "bun:test";
+
+import { buildCreateEventCommand, parseEventText } from "./event";
+import { APP_MODE, COMMAND_HANDLER, SettingsSchema } from "@getgrinta/core";
+import type { PluginContext } from "@getgrinta/plugin";
+
+// Wednesday, July 3rd 2024, 16:30 local time
+const NOW = new Date(2024, 6, 3, 16, 30);
+
+function at(day: number, hours = 0, minutes = 0) {
+  return new Date(2024, 6, day, hours, minutes);
+}
+
+describe("parseEventText", () => {
+  it("reads a day and a time", () => {
+    expect(parseEventText("meeting with Anna tomorrow 3pm", NOW)).toEqual({
+      title: "Meeting with Anna",
+      start: at(4, 15),
+      end: at(4, 16),
+      isAllDay: false,
+    });
+  });
+
+  it("reads time ranges and durations", () => {
+    const lunch = parseEventText("lunch friday 12-1pm", NOW);
+    expect([lunch?.start, lunch?.end]).toEqual([at(5, 12), at(5, 13)]);
+
+    const standup = parseEventText("standup on monday at 9:30 for 15 min", NOW);
+    expect(standup?.title).toBe("Standup");
+    expect([standup?.start, standup?.end]).toEqual([at(8, 9, 30), at(8, 9, 45)]);
+
+    const party = parseEventText("party 11pm-1am", NOW);
+    expect([party?.start, party?.end]).toEqual([at(3, 23), at(4, 1)]);
+  });
+
+  it("moves times that already passed today to tomorrow", () => {
+    expect(parseEventText("call mom 3pm", NOW)?.start).toEqual(at(4, 15));
+    expect(parseEventText("dinner for 2 at 7pm", NOW)).toMatchObject({
+      title: "Dinner for 2",
+      start: at(3, 19),
+    });
+  });
+
+  it("creates all-day events for days without a time", () => {
+    expect(parseEventText("vacation next wednesday", NOW)).toEqual({
+      title: "Vacation",
+      start: at(10),
+      end: at(11),
+      isAllDay: true,
+    });
+    expect(parseEventText("report due 5th of august", NOW)?.start).toEqual(
+      new Date(2024, 7, 5),
+    );
+    expect(parseEventText("dentist june 5", NOW)?.start).toEqual(
+      new Date(2025, 5, 5),
+    );
+  });
+
+  it("ignores text without a date or a title", () => {
+    expect(parseEventText("weather", NOW)).toBeNull();
+    expect(parseEventText("2-3 apples", NOW)).toBeNull();
+    expect(parseEventText("tomorrow at 3pm", NOW)).toBeNull();
+  });
+});
+
+describe("buildCreateEventCommand", () => {
+  it("carries the dates in the calendar metadata", () => {
+    const context = {
+      t: mock((key: string) => key),
+      app: { query: "", appMode: APP_MODE.INITIAL },
+      settings: SettingsSchema.parse({}),
+    } as unknown as PluginContext;
+    const command = buildCreateEventCommand("review 2024-07-10 14:00", context);
+    expect(command?.handler).toBe(COMMAND_HANDLER.CREATE_EVENT);
+    expect(command?.value).toBe("Review");
+    expect(command?.metadata.calendarSchema).toMatchObject({
+      startTime: at(10, 14).toISOString(),
+      endTime: at(10, 15).toISOString(),
+      isAllDay: false,
+    });
+  });
+});
@@ -0,0 +1,291 @@
+import dayjs from "dayjs";
+import {
+  APP_MODE,
+  COMMAND_HANDLER,
+  COMMAND_PRIORITY,
+  ExecutableCommandSchema,
+} from "@getgrinta/core";
+import type { PluginContext } from "@getgrinta/plugin";
+
+export type ParsedEvent = {
+  title: string;
+  start: Date;
+  end: Date;
+  isAllDay: boolean;
+};
+
+const DEFAULT_DURATION_MINUTES = 60;
+
+const WEEKDAYS = [
+  "sunday",
+  "monday",
+  "tuesday",
+  "wednesday",
+  "thursday",
+  "friday",
+  "saturday",
+];
+
+const MONTHS = [
+  "jan",
+  "feb",
+  "mar",
+  "apr",
+  "may",
+  "jun",
+  "jul",
+  "aug",
+  "sep",
+  "oct",
+  "nov",
+  "dec",
+];
+
+const MONTH_PATTERN =
+  "(jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?|aug(?:ust)?|sept?(?:ember)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?)";
+const TIME_PATTERN = "(\\d{1,2})(?::(\\d{2}))?\\s*(am|pm)?";
+
+// Matches are cut out of the text, whatever is left becomes the title
+type Match = RegExpMatchArray;
+
+// The first whole-word match of `pattern` that `accept` agrees with
+function find(
+  text: string,
+  pattern: string,
+  accept: (match: Match) => boolean = () => true,
+) {
+  const regex = new RegExp(`(?:^|\\s)(?:${pattern})(?=$|\\s|[,.!?])`, "gi");
+  for (const match of text.matchAll(regex)) {
+    if (accept(match)) return match;
+  }
+  return null;
+}
+
+function cut(text: string, match: Match) {
+  const index = match.index ?? 0;
+  return `${text.slice(0, index)} ${text.slice(index + match[0].length)}`;
+}
+
+function capitalize(text: string) {
+  return text.charAt(0).toUpperCase() + text.slice(1);
+}
+
+function startOfDay(date: Date, addDays = 0) {
+  return new Date(
+    date.getFullYear(),
+    date.getMonth(),
+    date.getDate() + addDays,
+  );
+}
+
+function parseDay(text: string, now: Date): [Date, Match] | null {
+  const today = startOfDay(now);
+  const relative = find(text, "(?:on )?(today|tonight|tomorrow)");
+  if (relative) {
+    const days = relative[1].toLowerCase() === "tomorrow" ? 1 : 0;
+    return [startOfDay(today, days), relative];
+  }
+
+  const inDays = find(text, "in (\\d+) (days?|weeks?)");
+  if (inDays) {
+    const unit = inDays[2].toLowerCase().startsWith("week") ? 7 : 1;
+    return [startOfDay(today, Number(inDays[1]) * unit), inDays];
+  }
+
+  const weekday = find(text, `(?:(next|on) )?(${WEEKDAYS.join("|")})`);
+  if (weekday) {
+    const target = WEEKDAYS.indexOf(weekday[2].toLowerCase());
+    let days = (target - today.getDay() + 7) % 7;
+    if (days === 0 && weekday[1]?.toLowerCase() === "next") days = 7;
+    return [startOfDay(today, days), weekday];
+  }
+
+  const iso = find(text, "(?:on )?(\\d{4})-(\\d{2})-(\\d{2})");
+  if (iso) {
+    const [year, month, day] = iso.slice(1, 4).map(Number);
+    return [new Date(year, month - 1, day), iso];
+  }
+
+  // "june 5th" and "5 june", next year once the day has passed
+  const monthFirst = find(
+    text,
+    `(?:on )?${MONTH_PATTERN} (\\d{1,2})(?:st|nd|rd|th)?`,
+  );
+  const dayFirst = find(
+    text,
+    `(?:on )?(\\d{1,2})(?:st|nd|rd|th)? (?:of )?${MONTH_PATTERN}`,
+  );
+  const date = monthFirst ?? dayFirst;
+  if (date) {
+    const [monthName, day] = monthFirst
+      ? [date[1], date[2]]
+      : [date[2], date[1]];
+    const month = MONTHS.indexOf(monthName.toLowerCase().slice(0, 3));
+    let result = new Date(today.getFullYear(), month, Number(day));
+    if (result < today) {
+      result = new Date(today.getFullYear() + 1, month, Number(day));
+    }
+    return [result, date];
+  }
+  return null;
+}
+
+function toMinutes(
+  hours: string,
+  minutes: string | undefined,
+  meridiem: string | undefined,
+) {
+  let hour = Number(hours);
+  const minute = Number(minutes ?? 0);
+  if (hour > 23 || minute > 59) return null;
+  if (meridiem) {
+    if (hour === 0 || hour > 12) return null;
+    hour = (hour % 12) + (meridiem.toLowerCase() === "pm" ? 12 : 0);
+  }
+  return hour * 60 + minute;
+}
+
+// Start and end in minutes since midnight, the end being null without a range
+function parseTime(
+  text: string,
+): [number, number | null, Match] | null {
+  // Bare numbers like "2-3" aren't times
+  const range = find(
+    text,
+    `(?:at |from )?${TIME_PATTERN}\\s*(?:-|–|to|until) ?${TIME_PATTERN}`,
+    (match) => Boolean(match[2] || match[3] || match[5] || match[6]),
+  );
+  if (range) {
+    const [, startHours, startMinutes, startMeridiem] = range;
+    const [endHours, endMinutes, endMeridiem] = range.slice(4, 7);
+    const end = toMinutes(endHours, endMinutes, endMeridiem);
+    // "3-4pm" is in the afternoon, "11-1pm" starts in the morning
+    let start = toMinutes(startHours, startMinutes, startMeridiem ?? endMeridiem);
+    if (start !== null && end !== null && !startMeridiem && start > end) {
+      start = toMinutes(startHours, startMinutes, "am");
+    }
+    if (start !== null && end !== null) return [start, end, range];
+  }
+
+  const named = find(text, "(?:at )?(noon|midnight)");
+  if (named) {
+    return [named[1].toLowerCase() === "noon" ? 12 * 60 : 0, null, named];
+  }
+
+  // "3pm" or "15:00", but not a bare number
+  const time = find(
+    text,
+    `(?:at )?${TIME_PATTERN}`,
+    (match) => Boolean(match[2] || match[3]),
+  );
+  if (time) {
+    const start = toMinutes(time[1], time[2], time[3]);
+    if (start !== null) return [start, null, time];
+  }
+  return null;
+}
+
+function parseDuration(text: string): [number, Match] | null {
+  const half = find(text, "for (?:half an hour|30 ?m(?:ins?|inutes?)?)");
+  if (half) return [30, half];
+  const hour = find(text, "for (?:an|one) hour");
+  if (hour) return [60, hour];
+  const duration = find(
+    text,
+    "for (\\d+(?:\\.\\d+)?) ?(m|mins?|minutes?|h|hrs?|hours?)",
+  );
+  if (duration) {
+    const unit = duration[2].toLowerCase().startsWith("m") ? 1 : 60;
+    return [Math.round(Number(duration[1]) * unit), duration];
+  }
+  return null;
+}
+
+/**
+ * Reads an event out of text like "meeting with Anna tomorrow 3pm" or "lunch
+ * friday 12-1pm for 30 min". Needs a day or a time, and some title besides.
+ * A day without a time is an all-day event, a time without a day is the next
+ * time it comes around.
+ */
+export function parseEventText(
+  query: string,
+  now = new Date(),
+): ParsedEvent | null {
+  let text = query.trim();
+  const day = parseDay(text, now);
+  if (day) text = cut(text, day[1]);
+  const time = parseTime(text);
+  if (time) text = cut(text, time[2]);
+  const duration = parseDuration(text);
+  if (duration) text = cut(text, duration[1]);
+  if (!day && !time) return null;
+
+  const title = text
+    .replace(/\s+/g, " ")
+    .replace(/^(?:at|on|from|for)\s|\s(?:at|on|from|for)$|[\s,]+$/gi, "")
+    .trim();
+  if (title.length === 0) return null;
+
+  if (!time) {
+    const start = day ? day[0] : startOfDay(now);
+    return {
+      title: capitalize(title),
+      start,
+      end: startOfDay(start, 1),
+      isAllDay: true,
+    };
+  }
+
+  const [startMinutes, endMinutes] = time;
+  let date = day ? day[0] : startOfDay(now);
+  let start = dayjs(date).add(startMinutes, "minute").toDate();
+  if (!day && start < now) {
+    date = startOfDay(date, 1);
+    start = dayjs(date).add(startMinutes, "minute").toDate();
+  }
+  let end =
+    endMinutes !== null
+      ? dayjs(date).add(endMinutes, "minute").toDate()
+      : dayjs(start)
+          .add(duration?.[0] ?? DEFAULT_DURATION_MINUTES, "minute")
+          .toDate();
+  // "11pm-1am" ends the next day
+  if (end <= start) end = dayjs(end).add(1, "day").toDate();
+
+  return {
+    title: capitalize(title),
+    start,
+    end,
+    isAllDay: false,
+  };
+}
+
+export function buildCreateEventCommand(
+  query: string,
+  context: PluginContext,
+  now = new Date(),
+) {
+  const event = parseEventText(query, now);
+  if (!event) return null;
+  const date = dayjs(event.start).format(event.isAllDay ? "ll" : "lll");
+  const label = context.t("commands.actions.createEvent", {
+    title: event.title,
+    date,
+  });
+  return ExecutableCommandSchema.parse({
+    label,
+    localizedLabel: label,
+    value: event.title,
+    handler: COMMAND_HANDLER.CREATE_EVENT,
+    metadata: {
+      calendarSchema: {
+        startTime: event.start.toISOString(),
+        endTime: event.end.toISOString(),
+        isAllDay: event.isAllDay,
+      },
+    },
+    smartMatch: true,
+    priority: COMMAND_PRIORITY.MEDIUM,
+    appModes: [APP_MODE.INITIAL],
+  });
+}
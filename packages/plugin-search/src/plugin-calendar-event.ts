@@ -0,0 +1,11 @@
+import { createPlugin } from "@getgrinta/plugin";
+import { buildCreateEventCommand } from "./lib/event";
+
+export const PluginCalendarEvent = createPlugin({
+  name: "CalendarEvent",
+  async addSearchResults(query, context) {
+    // "meeting with Anna tomorrow 3pm"
+    const command = buildCreateEventCommand(query, context);
+    return command ? [command] : [];
+  },
+});